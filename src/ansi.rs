@@ -0,0 +1,153 @@
+//! Rendering of ratatui text as ANSI escape sequences.
+use crate::Palette;
+
+/// Renders a [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html)
+/// as a string containing 24-bit ANSI escape sequences, e.g. to print highlighted content outside
+/// of a TUI.
+///
+/// The line's own style is patched with each span's style. Named and indexed colours are resolved
+/// using the default xterm [Palette](crate::Palette), `Color::Reset` becomes the terminal's default
+/// colour and every styled span is followed by a reset, so the output never leaks styles.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Modifier, Style};
+/// use ratatui::text::{Line, Span};
+///
+/// let line = Line::from(vec![
+///     Span::styled("fn", Style::new().fg(Color::Rgb(255, 0, 0)).add_modifier(Modifier::BOLD)),
+///     Span::raw(" main"),
+/// ]);
+/// assert_eq!(
+///     "\x1b[1;38;2;255;0;0mfn\x1b[0m main",
+///     syntect_tui::to_ansi_string(&line)
+/// );
+/// ```
+pub fn to_ansi_string(line: &ratatui::text::Line<'_>) -> String {
+    let palette = Palette::default();
+    line.spans
+        .iter()
+        .map(|span| {
+            let codes = sgr_codes(line.style.patch(span.style), &palette);
+            if codes.is_empty() {
+                span.content.to_string()
+            } else {
+                format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.content)
+            }
+        })
+        .collect()
+}
+
+/// Renders a [ratatui::text::Text](https://docs.rs/ratatui/latest/ratatui/text/struct.Text.html)
+/// as a string containing 24-bit ANSI escape sequences using
+/// [to_ansi_string](crate::to_ansi_string), joining lines with `\n`.
+///
+/// The text's own style is patched with each line's style, as it is when ratatui renders the text.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use ratatui::text::Text;
+///
+/// let text = Text::from("a\nb").style(Style::new().bg(Color::Rgb(0, 0, 255)));
+/// assert_eq!(
+///     "\x1b[48;2;0;0;255ma\x1b[0m\n\x1b[48;2;0;0;255mb\x1b[0m",
+///     syntect_tui::text_to_ansi_string(&text)
+/// );
+/// ```
+pub fn text_to_ansi_string(text: &ratatui::text::Text<'_>) -> String {
+    text.lines
+        .iter()
+        .map(|line| {
+            to_ansi_string(&ratatui::text::Line {
+                style: text.style.patch(line.style),
+                ..line.clone()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn sgr_codes(style: ratatui::style::Style, palette: &Palette) -> Vec<String> {
+    use ratatui::style::Modifier;
+    let mut codes: Vec<String> = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ]
+    .into_iter()
+    .filter(|(modifier, _)| style.add_modifier.contains(*modifier))
+    .map(|(_, code)| code.to_string())
+    .collect();
+    codes.extend(style.fg.map(|colour| colour_code(colour, 38, 39, palette)));
+    codes.extend(style.bg.map(|colour| colour_code(colour, 48, 49, palette)));
+    #[cfg(feature = "underline-color")]
+    codes.extend(
+        style
+            .underline_color
+            .map(|colour| colour_code(colour, 58, 59, palette)),
+    );
+    codes
+}
+
+fn colour_code(colour: ratatui::style::Color, set: u8, reset: u8, palette: &Palette) -> String {
+    match palette.rgb(colour) {
+        Some((r, g, b)) => format!("{set};2;{r};{g};{b}"),
+        None => reset.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::{Line, Span, Text};
+    use rstest::*;
+
+    #[rstest]
+    #[case::plain(Style::new(), "fn")]
+    #[case::reset(Style::new().fg(Color::Reset).bg(Color::Reset), "\x1b[39;49mfn\x1b[0m")]
+    #[case::named(Style::new().fg(Color::Red), "\x1b[38;2;205;0;0mfn\x1b[0m")]
+    #[case::modifiers(
+        Style::new().add_modifier(Modifier::ITALIC | Modifier::CROSSED_OUT),
+        "\x1b[3;9mfn\x1b[0m"
+    )]
+    #[case::removed_modifiers(Style::new().remove_modifier(Modifier::BOLD), "fn")]
+    fn check_to_ansi_string(#[case] style: Style, #[case] expected: &str) {
+        assert_eq!(
+            expected,
+            to_ansi_string(&Line::from(Span::styled("fn", style)))
+        );
+    }
+
+    #[cfg(feature = "underline-color")]
+    #[test]
+    fn renders_underline_colour() {
+        let style = Style::new()
+            .add_modifier(Modifier::UNDERLINED)
+            .underline_color(Color::Rgb(1, 2, 3));
+        assert_eq!(
+            "\x1b[4;58;2;1;2;3mfn\x1b[0m",
+            to_ansi_string(&Line::from(Span::styled("fn", style)))
+        );
+    }
+
+    #[test]
+    fn line_styles_override_text_style() {
+        let text = Text::from(vec![
+            Line::from("a"),
+            Line::from("b").style(Style::new().fg(Color::Rgb(255, 0, 0))),
+        ])
+        .style(Style::new().fg(Color::Rgb(0, 0, 255)));
+        assert_eq!(
+            "\x1b[38;2;0;0;255ma\x1b[0m\n\x1b[38;2;255;0;0mb\x1b[0m",
+            text_to_ansi_string(&text)
+        );
+    }
+}
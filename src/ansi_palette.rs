@@ -0,0 +1,295 @@
+use crate::{Appearance, PaletteQuantizer};
+use ratatui::style::Color;
+
+/// The hue in degrees at the centre of each ANSI colour, in order around the colour wheel, and
+/// its normal and light variants. Terminal palettes and themes tend to use oranges for yellow
+/// and azures for blue, so those centres sit nearer to red and cyan than an even split.
+const HUES: [(f32, Color, Color); 6] = [
+    (0.0, Color::Red, Color::LightRed),
+    (45.0, Color::Yellow, Color::LightYellow),
+    (120.0, Color::Green, Color::LightGreen),
+    (180.0, Color::Cyan, Color::LightCyan),
+    (225.0, Color::Blue, Color::LightBlue),
+    (295.0, Color::Magenta, Color::LightMagenta),
+];
+
+/// The ANSI colours for colours without a hue, from darkest to lightest.
+const GREYS: [Color; 4] = [Color::Black, Color::DarkGray, Color::Gray, Color::White];
+
+/// The difference between the largest and smallest channels, out of 1, below which a colour is
+/// treated as grey.
+const GREY_CHROMA: f32 = 0.15;
+
+/// An assignment of a theme's colours to the 16 ANSI colours, e.g. its green strings to
+/// `Color::Green` and its purple keywords to `Color::Magenta`, for terminals that only have the
+/// 16 colours of their own palette.
+///
+/// Snapping each colour to the nearest ANSI colour on its own, e.g. with a
+/// [PaletteQuantizer], compares it with the default xterm colours rather than the user's
+/// palette, so similar colours often collapse into one and pastel colours into greys. Instead,
+/// the theme's foreground colours are analysed together:
+/// - Colours are grouped by hue into red, yellow, green, cyan, blue and magenta, and the darker of
+///   a group gets the normal ANSI colour and the lighter one the light variant.
+/// - A group with more than two colours hands its colours nearest in hue to empty neighbouring
+///   groups, so the theme's colours stay apart.
+/// - Greys are assigned to black, dark grey, grey and white by lightness, avoiding the colour of
+///   the theme's background, i.e. black for dark themes and white for light ones.
+///
+/// The assignment only depends on the colours the theme uses, not the order of its rules, so it
+/// is stable across runs and small changes to a theme only move the colours they change.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect_tui::{AnsiThemePalette, StyleTranslator};
+///
+/// let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+/// let palette = AnsiThemePalette::new(theme);
+/// // The theme's green strings.
+/// assert_eq!(Some(Color::Green), palette.colour((163, 190, 140)));
+/// let translator = StyleTranslator::new().quantize_colours(palette.quantizer());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnsiThemePalette {
+    /// The ANSI colour for each of the theme's foreground colours, sorted by colour.
+    assignments: Vec<((u8, u8, u8), Color)>,
+}
+
+/// A theme colour along with its hue in degrees and lightness out of 1.
+#[derive(Clone, Copy, Debug)]
+struct Analysed {
+    rgb: (u8, u8, u8),
+    hue: f32,
+    lightness: f32,
+}
+
+impl AnsiThemePalette {
+    /// Assigns the foreground colours of `theme` to the 16 ANSI colours.
+    pub fn new(theme: &syntect::highlighting::Theme) -> Self {
+        let mut colours: Vec<(u8, u8, u8)> = std::iter::once(theme.settings.foreground)
+            .chain(theme.scopes.iter().map(|item| item.style.foreground))
+            .flatten()
+            .filter(|colour| colour.a > 0)
+            .map(|colour| (colour.r, colour.g, colour.b))
+            .collect();
+        colours.sort_unstable();
+        colours.dedup();
+        let appearance = theme
+            .settings
+            .background
+            .map(|colour| Appearance::of_colour((colour.r, colour.g, colour.b)))
+            .unwrap_or(Appearance::Dark);
+
+        let mut groups: [Vec<Analysed>; 6] = Default::default();
+        let mut assignments = Vec::with_capacity(colours.len());
+        for rgb in colours {
+            let (hue, chroma, lightness) = hue_chroma_lightness(rgb);
+            if chroma < GREY_CHROMA {
+                assignments.push((rgb, grey(lightness, appearance)));
+            } else {
+                groups[hue_bucket(hue)].push(Analysed {
+                    rgb,
+                    hue,
+                    lightness,
+                });
+            }
+        }
+        spread(&mut groups);
+        for (group, (_, normal, light)) in groups.iter_mut().zip(HUES) {
+            group.sort_by(|a, b| a.lightness.total_cmp(&b.lightness).then(a.rgb.cmp(&b.rgb)));
+            let (Some(darkest), Some(lightest)) = (group.first(), group.last()) else {
+                continue;
+            };
+            let (darkest, lightest) = (darkest.lightness, lightest.lightness);
+            for (index, colour) in group.iter().enumerate() {
+                let is_light =
+                    index > 0 && (lightest - colour.lightness) <= (colour.lightness - darkest);
+                assignments.push((colour.rgb, if is_light { light } else { normal }));
+            }
+        }
+        assignments.sort_unstable_by_key(|(rgb, _)| *rgb);
+        Self { assignments }
+    }
+
+    /// Returns the ANSI colour each of the theme's foreground colours was assigned, sorted by
+    /// colour.
+    pub fn assignments(&self) -> &[((u8, u8, u8), Color)] {
+        &self.assignments
+    }
+
+    /// Returns the ANSI colour the theme colour `rgb` was assigned, or `None` if the theme's
+    /// rules do not use it.
+    pub fn colour(&self, rgb: (u8, u8, u8)) -> Option<Color> {
+        self.assignments
+            .binary_search_by_key(&rgb, |(colour, _)| *colour)
+            .ok()
+            .map(|index| self.assignments[index].1)
+    }
+
+    /// Returns a quantizer translating each of the theme's colours into its ANSI colour, and
+    /// other colours, e.g. backgrounds, into the ANSI colour of the theme colour nearest to them,
+    /// for use with [StyleTranslator::quantize_colours](crate::StyleTranslator::quantize_colours).
+    pub fn quantizer(&self) -> PaletteQuantizer {
+        PaletteQuantizer::from_entries(
+            self.assignments
+                .iter()
+                .map(|(rgb, colour)| (*colour, *rgb))
+                .collect(),
+        )
+    }
+}
+
+/// Returns the hue in degrees, the chroma out of 1 and the HSL lightness out of 1 of `rgb`.
+fn hue_chroma_lightness((r, g, b): (u8, u8, u8)) -> (f32, f32, f32) {
+    let [r, g, b] = [r, g, b].map(|channel| f32::from(channel) / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    (hue, chroma, (max + min) / 2.0)
+}
+
+/// Returns the index in [HUES] of the hue nearest to `hue`.
+fn hue_bucket(hue: f32) -> usize {
+    (0..HUES.len())
+        .min_by(|a, b| hue_distance(hue, *a).total_cmp(&hue_distance(hue, *b)))
+        .unwrap_or_default()
+}
+
+/// Returns how far `hue` is from the centre of the hue of `bucket`, in degrees.
+fn hue_distance(hue: f32, bucket: usize) -> f32 {
+    let distance = (hue - HUES[bucket].0).rem_euclid(360.0);
+    distance.min(360.0 - distance)
+}
+
+/// Moves colours out of groups with more than two of them into empty neighbouring groups, each
+/// time moving the colour that leans furthest towards an empty neighbour.
+fn spread(groups: &mut [Vec<Analysed>; 6]) {
+    for bucket in 0..groups.len() {
+        while groups[bucket].len() > 2 {
+            let neighbours = [(bucket + 1) % groups.len(), (bucket + 5) % groups.len()];
+            let lean = |colour: &Analysed, neighbour: usize| {
+                hue_distance(colour.hue, neighbour) - hue_distance(colour.hue, bucket)
+            };
+            let nearest = neighbours
+                .into_iter()
+                .filter(|neighbour| groups[*neighbour].is_empty())
+                .flat_map(|neighbour| {
+                    (0..groups[bucket].len()).map(move |index| (index, neighbour))
+                })
+                .min_by(|(a, a_neighbour), (b, b_neighbour)| {
+                    lean(&groups[bucket][*a], *a_neighbour)
+                        .total_cmp(&lean(&groups[bucket][*b], *b_neighbour))
+                });
+            let Some((index, neighbour)) = nearest else {
+                break;
+            };
+            let colour = groups[bucket].remove(index);
+            groups[neighbour].push(colour);
+        }
+    }
+}
+
+/// Returns the grey ANSI colour for a colour without a hue, avoiding the colour of the theme's
+/// background.
+fn grey(lightness: f32, appearance: Appearance) -> Color {
+    let level = ((lightness * GREYS.len() as f32) as usize).min(GREYS.len() - 1);
+    match (appearance, GREYS[level]) {
+        (Appearance::Dark, Color::Black) => Color::DarkGray,
+        (Appearance::Light, Color::White) => Color::Gray,
+        (_, grey) => grey,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Style;
+    use rstest::*;
+
+    /// Builds a theme with a dark or light background whose rules colour `colours`.
+    fn theme(background: Color, colours: &[(u8, u8, u8)]) -> syntect::highlighting::Theme {
+        let options = crate::ReverseOptions::new().default_background(
+            crate::translate_colour_back(background, &crate::Palette::default()),
+        );
+        options
+            .build_theme(colours.iter().enumerate().map(|(index, (r, g, b))| {
+                (
+                    format!("scope{index}"),
+                    Style::new().fg(Color::Rgb(*r, *g, *b)),
+                )
+            }))
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case::string_green((152, 195, 121), Color::Green)]
+    #[case::keyword_purple((198, 120, 221), Color::Magenta)]
+    #[case::function_blue((97, 175, 239), Color::Blue)]
+    #[case::type_yellow((229, 192, 123), Color::Yellow)]
+    #[case::orange((209, 154, 102), Color::Yellow)]
+    #[case::comment_grey((92, 99, 112), Color::DarkGray)]
+    #[case::text_grey((171, 178, 191), Color::Gray)]
+    #[case::near_black((20, 20, 20), Color::DarkGray)]
+    fn assigns_colours_by_hue(#[case] rgb: (u8, u8, u8), #[case] expected: Color) {
+        let palette = AnsiThemePalette::new(&theme(Color::Black, &[rgb]));
+        assert_eq!(Some(expected), palette.colour(rgb));
+    }
+
+    #[test]
+    fn light_themes_avoid_white() {
+        let palette = AnsiThemePalette::new(&theme(Color::White, &[(250, 250, 250)]));
+        assert_eq!(Some(Color::Gray), palette.colour((250, 250, 250)));
+    }
+
+    #[test]
+    fn separates_colours_of_the_same_hue() {
+        let dark_red = (150, 30, 30);
+        let red = (224, 108, 117);
+        let palette = AnsiThemePalette::new(&theme(Color::Black, &[red, dark_red]));
+        assert_eq!(Some(Color::Red), palette.colour(dark_red));
+        assert_eq!(Some(Color::LightRed), palette.colour(red));
+    }
+
+    #[test]
+    fn crowded_hues_spread_into_empty_neighbours() {
+        let reds = [(200, 40, 40), (230, 60, 100), (230, 120, 120)];
+        let palette = AnsiThemePalette::new(&theme(Color::Black, &reds));
+        assert_eq!(Some(Color::Magenta), palette.colour((230, 60, 100)));
+        assert_eq!(Some(Color::Red), palette.colour((200, 40, 40)));
+        assert_eq!(Some(Color::LightRed), palette.colour((230, 120, 120)));
+    }
+
+    #[test]
+    fn ignores_the_order_of_rules() {
+        let colours = [
+            (152, 195, 121),
+            (198, 120, 221),
+            (92, 99, 112),
+            (224, 108, 117),
+        ];
+        let mut reversed = colours;
+        reversed.reverse();
+        assert_eq!(
+            AnsiThemePalette::new(&theme(Color::Black, &colours)),
+            AnsiThemePalette::new(&theme(Color::Black, &reversed))
+        );
+    }
+
+    #[test]
+    fn quantizer_uses_the_assignments() {
+        let colours = [(152, 195, 121), (97, 175, 239)];
+        let quantizer = AnsiThemePalette::new(&theme(Color::Black, &colours)).quantizer();
+        assert_eq!(Color::Green, quantizer.quantize(Color::Rgb(152, 195, 121)));
+        assert_eq!(Color::Blue, quantizer.quantize(Color::Rgb(90, 170, 230)));
+    }
+}
@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+/// The query for the terminal's background colour (OSC 11), followed by a query for its
+/// attributes (DA1), which every terminal answers, so terminals that do not report their
+/// background are found out without waiting for the timeout.
+const BACKGROUND_QUERY: &[u8] = b"\x1b]11;?\x1b\\\x1b[c";
+
+/// The longest answer to [BACKGROUND_QUERY] read before giving up.
+const MAX_RESPONSE_BYTES: usize = 256;
+
+/// The relative luminance at which black and white text contrast equally with a background,
+/// above which backgrounds are light.
+const LIGHT_LUMINANCE: f32 = 0.179;
+
+/// Whether a terminal has a light or a dark background, to pick a theme whose text is readable
+/// on it.
+///
+/// [detect](Appearance::detect) asks the terminal for its background colour, falling back to the
+/// `COLORFGBG` environment variable some terminals set, and [choose](Appearance::choose) picks
+/// between a light and a dark theme, or anything else that comes in both.
+///
+/// # Examples
+/// ```no_run
+/// use syntect::highlighting::ThemeSet;
+/// use syntect_tui::Appearance;
+///
+/// let ts = ThemeSet::load_defaults();
+/// let theme = Appearance::detect()
+///     .unwrap_or(Appearance::Dark)
+///     .choose(&ts.themes["InspiredGitHub"], &ts.themes["base16-ocean.dark"]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Appearance {
+    /// A light background, which needs dark text.
+    Light,
+    /// A dark background, which needs light text.
+    Dark,
+}
+
+impl Appearance {
+    /// Classifies a background colour as light or dark by its relative luminance.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect_tui::Appearance;
+    ///
+    /// assert_eq!(Appearance::Dark, Appearance::of_colour((43, 48, 59)));
+    /// assert_eq!(Appearance::Light, Appearance::of_colour((253, 246, 227)));
+    /// ```
+    pub fn of_colour((r, g, b): (u8, u8, u8)) -> Self {
+        match crate::colour::relative_luminance(r, g, b) > LIGHT_LUMINANCE {
+            true => Appearance::Light,
+            false => Appearance::Dark,
+        }
+    }
+
+    /// Detects the terminal's appearance, from the background colour it reports within 100ms,
+    /// or otherwise from the `COLORFGBG` environment variable, or `None` if neither is
+    /// available.
+    ///
+    /// See [query_background_colour] for when the terminal can be asked.
+    pub fn detect() -> Option<Self> {
+        query_background_colour(Duration::from_millis(100))
+            .map(Self::of_colour)
+            .or_else(Self::from_env)
+    }
+
+    /// Returns the appearance the `COLORFGBG` environment variable gives, e.g. `15;0` for white
+    /// text on a black background, or `None` if it is not set or its background is not one of
+    /// the 16 ANSI colours.
+    pub fn from_env() -> Option<Self> {
+        Self::from_colorfgbg(&std::env::var("COLORFGBG").ok()?)
+    }
+
+    fn from_colorfgbg(value: &str) -> Option<Self> {
+        match value.rsplit(';').next()?.trim().parse::<u8>().ok()? {
+            0..=6 | 8 => Some(Appearance::Dark),
+            7 | 9..=15 => Some(Appearance::Light),
+            _ => None,
+        }
+    }
+
+    /// Returns `light` for a light background and `dark` for a dark one.
+    pub fn choose<T>(self, light: T, dark: T) -> T {
+        match self {
+            Appearance::Light => light,
+            Appearance::Dark => dark,
+        }
+    }
+}
+
+/// Asks the terminal for its background colour with an OSC 11 query, waiting at most `timeout`
+/// for its answer, and returns the colour, or `None` if the terminal did not report it.
+///
+/// The terminal is queried through `/dev/tty`, switching it to raw mode with `stty` for the
+/// duration of the query, so this only works on Unix, and returns `None` elsewhere or when there
+/// is no controlling terminal. The answer is read from the terminal's input, so this must be
+/// called before an application starts reading input events, e.g. before starting its event
+/// loop.
+pub fn query_background_colour(timeout: Duration) -> Option<(u8, u8, u8)> {
+    if std::env::var_os("TERM").is_some_and(|term| term == "dumb") {
+        return None;
+    }
+    parse_background_response(&query_terminal(timeout)?)
+}
+
+#[cfg(unix)]
+fn query_terminal(timeout: Duration) -> Option<Vec<u8>> {
+    use std::io::{Read, Write};
+    let tty = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    let stty = |args: &[&str]| {
+        std::process::Command::new("stty")
+            .args(args)
+            .stdin(tty.try_clone().ok()?)
+            .stderr(std::process::Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+    };
+    let saved = String::from_utf8(stty(&["-g"])?.stdout).ok()?;
+    let deciseconds = timeout.as_millis().div_ceil(100).clamp(1, 255).to_string();
+    stty(&["raw", "-echo", "min", "0", "time", &deciseconds])?;
+    let mut response = Vec::new();
+    if (&tty).write_all(BACKGROUND_QUERY).is_ok() {
+        let mut buffer = [0; 64];
+        while response.len() < MAX_RESPONSE_BYTES && !ends_with_attributes(&response) {
+            match (&tty).read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => response.extend_from_slice(&buffer[..read]),
+            }
+        }
+    }
+    stty(&[saved.trim()]);
+    Some(response)
+}
+
+#[cfg(not(unix))]
+fn query_terminal(_timeout: Duration) -> Option<Vec<u8>> {
+    None
+}
+
+/// Returns whether `response` ends with the terminal's answer to the attributes query, which
+/// comes after its answer to the background query, if it gives one.
+#[cfg(unix)]
+fn ends_with_attributes(response: &[u8]) -> bool {
+    response
+        .windows(3)
+        .rposition(|window| window == b"\x1b[?")
+        .is_some_and(|start| response[start..].ends_with(b"c"))
+}
+
+/// Parses the background colour out of a terminal's answer to an OSC 11 query, e.g.
+/// `\x1b]11;rgb:2b2b/3030/3b3b\x1b\\`, with 1 to 4 hexadecimal digits per channel, and either
+/// terminator.
+///
+/// # Examples
+/// ```
+/// let response = b"\x1b]11;rgb:ffff/f6f6/e3e3\x07";
+/// assert_eq!(
+///     Some((255, 246, 227)),
+///     syntect_tui::parse_background_response(response)
+/// );
+/// ```
+pub fn parse_background_response(response: &[u8]) -> Option<(u8, u8, u8)> {
+    const PREFIX: &[u8] = b"\x1b]11;rgb:";
+    let start = response
+        .windows(PREFIX.len())
+        .position(|window| window == PREFIX)?
+        + PREFIX.len();
+    let rest = &response[start..];
+    let end = rest.iter().position(|byte| matches!(byte, 0x07 | 0x1b))?;
+    let colour = std::str::from_utf8(&rest[..end]).ok()?;
+    let mut channels = colour.split('/').map(|channel| {
+        let digits = u32::try_from(channel.len())
+            .ok()
+            .filter(|len| (1..=4).contains(len))?;
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        let max = 16u32.pow(digits) - 1;
+        u8::try_from((value * 255 + max / 2) / max).ok()
+    });
+    let rgb = (channels.next()??, channels.next()??, channels.next()??);
+    channels.next().is_none().then_some(rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::four_digits(b"\x1b]11;rgb:2b2b/3030/3b3b\x1b\\\x1b[?62;22c", Some((43, 48, 59)))]
+    #[case::two_digits(b"\x1b]11;rgb:ff/80/00\x07", Some((255, 128, 0)))]
+    #[case::one_digit(b"\x1b]11;rgb:f/0/8\x07", Some((255, 0, 136)))]
+    #[case::attributes_only(b"\x1b[?62;22c", None)]
+    #[case::unterminated(b"\x1b]11;rgb:ff/ff/ff", None)]
+    #[case::too_many_digits(b"\x1b]11;rgb:fffff/0/0\x07", None)]
+    #[case::missing_channel(b"\x1b]11;rgb:ff/ff\x07", None)]
+    fn check_parse_background_response(
+        #[case] response: &[u8],
+        #[case] expected: Option<(u8, u8, u8)>,
+    ) {
+        assert_eq!(expected, parse_background_response(response));
+    }
+
+    #[rstest]
+    #[case::black((0, 0, 0), Appearance::Dark)]
+    #[case::dark_grey((64, 64, 64), Appearance::Dark)]
+    #[case::mid_grey((128, 128, 128), Appearance::Light)]
+    #[case::white((255, 255, 255), Appearance::Light)]
+    fn check_of_colour(#[case] colour: (u8, u8, u8), #[case] expected: Appearance) {
+        assert_eq!(expected, Appearance::of_colour(colour));
+    }
+
+    #[rstest]
+    #[case::dark("15;0", Some(Appearance::Dark))]
+    #[case::light("0;15", Some(Appearance::Light))]
+    #[case::with_default("15;default;0", Some(Appearance::Dark))]
+    #[case::grey("0;7", Some(Appearance::Light))]
+    #[case::not_ansi("0;default", None)]
+    #[case::out_of_range("0;16", None)]
+    fn check_from_colorfgbg(#[case] value: &str, #[case] expected: Option<Appearance>) {
+        assert_eq!(expected, Appearance::from_colorfgbg(value));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn attributes_end_the_response() {
+        assert!(!ends_with_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?6"));
+        assert!(ends_with_attributes(b"\x1b]11;rgb:0/0/0\x07\x1b[?62;22c"));
+    }
+
+    #[test]
+    fn choose_picks_by_appearance() {
+        assert_eq!("light", Appearance::Light.choose("light", "dark"));
+        assert_eq!("dark", Appearance::Dark.choose("light", "dark"));
+    }
+}
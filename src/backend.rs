@@ -0,0 +1,220 @@
+//! Abstractions over the crate that converted text is rendered with.
+//!
+//! Conversion always goes through ratatui's types, which are then mapped into the target crate's
+//! types. Implementations are provided for ratatui and, behind the `tui` feature, for the legacy
+//! [tui](https://docs.rs/tui/latest/tui/) crate.
+use crate::{StyleTranslator, SyntectTuiError};
+
+/// A style type that converted syntect styles can be expressed as.
+pub trait TuiStyle: Sized {
+    /// Creates this style from a ratatui style.
+    fn from_ratatui(style: ratatui::style::Style) -> Self;
+}
+
+/// A styled span type that converted line segments can be expressed as.
+pub trait TuiSpan<'a>: Sized {
+    /// The style type of this span.
+    type Style: TuiStyle;
+
+    /// Creates a span from its content and style.
+    fn styled(content: std::borrow::Cow<'a, str>, style: Self::Style) -> Self;
+}
+
+/// A line type that converted lines can be expressed as.
+pub trait TuiLine<'a>: Sized {
+    /// The span type of this line.
+    type Span: TuiSpan<'a>;
+
+    /// Creates a line from its spans.
+    fn from_spans(spans: Vec<Self::Span>) -> Self;
+}
+
+impl TuiStyle for ratatui::style::Style {
+    fn from_ratatui(style: ratatui::style::Style) -> Self {
+        style
+    }
+}
+
+impl<'a> TuiSpan<'a> for ratatui::text::Span<'a> {
+    type Style = ratatui::style::Style;
+
+    fn styled(content: std::borrow::Cow<'a, str>, style: Self::Style) -> Self {
+        ratatui::text::Span::styled(content, style)
+    }
+}
+
+impl<'a> TuiLine<'a> for ratatui::text::Line<'a> {
+    type Span = ratatui::text::Span<'a>;
+
+    fn from_spans(spans: Vec<Self::Span>) -> Self {
+        ratatui::text::Line::from(spans)
+    }
+}
+
+#[cfg(feature = "tui")]
+impl TuiStyle for tui::style::Style {
+    fn from_ratatui(style: ratatui::style::Style) -> Self {
+        tui::style::Style {
+            fg: style.fg.map(tui_colour),
+            bg: style.bg.map(tui_colour),
+            add_modifier: tui::style::Modifier::from_bits_truncate(style.add_modifier.bits()),
+            sub_modifier: tui::style::Modifier::from_bits_truncate(style.sub_modifier.bits()),
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl<'a> TuiSpan<'a> for tui::text::Span<'a> {
+    type Style = tui::style::Style;
+
+    fn styled(content: std::borrow::Cow<'a, str>, style: Self::Style) -> Self {
+        tui::text::Span::styled(content, style)
+    }
+}
+
+#[cfg(feature = "tui")]
+impl<'a> TuiLine<'a> for tui::text::Spans<'a> {
+    type Span = tui::text::Span<'a>;
+
+    fn from_spans(spans: Vec<Self::Span>) -> Self {
+        tui::text::Spans::from(spans)
+    }
+}
+
+#[cfg(feature = "tui")]
+fn tui_colour(colour: ratatui::style::Color) -> tui::style::Color {
+    use ratatui::style::Color;
+    match colour {
+        Color::Reset => tui::style::Color::Reset,
+        Color::Black => tui::style::Color::Black,
+        Color::Red => tui::style::Color::Red,
+        Color::Green => tui::style::Color::Green,
+        Color::Yellow => tui::style::Color::Yellow,
+        Color::Blue => tui::style::Color::Blue,
+        Color::Magenta => tui::style::Color::Magenta,
+        Color::Cyan => tui::style::Color::Cyan,
+        Color::Gray => tui::style::Color::Gray,
+        Color::DarkGray => tui::style::Color::DarkGray,
+        Color::LightRed => tui::style::Color::LightRed,
+        Color::LightGreen => tui::style::Color::LightGreen,
+        Color::LightYellow => tui::style::Color::LightYellow,
+        Color::LightBlue => tui::style::Color::LightBlue,
+        Color::LightMagenta => tui::style::Color::LightMagenta,
+        Color::LightCyan => tui::style::Color::LightCyan,
+        Color::White => tui::style::Color::White,
+        Color::Rgb(r, g, b) => tui::style::Color::Rgb(r, g, b),
+        Color::Indexed(index) => tui::style::Color::Indexed(index),
+    }
+}
+
+impl StyleTranslator {
+    /// Converts a
+    /// [syntect::highlighting::Style](https://docs.rs/syntect/latest/syntect/highlighting/struct.Style.html)
+    /// into any [TuiStyle] according to this translator's options.
+    ///
+    /// # Examples
+    /// ```
+    /// let translator = syntect_tui::StyleTranslator::new();
+    /// let input = syntect::highlighting::Style::default();
+    /// let actual: ratatui::style::Style = translator.translate_style_as(input).unwrap();
+    /// assert_eq!(translator.translate_style(input).unwrap(), actual);
+    /// ```
+    ///
+    /// # Errors
+    /// Can return `SyntectTuiError::UnknownFontStyle` under the same conditions as
+    /// [translate_style](crate::translate_style).
+    pub fn translate_style_as<S: TuiStyle>(
+        &self,
+        syntect_style: syntect::highlighting::Style,
+    ) -> Result<S, SyntectTuiError> {
+        self.translate_style(syntect_style).map(S::from_ratatui)
+    }
+
+    /// Converts a highlighted line segment into any [TuiSpan] according to this translator's
+    /// options.
+    ///
+    /// # Errors
+    /// Can return `SyntectTuiError::UnknownFontStyle` under the same conditions as
+    /// [into_span](crate::into_span).
+    pub fn into_span_as<'a, S: TuiSpan<'a>>(
+        &self,
+        segment: (syntect::highlighting::Style, &'a str),
+    ) -> Result<S, SyntectTuiError> {
+        let span = self.into_span(segment)?;
+        Ok(S::styled(span.content, S::Style::from_ratatui(span.style)))
+    }
+
+    /// Converts a highlighted line into any [TuiLine] according to this translator's options.
+    ///
+    /// See [into_line](crate::into_line) for details.
+    ///
+    /// # Errors
+    /// Can return the same errors as [into_line](crate::into_line).
+    pub fn into_line_as<'a, L, I>(&self, segments: I) -> Result<L, SyntectTuiError>
+    where
+        L: TuiLine<'a>,
+        I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+    {
+        let line = self.into_line(segments)?;
+        Ok(L::from_spans(
+            line.spans
+                .into_iter()
+                .map(|span| {
+                    <L::Span as TuiSpan>::styled(
+                        span.content,
+                        <L::Span as TuiSpan>::Style::from_ratatui(span.style),
+                    )
+                })
+                .collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syntect::highlighting::{Color as SyntectColour, FontStyle, Style as SyntectStyle};
+
+    fn fake_syntect_style() -> SyntectStyle {
+        SyntectStyle {
+            font_style: FontStyle::BOLD | FontStyle::UNDERLINE,
+            foreground: SyntectColour {
+                r: 12,
+                g: 123,
+                b: 234,
+                a: 255,
+            },
+            background: SyntectColour {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn ratatui_line_matches_into_line() {
+        let style = fake_syntect_style();
+        let translator = StyleTranslator::new();
+        let expected = translator.into_line(vec![(style, "fn"), (style, "\n")]);
+        let actual: Result<ratatui::text::Line, _> =
+            translator.into_line_as(vec![(style, "fn"), (style, "\n")]);
+        assert_eq!(expected, actual);
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn tui_span_matches_ratatui_span() {
+        let actual: tui::text::Span = StyleTranslator::new()
+            .into_span_as((fake_syntect_style(), "fn"))
+            .unwrap();
+        let expected = tui::text::Span::styled(
+            "fn",
+            tui::style::Style::default()
+                .fg(tui::style::Color::Rgb(12, 123, 234))
+                .add_modifier(tui::style::Modifier::BOLD | tui::style::Modifier::UNDERLINED),
+        );
+        assert_eq!(expected, actual);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::SyntectTuiError;
+use std::path::{Path, PathBuf};
+
+/// The file bat caches its syntaxes in.
+const SYNTAXES_FILE: &str = "syntaxes.bin";
+/// The file bat caches its themes in.
+const THEMES_FILE: &str = "themes.bin";
+
+/// The themes of a bat `themes.bin`, each kept as its own dump so that bat only loads the theme
+/// it uses.
+#[derive(serde::Deserialize)]
+struct LazyThemeSet {
+    themes: std::collections::BTreeMap<String, LazyTheme>,
+}
+
+#[derive(serde::Deserialize)]
+struct LazyTheme {
+    serialized: Vec<u8>,
+}
+
+/// The syntaxes and themes of [bat](https://github.com/sharkdp/bat), so that a TUI can highlight
+/// the same languages with the same themes as users' bat does.
+///
+/// bat's curated assets are built into its binary, and written to its cache directory along with
+/// the user's own syntaxes and themes by `bat cache --build`, which
+/// [from_cache](BatAssets::from_cache) loads them from. Dumps read some other way, e.g. embedded
+/// with `include_bytes!`, are loaded by [from_dumps](BatAssets::from_dumps). Both the compressed
+/// and uncompressed dump formats are accepted, as are the themes of bat releases from before it
+/// loaded themes lazily.
+///
+/// # Examples
+/// ```no_run
+/// use std::sync::Arc;
+/// use syntect_tui::{BackgroundHighlighter, BatAssets};
+///
+/// let assets = BatAssets::from_cache().unwrap();
+/// let syntax = assets.syntax_set.find_syntax_by_extension("toml").unwrap().clone();
+/// let theme = Arc::new(assets.theme_set.themes["Monokai Extended"].clone());
+/// let highlighter = BackgroundHighlighter::new(&syntax, Arc::new(assets.syntax_set), theme);
+/// ```
+#[derive(Debug)]
+pub struct BatAssets {
+    /// bat's syntaxes.
+    pub syntax_set: syntect::parsing::SyntaxSet,
+    /// bat's themes, keyed by the names bat lists them by.
+    pub theme_set: syntect::highlighting::ThemeSet,
+}
+
+impl BatAssets {
+    /// Returns bat's cache directory: `$BAT_CACHE_PATH` if it is set, otherwise `bat` in the
+    /// platform's cache directory, or `None` if there is no home directory to find it in.
+    pub fn cache_dir() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("BAT_CACHE_PATH").filter(|path| !path.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+        let cache = match std::env::var_os("XDG_CACHE_HOME").filter(|path| !path.is_empty()) {
+            Some(path) => PathBuf::from(path),
+            None if cfg!(target_os = "macos") => {
+                PathBuf::from(std::env::var_os("HOME")?).join("Library/Caches")
+            }
+            None if cfg!(windows) => PathBuf::from(std::env::var_os("LOCALAPPDATA")?),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+        };
+        Some(cache.join("bat"))
+    }
+
+    /// Loads the assets bat has cached in its [cache directory](BatAssets::cache_dir).
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::FileRead` if there is no cache directory or its dumps cannot be
+    /// read, e.g. because `bat cache --build` has not been run, and can return the same errors as
+    /// [BatAssets::from_dumps].
+    pub fn from_cache() -> Result<Self, SyntectTuiError> {
+        let dir = Self::cache_dir().ok_or_else(|| SyntectTuiError::FileRead {
+            path: "bat".to_string(),
+            message: "no cache directory was found".to_string(),
+        })?;
+        Self::from_cache_dir(dir)
+    }
+
+    /// Loads the assets bat has cached in `dir`.
+    ///
+    /// # Errors
+    /// Can return the same errors as [BatAssets::from_cache].
+    pub fn from_cache_dir(dir: impl AsRef<Path>) -> Result<Self, SyntectTuiError> {
+        let read = |file: &str| {
+            let path = dir.as_ref().join(file);
+            std::fs::read(&path).map_err(|error| SyntectTuiError::FileRead {
+                path: path.display().to_string(),
+                message: error.to_string(),
+            })
+        };
+        Self::from_dumps(&read(SYNTAXES_FILE)?, &read(THEMES_FILE)?)
+    }
+
+    /// Loads assets from the contents of bat's `syntaxes.bin` and `themes.bin` dumps.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::AssetLoading` if `syntaxes` is not a dump of syntaxes, and
+    /// `SyntectTuiError::ThemeLoading` if `themes` or any theme in it is not a dump of themes.
+    pub fn from_dumps(syntaxes: &[u8], themes: &[u8]) -> Result<Self, SyntectTuiError> {
+        let syntax_set = from_dump(syntaxes).map_err(|message| SyntectTuiError::AssetLoading {
+            message: format!("invalid syntax dump ({message})"),
+        })?;
+        let theme_set = match from_dump::<LazyThemeSet>(themes) {
+            Ok(lazy) => {
+                let themes = lazy
+                    .themes
+                    .into_iter()
+                    .map(|(name, theme)| {
+                        let theme = from_dump(&theme.serialized).map_err(|message| {
+                            SyntectTuiError::ThemeLoading {
+                                message: format!("invalid dump of theme \"{name}\" ({message})"),
+                            }
+                        })?;
+                        Ok((name, theme))
+                    })
+                    .collect::<Result<_, SyntectTuiError>>()?;
+                syntect::highlighting::ThemeSet { themes }
+            }
+            Err(_) => from_dump(themes).map_err(|message| SyntectTuiError::ThemeLoading {
+                message: format!("invalid theme dump ({message})"),
+            })?,
+        };
+        Ok(Self {
+            syntax_set,
+            theme_set,
+        })
+    }
+}
+
+/// Loads a dump, whether it is compressed or not, in the format of
+/// [syntect::dumps](https://docs.rs/syntect/latest/syntect/dumps/index.html).
+///
+/// Unlike syntect, which trusts its dumps, this never reads past the end of the dump, so that a
+/// dump in another format fails rather than allocating lengths read from the wrong place.
+fn from_dump<T: serde::de::DeserializeOwned>(dump: &[u8]) -> Result<T, String> {
+    use bincode::Options;
+    use std::io::Read;
+    let mut decompressed = Vec::new();
+    let data = match is_compressed(dump) {
+        true => {
+            flate2::read::ZlibDecoder::new(dump)
+                .read_to_end(&mut decompressed)
+                .map_err(|error| error.to_string())?;
+            &decompressed[..]
+        }
+        false => dump,
+    };
+    bincode::options()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(data.len() as u64)
+        .deserialize(data)
+        .map_err(|error| error.to_string())
+}
+
+/// Returns whether `dump` starts with a zlib header, which an uncompressed dump, starting with
+/// the length of a list in little-endian order, does not.
+fn is_compressed(dump: &[u8]) -> bool {
+    match dump {
+        [method, flags, ..] => {
+            method & 0x0F == 8 && u16::from_be_bytes([*method, *flags]) % 31 == 0
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    /// Writes bat's dumps of `syntaxes` and `themes` to a new directory, with the themes in the
+    /// lazy format of bat 0.19 and later if `lazy` is set.
+    fn write_cache(name: &str, lazy: bool) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("syntect-tui-bat-{name}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let syntaxes = SyntaxSet::load_defaults_newlines();
+        syntect::dumps::dump_to_uncompressed_file(&syntaxes, dir.join(SYNTAXES_FILE)).unwrap();
+        let themes = ThemeSet::load_defaults();
+        let themes = if lazy {
+            let serialized: std::collections::BTreeMap<_, _> = themes
+                .themes
+                .iter()
+                .map(|(name, theme)| {
+                    let path = dir.join("theme.bin");
+                    syntect::dumps::dump_to_uncompressed_file(theme, &path).unwrap();
+                    (name.clone(), (std::fs::read(path).unwrap(),))
+                })
+                .collect();
+            syntect::dumps::dump_binary(&(serialized,))
+        } else {
+            syntect::dumps::dump_binary(&themes)
+        };
+        std::fs::write(dir.join(THEMES_FILE), themes).unwrap();
+        dir
+    }
+
+    #[rstest]
+    #[case::lazy_themes("lazy", true)]
+    #[case::theme_set("eager", false)]
+    fn loads_cached_assets(#[case] name: &str, #[case] lazy: bool) {
+        let dir = write_cache(name, lazy);
+        let assets = BatAssets::from_cache_dir(&dir).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(assets.syntax_set.find_syntax_by_extension("rs").is_some());
+        let expected = ThemeSet::load_defaults();
+        assert_eq!(expected.themes.len(), assets.theme_set.themes.len());
+        assert_eq!(
+            expected.themes["base16-ocean.dark"].settings.background,
+            assets.theme_set.themes["base16-ocean.dark"]
+                .settings
+                .background
+        );
+    }
+
+    #[test]
+    fn invalid_dumps_fail() {
+        assert!(matches!(
+            BatAssets::from_dumps(b"not a dump", b""),
+            Err(SyntectTuiError::AssetLoading { .. })
+        ));
+        assert!(matches!(
+            BatAssets::from_cache_dir(std::env::temp_dir().join("syntect-tui-bat-missing")),
+            Err(SyntectTuiError::FileRead { .. })
+        ));
+    }
+}
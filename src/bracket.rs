@@ -0,0 +1,153 @@
+/// The bracket pairs matched by [matching_bracket].
+const PAIRS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+/// Finds the bracket at a cursor position in already converted lines and the bracket matching it,
+/// returning the `(line, column)` positions of both, with columns as byte offsets within a line.
+///
+/// The bracket under the cursor is used if there is one, or else the bracket just before it, as
+/// editors do when the cursor sits after a closing bracket. Round, square and curly brackets are
+/// supported, and only brackets of the same kind are counted when looking for the match, across
+/// lines if needed. Brackets in strings or comments are counted too, as converted lines no longer
+/// carry scopes.
+///
+/// Returns `None` if there is no bracket at the cursor or it is unmatched.
+///
+/// # Examples
+/// ```
+/// use ratatui::text::Line;
+///
+/// let lines = [Line::raw("fn main() {"), Line::raw("    f(x[0]);"), Line::raw("}")];
+/// assert_eq!(
+///     Some(((0, 10), (2, 0))),
+///     syntect_tui::matching_bracket(&lines, (0, 10))
+/// );
+/// // After the closing bracket of `f(x[0])`.
+/// assert_eq!(
+///     Some(((1, 10), (1, 5))),
+///     syntect_tui::matching_bracket(&lines, (1, 11))
+/// );
+/// assert_eq!(None, syntect_tui::matching_bracket(&lines, (1, 2)));
+/// ```
+pub fn matching_bracket(
+    lines: &[ratatui::text::Line],
+    (line, column): (usize, usize),
+) -> Option<((usize, usize), (usize, usize))> {
+    let text = lines.get(line)?.to_string();
+    let bracket_at = |column: usize| {
+        let character = text.get(column..)?.chars().next()?;
+        let pair = PAIRS
+            .iter()
+            .find(|(open, close)| character == *open || character == *close)?;
+        Some((column, character, *pair))
+    };
+    let before = text
+        .get(..column)
+        .and_then(|before| before.char_indices().next_back())
+        .map(|(index, _)| index);
+    let (column, character, (open, close)) =
+        bracket_at(column).or_else(|| before.and_then(bracket_at))?;
+    let mut depth = 0usize;
+    let mut visit = |index: usize, found: char| {
+        if found == character {
+            depth += 1;
+        } else if found == open || found == close {
+            depth -= 1;
+        }
+        (depth == 0).then_some(index)
+    };
+    let found = if character == open {
+        (line..lines.len()).find_map(|index| {
+            let text = lines[index].to_string();
+            let start = if index == line { column } else { 0 };
+            text[start..]
+                .char_indices()
+                .find_map(|(offset, found)| visit(start + offset, found))
+                .map(|offset| (index, offset))
+        })
+    } else {
+        (0..=line).rev().find_map(|index| {
+            let text = lines[index].to_string();
+            let end = if index == line {
+                column + character.len_utf8()
+            } else {
+                text.len()
+            };
+            text[..end]
+                .char_indices()
+                .rev()
+                .find_map(|(offset, found)| visit(offset, found))
+                .map(|offset| (index, offset))
+        })
+    }?;
+    Some(((line, column), found))
+}
+
+/// Patches `style` over the bracket at a cursor position in already converted text and the
+/// bracket matching it, as found by [matching_bracket], splitting spans as needed.
+///
+/// The text is returned unchanged if there is no matched bracket at the cursor.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use ratatui::text::{Line, Span, Text};
+///
+/// let text = Text::from(Line::from(vec![Span::raw("f(x)").blue(), Span::raw(";")]));
+/// let text = syntect_tui::highlight_matching_brackets(text, (0, 1), Style::new().reversed());
+/// assert_eq!(
+///     vec![
+///         Span::raw("f").blue(),
+///         Span::raw("(").blue().reversed(),
+///         Span::raw("x").blue(),
+///         Span::raw(")").blue().reversed(),
+///         Span::raw(";"),
+///     ],
+///     text.lines[0].spans
+/// );
+/// ```
+pub fn highlight_matching_brackets<'a>(
+    mut text: ratatui::text::Text<'a>,
+    cursor: (usize, usize),
+    style: ratatui::style::Style,
+) -> ratatui::text::Text<'a> {
+    if let Some((bracket, matching)) = matching_bracket(&text.lines, cursor) {
+        for (line, column) in [bracket, matching] {
+            text.lines[line] =
+                crate::overlay::select_line(&text.lines[line], column..column + 1, style);
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Line;
+    use rstest::*;
+
+    fn fake_lines() -> Vec<Line<'static>> {
+        vec![
+            Line::raw("a(b[c]{"),
+            Line::raw("  (d) ]"),
+            Line::raw("}日)"),
+        ]
+    }
+
+    #[rstest]
+    #[case::open_across_lines((0, 1), Some(((0, 1), (2, 4))))]
+    #[case::close_across_lines((2, 4), Some(((2, 4), (0, 1))))]
+    #[case::same_line((0, 3), Some(((0, 3), (0, 5))))]
+    #[case::after_close((1, 5), Some(((1, 4), (1, 2))))]
+    #[case::end_of_line((0, 7), Some(((0, 6), (2, 0))))]
+    #[case::after_wide_character((2, 0), Some(((2, 0), (0, 6))))]
+    #[case::unmatched((1, 6), None)]
+    #[case::no_bracket((1, 0), None)]
+    #[case::inside_character((2, 2), None)]
+    #[case::past_last_line((5, 0), None)]
+    fn check_matching_bracket(
+        #[case] cursor: (usize, usize),
+        #[case] expected: Option<((usize, usize), (usize, usize))>,
+    ) {
+        assert_eq!(expected, matching_bracket(&fake_lines(), cursor));
+    }
+}
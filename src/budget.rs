@@ -0,0 +1,215 @@
+use crate::{Metrics, SyntectTuiError};
+use std::time::{Duration, Instant};
+
+/// Highlights a document a frame at a time, converting as many lines as a time budget allows on
+/// each call, e.g. a few milliseconds of each frame, so a render loop keeps a stable frame rate
+/// while the highlighting catches up over a few frames.
+///
+/// The cursor keeps the states its last call ended in, so each call resumes from the line after
+/// the last one it converted, and the lines of every call put together are the same as the whole
+/// document highlighted at once. Every call converts at least one line, so highlighting always
+/// makes progress however small the budget is, and the budget is checked between lines, so a
+/// call overruns it by at most one line. The lines borrow their contents from the document.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::HighlightCursor;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "let x = 1;\n".repeat(1_000);
+/// let mut cursor = HighlightCursor::new(&source, syntax, &ps, &ts.themes["base16-ocean.dark"]);
+/// let mut document = Vec::new();
+/// while !cursor.is_finished() {
+///     document.extend(cursor.highlight_for(Duration::from_millis(3)).unwrap());
+///     // Draw the lines highlighted so far.
+/// }
+/// assert_eq!(1_000, document.len());
+/// assert_eq!(1_000, cursor.position());
+/// ```
+pub struct HighlightCursor<'a> {
+    highlighter: syntect::easy::HighlightLines<'a>,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    /// The lines not yet highlighted.
+    rest: &'a str,
+    position: usize,
+    fallback: crate::LongLineFallback,
+    translator: crate::StyleTranslator,
+    metrics: Metrics,
+    failed: bool,
+}
+
+impl<'a> HighlightCursor<'a> {
+    /// Creates a cursor at the start of `source`, which highlights it using `syntax`, which must
+    /// be one of the syntaxes of `syntax_set`, and `theme`.
+    pub fn new(
+        source: &'a str,
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            highlighter: syntect::easy::HighlightLines::new(syntax, theme),
+            syntax_set,
+            theme,
+            rest: source,
+            position: 0,
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+            metrics: Metrics::new(),
+            failed: false,
+        }
+    }
+
+    /// Returns the index of the next line to highlight, which is the number of lines highlighted
+    /// so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the measurements of the lines highlighted so far.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Returns whether the whole document has been highlighted, or highlighting has failed.
+    pub fn is_finished(&self) -> bool {
+        self.failed || self.rest.is_empty()
+    }
+
+    /// Highlights lines from the cursor until `budget` has elapsed, returning the converted
+    /// lines, or no lines once the cursor [is finished](HighlightCursor::is_finished).
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line of the document they occurred on, after which the cursor is finished.
+    pub fn highlight_for(
+        &mut self,
+        budget: Duration,
+    ) -> Result<Vec<ratatui::text::Line<'a>>, SyntectTuiError> {
+        match Instant::now().checked_add(budget) {
+            Some(deadline) => self.highlight_until(deadline),
+            None => self.highlight_while(|| true),
+        }
+    }
+
+    /// Highlights lines from the cursor until `deadline`, e.g. the time the next frame must be
+    /// drawn by, like [HighlightCursor::highlight_for].
+    ///
+    /// # Errors
+    /// Can return the same errors as [HighlightCursor::highlight_for].
+    pub fn highlight_until(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<Vec<ratatui::text::Line<'a>>, SyntectTuiError> {
+        self.highlight_while(|| Instant::now() < deadline)
+    }
+
+    fn highlight_while(
+        &mut self,
+        mut within_budget: impl FnMut() -> bool,
+    ) -> Result<Vec<ratatui::text::Line<'a>>, SyntectTuiError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("highlight_cursor", position = self.position).entered();
+        let mut highlighted = Vec::new();
+        while !self.is_finished() {
+            let end = self
+                .rest
+                .find('\n')
+                .map_or(self.rest.len(), |index| index + 1);
+            let (line, rest) = self.rest.split_at(end);
+            self.rest = rest;
+            let converted = self
+                .fallback
+                .highlight_line(
+                    &mut self.highlighter,
+                    self.syntax_set,
+                    self.theme,
+                    line,
+                    &self.translator,
+                    Some(&mut self.metrics),
+                )
+                .map_err(|error| error.at_line(self.position));
+            match converted {
+                Ok(converted) => highlighted.push(converted),
+                Err(error) => {
+                    self.failed = true;
+                    return Err(error);
+                }
+            }
+            self.position += 1;
+            if !within_budget() {
+                break;
+            }
+        }
+        Ok(highlighted)
+    }
+}
+
+impl crate::LineConversion for HighlightCursor<'_> {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+impl std::fmt::Debug for HighlightCursor<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HighlightCursor")
+            .field("position", &self.position)
+            .field("failed", &self.failed)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\n*/\nlet c = \"\nd\";\nlet e = 5;";
+
+    #[test]
+    fn resuming_matches_highlighting_at_once() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(SOURCE)
+            .map(|line| crate::highlight_line_to_line(&mut sequential, &ps, theme, line).unwrap())
+            .collect::<Vec<_>>();
+        let mut cursor = HighlightCursor::new(SOURCE, syntax, &ps, theme);
+        let mut resumed = Vec::new();
+        for position in 1..=7 {
+            let lines = cursor.highlight_for(Duration::ZERO).unwrap();
+            assert_eq!(1, lines.len());
+            assert_eq!(position, cursor.position());
+            resumed.extend(lines);
+        }
+        assert!(cursor.is_finished());
+        assert!(cursor.highlight_for(Duration::ZERO).unwrap().is_empty());
+        assert_eq!(expected, resumed);
+    }
+
+    #[test]
+    fn unlimited_budget_highlights_everything() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cursor = HighlightCursor::new(SOURCE, syntax, &ps, &ts.themes["base16-ocean.dark"]);
+        assert_eq!(7, cursor.highlight_for(Duration::MAX).unwrap().len());
+        assert!(cursor.is_finished());
+    }
+}
@@ -0,0 +1,395 @@
+use crate::{CompiledTheme, HighlightedText, StyleTranslator, SyntectTuiError};
+use std::path::{Path, PathBuf};
+
+/// The theme a [HighlighterBuilder] highlights with unless told otherwise.
+const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Sets up a [Highlighter] from syntect's default syntaxes and themes, along with the user's own,
+/// in one place.
+///
+/// Syntaxes are loaded from `.sublime-syntax` files and themes from `.tmTheme` files, recursively,
+/// and themes are named after their file stems, so user themes can replace the defaults by using
+/// their names. The syntaxes expect lines with their line endings, like
+/// [SyntaxSet::load_defaults_newlines](https://docs.rs/syntect/latest/syntect/parsing/struct.SyntaxSet.html#method.load_defaults_newlines).
+///
+/// # Examples
+/// ```no_run
+/// use syntect_tui::{HighlighterBuilder, StyleTranslator};
+///
+/// let highlighter = HighlighterBuilder::new()
+///     .syntax_dir("/home/me/.config/app/syntaxes")
+///     .theme_dir("/home/me/.config/app/themes")
+///     .theme("mine")
+///     .translator(StyleTranslator::from_env())
+///     .build()
+///     .unwrap();
+/// let syntax = highlighter.find_syntax("rs").unwrap();
+/// let text = highlighter.highlight("fn main() {}\n", syntax).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct HighlighterBuilder {
+    syntax_set: Option<syntect::parsing::SyntaxSet>,
+    theme_set: Option<syntect::highlighting::ThemeSet>,
+    syntax_dirs: Vec<PathBuf>,
+    theme_dirs: Vec<PathBuf>,
+    theme: String,
+    translator: StyleTranslator,
+}
+
+impl Default for HighlighterBuilder {
+    fn default() -> Self {
+        Self {
+            syntax_set: None,
+            theme_set: None,
+            syntax_dirs: Vec::new(),
+            theme_dirs: Vec::new(),
+            theme: DEFAULT_THEME.to_string(),
+            translator: StyleTranslator::new(),
+        }
+    }
+}
+
+impl HighlighterBuilder {
+    /// Creates a builder for a highlighter using syntect's default syntaxes and themes,
+    /// highlighting with `base16-ocean.dark` and the default [StyleTranslator].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from `syntax_set` instead of syntect's default syntaxes, e.g. from
+    /// [BatAssets](crate::BatAssets) or a dump the application ships.
+    pub fn syntax_set(mut self, syntax_set: syntect::parsing::SyntaxSet) -> Self {
+        self.syntax_set = Some(syntax_set);
+        self
+    }
+
+    /// Starts from `theme_set` instead of syntect's default themes.
+    pub fn theme_set(mut self, theme_set: syntect::highlighting::ThemeSet) -> Self {
+        self.theme_set = Some(theme_set);
+        self
+    }
+
+    /// Adds the syntaxes of the `.sublime-syntax` files in `dir` and its subdirectories. Can be
+    /// called several times.
+    pub fn syntax_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.syntax_dirs.push(dir.into());
+        self
+    }
+
+    /// Adds the themes of the `.tmTheme` files in `dir` and its subdirectories, replacing themes
+    /// of the same names added before. Can be called several times.
+    pub fn theme_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.theme_dirs.push(dir.into());
+        self
+    }
+
+    /// Sets the name of the theme to highlight with.
+    pub fn theme(mut self, name: impl Into<String>) -> Self {
+        self.theme = name.into();
+        self
+    }
+
+    /// Sets the translator highlighted lines are converted with.
+    pub fn translator(mut self, translator: StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    /// Loads the syntaxes and themes, and compiles the chosen theme.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::AssetLoading` if the syntaxes of a directory cannot be loaded,
+    /// `SyntectTuiError::ThemeLoading` if the themes of a directory cannot be loaded or there is
+    /// no theme with the chosen name, and the errors of [CompiledTheme::new].
+    pub fn build(self) -> Result<Highlighter, SyntectTuiError> {
+        let mut syntax_set = self
+            .syntax_set
+            .unwrap_or_else(syntect::parsing::SyntaxSet::load_defaults_newlines);
+        if !self.syntax_dirs.is_empty() {
+            let mut builder = syntax_set.into_builder();
+            for dir in &self.syntax_dirs {
+                builder.add_from_folder(dir, true).map_err(|error| {
+                    SyntectTuiError::AssetLoading {
+                        message: format!("{} ({error})", dir.display()),
+                    }
+                })?;
+            }
+            syntax_set = builder.build();
+        }
+        let mut theme_set = self
+            .theme_set
+            .unwrap_or_else(syntect::highlighting::ThemeSet::load_defaults);
+        for dir in &self.theme_dirs {
+            theme_set
+                .add_from_folder(dir)
+                .map_err(|error| SyntectTuiError::ThemeLoading {
+                    message: format!("{} ({error})", dir.display()),
+                })?;
+        }
+        let theme =
+            theme_set
+                .themes
+                .get(&self.theme)
+                .ok_or_else(|| SyntectTuiError::ThemeLoading {
+                    message: format!("there is no theme named \"{}\"", self.theme),
+                })?;
+        let compiled = CompiledTheme::new(theme, self.translator)?;
+        Ok(Highlighter {
+            syntax_set,
+            theme_set,
+            theme: self.theme,
+            compiled,
+        })
+    }
+}
+
+/// Syntaxes, themes and a compiled theme to highlight with, set up by a [HighlighterBuilder].
+///
+/// # Examples
+/// ```
+/// use syntect_tui::Highlighter;
+///
+/// let highlighter = Highlighter::builder().theme("InspiredGitHub").build().unwrap();
+/// let syntax = highlighter.find_syntax("rs").unwrap();
+/// let text = highlighter.highlight("fn main() {\n}\n", syntax).unwrap();
+/// assert_eq!(2, text.lines.len());
+/// assert_eq!(Some(ratatui::style::Color::Rgb(255, 255, 255)), text.background);
+/// ```
+#[derive(Debug)]
+pub struct Highlighter {
+    syntax_set: syntect::parsing::SyntaxSet,
+    theme_set: syntect::highlighting::ThemeSet,
+    theme: String,
+    compiled: CompiledTheme,
+}
+
+impl Highlighter {
+    /// Creates a [HighlighterBuilder].
+    pub fn builder() -> HighlighterBuilder {
+        HighlighterBuilder::new()
+    }
+
+    /// Returns the syntaxes.
+    pub fn syntax_set(&self) -> &syntect::parsing::SyntaxSet {
+        &self.syntax_set
+    }
+
+    /// Returns the themes.
+    pub fn theme_set(&self) -> &syntect::highlighting::ThemeSet {
+        &self.theme_set
+    }
+
+    /// Returns the name of the theme highlighted with.
+    pub fn theme_name(&self) -> &str {
+        &self.theme
+    }
+
+    /// Returns the theme highlighted with.
+    pub fn theme(&self) -> &syntect::highlighting::Theme {
+        &self.theme_set.themes[&self.theme]
+    }
+
+    /// Highlights with the theme named `name` from now on, compiling it with the translator of the
+    /// current theme.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeLoading` if there is no theme named `name`, and the errors
+    /// of [CompiledTheme::new]. The current theme is kept.
+    pub fn set_theme(&mut self, name: impl Into<String>) -> Result<(), SyntectTuiError> {
+        let name = name.into();
+        let theme =
+            self.theme_set
+                .themes
+                .get(&name)
+                .ok_or_else(|| SyntectTuiError::ThemeLoading {
+                    message: format!("there is no theme named \"{name}\""),
+                })?;
+        self.compiled = CompiledTheme::new(theme, self.compiled.translator().clone())?;
+        self.theme = name;
+        Ok(())
+    }
+
+    /// Returns the compiled theme lines are converted with.
+    pub fn compiled_theme(&self) -> &CompiledTheme {
+        &self.compiled
+    }
+
+    /// Finds a syntax by its name, e.g. `Rust`, or one of its file extensions, e.g. `rs`.
+    pub fn find_syntax(&self, token: &str) -> Option<&syntect::parsing::SyntaxReference> {
+        self.syntax_set.find_syntax_by_token(token)
+    }
+
+    /// Finds the syntax for the file at `path` by its extension, or else by its first line,
+    /// falling back to plain text.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::FileRead` if the file needs to be read and cannot be.
+    pub fn syntax_for_file(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<&syntect::parsing::SyntaxReference, SyntectTuiError> {
+        let path = path.as_ref();
+        let syntax = self
+            .syntax_set
+            .find_syntax_for_file(path)
+            .map_err(|error| SyntectTuiError::FileRead {
+                path: path.display().to_string(),
+                message: error.to_string(),
+            })?;
+        Ok(syntax.unwrap_or_else(|| self.syntax_set.find_syntax_plain_text()))
+    }
+
+    /// Creates a syntect highlighter for `syntax` and the theme, to highlight a document a line at
+    /// a time with [Highlighter::highlight_line].
+    pub fn highlight_lines(
+        &self,
+        syntax: &syntect::parsing::SyntaxReference,
+    ) -> syntect::easy::HighlightLines<'_> {
+        syntect::easy::HighlightLines::new(syntax, self.theme())
+    }
+
+    /// Highlights the next line of a document with `highlighter`, created by
+    /// [Highlighter::highlight_lines], and converts it.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight_line_to_line](crate::highlight_line_to_line).
+    pub fn highlight_line<'a>(
+        &self,
+        highlighter: &mut syntect::easy::HighlightLines<'_>,
+        line: &'a str,
+    ) -> Result<ratatui::text::Line<'a>, SyntectTuiError> {
+        self.compiled
+            .highlight_line_to_line(highlighter, &self.syntax_set, line)
+    }
+
+    /// Highlights the whole of `source` using `syntax` and converts it, along with the theme's
+    /// background.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight_line_to_line](crate::highlight_line_to_line),
+    /// identifying the line they occurred on.
+    pub fn highlight<'a>(
+        &self,
+        source: &'a str,
+        syntax: &syntect::parsing::SyntaxReference,
+    ) -> Result<HighlightedText<'a>, SyntectTuiError> {
+        let mut highlighter = self.highlight_lines(syntax);
+        let lines = syntect::util::LinesWithEndings::from(source)
+            .enumerate()
+            .map(|(index, line)| {
+                self.highlight_line(&mut highlighter, line)
+                    .map_err(|error| error.at_line(index))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(HighlightedText::new(lines, self.compiled.background()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    const SYNTAX: &str = r#"%YAML 1.2
+---
+name: Greeting
+file_extensions: [greet]
+scope: source.greet
+contexts:
+  main:
+    - match: '\bhello\b'
+      scope: keyword.greet
+"#;
+
+    const THEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+<key>settings</key><array>
+<dict><key>settings</key><dict><key>foreground</key><string>#FFFFFF</string></dict></dict>
+<dict><key>scope</key><string>keyword</string>
+<key>settings</key><dict><key>foreground</key><string>#FF0000</string></dict></dict>
+</array>
+</dict></plist>"#;
+
+    fn user_dirs(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("syntect-tui-builder-{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("syntaxes")).unwrap();
+        std::fs::create_dir_all(dir.join("themes")).unwrap();
+        std::fs::write(dir.join("syntaxes/Greeting.sublime-syntax"), SYNTAX).unwrap();
+        std::fs::write(dir.join("themes/greeting.tmTheme"), THEME).unwrap();
+        dir
+    }
+
+    #[test]
+    fn loads_user_syntaxes_and_themes() {
+        let dir = user_dirs("load");
+        let highlighter = Highlighter::builder()
+            .syntax_dir(dir.join("syntaxes"))
+            .theme_dir(dir.join("themes"))
+            .theme("greeting")
+            .build()
+            .unwrap();
+        assert!(highlighter.find_syntax("rs").is_some());
+        assert!(highlighter
+            .theme_set()
+            .themes
+            .contains_key("InspiredGitHub"));
+        assert_eq!("greeting", highlighter.theme_name());
+        let syntax = highlighter.find_syntax("greet").unwrap();
+        assert_eq!(
+            syntax.name,
+            highlighter.syntax_for_file("hi.greet").unwrap().name
+        );
+        let text = highlighter.highlight("hello world\n", syntax).unwrap();
+        let styles: Vec<_> = text.lines[0]
+            .spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style.fg))
+            .collect();
+        assert_eq!(
+            vec![
+                ("hello", Some(Color::Rgb(255, 0, 0))),
+                (" world", Some(Color::Rgb(255, 255, 255)))
+            ],
+            styles
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn unknown_themes_are_rejected() {
+        let error = Highlighter::builder().theme("missing").build().unwrap_err();
+        assert!(matches!(error, SyntectTuiError::ThemeLoading { .. }));
+    }
+
+    #[test]
+    fn switches_themes() {
+        let mut highlighter = Highlighter::builder()
+            .translator(crate::StyleTranslator::new().plain(true))
+            .build()
+            .unwrap();
+        highlighter.set_theme("InspiredGitHub").unwrap();
+        assert_eq!("InspiredGitHub", highlighter.theme_name());
+        let syntax = highlighter.find_syntax("rs").unwrap();
+        let text = highlighter.highlight("fn main() {}\n", syntax).unwrap();
+        assert!(text.lines[0]
+            .spans
+            .iter()
+            .all(|span| span.style == ratatui::style::Style::default()));
+        let error = highlighter.set_theme("missing").unwrap_err();
+        assert!(matches!(error, SyntectTuiError::ThemeLoading { .. }));
+        assert_eq!("InspiredGitHub", highlighter.theme_name());
+    }
+
+    #[test]
+    fn invalid_syntaxes_are_rejected() {
+        let dir = user_dirs("invalid");
+        std::fs::write(dir.join("syntaxes/Broken.sublime-syntax"), "contexts: [").unwrap();
+        let error = Highlighter::builder()
+            .syntax_dir(dir.join("syntaxes"))
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, SyntectTuiError::AssetLoading { .. }));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,503 @@
+use crate::{HighlightedText, Metrics, SyntectTuiError};
+
+/// A converted line with the highlighter states before and after it.
+#[derive(Clone, Debug)]
+struct CacheEntry {
+    incoming: crate::highlight::LineStates,
+    outgoing: crate::highlight::LineStates,
+    line: ratatui::text::Line<'static>,
+    /// When the line was last visited, which also identifies it in the recency order.
+    last_used: u64,
+    bytes: usize,
+}
+
+impl CacheEntry {
+    /// Estimates the memory taken by an entry for `line`, not counting the heap memory of its
+    /// states, which is small and hard to measure.
+    fn estimated_bytes(line: &ratatui::text::Line<'_>) -> usize {
+        std::mem::size_of::<Self>()
+            + line.spans.len() * std::mem::size_of::<ratatui::text::Span>()
+            + line
+                .spans
+                .iter()
+                .map(|span| span.content.len())
+                .sum::<usize>()
+    }
+}
+
+/// Counts of how a [HighlightCache] has been used since it was created or its counts were
+/// [reset](HighlightCache::reset_stats), e.g. to tune its capacity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CacheStats {
+    /// The number of lines reused rather than highlighted.
+    pub hits: u64,
+    /// The number of lines highlighted because they were not cached.
+    pub misses: u64,
+    /// The number of lines removed to keep within the cache's capacity, or because
+    /// [highlight](HighlightCache::highlight) did not visit them.
+    pub evictions: u64,
+}
+
+/// Caches converted lines between calls, so that re-highlighting a document that has barely
+/// changed, e.g. every frame of an editor, only highlights the lines that did.
+///
+/// Lines are cached by a hash of their contents and the parser and highlighter states they were
+/// highlighted from, rather than by their position. A line is reused if both are unchanged, so an
+/// edit invalidates the edited line and only those after it whose incoming state it changed, e.g.
+/// all of them after opening a block comment, but none after an edit inside a string or inserting
+/// a line between two statements. Lines are still visited in order from the first, as each
+/// line's state depends on the ones before it, but reusing a line is far cheaper than
+/// highlighting it.
+///
+/// [highlight](HighlightCache::highlight) evicts the lines it did not visit, while
+/// [highlight_range](HighlightCache::highlight_range) keeps them, so a cache only used for ranges
+/// grows with every edit unless it is [cleared](HighlightCache::clear) or given a capacity, by
+/// [entries](HighlightCache::max_entries) or [estimated bytes](HighlightCache::max_bytes), beyond
+/// which the least recently used lines are evicted. [stats](HighlightCache::stats) counts hits,
+/// misses and evictions to help tune it.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::HighlightCache;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut cache = HighlightCache::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+/// cache.highlight("let a = 1;\nlet b = 2;\nlet c = 3;\n").unwrap();
+/// assert_eq!(0, cache.reused());
+/// let text = cache.highlight("let a = 1;\nlet b = 20;\nlet c = 3;\n").unwrap();
+/// assert_eq!(2, cache.reused());
+/// assert_eq!("let b = 20;", text.lines[1].to_string());
+/// ```
+#[derive(Clone, Debug)]
+pub struct HighlightCache<'a> {
+    syntax: &'a syntect::parsing::SyntaxReference,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    entries: std::collections::HashMap<u64, Vec<CacheEntry>>,
+    /// The hash of each entry's line by when it was last used.
+    recency: std::collections::BTreeMap<u64, u64>,
+    clock: u64,
+    bytes: usize,
+    max_entries: Option<usize>,
+    max_bytes: Option<usize>,
+    stats: CacheStats,
+    fallback: crate::LongLineFallback,
+    translator: crate::StyleTranslator,
+    metrics: Metrics,
+    reused: usize,
+}
+
+impl<'a> HighlightCache<'a> {
+    /// Creates an empty cache for lines highlighted using `syntax` and `theme`.
+    pub fn new(
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            syntax,
+            syntax_set,
+            theme,
+            entries: std::collections::HashMap::new(),
+            recency: std::collections::BTreeMap::new(),
+            clock: 0,
+            bytes: 0,
+            max_entries: None,
+            max_bytes: None,
+            stats: CacheStats::default(),
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+            metrics: Metrics::new(),
+            reused: 0,
+        }
+    }
+
+    /// Highlights with `theme` from now on, and clears the cache, as every cached line was
+    /// converted with the previous theme.
+    pub fn set_theme(&mut self, theme: &'a syntect::highlighting::Theme) {
+        self.theme = theme;
+        self.clear();
+    }
+
+    /// Highlights `source`, reusing the lines that are unchanged since the last call.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line),
+    /// identifying the line of `source` they occurred on.
+    pub fn highlight(&mut self, source: &str) -> Result<HighlightedText<'static>, SyntectTuiError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("highlight_cached", bytes = source.len()).entered();
+        let mut lines = Vec::new();
+        let start = self.clock;
+        self.highlight_to(source, usize::MAX, |_, line| lines.push(line.clone()))?;
+        let unvisited: Vec<_> = self
+            .recency
+            .range(..start)
+            .map(|(used, hash)| (*used, *hash))
+            .collect();
+        for (used, hash) in unvisited {
+            self.remove(hash, used);
+        }
+        Ok(HighlightedText::new(
+            lines,
+            self.translator.theme_background(self.theme),
+        ))
+    }
+
+    /// Limits the cache to `entries` lines, evicting the least recently used lines beyond them.
+    pub fn max_entries(mut self, entries: usize) -> Self {
+        self.max_entries = Some(entries.max(1));
+        self.evict();
+        self
+    }
+
+    /// Limits the cache to an estimated `bytes` of memory, evicting the least recently used lines
+    /// beyond it. The estimate counts the converted lines but not the heap memory of the parser
+    /// states kept with them.
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self.evict();
+        self
+    }
+
+    /// Highlights the lines of `source` in `lines`, e.g. those visible in a viewport, reusing the
+    /// lines up to them that are unchanged since the last call. Returns fewer lines if `source`
+    /// ends before `lines` does.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight](HighlightCache::highlight).
+    pub fn highlight_range(
+        &mut self,
+        source: &str,
+        lines: std::ops::Range<usize>,
+    ) -> Result<Vec<ratatui::text::Line<'static>>, SyntectTuiError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!(
+            "highlight_cached_range",
+            start = lines.start,
+            end = lines.end
+        )
+        .entered();
+        let mut highlighted = Vec::with_capacity(lines.len());
+        self.highlight_to(source, lines.end, |index, line| {
+            if lines.contains(&index) {
+                highlighted.push(line.clone());
+            }
+        })?;
+        Ok(highlighted)
+    }
+
+    /// Returns the number of lines the last call reused rather than highlighted.
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// Returns the number of cached lines.
+    pub fn len(&self) -> usize {
+        self.recency.len()
+    }
+
+    /// Returns whether no lines are cached.
+    pub fn is_empty(&self) -> bool {
+        self.recency.is_empty()
+    }
+
+    /// Returns the estimated memory taken by the cached lines, as limited by
+    /// [max_bytes](HighlightCache::max_bytes).
+    pub fn estimated_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Returns the counts of hits, misses and evictions.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Resets the counts of hits, misses and evictions to zero.
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    /// Returns the measurements of the lines looked up and highlighted since the cache was
+    /// created or its metrics were [reset](HighlightCache::reset_metrics).
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Resets the cache's metrics.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = Metrics::new();
+    }
+
+    /// Removes all cached lines, e.g. after changing the syntax set.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+        self.bytes = 0;
+    }
+
+    /// Evicts the least recently used lines until the cache is within its capacity.
+    fn evict(&mut self) {
+        while self.max_entries.is_some_and(|max| self.recency.len() > max)
+            || self.max_bytes.is_some_and(|max| self.bytes > max)
+        {
+            let Some((used, hash)) = self.recency.pop_first() else {
+                break;
+            };
+            self.recency.insert(used, hash);
+            self.remove(hash, used);
+        }
+    }
+
+    /// Removes the entry for a line with `hash` last used at `used`.
+    fn remove(&mut self, hash: u64, used: u64) {
+        self.recency.remove(&used);
+        if let Some(entries) = self.entries.get_mut(&hash) {
+            if let Some(position) = entries.iter().position(|entry| entry.last_used == used) {
+                self.bytes -= entries.swap_remove(position).bytes;
+                self.stats.evictions += 1;
+            }
+            if entries.is_empty() {
+                self.entries.remove(&hash);
+            }
+        }
+    }
+
+    /// Highlights the first `end` lines of `source`, reusing cached lines where possible and
+    /// passing every line to `visit`.
+    fn highlight_to(
+        &mut self,
+        source: &str,
+        end: usize,
+        mut visit: impl FnMut(usize, &ratatui::text::Line<'static>),
+    ) -> Result<(), SyntectTuiError> {
+        use std::hash::{Hash, Hasher};
+        self.reused = 0;
+        let mut states = syntect::easy::HighlightLines::new(self.syntax, self.theme).state();
+        for (index, line) in syntect::util::LinesWithEndings::from(source)
+            .take(end)
+            .enumerate()
+        {
+            let mut hasher = std::hash::DefaultHasher::new();
+            line.hash(&mut hasher);
+            let hash = hasher.finish();
+            let entries = self.entries.entry(hash).or_default();
+            let position = match entries.iter().position(|entry| entry.incoming == states) {
+                Some(position) => {
+                    self.reused += 1;
+                    self.stats.hits += 1;
+                    self.metrics.cache_hits += 1;
+                    position
+                }
+                None => {
+                    let (converted, outgoing) = crate::highlight::highlight_from_states(
+                        &states,
+                        self.syntax_set,
+                        self.theme,
+                        line,
+                        &self.fallback,
+                        &self.translator,
+                        Some(&mut self.metrics),
+                    )
+                    .map_err(|error| error.at_line(index))?;
+                    self.stats.misses += 1;
+                    self.metrics.cache_misses += 1;
+                    let bytes = CacheEntry::estimated_bytes(&converted);
+                    self.bytes += bytes;
+                    entries.push(CacheEntry {
+                        incoming: states.clone(),
+                        outgoing,
+                        line: converted,
+                        last_used: self.clock,
+                        bytes,
+                    });
+                    entries.len() - 1
+                }
+            };
+            let entry = &mut entries[position];
+            self.recency.remove(&entry.last_used);
+            entry.last_used = self.clock;
+            self.recency.insert(self.clock, hash);
+            self.clock += 1;
+            states.clone_from(&entry.outgoing);
+            visit(index, &entry.line);
+            self.evict();
+        }
+        Ok(())
+    }
+}
+
+/// Setting either option clears the cache.
+impl crate::LineConversion for HighlightCache<'_> {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self.clear();
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self.clear();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\nlet b = \"x\";\nlet c = 3;\nlet d = 4;\n";
+
+    #[rstest]
+    #[case::unchanged(SOURCE, 4)]
+    #[case::edited_line("let a = 1;\nlet b = 2;\nlet c = 3;\nlet d = 4;\n", 3)]
+    #[case::edited_inside_string("let a = 1;\nlet b = \"xy\";\nlet c = 3;\nlet d = 4;\n", 3)]
+    #[case::opened_comment("let a = 1;\n/* b = \"x\";\nlet c = 3;\nlet d = 4;\n", 1)]
+    #[case::inserted_line("let a = 1;\nlet z = 0;\nlet b = \"x\";\nlet c = 3;\nlet d = 4;\n", 4)]
+    #[case::removed_line("let a = 1;\nlet c = 3;\nlet d = 4;\n", 3)]
+    fn check_reused(#[case] edited: &str, #[case] expected: usize) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cache = HighlightCache::new(syntax, &ps, theme);
+        cache.highlight(SOURCE).unwrap();
+        let cached = cache.highlight(edited).unwrap();
+        assert_eq!(expected, cache.reused());
+        let uncached = HighlightCache::new(syntax, &ps, theme)
+            .highlight(edited)
+            .unwrap();
+        assert_eq!(uncached, cached);
+    }
+
+    #[test]
+    fn switching_themes_clears_the_cache() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["InspiredGitHub"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cache = HighlightCache::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+        cache.highlight(SOURCE).unwrap();
+        cache.set_theme(theme);
+        assert!(cache.is_empty());
+        let text = cache.highlight(SOURCE).unwrap();
+        assert_eq!(0, cache.reused());
+        let uncached = HighlightCache::new(syntax, &ps, theme)
+            .highlight(SOURCE)
+            .unwrap();
+        assert_eq!(uncached, text);
+    }
+
+    #[test]
+    fn highlight_range_returns_only_the_range() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cache = HighlightCache::new(syntax, &ps, theme);
+        let lines = cache.highlight_range(SOURCE, 1..3).unwrap();
+        let all = cache.highlight(SOURCE).unwrap();
+        assert_eq!(all.lines[1..3], lines[..]);
+        assert_eq!(3, cache.reused());
+        assert_eq!(4, cache.len());
+        assert_eq!(1, cache.highlight_range(SOURCE, 3..10).unwrap().len());
+    }
+
+    #[rstest]
+    #[case::unlimited(None, 4, 0)]
+    #[case::fits(Some(4), 4, 0)]
+    #[case::two(Some(2), 2, 2)]
+    #[case::zero_keeps_one(Some(0), 1, 3)]
+    fn check_max_entries(#[case] max: Option<usize>, #[case] len: usize, #[case] evictions: u64) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cache = HighlightCache::new(syntax, &ps, theme);
+        if let Some(max) = max {
+            cache = cache.max_entries(max);
+        }
+        let cached = cache.highlight_range(SOURCE, 0..4).unwrap();
+        assert_eq!(len, cache.len());
+        assert_eq!(evictions, cache.stats().evictions);
+        let uncached = HighlightCache::new(syntax, &ps, theme)
+            .highlight(SOURCE)
+            .unwrap();
+        assert_eq!(uncached.lines, cached);
+    }
+
+    #[test]
+    fn evicts_least_recently_used_lines() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cache = HighlightCache::new(syntax, &ps, theme).max_entries(3);
+        cache.highlight_range(SOURCE, 0..2).unwrap();
+        cache
+            .highlight_range("let a = 1;\nlet z = 0;\n", 0..2)
+            .unwrap();
+        // Evicts line 1 rather than line 0, which was added first but used since.
+        cache
+            .highlight_range("let a = 1;\nlet y = 0;\n", 0..2)
+            .unwrap();
+        cache.reset_stats();
+        cache.highlight_range(SOURCE, 0..2).unwrap();
+        assert_eq!(
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 1
+            },
+            cache.stats()
+        );
+    }
+
+    #[test]
+    fn max_bytes_bounds_estimated_bytes() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cache = HighlightCache::new(syntax, &ps, theme);
+        cache.highlight(SOURCE).unwrap();
+        let bytes = cache.estimated_bytes();
+        assert!(bytes > 0);
+        let mut cache = cache.max_bytes(bytes / 2);
+        assert!(cache.estimated_bytes() <= bytes / 2);
+        assert!(cache.len() < 4);
+        cache.highlight(SOURCE).unwrap();
+        assert!(cache.estimated_bytes() <= bytes / 2);
+        cache.clear();
+        assert_eq!(0, cache.estimated_bytes());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn counts_hits_and_misses() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut cache = HighlightCache::new(syntax, &ps, theme);
+        cache.highlight(SOURCE).unwrap();
+        cache
+            .highlight("let a = 1;\nlet b = 2;\nlet c = 3;\nlet d = 4;\n")
+            .unwrap();
+        assert_eq!(
+            CacheStats {
+                hits: 3,
+                misses: 5,
+                evictions: 1
+            },
+            cache.stats()
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag shared between the code that starts highlighting a document and the code doing it,
+/// which stops the highlighting before its next line once it is cancelled, e.g. when the user
+/// switches to another file.
+///
+/// Clones of a token share its flag, so one clone can be kept to cancel the highlighting while
+/// another is handed to it. Highlighting that is cancelled returns
+/// `SyntectTuiError::Cancelled` with the line it stopped at, or, in the background, stops sending
+/// lines and releases the document.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::{CancellationToken, HighlightChunks, SyntectTuiError};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "let x = 1;\n".repeat(100);
+/// let token = CancellationToken::new();
+/// let mut chunks = HighlightChunks::new(&source, syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .chunk_lines(10)
+///     .cancel_with(&token);
+/// assert!(chunks.next().unwrap().is_ok());
+/// token.cancel();
+/// assert_eq!(Some(Err(SyntectTuiError::Cancelled { line: 10 })), chunks.next());
+/// assert!(chunks.next().is_none());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    ancestors: Vec<Arc<AtomicBool>>,
+}
+
+impl CancellationToken {
+    /// Creates a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the highlighting that the token, or any of its clones, was handed to.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        std::iter::once(&self.cancelled)
+            .chain(&self.ancestors)
+            .any(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Returns a token that is cancelled along with this one or any token this one is a child of,
+    /// but can also be cancelled on its own without cancelling them, e.g. when the worker it was
+    /// handed to is dropped.
+    pub(crate) fn child(&self) -> Self {
+        let mut ancestors = self.ancestors.clone();
+        ancestors.push(self.cancelled.clone());
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            ancestors,
+        }
+    }
+
+    /// Returns `SyntectTuiError::Cancelled` at line `index` if the token has been cancelled.
+    pub(crate) fn check(&self, index: usize) -> Result<(), crate::SyntectTuiError> {
+        match self.is_cancelled() {
+            true => Err(crate::SyntectTuiError::Cancelled { line: index }),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        assert_eq!(Ok(()), clone.check(3));
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert_eq!(
+            Err(crate::SyntectTuiError::Cancelled { line: 3 }),
+            clone.check(3)
+        );
+    }
+
+    #[test]
+    fn child_is_cancelled_with_parent_only() {
+        let token = CancellationToken::new();
+        let child = token.child();
+        child.cancel();
+        assert!(child.is_cancelled());
+        assert!(!token.is_cancelled());
+        let child = token.child();
+        token.cancel();
+        assert!(child.is_cancelled());
+    }
+
+    #[test]
+    fn grandchild_is_cancelled_with_any_ancestor() {
+        let token = CancellationToken::new();
+        let child = token.child();
+        let grandchild = child.child();
+        grandchild.cancel();
+        assert!(!child.is_cancelled());
+        let grandchild = child.child();
+        token.cancel();
+        assert!(grandchild.is_cancelled());
+    }
+}
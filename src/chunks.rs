@@ -0,0 +1,227 @@
+use crate::{CancellationToken, Metrics, SyntectTuiError};
+
+/// Highlights a document a chunk of lines at a time, e.g. to write a large file's converted lines
+/// out as they are produced rather than holding all of them at once.
+///
+/// Each chunk carries on from the states the chunk before it ended in, so the chunks put together
+/// are the same as the whole document highlighted at once, while only one chunk's lines are held
+/// at a time. The lines borrow their contents from the document. The iterator ends after the last
+/// chunk, or after the first error, including stopping because a
+/// [CancellationToken] it was handed has been cancelled.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::HighlightChunks;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "/*\n".to_string() + &"let x = 1;\n".repeat(4) + "*/\n";
+/// let chunks = HighlightChunks::new(&source, syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .chunk_lines(4)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(vec![4, 2], chunks.iter().map(Vec::len).collect::<Vec<_>>());
+/// assert_eq!(chunks[0][1].spans[0].style, chunks[1][0].spans[0].style);
+/// ```
+pub struct HighlightChunks<'a> {
+    highlighter: syntect::easy::HighlightLines<'a>,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    lines: std::iter::Enumerate<syntect::util::LinesWithEndings<'a>>,
+    chunk_lines: usize,
+    cancellation: Option<&'a CancellationToken>,
+    fallback: crate::LongLineFallback,
+    translator: crate::StyleTranslator,
+    metrics: Metrics,
+    failed: bool,
+}
+
+/// Highlights `source` using `syntax` and `theme` in chunks of 1000 lines.
+///
+/// See [HighlightChunks] for details.
+pub fn highlight_chunks<'a>(
+    source: &'a str,
+    syntax: &'a syntect::parsing::SyntaxReference,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+) -> HighlightChunks<'a> {
+    HighlightChunks::new(source, syntax, syntax_set, theme)
+}
+
+impl<'a> HighlightChunks<'a> {
+    /// Creates an iterator over the chunks of 1000 lines of `source`, highlighted using `syntax`,
+    /// which must be one of the syntaxes of `syntax_set`, and `theme`.
+    pub fn new(
+        source: &'a str,
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            highlighter: syntect::easy::HighlightLines::new(syntax, theme),
+            syntax_set,
+            theme,
+            lines: syntect::util::LinesWithEndings::from(source).enumerate(),
+            chunk_lines: 1000,
+            cancellation: None,
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+            metrics: Metrics::new(),
+            failed: false,
+        }
+    }
+
+    /// Sets the number of lines in each chunk, which bounds how many converted lines are held at
+    /// once.
+    pub fn chunk_lines(mut self, lines: usize) -> Self {
+        self.chunk_lines = lines.max(1);
+        self
+    }
+
+    /// Stops highlighting before the next line once `token` has been cancelled, ending with
+    /// `SyntectTuiError::Cancelled` and the index of that line.
+    pub fn cancel_with(mut self, token: &'a CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns the measurements of the lines highlighted so far.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+}
+
+impl crate::LineConversion for HighlightChunks<'_> {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+impl<'a> Iterator for HighlightChunks<'a> {
+    /// The lines of a chunk, or the errors of
+    /// [highlight_line_to_line](crate::highlight_line_to_line), identifying the line of the
+    /// document they occurred on.
+    type Item = Result<Vec<ratatui::text::Line<'a>>, SyntectTuiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("highlight_chunk", lines = self.chunk_lines).entered();
+        let mut chunk = Vec::with_capacity(self.chunk_lines);
+        for (index, line) in self.lines.by_ref().take(self.chunk_lines) {
+            let converted = match self.cancellation {
+                Some(token) => token.check(index),
+                None => Ok(()),
+            }
+            .and_then(|()| {
+                self.fallback.highlight_line(
+                    &mut self.highlighter,
+                    self.syntax_set,
+                    self.theme,
+                    line,
+                    &self.translator,
+                    Some(&mut self.metrics),
+                )
+            });
+            match converted {
+                Ok(converted) => chunk.push(converted),
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error.at_line(index)));
+                }
+            }
+        }
+        (!chunk.is_empty()).then_some(Ok(chunk))
+    }
+}
+
+impl std::iter::FusedIterator for HighlightChunks<'_> {}
+
+impl std::fmt::Debug for HighlightChunks<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HighlightChunks")
+            .field("chunk_lines", &self.chunk_lines)
+            .field("cancellation", &self.cancellation)
+            .field("failed", &self.failed)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineConversion;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\n*/\nlet c = \"\nd\";\nlet e = 5;";
+
+    #[rstest]
+    #[case::one_per_chunk(1, vec![1; 7])]
+    #[case::uneven(3, vec![3, 3, 1])]
+    #[case::even(7, vec![7])]
+    #[case::single_chunk(100, vec![7])]
+    fn matches_highlighting_at_once(#[case] chunk_lines: usize, #[case] sizes: Vec<usize>) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(SOURCE)
+            .map(|line| crate::highlight_line_to_line(&mut sequential, &ps, theme, line).unwrap())
+            .collect::<Vec<_>>();
+        let chunks = HighlightChunks::new(SOURCE, syntax, &ps, theme)
+            .chunk_lines(chunk_lines)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(sizes, chunks.iter().map(Vec::len).collect::<Vec<_>>());
+        assert_eq!(expected, chunks.concat());
+    }
+
+    #[test]
+    fn converts_with_the_translator() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let translator = crate::StyleTranslator::new().quantize_colours(
+            crate::PaletteQuantizer::new((16..=255).map(ratatui::style::Color::Indexed)),
+        );
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(SOURCE)
+            .map(|line| {
+                translator
+                    .highlight_line_to_line(&mut sequential, &ps, theme, line)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let lines = HighlightChunks::new(SOURCE, syntax, &ps, theme)
+            .translator(translator)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+            .concat();
+        assert_eq!(expected, lines);
+        assert_eq!(Some(ratatui::style::Color::Indexed(236)), lines[0].style.bg);
+    }
+
+    #[test]
+    fn empty_source_has_no_chunks() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut chunks = highlight_chunks("", syntax, &ps, &ts.themes["base16-ocean.dark"]);
+        assert!(chunks.next().is_none());
+    }
+}
@@ -0,0 +1,362 @@
+//! Colour arithmetic shared by the configurable translation layer.
+
+/// Post-processing applied to every RGB colour produced by a
+/// [StyleTranslator](crate::StyleTranslator).
+///
+/// Adjustments are applied in HSL space, which lets applications brighten, desaturate or recolour
+/// a theme without editing its `.tmTheme` file. The default value leaves colours unchanged.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+///
+/// let adjustment = syntect_tui::ColourAdjustment { brightness: 1.5, ..Default::default() };
+/// assert_eq!(Color::Rgb(0, 0, 192), adjustment.apply(Color::Rgb(0, 0, 128)));
+/// assert_eq!(Color::Red, adjustment.apply(Color::Red));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColourAdjustment {
+    /// Multiplier applied to a colour's lightness, where `1.0` leaves it unchanged.
+    pub brightness: f32,
+    /// Multiplier applied to a colour's saturation, where `1.0` leaves it unchanged and `0.0`
+    /// produces greys.
+    pub saturation: f32,
+    /// Rotation applied to a colour's hue, in degrees.
+    pub hue_shift: f32,
+}
+
+impl Default for ColourAdjustment {
+    fn default() -> Self {
+        Self {
+            brightness: 1.0,
+            saturation: 1.0,
+            hue_shift: 0.0,
+        }
+    }
+}
+
+impl ColourAdjustment {
+    /// Applies this adjustment to `colour`.
+    ///
+    /// Only `Color::Rgb` values are adjusted, all other colours are returned unchanged.
+    pub fn apply(&self, colour: ratatui::style::Color) -> ratatui::style::Color {
+        match colour {
+            ratatui::style::Color::Rgb(r, g, b) if *self != Self::default() => {
+                let (hue, saturation, lightness) = rgb_to_hsl(r, g, b);
+                let (r, g, b) = hsl_to_rgb(
+                    (hue + self.hue_shift).rem_euclid(360.0),
+                    (saturation * self.saturation).clamp(0.0, 1.0),
+                    (lightness * self.brightness).clamp(0.0, 1.0),
+                );
+                ratatui::style::Color::Rgb(r, g, b)
+            }
+            other => other,
+        }
+    }
+}
+
+/// The colour space in which colour math (e.g. blending translucent colours or finding the
+/// nearest colour of a palette) is performed.
+///
+/// Averaging sRGB values directly is cheap but makes blends look muddy and darker than they
+/// should, since sRGB values are not proportional to light intensity. Decoding them into
+/// linear light first gives perceptually more accurate results.
+///
+/// # Examples
+/// ```
+/// use syntect_tui::ColourSpace;
+///
+/// assert_eq!(0.0, ColourSpace::Srgb.to_linear(0));
+/// assert!(ColourSpace::Srgb.to_linear(128) > 0.5);
+/// assert!(ColourSpace::Linear.to_linear(128) < 0.25);
+/// assert_eq!(128, ColourSpace::Linear.from_linear(ColourSpace::Linear.to_linear(128)));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColourSpace {
+    /// Colour math is performed directly on sRGB values. This is the default.
+    #[default]
+    Srgb,
+    /// Colour math is performed in linear light, decoding sRGB values using the sRGB transfer
+    /// function.
+    Linear,
+    /// Colour math is performed in linear light, decoding values using a simple power law with
+    /// the given gamma (e.g. `2.2`).
+    Gamma(f32),
+}
+
+impl ColourSpace {
+    /// Converts an 8-bit channel value into this colour space, ranging from `0.0` to `1.0`.
+    pub fn to_linear(self, channel: u8) -> f32 {
+        let channel = f32::from(channel) / 255.0;
+        match self {
+            ColourSpace::Srgb => channel,
+            ColourSpace::Linear if channel <= 0.04045 => channel / 12.92,
+            ColourSpace::Linear => ((channel + 0.055) / 1.055).powf(2.4),
+            ColourSpace::Gamma(gamma) => channel.powf(gamma),
+        }
+    }
+
+    /// Converts a value in this colour space, ranging from `0.0` to `1.0`, back into an 8-bit
+    /// channel value.
+    pub fn from_linear(self, value: f32) -> u8 {
+        let value = value.clamp(0.0, 1.0);
+        let channel = match self {
+            ColourSpace::Srgb => value,
+            ColourSpace::Linear if value <= 0.0031308 => value * 12.92,
+            ColourSpace::Linear => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+            ColourSpace::Gamma(gamma) => value.powf(1.0 / gamma),
+        };
+        (channel * 255.0).round() as u8
+    }
+}
+
+/// Composites `colour` over an opaque `backdrop` using `colour`'s alpha value, mixing channels in
+/// `space`.
+///
+/// The returned colour is always opaque.
+pub(crate) fn blend(
+    colour: syntect::highlighting::Color,
+    backdrop: syntect::highlighting::Color,
+    space: ColourSpace,
+) -> syntect::highlighting::Color {
+    let mix = |channel: u8, backdrop_channel: u8| -> u8 {
+        if space == ColourSpace::Srgb {
+            let alpha = u16::from(colour.a);
+            let mixed =
+                u16::from(channel) * alpha + u16::from(backdrop_channel) * (255 - alpha) + 127;
+            return (mixed / 255) as u8;
+        }
+        let alpha = f32::from(colour.a) / 255.0;
+        space.from_linear(
+            space.to_linear(channel) * alpha + space.to_linear(backdrop_channel) * (1.0 - alpha),
+        )
+    };
+    syntect::highlighting::Color {
+        r: mix(colour.r, backdrop.r),
+        g: mix(colour.g, backdrop.g),
+        b: mix(colour.b, backdrop.b),
+        a: 255,
+    }
+}
+
+/// Converts a syntect colour into a ratatui RGB colour, ignoring its alpha value.
+pub(crate) fn rgb(colour: syntect::highlighting::Color) -> ratatui::style::Color {
+    ratatui::style::Color::Rgb(colour.r, colour.g, colour.b)
+}
+
+/// Converts an RGB colour into hue (in degrees), saturation and lightness.
+pub(crate) fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
+    let (r, g, b) = (
+        f32::from(r) / 255.0,
+        f32::from(g) / 255.0,
+        f32::from(b) / 255.0,
+    );
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+/// Converts hue (in degrees), saturation and lightness into an RGB colour.
+pub(crate) fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = lightness - chroma / 2.0;
+    let (r, g, b) = match hue {
+        h if h < 60.0 => (chroma, x, 0.0),
+        h if h < 120.0 => (x, chroma, 0.0),
+        h if h < 180.0 => (0.0, chroma, x),
+        h if h < 240.0 => (0.0, x, chroma),
+        h if h < 300.0 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |value: f32| ((value + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Calculates the [WCAG relative luminance](https://www.w3.org/TR/WCAG21/#dfn-relative-luminance)
+/// of an RGB colour.
+pub(crate) fn relative_luminance(r: u8, g: u8, b: u8) -> f32 {
+    let linear = |channel: u8| {
+        let channel = f32::from(channel) / 255.0;
+        if channel <= 0.03928 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// Calculates the [WCAG contrast ratio](https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio)
+/// between two RGB colours, which ranges from `1.0` to `21.0`.
+pub(crate) fn contrast_ratio(first: (u8, u8, u8), second: (u8, u8, u8)) -> f32 {
+    let first = relative_luminance(first.0, first.1, first.2);
+    let second = relative_luminance(second.0, second.1, second.2);
+    (first.max(second) + 0.05) / (first.min(second) + 0.05)
+}
+
+/// Lightens or darkens `foreground` until its contrast ratio against `background` is at least
+/// `minimum`, changing its lightness as little as possible.
+///
+/// The foreground is moved towards whichever of black or white contrasts best with the
+/// background. If even that extreme does not meet `minimum`, the extreme is returned.
+pub(crate) fn enforce_contrast(
+    foreground: (u8, u8, u8),
+    background: (u8, u8, u8),
+    minimum: f32,
+) -> (u8, u8, u8) {
+    if contrast_ratio(foreground, background) >= minimum {
+        return foreground;
+    }
+    let (hue, saturation, lightness) = rgb_to_hsl(foreground.0, foreground.1, foreground.2);
+    let target =
+        if contrast_ratio((255, 255, 255), background) >= contrast_ratio((0, 0, 0), background) {
+            1.0
+        } else {
+            0.0
+        };
+    let extreme = hsl_to_rgb(hue, saturation, target);
+    if contrast_ratio(extreme, background) < minimum {
+        return extreme;
+    }
+    let (mut low, mut high) = (0.0_f32, 1.0_f32);
+    for _ in 0..16 {
+        let step = (low + high) / 2.0;
+        let candidate = hsl_to_rgb(hue, saturation, lightness + (target - lightness) * step);
+        if contrast_ratio(candidate, background) >= minimum {
+            high = step;
+        } else {
+            low = step;
+        }
+    }
+    hsl_to_rgb(hue, saturation, lightness + (target - lightness) * high)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::Color;
+    use syntect::highlighting::Color as SyntectColour;
+
+    fn fake_syntect_colour(r: u8, g: u8, b: u8, a: u8) -> SyntectColour {
+        SyntectColour { r, g, b, a }
+    }
+
+    #[rstest]
+    #[case::opaque(
+        fake_syntect_colour(200, 100, 0, 255),
+        fake_syntect_colour(200, 100, 0, 255)
+    )]
+    #[case::half(
+        fake_syntect_colour(200, 100, 0, 128),
+        fake_syntect_colour(150, 100, 50, 255)
+    )]
+    #[case::transparent(
+        fake_syntect_colour(200, 100, 0, 0),
+        fake_syntect_colour(100, 100, 100, 255)
+    )]
+    fn check_blend(#[case] input: SyntectColour, #[case] expected: SyntectColour) {
+        let backdrop = fake_syntect_colour(100, 100, 100, 255);
+        assert_eq!(expected, blend(input, backdrop, ColourSpace::Srgb));
+    }
+
+    #[rstest]
+    #[case::srgb(ColourSpace::Srgb, 128)]
+    #[case::linear(ColourSpace::Linear, 188)]
+    #[case::gamma(ColourSpace::Gamma(2.2), 186)]
+    fn check_blend_colour_space(#[case] space: ColourSpace, #[case] expected: u8) {
+        let white = fake_syntect_colour(255, 255, 255, 128);
+        let black = fake_syntect_colour(0, 0, 0, 255);
+        let actual = blend(white, black, space);
+        assert_eq!(
+            fake_syntect_colour(expected, expected, expected, 255),
+            actual
+        );
+    }
+
+    #[rstest]
+    #[case::srgb(ColourSpace::Srgb)]
+    #[case::linear(ColourSpace::Linear)]
+    #[case::gamma(ColourSpace::Gamma(2.2))]
+    fn colour_space_round_trip(#[case] space: ColourSpace) {
+        for channel in 0..=u8::MAX {
+            assert_eq!(channel, space.from_linear(space.to_linear(channel)));
+        }
+    }
+
+    #[rstest]
+    #[case::black(0, 0, 0)]
+    #[case::white(255, 255, 255)]
+    #[case::red(255, 0, 0)]
+    #[case::teal(12, 123, 234)]
+    #[case::olive(123, 234, 12)]
+    #[case::purple(150, 30, 200)]
+    fn hsl_round_trip(#[case] r: u8, #[case] g: u8, #[case] b: u8) {
+        let (hue, saturation, lightness) = rgb_to_hsl(r, g, b);
+        assert_eq!((r, g, b), hsl_to_rgb(hue, saturation, lightness));
+    }
+
+    #[rstest]
+    #[case::black_white((0, 0, 0), (255, 255, 255), 21.0)]
+    #[case::identical((12, 123, 234), (12, 123, 234), 1.0)]
+    fn check_contrast_ratio(
+        #[case] first: (u8, u8, u8),
+        #[case] second: (u8, u8, u8),
+        #[case] expected: f32,
+    ) {
+        assert!((expected - contrast_ratio(first, second)).abs() < 0.01);
+    }
+
+    #[rstest]
+    #[case::already_sufficient((255, 255, 255), (0, 0, 0), 4.5)]
+    #[case::lightens_on_dark((40, 40, 60), (30, 30, 30), 4.5)]
+    #[case::darkens_on_light((200, 200, 180), (250, 250, 250), 4.5)]
+    #[case::near_maximum((128, 128, 128), (128, 128, 128), 5.0)]
+    fn enforce_contrast_meets_minimum(
+        #[case] foreground: (u8, u8, u8),
+        #[case] background: (u8, u8, u8),
+        #[case] minimum: f32,
+    ) {
+        let actual = enforce_contrast(foreground, background, minimum);
+        assert!(contrast_ratio(actual, background) >= minimum);
+    }
+
+    #[test]
+    fn enforce_contrast_falls_back_to_extreme() {
+        let actual = enforce_contrast((100, 100, 100), (128, 128, 128), 21.0);
+        assert_eq!((0, 0, 0), actual);
+    }
+
+    #[rstest]
+    #[case::identity(ColourAdjustment::default(), Color::Rgb(12, 123, 234))]
+    #[case::brightness(
+        ColourAdjustment { brightness: 0.5, ..Default::default() },
+        Color::Rgb(6, 62, 117)
+    )]
+    #[case::saturation(
+        ColourAdjustment { saturation: 0.0, ..Default::default() },
+        Color::Rgb(123, 123, 123)
+    )]
+    #[case::hue_shift(
+        ColourAdjustment { hue_shift: 120.0, ..Default::default() },
+        Color::Rgb(234, 12, 123)
+    )]
+    fn check_colour_adjustment(#[case] adjustment: ColourAdjustment, #[case] expected: Color) {
+        assert_eq!(expected, adjustment.apply(Color::Rgb(12, 123, 234)));
+    }
+}
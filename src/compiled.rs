@@ -0,0 +1,242 @@
+use crate::{StyleTranslator, SyntectTuiError};
+
+/// The colours and font style of a syntect style, which identify its translation.
+type StyleKey = (
+    syntect::highlighting::Color,
+    syntect::highlighting::Color,
+    u8,
+);
+
+fn key(style: &syntect::highlighting::Style) -> StyleKey {
+    (style.foreground, style.background, style.font_style.bits())
+}
+
+/// A theme whose styles have been translated into ratatui styles up front.
+///
+/// Compiling walks the theme's rules once, resolving the style each of their scope selectors
+/// highlights text with, and translates every distinct style with a [StyleTranslator]. Converting
+/// highlighted lines then looks their styles up in the table, without the translator's locking or
+/// colour math, and only translates the rare styles that combine several rules, e.g. a bold rule
+/// with a coloured one. Switching colour modes, e.g. from true colour to 256 colours, only
+/// [recompiles](CompiledTheme::recompile) the table's few dozen styles.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+/// use syntect::easy::HighlightLines;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::{CompiledTheme, PaletteQuantizer, StyleTranslator};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let theme = &ts.themes["base16-ocean.dark"];
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let compiled = CompiledTheme::new(theme, StyleTranslator::new()).unwrap();
+/// assert!(compiled.len() > 10);
+/// let mut h = HighlightLines::new(syntax, theme);
+/// let line = compiled.highlight_line_to_line(&mut h, &ps, "fn main() {}\n").unwrap();
+/// assert!(matches!(line.spans[0].style.fg, Some(Color::Rgb(..))));
+///
+/// let indexed = StyleTranslator::new().quantize_colours(PaletteQuantizer::new(
+///     (16..=255).map(Color::Indexed),
+/// ));
+/// let compiled = compiled.recompile(indexed).unwrap();
+/// let mut h = HighlightLines::new(syntax, theme);
+/// let line = compiled.highlight_line_to_line(&mut h, &ps, "fn main() {}\n").unwrap();
+/// assert!(matches!(line.spans[0].style.fg, Some(Color::Indexed(..))));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CompiledTheme {
+    translator: StyleTranslator,
+    /// The distinct styles resolved from the theme, in the order they were found.
+    resolved: Vec<syntect::highlighting::Style>,
+    table: std::collections::HashMap<StyleKey, ratatui::style::Style>,
+    theme_background: Option<syntect::highlighting::Color>,
+    background: Option<ratatui::style::Color>,
+}
+
+impl CompiledTheme {
+    /// Compiles `theme`, translating its styles using `translator`.
+    ///
+    /// # Errors
+    /// Returns the errors of [StyleTranslator::translate_style] for any style of the theme.
+    pub fn new(
+        theme: &syntect::highlighting::Theme,
+        translator: StyleTranslator,
+    ) -> Result<Self, SyntectTuiError> {
+        let highlighter = syntect::highlighting::Highlighter::new(theme);
+        let mut resolved = vec![highlighter.get_default()];
+        for item in &theme.scopes {
+            for selector in &item.scope.selectors {
+                let style = highlighter.style_for_stack(selector.path.as_slice());
+                if !resolved.contains(&style) {
+                    resolved.push(style);
+                }
+            }
+        }
+        Self::translate(translator, resolved, theme.settings.background)
+    }
+
+    /// Translates the theme's styles again using `translator`, e.g. to switch colour modes,
+    /// without walking the theme again.
+    ///
+    /// # Errors
+    /// Can return the same errors as [CompiledTheme::new].
+    pub fn recompile(&self, translator: StyleTranslator) -> Result<Self, SyntectTuiError> {
+        Self::translate(translator, self.resolved.clone(), self.theme_background)
+    }
+
+    fn translate(
+        translator: StyleTranslator,
+        resolved: Vec<syntect::highlighting::Style>,
+        theme_background: Option<syntect::highlighting::Color>,
+    ) -> Result<Self, SyntectTuiError> {
+        let background = theme_background
+            .filter(|_| !translator.is_plain())
+            .and_then(|colour| translator.translate_colour(colour));
+        let table = resolved
+            .iter()
+            .map(|style| Ok((key(style), translator.translate_style(*style)?)))
+            .collect::<Result<_, SyntectTuiError>>()?;
+        Ok(Self {
+            translator,
+            resolved,
+            table,
+            theme_background,
+            background,
+        })
+    }
+
+    /// Returns the number of styles in the table.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Returns whether the table has no styles, which is never the case as it always has the
+    /// theme's default style.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Returns the translator the table was compiled with.
+    pub fn translator(&self) -> &StyleTranslator {
+        &self.translator
+    }
+
+    /// Returns the translated background colour of the theme, if it has one.
+    pub fn background(&self) -> Option<ratatui::style::Color> {
+        self.background
+    }
+
+    /// Translates `style`, looking it up in the table or translating it if it is not there.
+    ///
+    /// # Errors
+    /// Can return the same errors as [StyleTranslator::translate_style] for styles that are not
+    /// in the table.
+    pub fn translate_style(
+        &self,
+        style: syntect::highlighting::Style,
+    ) -> Result<ratatui::style::Style, SyntectTuiError> {
+        match self.table.get(&key(&style)) {
+            Some(translated) => Ok(*translated),
+            None => self.translator.translate_style(style),
+        }
+    }
+
+    /// Converts a highlighted line, looking its styles up in the table, according to the
+    /// translator's options.
+    ///
+    /// # Errors
+    /// Can return the same errors as [StyleTranslator::into_line].
+    pub fn into_line<'a, I>(&self, segments: I) -> Result<ratatui::text::Line<'a>, SyntectTuiError>
+    where
+        I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+    {
+        let mut line = ratatui::text::Line::default();
+        self.translator
+            .convert_line_with(segments, &mut line, |style| self.translate_style(style))?;
+        Ok(line)
+    }
+
+    /// Highlights a single line and converts it, looking its styles up in the table, with the
+    /// theme's background, like [highlight_line_to_line](crate::highlight_line_to_line).
+    /// `highlighter` must highlight with the theme that was compiled.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight_line_to_line](crate::highlight_line_to_line).
+    pub fn highlight_line_to_line<'a>(
+        &self,
+        highlighter: &mut syntect::easy::HighlightLines<'_>,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        line: &'a str,
+    ) -> Result<ratatui::text::Line<'a>, SyntectTuiError> {
+        let segments = highlighter
+            .highlight_line(line, syntax_set)
+            .map_err(|error| SyntectTuiError::HighlightFailed {
+                message: error.to_string(),
+            })?;
+        let line = self.into_line(segments)?;
+        Ok(match self.background {
+            Some(background) => line.style(ratatui::style::Style::new().bg(background)),
+            None => line,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "/// Docs\n#[derive(Debug)]\npub struct Wow { hi: u64 }\nlet s = \"x\";\n";
+
+    #[rstest]
+    #[case::default(StyleTranslator::new())]
+    #[case::quantized(StyleTranslator::new().quantize_colours(crate::PaletteQuantizer::new(
+        (0..16).map(Color::Indexed),
+    )))]
+    #[case::merged(StyleTranslator::new().merge_adjacent_spans(true).expand_tabs(4))]
+    #[case::plain(StyleTranslator::new().plain(true))]
+    fn matches_translator(
+        #[case] translator: StyleTranslator,
+        #[values("base16-ocean.dark", "InspiredGitHub", "Solarized (light)")] theme: &str,
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes[theme];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let compiled = CompiledTheme::new(theme, translator.clone()).unwrap();
+        let mut expected = syntect::easy::HighlightLines::new(syntax, theme);
+        let mut actual = syntect::easy::HighlightLines::new(syntax, theme);
+        for line in syntect::util::LinesWithEndings::from(SOURCE) {
+            assert_eq!(
+                translator
+                    .highlight_line_to_line(&mut expected, &ps, theme, line)
+                    .unwrap(),
+                compiled
+                    .highlight_line_to_line(&mut actual, &ps, line)
+                    .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn recompiling_matches_compiling() {
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let indexed = StyleTranslator::new()
+            .quantize_colours(crate::PaletteQuantizer::new((0..16).map(Color::Indexed)));
+        let recompiled = CompiledTheme::new(theme, StyleTranslator::new())
+            .unwrap()
+            .recompile(indexed.clone())
+            .unwrap();
+        let compiled = CompiledTheme::new(theme, indexed).unwrap();
+        assert_eq!(compiled.table, recompiled.table);
+        assert!(matches!(recompiled.background(), Some(Color::Indexed(_))));
+        assert_eq!(compiled.background(), recompiled.background());
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{LongLineFallback, StyleTranslator};
+
+/// The conversion options shared by the document-level highlighters, e.g.
+/// [HighlightChunks](crate::HighlightChunks) or [LazyHighlighter](crate::LazyHighlighter).
+///
+/// Every document-level highlighter converts the lines it highlights with a [StyleTranslator],
+/// a default one unless another is set, and leaves lines longer than 16 KiB unhighlighted as
+/// described in [LongLineFallback]. Highlighters that keep converted lines or parser states
+/// around say what setting these options does to them.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::{HighlightChunks, LineConversion, LongLineFallback, StyleTranslator};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "let a = 1;\n".repeat(2) + &"x".repeat(100);
+/// let lines = HighlightChunks::new(&source, syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .translator(StyleTranslator::new().plain(true))
+///     .long_lines(LongLineFallback::new().max_line_bytes(50).style(Style::new().dim()))
+///     .flatten()
+///     .flatten()
+///     .collect::<Vec<_>>();
+/// assert!(lines[0].spans.iter().all(|span| span.style == Style::new()));
+/// assert_eq!(Style::new().dim(), lines[2].spans[0].style);
+/// ```
+pub trait LineConversion: Sized {
+    /// Sets the translator lines are converted with, e.g. one that
+    /// [quantizes](StyleTranslator::quantize_colours) colours for the terminal or one
+    /// [from the environment](StyleTranslator::from_env) that honours `NO_COLOR`.
+    fn translator(self, translator: StyleTranslator) -> Self;
+
+    /// Sets how lines too long to highlight are converted, instead of leaving lines longer than
+    /// 16 KiB unstyled.
+    fn long_lines(self, fallback: LongLineFallback) -> Self;
+}
@@ -0,0 +1,110 @@
+use crate::SyntectTuiError;
+
+/// A wrapper that marks a syntect value for conversion via the standard [From] and [TryFrom]
+/// traits.
+///
+/// Rust's orphan rules forbid this crate from implementing conversion traits directly between
+/// syntect and ratatui types, since it owns neither. Wrapping the syntect value in `Syntect` makes
+/// the conversions available to generic code, `into()`/`try_into()` and `?`, while delegating to
+/// the free functions of this crate.
+///
+/// # Examples
+/// ```
+/// use syntect_tui::Syntect;
+///
+/// fn convert(
+///     style: syntect::highlighting::Style,
+/// ) -> Result<ratatui::style::Style, syntect_tui::SyntectTuiError> {
+///     let style: ratatui::style::Style = Syntect(style).try_into()?;
+///     Ok(style)
+/// }
+///
+/// let input = syntect::highlighting::Style {
+///     foreground: syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 },
+///     background: syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 0 },
+///     font_style: syntect::highlighting::FontStyle::BOLD
+/// };
+/// assert_eq!(syntect_tui::translate_style(input), convert(input));
+///
+/// let colour: Option<ratatui::style::Color> = Syntect(input.foreground).into();
+/// assert_eq!(Some(ratatui::style::Color::Rgb(255, 0, 0)), colour);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Syntect<T>(pub T);
+
+impl TryFrom<Syntect<syntect::highlighting::Style>> for ratatui::style::Style {
+    type Error = SyntectTuiError;
+
+    fn try_from(value: Syntect<syntect::highlighting::Style>) -> Result<Self, Self::Error> {
+        crate::translate_style(value.0)
+    }
+}
+
+impl TryFrom<Syntect<syntect::highlighting::FontStyle>> for ratatui::style::Modifier {
+    type Error = SyntectTuiError;
+
+    fn try_from(value: Syntect<syntect::highlighting::FontStyle>) -> Result<Self, Self::Error> {
+        crate::translate_font_style(value.0)
+    }
+}
+
+impl From<Syntect<syntect::highlighting::Color>> for Option<ratatui::style::Color> {
+    fn from(value: Syntect<syntect::highlighting::Color>) -> Self {
+        crate::translate_colour(value.0)
+    }
+}
+
+impl<'a> TryFrom<Syntect<(syntect::highlighting::Style, &'a str)>> for ratatui::text::Span<'a> {
+    type Error = SyntectTuiError;
+
+    fn try_from(
+        value: Syntect<(syntect::highlighting::Style, &'a str)>,
+    ) -> Result<Self, Self::Error> {
+        crate::into_span(value.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Modifier;
+    use syntect::highlighting::{Color as SyntectColour, FontStyle, Style as SyntectStyle};
+
+    #[test]
+    fn conversions_match_free_functions() {
+        let style = SyntectStyle {
+            font_style: FontStyle::ITALIC,
+            foreground: SyntectColour {
+                r: 12,
+                g: 123,
+                b: 234,
+                a: 255,
+            },
+            background: SyntectColour {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            },
+        };
+        assert_eq!(
+            crate::translate_style(style),
+            ratatui::style::Style::try_from(Syntect(style))
+        );
+        assert_eq!(
+            crate::translate_colour(style.background),
+            Syntect(style.background).into()
+        );
+        assert_eq!(
+            crate::into_span((style, "text")),
+            ratatui::text::Span::try_from(Syntect((style, "text")))
+        );
+    }
+
+    #[test]
+    fn font_style_conversion_error() {
+        let input = unsafe { FontStyle::from_bits_unchecked(254) };
+        let expected = Err(SyntectTuiError::UnknownFontStyle { bits: 254 });
+        assert_eq!(expected, Modifier::try_from(Syntect(input)));
+    }
+}
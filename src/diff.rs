@@ -0,0 +1,394 @@
+use crate::colour::rgb;
+use crate::settings::scope_style;
+use crate::{HighlightedText, SyntectTuiError};
+
+const DEFAULT_ADDED: syntect::highlighting::Color = syntect::highlighting::Color {
+    r: 0,
+    g: 255,
+    b: 0,
+    a: 255,
+};
+const DEFAULT_REMOVED: syntect::highlighting::Color = syntect::highlighting::Color {
+    r: 255,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+const TINT_ALPHA: u8 = 0x40;
+
+/// Highlights a unified diff, e.g. the output of `git diff`, as the code it changes rather than
+/// as a diff.
+///
+/// The content of each hunk is highlighted with the syntax of the file being changed, detected
+/// from the `---`/`+++` file headers by extension unless [set explicitly](DiffHighlighter::syntax).
+/// Removed and context lines are highlighted as the old file and added and context lines as the
+/// new one, so each side keeps its own parse state within a hunk. Added and removed lines are
+/// then tinted by blending a translucent colour over their backgrounds, including any token
+/// backgrounds, and keep their `+`/`-` marker in the opaque colour. The tints default to the
+/// foreground colours the theme gives the `markup.inserted` and `markup.deleted` scopes (or green
+/// and red if it has no rules for them), and file and hunk headers are styled like the theme's
+/// `meta.diff.header` and `meta.diff.range`.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let theme = &ts.themes["base16-ocean.dark"];
+/// let diff = "\
+/// --- a/src/main.rs
+/// +++ b/src/main.rs
+/// @@ -1,2 +1,2 @@
+///  fn main() {
+/// -    println!(\"hello\");
+/// +    println!(\"world\");
+/// ";
+/// let text = syntect_tui::DiffHighlighter::new(&ps, theme).highlight(diff).unwrap();
+/// assert_eq!("+    println!(\"world\");", text.lines[5].to_string());
+/// assert_eq!(Some(Color::Rgb(43, 48, 59)), text.lines[3].style.bg);
+/// assert_ne!(text.lines[3].style.bg, text.lines[5].style.bg);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DiffHighlighter<'a> {
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    syntax: Option<&'a syntect::parsing::SyntaxReference>,
+    added: Option<syntect::highlighting::Color>,
+    removed: Option<syntect::highlighting::Color>,
+}
+
+/// The kind of a line of a unified diff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DiffLine {
+    /// A file or hunk header, or any other line outside a hunk.
+    Header,
+    /// A line present in both files.
+    Context,
+    /// A line only present in the old file.
+    Removed,
+    /// A line only present in the new file.
+    Added,
+}
+
+/// Highlights a unified diff with syntaxes from `syntax_set` and colours from `theme`.
+///
+/// See [DiffHighlighter] for details.
+///
+/// # Errors
+/// Can return the same errors as [DiffHighlighter::highlight].
+pub fn highlight_diff<'a>(
+    diff: &'a str,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+) -> Result<HighlightedText<'a>, SyntectTuiError> {
+    DiffHighlighter::new(syntax_set, theme).highlight(diff)
+}
+
+impl<'a> DiffHighlighter<'a> {
+    /// Creates a diff highlighter that uses syntaxes from `syntax_set` and colours from `theme`.
+    pub fn new(
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            syntax_set,
+            theme,
+            syntax: None,
+            added: None,
+            removed: None,
+        }
+    }
+
+    /// Highlights every hunk with `syntax` instead of detecting it from the file headers.
+    pub fn syntax(mut self, syntax: &'a syntect::parsing::SyntaxReference) -> Self {
+        self.syntax = Some(syntax);
+        self
+    }
+
+    /// Sets the colour blended over added lines, using its alpha value.
+    pub fn added_tint(mut self, colour: syntect::highlighting::Color) -> Self {
+        self.added = Some(colour);
+        self
+    }
+
+    /// Sets the colour blended over removed lines, using its alpha value.
+    pub fn removed_tint(mut self, colour: syntect::highlighting::Color) -> Self {
+        self.removed = Some(colour);
+        self
+    }
+
+    /// Highlights `diff`, returning one line per line of the diff.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line) and
+    /// [translate_style](crate::translate_style), identifying the line of the diff they occurred
+    /// on.
+    pub fn highlight<'b>(&self, diff: &'b str) -> Result<HighlightedText<'b>, SyntectTuiError> {
+        let lines = self
+            .highlight_lines(diff)?
+            .into_iter()
+            .map(|(_, line)| line)
+            .collect();
+        Ok(HighlightedText::from_theme(lines, self.theme))
+    }
+
+    /// Returns the colours blended over added and removed lines.
+    pub(crate) fn tints(&self) -> (syntect::highlighting::Color, syntect::highlighting::Color) {
+        let (added, removed) = self.markers();
+        (
+            self.added.unwrap_or(syntect::highlighting::Color {
+                a: TINT_ALPHA,
+                ..added
+            }),
+            self.removed.unwrap_or(syntect::highlighting::Color {
+                a: TINT_ALPHA,
+                ..removed
+            }),
+        )
+    }
+
+    /// Returns the theme the diff is highlighted with.
+    #[cfg(feature = "widget")]
+    pub(crate) fn theme(&self) -> &'a syntect::highlighting::Theme {
+        self.theme
+    }
+
+    /// Returns the opaque colours of the added and removed line markers.
+    fn markers(&self) -> (syntect::highlighting::Color, syntect::highlighting::Color) {
+        let marker = |scope: &str, fallback| {
+            scope_style(self.theme, scope).map_or(fallback, |style| style.foreground)
+        };
+        (
+            marker("markup.inserted", DEFAULT_ADDED),
+            marker("markup.deleted", DEFAULT_REMOVED),
+        )
+    }
+
+    /// Highlights `diff` like [highlight](DiffHighlighter::highlight), along with the kind of
+    /// each line.
+    pub(crate) fn highlight_lines<'b>(
+        &self,
+        diff: &'b str,
+    ) -> Result<Vec<(DiffLine, ratatui::text::Line<'b>)>, SyntectTuiError> {
+        let (added_marker, removed_marker) = self.markers();
+        let (added, removed) = self.tints();
+        let background = self.theme.settings.background.map(rgb);
+        let header_style = match scope_style(self.theme, "meta.diff.header") {
+            Some(style) => crate::translate_style(style)?,
+            None => ratatui::style::Style::new(),
+        };
+        let range_style = match scope_style(self.theme, "meta.diff.range") {
+            Some(style) => crate::translate_style(style)?,
+            None => ratatui::style::Style::new(),
+        };
+        let mut syntax = self
+            .syntax
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+        let mut old = syntect::easy::HighlightLines::new(syntax, self.theme);
+        let mut new = syntect::easy::HighlightLines::new(syntax, self.theme);
+        let mut remaining: (usize, usize) = (0, 0);
+        let mut lines = Vec::new();
+        for (index, line) in syntect::util::LinesWithEndings::from(diff).enumerate() {
+            let in_hunk = remaining != (0, 0);
+            let (kind, converted) = match line.chars().next() {
+                Some(marker @ ('+' | '-')) if in_hunk => {
+                    let (kind, highlighter, tint, colour) = if marker == '+' {
+                        remaining.1 = remaining.1.saturating_sub(1);
+                        (DiffLine::Added, &mut new, added, added_marker)
+                    } else {
+                        remaining.0 = remaining.0.saturating_sub(1);
+                        (DiffLine::Removed, &mut old, removed, removed_marker)
+                    };
+                    let mut converted = crate::highlight_line_to_line(
+                        highlighter,
+                        self.syntax_set,
+                        self.theme,
+                        &line[1..],
+                    )
+                    .map_err(|error| error.at_line(index))?;
+                    crate::overlay::tint_line(&mut converted, tint, background);
+                    let marker_style = ratatui::style::Style::new().fg(rgb(colour));
+                    converted
+                        .spans
+                        .insert(0, ratatui::text::Span::styled(&line[..1], marker_style));
+                    (kind, converted)
+                }
+                Some(' ') if in_hunk => {
+                    remaining = (remaining.0.saturating_sub(1), remaining.1.saturating_sub(1));
+                    old.highlight_line(&line[1..], self.syntax_set)
+                        .map_err(|error| {
+                            SyntectTuiError::HighlightFailed {
+                                message: error.to_string(),
+                            }
+                            .at_line(index)
+                        })?;
+                    let mut converted = crate::highlight_line_to_line(
+                        &mut new,
+                        self.syntax_set,
+                        self.theme,
+                        &line[1..],
+                    )
+                    .map_err(|error| error.at_line(index))?;
+                    converted
+                        .spans
+                        .insert(0, ratatui::text::Span::raw(&line[..1]));
+                    (DiffLine::Context, converted)
+                }
+                _ => {
+                    let style = if let Some(lengths) = hunk_lengths(line) {
+                        remaining = lengths;
+                        old = syntect::easy::HighlightLines::new(syntax, self.theme);
+                        new = syntect::easy::HighlightLines::new(syntax, self.theme);
+                        range_style
+                    } else {
+                        if let Some(path) = file_header_path(line).filter(|_| self.syntax.is_none())
+                        {
+                            syntax = syntax_for_path(self.syntax_set, path).unwrap_or(syntax);
+                        }
+                        header_style
+                    };
+                    let content = line.trim_end_matches(['\n', '\r']);
+                    (
+                        DiffLine::Header,
+                        ratatui::text::Line::from(ratatui::text::Span::styled(content, style)),
+                    )
+                }
+            };
+            let converted = match background {
+                Some(background) if converted.style.bg.is_none() => {
+                    converted.style(ratatui::style::Style::new().bg(background))
+                }
+                _ => converted,
+            };
+            lines.push((kind, converted));
+        }
+        Ok(lines)
+    }
+}
+
+/// Returns the number of old and new lines in the hunk whose header is `line`, or `None` if it is
+/// not a hunk header.
+fn hunk_lengths(line: &str) -> Option<(usize, usize)> {
+    let mut ranges = line.strip_prefix("@@ ")?.split_whitespace();
+    let length = |range: &str| match range.split_once(',') {
+        Some((_, length)) => length.parse().ok(),
+        None => range.parse::<usize>().ok().map(|_| 1),
+    };
+    let old = length(ranges.next()?.strip_prefix('-')?)?;
+    let new = length(ranges.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+/// Returns the path named by a `---` or `+++` file header, without its `a/` or `b/` prefix, or
+/// `None` if `line` is not a file header or names `/dev/null`.
+fn file_header_path(line: &str) -> Option<&str> {
+    let path = line
+        .strip_prefix("+++ ")
+        .or_else(|| line.strip_prefix("--- "))?;
+    let path = path.split('\t').next()?.trim_end();
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    (path != "/dev/null").then_some(path)
+}
+
+/// Finds the syntax for `path` by its extension, or else by its file name (e.g. `Makefile`).
+pub(crate) fn syntax_for_path<'a>(
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    path: &str,
+) -> Option<&'a syntect::parsing::SyntaxReference> {
+    let path = std::path::Path::new(path);
+    let by = |name: Option<&std::ffi::OsStr>| {
+        name.and_then(|name| name.to_str())
+            .and_then(|name| syntax_set.find_syntax_by_extension(name))
+    };
+    by(path.extension()).or_else(|| by(path.file_name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case::counts("@@ -1,2 +3,4 @@ fn main() {\n", Some((2, 4)))]
+    #[case::implicit_counts("@@ -1 +1 @@\n", Some((1, 1)))]
+    #[case::empty_side("@@ -0,0 +1,3 @@\n", Some((0, 3)))]
+    #[case::not_a_header("@@@ -1,2 -1,2 +1,3 @@@\n", None)]
+    #[case::code(" @@ -1,2 +1,2 @@\n", None)]
+    fn check_hunk_lengths(#[case] line: &str, #[case] expected: Option<(usize, usize)>) {
+        assert_eq!(expected, hunk_lengths(line));
+    }
+
+    #[rstest]
+    #[case::new_file("+++ b/src/lib.rs\n", Some("src/lib.rs"))]
+    #[case::old_file("--- a/src/lib.rs\t2024-01-01\n", Some("src/lib.rs"))]
+    #[case::no_prefix("+++ lib.rs\n", Some("lib.rs"))]
+    #[case::dev_null("--- /dev/null\n", None)]
+    #[case::not_a_header("diff --git a/x b/x\n", None)]
+    fn check_file_header_path(#[case] line: &str, #[case] expected: Option<&str>) {
+        assert_eq!(expected, file_header_path(line));
+    }
+
+    #[rstest]
+    #[case::extension("src/main.rs", Some("Rust"))]
+    #[case::file_name("Makefile", Some("Makefile"))]
+    #[case::unknown("notes.unknown", None)]
+    fn check_syntax_for_path(#[case] path: &str, #[case] expected: Option<&str>) {
+        let ps = syntect::parsing::SyntaxSet::load_defaults_newlines();
+        assert_eq!(
+            expected,
+            syntax_for_path(&ps, path).map(|syntax| syntax.name.as_str())
+        );
+    }
+
+    #[test]
+    fn highlight_tints_changed_lines() {
+        let ps = syntect::parsing::SyntaxSet::load_defaults_newlines();
+        let theme = syntect::highlighting::Theme {
+            settings: syntect::highlighting::ThemeSettings {
+                background: Some(syntect::highlighting::Color::BLACK),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let diff = "--- a/x.rs\n+++ b/x.rs\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n+++ d\n";
+        let text = DiffHighlighter::new(&ps, &theme)
+            .removed_tint(syntect::highlighting::Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            })
+            .highlight(diff)
+            .unwrap();
+        let backgrounds: Vec<_> = text.lines.iter().map(|line| line.style.bg).collect();
+        use ratatui::style::Color;
+        assert_eq!(
+            vec![
+                Some(Color::Rgb(0, 0, 0)),
+                Some(Color::Rgb(0, 0, 0)),
+                Some(Color::Rgb(0, 0, 0)),
+                Some(Color::Rgb(0, 0, 0)),
+                Some(Color::Rgb(255, 0, 0)),
+                Some(Color::Rgb(0, 64, 0)),
+                Some(Color::Rgb(0, 0, 0)),
+            ],
+            backgrounds
+        );
+        assert_eq!(
+            vec!["-", "b"],
+            text.lines[4]
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("+++ d", text.lines[6].to_string());
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::{Arc, RwLock};
+
+use crate::InternedText;
+
+/// A converted document shared by several viewports, e.g. the panes of a split window, which may
+/// draw it from different threads while it is being highlighted.
+///
+/// Viewports take a [snapshot](HighlightedDocument::snapshot) of the document to draw, which is a
+/// shared, immutable [InternedText] that later changes do not affect, so drawing never waits on
+/// highlighting and never sees a half-updated document. Updates replace the document, or change
+/// it in place when no snapshot of it is held, and bump its [version](HighlightedDocument::version),
+/// so viewports can tell whether to draw again. The document is usually shared in an [Arc].
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use ratatui::text::Line;
+/// use syntect_tui::{HighlightedDocument, HighlightedText, InternedText};
+///
+/// let text = HighlightedText::new(vec![Line::raw("let x = 1;"); 100], None);
+/// let document = Arc::new(HighlightedDocument::new(InternedText::from(&text)));
+/// let pane = Arc::clone(&document);
+/// let version = pane.version();
+/// let drawn = std::thread::spawn(move || pane.snapshot().text(40..60).lines.len());
+///
+/// document.update(|text| text.push(&Line::raw("let y = 2;")));
+/// assert_eq!(20, drawn.join().unwrap());
+/// assert!(document.version() > version);
+/// assert_eq!(101, document.snapshot().len());
+/// ```
+#[derive(Debug, Default)]
+pub struct HighlightedDocument {
+    current: RwLock<Version>,
+}
+
+#[derive(Debug, Default)]
+struct Version {
+    text: Arc<InternedText>,
+    number: u64,
+}
+
+impl HighlightedDocument {
+    /// Creates a document holding `text`.
+    pub fn new(text: InternedText) -> Self {
+        Self {
+            current: RwLock::new(Version {
+                text: Arc::new(text),
+                number: 0,
+            }),
+        }
+    }
+
+    /// Returns the document as it is now, which stays unchanged while it is held.
+    pub fn snapshot(&self) -> Arc<InternedText> {
+        Arc::clone(&self.read().text)
+    }
+
+    /// Returns the document as it is now along with its version, which are always consistent,
+    /// unlike calling [HighlightedDocument::snapshot] and [HighlightedDocument::version].
+    pub fn versioned_snapshot(&self) -> (Arc<InternedText>, u64) {
+        let current = self.read();
+        (Arc::clone(&current.text), current.number)
+    }
+
+    /// Returns the number of times the document has been changed.
+    pub fn version(&self) -> u64 {
+        self.read().number
+    }
+
+    /// Returns whether the document has changed since `version`.
+    pub fn changed_since(&self, version: u64) -> bool {
+        self.version() != version
+    }
+
+    /// Replaces the document with `text`, returning its new version. Snapshots taken before keep
+    /// the previous document.
+    pub fn replace(&self, text: InternedText) -> u64 {
+        self.replace_shared(Arc::new(text))
+    }
+
+    /// Replaces the document with a document that may also be shared elsewhere, returning its new
+    /// version.
+    pub fn replace_shared(&self, text: Arc<InternedText>) -> u64 {
+        let mut current = self.write();
+        current.text = text;
+        current.number += 1;
+        current.number
+    }
+
+    /// Changes the document with `change`, returning its new version.
+    ///
+    /// The document is changed in place unless snapshots of it are held, in which case it is
+    /// copied first and the snapshots keep the previous document.
+    pub fn update(&self, change: impl FnOnce(&mut InternedText)) -> u64 {
+        let mut current = self.write();
+        change(Arc::make_mut(&mut current.text));
+        current.number += 1;
+        current.number
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Version> {
+        self.current
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Version> {
+        self.current
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl From<InternedText> for HighlightedDocument {
+    fn from(text: InternedText) -> Self {
+        Self::new(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Line;
+
+    fn document(lines: usize) -> InternedText {
+        InternedText::from(&crate::HighlightedText::new(
+            vec![Line::raw("x"); lines],
+            None,
+        ))
+    }
+
+    #[test]
+    fn snapshots_keep_the_document_they_were_taken_of() {
+        let shared = HighlightedDocument::new(document(2));
+        let (before, version) = shared.versioned_snapshot();
+        assert_eq!(0, version);
+        assert_eq!(1, shared.update(|text| text.push(&Line::raw("y"))));
+        assert_eq!(2, before.len());
+        assert_eq!(3, shared.snapshot().len());
+        assert!(shared.changed_since(version));
+        assert_eq!(2, shared.replace(document(5)));
+        assert_eq!(5, shared.snapshot().len());
+        assert!(!shared.changed_since(2));
+    }
+
+    #[test]
+    fn updates_without_snapshots_are_made_in_place() {
+        let shared = HighlightedDocument::new(document(2));
+        let address = Arc::as_ptr(&shared.snapshot());
+        shared.update(|text| text.push(&Line::raw("y")));
+        assert_eq!(address, Arc::as_ptr(&shared.snapshot()));
+        let held = shared.snapshot();
+        shared.update(|text| text.push(&Line::raw("z")));
+        assert_ne!(Arc::as_ptr(&held), Arc::as_ptr(&shared.snapshot()));
+    }
+
+    #[test]
+    fn document_is_shared_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<HighlightedDocument>();
+    }
+}
@@ -0,0 +1,226 @@
+//! Exporting translated themes, behind the `serde` feature.
+#[cfg(any(feature = "json", feature = "toml"))]
+use crate::SyntectTuiError;
+use crate::TuiTheme;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// The kinds of colours a translated theme uses, from none at all to true colour.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColourMode {
+    /// No colours.
+    #[default]
+    None,
+    /// The 16 named ANSI colours.
+    Ansi,
+    /// The 256 indexed colours, and the named ones.
+    Indexed,
+    /// RGB colours.
+    TrueColour,
+}
+
+impl ColourMode {
+    /// Returns the mode a colour needs, where `Color::Reset` needs none.
+    pub fn of_colour(colour: Color) -> Self {
+        match colour {
+            Color::Reset => ColourMode::None,
+            Color::Indexed(_) => ColourMode::Indexed,
+            Color::Rgb(..) => ColourMode::TrueColour,
+            _ => ColourMode::Ansi,
+        }
+    }
+
+    /// Returns the mode the colours of a style need.
+    pub fn of_style(style: ratatui::style::Style) -> Self {
+        #[cfg(feature = "underline-color")]
+        let underline = style.underline_color;
+        #[cfg(not(feature = "underline-color"))]
+        let underline = None;
+        [style.fg, style.bg, underline]
+            .into_iter()
+            .flatten()
+            .map(Self::of_colour)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// A [TuiTheme] along with the [ColourMode] its styles were translated for, which serializes
+/// into a table of scope selectors and ratatui styles, so applications can ship precomputed
+/// palettes, compare versions of a theme or let users adjust the result by hand.
+///
+/// Colours are kept as they were translated, e.g. `Color::Indexed(67)` when translating for 256
+/// colours, rather than resolved to RGB like [SerializedStyle](crate::SerializedStyle)s.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect_tui::{ColourMode, ExportedTheme, PaletteQuantizer, StyleTranslator};
+///
+/// let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+/// let translator = StyleTranslator::new()
+///     .quantize_colours(PaletteQuantizer::new((16..=255).map(ratatui::style::Color::Indexed)));
+/// let exported = ExportedTheme::new(translator.translate_theme(theme));
+/// assert_eq!(ColourMode::Indexed, exported.colour_mode);
+/// let json = serde_json::to_string(&exported).unwrap();
+/// assert_eq!(exported, serde_json::from_str(&json).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedTheme {
+    /// The kinds of colours the theme's styles use.
+    pub colour_mode: ColourMode,
+    /// The translated theme.
+    pub theme: TuiTheme,
+}
+
+impl ExportedTheme {
+    /// Wraps a translated theme, finding the colour mode its styles use.
+    pub fn new(theme: TuiTheme) -> Self {
+        let settings = theme.settings;
+        let colour_mode = [theme.default_style]
+            .into_iter()
+            .chain(theme.tokens.iter().map(|(_, style)| *style))
+            .chain(
+                [
+                    settings.background,
+                    settings.foreground,
+                    settings.caret,
+                    settings.selection,
+                    settings.line_highlight,
+                    settings.gutter,
+                    settings.find_highlight,
+                    settings.brackets,
+                ]
+                .into_iter()
+                .flatten(),
+            )
+            .map(ColourMode::of_style)
+            .max()
+            .unwrap_or_default();
+        Self { colour_mode, theme }
+    }
+
+    /// Writes the theme as pretty-printed JSON.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeExport` if the theme cannot be serialized.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String, SyntectTuiError> {
+        serde_json::to_string_pretty(self).map_err(export_error)
+    }
+
+    /// Reads a theme written by [ExportedTheme::to_json].
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeExport` if `json` is not a valid exported theme.
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> Result<Self, SyntectTuiError> {
+        serde_json::from_str(json).map_err(export_error)
+    }
+
+    /// Writes the theme as TOML.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect::highlighting::ThemeSet;
+    /// use syntect_tui::ExportedTheme;
+    ///
+    /// let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+    /// let exported = ExportedTheme::new(syntect_tui::StyleTranslator::new().translate_theme(theme));
+    /// let toml = exported.to_toml().unwrap();
+    /// assert!(toml.starts_with("colour_mode = \"true-colour\""));
+    /// assert_eq!(exported, ExportedTheme::from_toml(&toml).unwrap());
+    /// ```
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeExport` if the theme cannot be serialized.
+    #[cfg(feature = "toml")]
+    pub fn to_toml(&self) -> Result<String, SyntectTuiError> {
+        toml::to_string(self).map_err(export_error)
+    }
+
+    /// Reads a theme written by [ExportedTheme::to_toml].
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeExport` if `toml` is not a valid exported theme.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self, SyntectTuiError> {
+        toml::from_str(toml).map_err(export_error)
+    }
+}
+
+impl From<TuiTheme> for ExportedTheme {
+    fn from(theme: TuiTheme) -> Self {
+        Self::new(theme)
+    }
+}
+
+#[cfg(any(feature = "json", feature = "toml"))]
+fn export_error(error: impl std::fmt::Display) -> SyntectTuiError {
+    SyntectTuiError::ThemeExport {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Modifier, Style};
+    use rstest::*;
+
+    #[rstest]
+    #[case::empty(Style::new(), ColourMode::None)]
+    #[case::reset(Style::new().fg(Color::Reset), ColourMode::None)]
+    #[case::ansi(Style::new().fg(Color::Red), ColourMode::Ansi)]
+    #[case::indexed(Style::new().fg(Color::Red).bg(Color::Indexed(67)), ColourMode::Indexed)]
+    #[case::true_colour(Style::new().bg(Color::Rgb(1, 2, 3)).fg(Color::Indexed(67)), ColourMode::TrueColour)]
+    fn check_of_style(#[case] style: Style, #[case] expected: ColourMode) {
+        assert_eq!(expected, ColourMode::of_style(style));
+    }
+
+    fn theme() -> TuiTheme {
+        TuiTheme {
+            name: Some("Test".to_string()),
+            default_style: Style::new().fg(Color::White),
+            tokens: vec![(
+                "comment".to_string(),
+                Style::new()
+                    .fg(Color::Indexed(8))
+                    .add_modifier(Modifier::ITALIC),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_the_colour_mode() {
+        assert_eq!(ColourMode::Indexed, ExportedTheme::new(theme()).colour_mode);
+        assert_eq!(
+            ColourMode::None,
+            ExportedTheme::new(TuiTheme::default()).colour_mode
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn round_trips_through_json() {
+        let exported = ExportedTheme::new(theme());
+        let json = exported.to_json().unwrap();
+        assert!(json.contains("\"colour_mode\": \"indexed\""));
+        assert_eq!(exported, ExportedTheme::from_json(&json).unwrap());
+        assert!(matches!(
+            ExportedTheme::from_json("{}"),
+            Err(SyntectTuiError::ThemeExport { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn round_trips_through_toml() {
+        let exported = ExportedTheme::new(theme());
+        let toml = exported.to_toml().unwrap();
+        assert_eq!(exported, ExportedTheme::from_toml(&toml).unwrap());
+    }
+}
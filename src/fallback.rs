@@ -0,0 +1,176 @@
+use crate::{Metrics, StyleTranslator, SyntectTuiError};
+
+/// The length in bytes beyond which lines are not highlighted by default.
+const DEFAULT_MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// How the document-level highlighters treat pathologically long lines, e.g. a minified
+/// JavaScript bundle on a single line, which syntect could take seconds to highlight.
+///
+/// Lines longer than [max_line_bytes](LongLineFallback::max_line_bytes), 16 KiB by default, are
+/// not given to syntect at all, and are converted into a single span in the fallback
+/// [style](LongLineFallback::style), unstyled by default, with the theme's background. The parser
+/// carries on past them as if they were not there, so the lines after a long line are highlighted
+/// as usual, unless the long line opened or closed a construct spanning several lines.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::{HighlightChunks, LineConversion, LongLineFallback};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("js").unwrap();
+/// let source = "var a = 1;".repeat(1_000) + "\nvar b = 2;\n";
+/// let lines = HighlightChunks::new(&source, syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .long_lines(LongLineFallback::new().max_line_bytes(1_000).style(Style::new().dim()))
+///     .flatten()
+///     .flatten()
+///     .collect::<Vec<_>>();
+/// assert_eq!(1, lines[0].spans.len());
+/// assert_eq!(Style::new().dim(), lines[0].spans[0].style);
+/// assert!(lines[1].spans.len() > 1);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LongLineFallback {
+    max_line_bytes: Option<usize>,
+    style: ratatui::style::Style,
+}
+
+impl Default for LongLineFallback {
+    fn default() -> Self {
+        Self {
+            max_line_bytes: Some(DEFAULT_MAX_LINE_BYTES),
+            style: ratatui::style::Style::new(),
+        }
+    }
+}
+
+impl LongLineFallback {
+    /// Creates a fallback for lines longer than 16 KiB, which leaves them unstyled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a fallback that never applies, so every line is highlighted however long it is.
+    pub fn disabled() -> Self {
+        Self {
+            max_line_bytes: None,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the length in bytes, not counting the line ending, beyond which lines are not
+    /// highlighted.
+    pub fn max_line_bytes(mut self, bytes: usize) -> Self {
+        self.max_line_bytes = Some(bytes);
+        self
+    }
+
+    /// Sets the style of lines that are not highlighted, e.g. the theme's foreground or a dim
+    /// style to set them apart.
+    pub fn style(mut self, style: ratatui::style::Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns whether `line`, which may end with a line ending, is too long to highlight.
+    pub fn is_long(&self, line: &str) -> bool {
+        self.max_line_bytes
+            .is_some_and(|max| trim_line_ending(line).len() > max)
+    }
+
+    /// Highlights and converts a line with `translator` like
+    /// [StyleTranslator::highlight_line_to_line] unless it is too long, in which case it is
+    /// converted into a single span without being highlighted, adding the time each step takes to
+    /// `metrics` if there are any.
+    pub(crate) fn highlight_line<'a>(
+        &self,
+        highlighter: &mut syntect::easy::HighlightLines<'_>,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        theme: &syntect::highlighting::Theme,
+        line: &'a str,
+        translator: &StyleTranslator,
+        metrics: Option<&mut Metrics>,
+    ) -> Result<ratatui::text::Line<'a>, SyntectTuiError> {
+        if !self.is_long(line) {
+            return translator.highlight_line_measured(
+                highlighter,
+                syntax_set,
+                theme,
+                line,
+                metrics,
+            );
+        }
+        if let Some(metrics) = metrics {
+            metrics.lines += 1;
+            metrics.long_lines += 1;
+        }
+        let line = ratatui::text::Line::from(ratatui::text::Span::styled(
+            trim_line_ending(line),
+            self.style,
+        ));
+        Ok(match translator.theme_background(theme) {
+            Some(background) => line.style(ratatui::style::Style::new().bg(background)),
+            None => line,
+        })
+    }
+}
+
+/// Returns `line` without a single trailing `\n` or `\r\n`.
+fn trim_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    #[rstest]
+    #[case::short(LongLineFallback::new().max_line_bytes(4), "abcd\r\n", false)]
+    #[case::long(LongLineFallback::new().max_line_bytes(4), "abcde\n", true)]
+    #[case::default_short(LongLineFallback::new(), "abcde\n", false)]
+    #[case::disabled(LongLineFallback::disabled(), &"a".repeat(100_000), false)]
+    fn check_is_long(
+        #[case] fallback: LongLineFallback,
+        #[case] line: &str,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(expected, fallback.is_long(line));
+    }
+
+    #[test]
+    fn long_lines_leave_parser_state_unchanged() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let fallback = LongLineFallback::new().max_line_bytes(10);
+        let mut highlighter = syntect::easy::HighlightLines::new(syntax, theme);
+        let mut metrics = Metrics::new();
+        let line = fallback
+            .highlight_line(
+                &mut highlighter,
+                &ps,
+                theme,
+                "/* opens a comment\n",
+                &StyleTranslator::default(),
+                Some(&mut metrics),
+            )
+            .unwrap();
+        assert_eq!(
+            syntect::easy::HighlightLines::new(syntax, theme).state(),
+            highlighter.state()
+        );
+        assert_eq!(
+            ratatui::text::Line::raw("/* opens a comment").style(crate::theme_background(theme)),
+            line
+        );
+        assert_eq!(1, metrics.long_lines);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::toml_styles::parse_style;
+use crate::{ReverseOptions, SyntectTuiError};
+use ratatui::style::{Color, Style};
+
+/// The TextMate scopes Helix scopes are highlighted as, where their names differ, by the Helix
+/// scope they replace. Sub-scopes keep their suffixes, e.g. `function.macro` becomes
+/// `entity.name.function.macro`.
+const TEXTMATE_SCOPES: [(&str, &[&str]); 16] = [
+    ("function.builtin", &["support.function"]),
+    ("type.builtin", &["support.type"]),
+    ("variable.builtin", &["variable.language"]),
+    ("constant.builtin", &["constant.language"]),
+    (
+        "function",
+        &[
+            "entity.name.function",
+            "support.function",
+            "variable.function",
+        ],
+    ),
+    (
+        "type",
+        &["entity.name.type", "storage.type", "support.type"],
+    ),
+    ("constructor", &["entity.name.class", "entity.name.struct"]),
+    ("attribute", &["entity.other.attribute-name"]),
+    ("operator", &["keyword.operator"]),
+    ("label", &["entity.name.label"]),
+    ("namespace", &["entity.name.namespace"]),
+    ("tag", &["entity.name.tag"]),
+    ("diff.plus", &["markup.inserted"]),
+    ("diff.minus", &["markup.deleted"]),
+    ("diff.delta", &["markup.changed"]),
+    ("special", &[]),
+];
+
+/// The Helix scopes that style the editor rather than text, which have no TextMate scopes.
+const EDITOR_SCOPES: [&str; 7] = [
+    "ui",
+    "diagnostic",
+    "warning",
+    "error",
+    "info",
+    "hint",
+    "debug",
+];
+
+/// A [Helix](https://helix-editor.com) theme, parsed from its TOML, to highlight with the themes
+/// users already maintain for their editor.
+///
+/// Helix themes style Helix's scopes, which are mostly TextMate's, e.g. `comment` or
+/// `keyword.control`, with a few of their own, e.g. `function` for `entity.name.function`.
+/// [to_theme](HelixTheme::to_theme) converts the theme into a syntect theme, highlighting each
+/// Helix scope as its TextMate scopes, and taking the theme's default colours, caret, selection,
+/// current line and gutter from its `ui.*` scopes, while [styles](HelixTheme::styles) returns the
+/// theme's styles as they are. Colours may be hex colours, the names of the theme's palette or
+/// the names of the 16 ANSI colours, which are resolved using the
+/// [ReverseOptions](crate::ReverseOptions)' palette.
+///
+/// Themes that inherit from another theme only hold the styles they change, and are completed by
+/// [inheriting](HelixTheme::inherit) the theme they name in `inherits`, once the application has
+/// found it.
+///
+/// # Examples
+/// ```
+/// use syntect_tui::HelixTheme;
+///
+/// let theme = HelixTheme::from_toml(
+///     r##"
+///     "ui.background" = { bg = "base" }
+///     "comment" = { fg = "#65737e", modifiers = ["italic"] }
+///     "function" = "blue"
+///
+///     [palette]
+///     base = "#2b303b"
+///     "##,
+/// )
+/// .unwrap()
+/// .to_theme(&syntect_tui::ReverseOptions::new())
+/// .unwrap();
+/// let base = syntect::highlighting::Color { r: 43, g: 48, b: 59, a: 255 };
+/// assert_eq!(Some(base), theme.settings.background);
+/// assert_eq!(2, theme.scopes.len());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HelixTheme {
+    table: toml::Table,
+}
+
+/// Parses a [Helix](https://helix-editor.com) theme and converts it into a
+/// [syntect::highlighting::Theme](https://docs.rs/syntect/latest/syntect/highlighting/struct.Theme.html),
+/// using the default [ReverseOptions](crate::ReverseOptions).
+///
+/// See [HelixTheme] for details. The theme is converted as it is, without the theme it inherits
+/// from, if any.
+///
+/// # Errors
+/// Can return the same errors as [HelixTheme::from_toml] and [HelixTheme::to_theme].
+pub fn helix_theme(source: &str) -> Result<syntect::highlighting::Theme, SyntectTuiError> {
+    HelixTheme::from_toml(source)?.to_theme(&ReverseOptions::default())
+}
+
+impl HelixTheme {
+    /// Parses a Helix theme from its TOML.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeLoading` if `source` is not valid TOML.
+    pub fn from_toml(source: &str) -> Result<Self, SyntectTuiError> {
+        let table =
+            source
+                .parse()
+                .map_err(|error: toml::de::Error| SyntectTuiError::ThemeLoading {
+                    message: error.message().to_string(),
+                })?;
+        Ok(Self { table })
+    }
+
+    /// Returns the name of the theme this theme inherits from, if any.
+    pub fn inherits(&self) -> Option<&str> {
+        self.table.get("inherits")?.as_str()
+    }
+
+    /// Completes this theme with the styles and palette colours of `parent` it does not set
+    /// itself, and inherits from what `parent` inherits from.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect_tui::HelixTheme;
+    ///
+    /// let parent = HelixTheme::from_toml("comment = \"gray\"\nstring = \"green\"").unwrap();
+    /// let child = HelixTheme::from_toml("inherits = \"parent\"\nstring = \"red\"").unwrap();
+    /// let theme = child.inherit(parent);
+    /// assert_eq!(None, theme.inherits());
+    /// assert_eq!(2, theme.styles().unwrap().len());
+    /// ```
+    pub fn inherit(self, parent: HelixTheme) -> Self {
+        let mut table = parent.table;
+        for (key, value) in self.table {
+            match (table.get_mut(&key), value) {
+                (Some(toml::Value::Table(palette)), toml::Value::Table(colours))
+                    if key == "palette" =>
+                {
+                    palette.extend(colours);
+                }
+                (_, value) if key != "inherits" => {
+                    table.insert(key, value);
+                }
+                _ => {}
+            }
+        }
+        Self { table }
+    }
+
+    /// Returns the theme's styles, sorted by Helix scope.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeLoading` if a style is malformed or uses an unknown colour or
+    /// modifier.
+    pub fn styles(&self) -> Result<Vec<(String, Style)>, SyntectTuiError> {
+        let palette = self.table.get("palette").and_then(toml::Value::as_table);
+        self.table
+            .iter()
+            .filter(|(key, _)| !matches!(key.as_str(), "inherits" | "palette"))
+            .map(|(scope, value)| Ok((scope.clone(), parse_style(scope, value, palette)?)))
+            .collect()
+    }
+
+    /// Converts the theme into a syntect theme, resolving named colours with `options`' palette.
+    ///
+    /// # Errors
+    /// Can return the same errors as [HelixTheme::styles] and
+    /// [build_theme](crate::build_theme).
+    pub fn to_theme(
+        &self,
+        options: &ReverseOptions,
+    ) -> Result<syntect::highlighting::Theme, SyntectTuiError> {
+        let styles = self.styles()?;
+        let ui = |scope: &str| {
+            styles
+                .iter()
+                .find(|(name, _)| name == scope)
+                .map(|(_, style)| *style)
+                .unwrap_or_default()
+        };
+        let colour = |colour: Option<Color>| match colour {
+            None | Some(Color::Reset) => None,
+            Some(colour) => Some(crate::translate_colour_back(colour, &options.palette)),
+        };
+        let mut theme = options.build_theme(
+            styles
+                .iter()
+                .filter_map(|(scope, style)| Some((textmate_scopes(scope)?, *style))),
+        )?;
+        let settings = &mut theme.settings;
+        let text = ui("ui.text");
+        let background = ui("ui.background");
+        settings.foreground = colour(text.fg).or(settings.foreground);
+        settings.background = colour(background.bg).or(settings.background);
+        settings.caret = colour(ui("ui.cursor").bg);
+        let selection = ui("ui.selection");
+        settings.selection = colour(selection.bg);
+        settings.selection_foreground = colour(selection.fg);
+        settings.line_highlight = colour(ui("ui.cursorline.primary").bg);
+        settings.gutter = colour(ui("ui.gutter").bg);
+        settings.gutter_foreground = colour(ui("ui.linenr").fg);
+        Ok(theme)
+    }
+}
+
+/// Returns the TextMate scope selector a Helix scope is highlighted as, or `None` for scopes that
+/// style the editor.
+fn textmate_scopes(scope: &str) -> Option<String> {
+    let root = scope.split('.').next().unwrap_or_default();
+    if EDITOR_SCOPES.contains(&root) {
+        return None;
+    }
+    let replaced = TEXTMATE_SCOPES.iter().find_map(|(helix, textmate)| {
+        let rest = scope.strip_prefix(helix)?;
+        (rest.is_empty() || rest.starts_with('.')).then_some((*textmate, rest))
+    });
+    match replaced {
+        None => Some(scope.to_string()),
+        Some(([], _)) => None,
+        Some((textmate, rest)) => Some(
+            textmate
+                .iter()
+                .map(|textmate| format!("{textmate}{rest}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Modifier;
+    use rstest::*;
+
+    const THEME: &str = r##"
+        "ui.background" = { bg = "base" }
+        "ui.text" = "#c0c5ce"
+        "ui.selection" = { bg = "#4f5b66" }
+        "comment" = { fg = "gray", modifiers = ["italic"] }
+        "keyword" = { fg = "purple", modifiers = ["bold"] }
+        "function.macro" = "blue"
+        "diagnostic.error" = { underline = { color = "red", style = "curl" } }
+
+        [palette]
+        base = "#2b303b"
+        purple = "#b48ead"
+    "##;
+
+    #[rstest]
+    #[case::passed_through("keyword.control", Some("keyword.control"))]
+    #[case::replaced("operator", Some("keyword.operator"))]
+    #[case::replaced_with_suffix(
+        "function.macro",
+        Some("entity.name.function.macro, support.function.macro, variable.function.macro")
+    )]
+    #[case::builtin("type.builtin", Some("support.type"))]
+    #[case::not_a_prefix("typed", Some("typed"))]
+    #[case::editor("ui.cursor", None)]
+    #[case::unsupported("special", None)]
+    fn check_textmate_scopes(#[case] scope: &str, #[case] expected: Option<&str>) {
+        assert_eq!(expected.map(str::to_string), textmate_scopes(scope));
+    }
+
+    #[test]
+    fn converts_styles_and_settings() {
+        let theme = HelixTheme::from_toml(THEME).unwrap();
+        let styles = theme.styles().unwrap();
+        let (_, keyword) = styles.iter().find(|(scope, _)| scope == "keyword").unwrap();
+        assert_eq!(
+            Style::new()
+                .fg(Color::Rgb(180, 142, 173))
+                .add_modifier(Modifier::BOLD),
+            *keyword
+        );
+        let theme = theme.to_theme(&ReverseOptions::new()).unwrap();
+        let colour = |r, g, b| Some(syntect::highlighting::Color { r, g, b, a: 255 });
+        assert_eq!(colour(43, 48, 59), theme.settings.background);
+        assert_eq!(colour(192, 197, 206), theme.settings.foreground);
+        assert_eq!(colour(79, 91, 102), theme.settings.selection);
+        assert_eq!(3, theme.scopes.len());
+        assert_eq!(colour(127, 127, 127), theme.scopes[0].style.foreground);
+    }
+
+    #[test]
+    fn children_override_inherited_styles() {
+        let parent = HelixTheme::from_toml(
+            "inherits = \"grandparent\"\ncomment = \"accent\"\nstring = \"accent\"\n[palette]\naccent = \"#ff0000\"",
+        )
+        .unwrap();
+        let child = HelixTheme::from_toml(
+            "inherits = \"parent\"\nstring = \"green\"\n[palette]\naccent = \"#00ff00\"",
+        )
+        .unwrap();
+        let theme = child.inherit(parent);
+        assert_eq!(Some("grandparent"), theme.inherits());
+        let styles = theme.styles().unwrap();
+        assert_eq!(
+            vec![
+                (
+                    "comment".to_string(),
+                    Style::new().fg(Color::Rgb(0, 255, 0))
+                ),
+                ("string".to_string(), Style::new().fg(Color::Green)),
+            ],
+            styles
+        );
+    }
+
+    #[rstest]
+    #[case::invalid_toml("comment = ")]
+    #[case::unknown_colour("comment = \"mauve\"")]
+    #[case::unknown_modifier("comment = { modifiers = [\"sparkly\"] }")]
+    #[case::invalid_style("comment = 1")]
+    fn rejects_invalid_themes(#[case] source: &str) {
+        assert!(matches!(
+            helix_theme(source),
+            Err(SyntectTuiError::ThemeLoading { .. })
+        ));
+    }
+}
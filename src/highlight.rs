@@ -0,0 +1,249 @@
+use crate::{Metrics, StyleTranslator, SyntectTuiError};
+
+/// Highlights a single line and converts it into a
+/// [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html) in one
+/// call.
+///
+/// The line is highlighted using
+/// [syntect::easy::HighlightLines::highlight_line](https://docs.rs/syntect/latest/syntect/easy/struct.HighlightLines.html#method.highlight_line)
+/// and converted using [into_line](crate::into_line), so its trailing line ending is removed.
+/// `HighlightLines` does not expose its theme, so the same `theme` must be passed in; its
+/// background becomes the line's style so the rest of the row matches the theme.
+///
+/// # Examples
+/// ```
+/// use syntect::easy::HighlightLines;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect::util::LinesWithEndings;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let theme = &ts.themes["base16-ocean.dark"];
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut h = HighlightLines::new(syntax, theme);
+/// let lines = LinesWithEndings::from("fn main() {\n}\n")
+///     .map(|line| syntect_tui::highlight_line_to_line(&mut h, &ps, theme, line))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!("fn main() {", lines[0].to_string());
+/// assert_eq!(
+///     Some(ratatui::style::Color::Rgb(43, 48, 59)),
+///     lines[1].style.bg
+/// );
+/// ```
+/// # Errors
+/// Returns `SyntectTuiError::HighlightFailed` if syntect fails to highlight the line, and can
+/// return the same errors as [into_line](crate::into_line).
+pub fn highlight_line_to_line<'a>(
+    highlighter: &mut syntect::easy::HighlightLines<'_>,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+    line: &'a str,
+) -> Result<ratatui::text::Line<'a>, SyntectTuiError> {
+    StyleTranslator::default().highlight_line_to_line(highlighter, syntax_set, theme, line)
+}
+
+impl StyleTranslator {
+    /// Highlights a single line and converts it into a ratatui line according to this
+    /// translator's options.
+    ///
+    /// See [highlight_line_to_line](crate::highlight_line_to_line) for details.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight_line_to_line](crate::highlight_line_to_line).
+    pub fn highlight_line_to_line<'a>(
+        &self,
+        highlighter: &mut syntect::easy::HighlightLines<'_>,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        theme: &syntect::highlighting::Theme,
+        line: &'a str,
+    ) -> Result<ratatui::text::Line<'a>, SyntectTuiError> {
+        self.highlight_line_measured(highlighter, syntax_set, theme, line, None)
+    }
+
+    /// Highlights and converts a line like [StyleTranslator::highlight_line_to_line], adding the
+    /// time each step takes to `metrics` if there are any.
+    pub(crate) fn highlight_line_measured<'a>(
+        &self,
+        highlighter: &mut syntect::easy::HighlightLines<'_>,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        theme: &syntect::highlighting::Theme,
+        line: &'a str,
+        mut metrics: Option<&mut Metrics>,
+    ) -> Result<ratatui::text::Line<'a>, SyntectTuiError> {
+        let mut highlight = || {
+            highlighter
+                .highlight_line(line, syntax_set)
+                .map_err(|error| SyntectTuiError::HighlightFailed {
+                    message: error.to_string(),
+                })
+        };
+        let segments = match metrics.as_deref_mut() {
+            Some(metrics) => metrics.time_highlight(highlight),
+            None => highlight(),
+        }?;
+        let convert = || {
+            let background = self.theme_background(theme);
+            let line = self.into_line(segments)?;
+            Ok(match background {
+                Some(background) => line.style(ratatui::style::Style::new().bg(background)),
+                None => line,
+            })
+        };
+        match metrics {
+            Some(metrics) => metrics.time_conversion(convert),
+            None => convert(),
+        }
+    }
+}
+
+impl StyleTranslator {
+    /// Returns the background colour of `theme` translated according to this translator's
+    /// options, or `None` in plain mode or if the theme has no (opaque) background.
+    pub(crate) fn theme_background(
+        &self,
+        theme: &syntect::highlighting::Theme,
+    ) -> Option<ratatui::style::Color> {
+        if self.is_plain() {
+            return None;
+        }
+        theme
+            .settings
+            .background
+            .and_then(|colour| self.translate_colour(colour))
+    }
+}
+
+/// The highlighter and parser states between two lines, which are all a
+/// [HighlightLines](syntect::easy::HighlightLines) needs to carry on from a line.
+pub(crate) type LineStates = (
+    syntect::highlighting::HighlightState,
+    syntect::parsing::ParseState,
+);
+
+/// Returns `states` for highlighting with `theme` instead, keeping the parser state and the scopes
+/// the highlighter is in, so lines can be highlighted with another theme without parsing the
+/// lines before them again.
+pub(crate) fn restyle_states(
+    states: &LineStates,
+    theme: &syntect::highlighting::Theme,
+) -> LineStates {
+    let highlighter = syntect::highlighting::Highlighter::new(theme);
+    (
+        syntect::highlighting::HighlightState::new(&highlighter, states.0.path.clone()),
+        states.1.clone(),
+    )
+}
+
+/// Highlights `line` starting from `states` and converts it with `translator`, unless `fallback`
+/// finds it too long, returning the converted line, which no longer borrows `line`, and the
+/// states after it, and adding the time it takes to `metrics` if there are any.
+pub(crate) fn highlight_from_states(
+    states: &LineStates,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+    line: &str,
+    fallback: &crate::LongLineFallback,
+    translator: &StyleTranslator,
+    metrics: Option<&mut Metrics>,
+) -> Result<(ratatui::text::Line<'static>, LineStates), SyntectTuiError> {
+    let (highlight_state, parse_state) = states.clone();
+    let mut highlighter =
+        syntect::easy::HighlightLines::from_state(theme, highlight_state, parse_state);
+    let converted = fallback.highlight_line(
+        &mut highlighter,
+        syntax_set,
+        theme,
+        line,
+        translator,
+        metrics,
+    )?;
+    Ok((crate::line::into_owned_line(converted), highlighter.state()))
+}
+
+/// Advances `states` past `line` as [highlight_from_states] would, unless `fallback` finds it too
+/// long, without converting the line, e.g. when only the states after it are needed.
+#[cfg(feature = "rayon")]
+pub(crate) fn advance_states(
+    states: &mut LineStates,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+    line: &str,
+    fallback: &crate::LongLineFallback,
+) -> Result<(), SyntectTuiError> {
+    if fallback.is_long(line) {
+        return Ok(());
+    }
+    let (highlight_state, parse_state) = states;
+    let ops = parse_state.parse_line(line, syntax_set).map_err(|error| {
+        SyntectTuiError::HighlightFailed {
+            message: error.to_string(),
+        }
+    })?;
+    let highlighter = syntect::highlighting::Highlighter::new(theme);
+    syntect::highlighting::HighlightIterator::new(highlight_state, &ops, line, &highlighter)
+        .for_each(drop);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syntect::easy::HighlightLines;
+    use syntect::highlighting::{Theme, ThemeSet};
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn matches_manual_conversion() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let line = "pub struct Wow { hi: u64 }\r\n";
+        let segments = HighlightLines::new(syntax, theme)
+            .highlight_line(line, &ps)
+            .unwrap();
+        let expected = crate::into_line(segments)
+            .unwrap()
+            .style(crate::theme_background(theme));
+        let actual =
+            highlight_line_to_line(&mut HighlightLines::new(syntax, theme), &ps, theme, line);
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn themes_without_background_leave_line_unstyled() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let theme = Theme::default();
+        let mut h = HighlightLines::new(ps.find_syntax_plain_text(), &theme);
+        let actual = highlight_line_to_line(&mut h, &ps, &theme, "text\n").unwrap();
+        assert_eq!(ratatui::style::Style::default(), actual.style);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn advancing_matches_highlighting() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let fallback = crate::LongLineFallback::new().max_line_bytes(12);
+        let translator = StyleTranslator::new();
+        let mut highlighted = HighlightLines::new(syntax, theme).state();
+        let mut advanced = highlighted.clone();
+        for line in [
+            "let a = \"\n",
+            "/* too long to highlight\n",
+            "b\";\n",
+            "/* c\n",
+        ] {
+            highlighted =
+                highlight_from_states(&highlighted, &ps, theme, line, &fallback, &translator, None)
+                    .unwrap()
+                    .1;
+            advance_states(&mut advanced, &ps, theme, line, &fallback).unwrap();
+            assert_eq!(highlighted, advanced);
+        }
+    }
+}
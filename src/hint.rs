@@ -0,0 +1,199 @@
+#[cfg(feature = "widget")]
+use unicode_width::UnicodeWidthStr;
+
+/// Virtual text shown inside a line without being part of it, e.g. an inlay type hint or
+/// parameter name from a language server.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InlayHint {
+    /// The index of the line the hint is shown in.
+    pub line: usize,
+    /// The byte offset within the line the hint is shown before, or the end of the line if it is
+    /// past it.
+    pub column: usize,
+    /// The text of the hint.
+    pub text: String,
+    /// The style patched over the hint style, e.g. to tell kinds of hints apart.
+    pub style: Option<ratatui::style::Style>,
+}
+
+impl InlayHint {
+    /// Creates a hint that shows `text` before byte offset `column` of line `line`.
+    pub fn new(line: usize, column: usize, text: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            text: text.into(),
+            style: None,
+        }
+    }
+
+    /// Sets the style patched over the hint style.
+    pub fn style(mut self, style: ratatui::style::Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// Returns the span the hint is shown as, given the `style` of all hints.
+    pub(crate) fn span(&self, style: ratatui::style::Style) -> ratatui::text::Span<'static> {
+        let style = self.style.map_or(style, |own| style.patch(own));
+        ratatui::text::Span::styled(self.text.clone(), style)
+    }
+}
+
+/// Inserts `hints` into already converted text as spans in `style`, patched with each hint's own
+/// style, splitting the spans of the text at the hints' byte offsets.
+///
+/// Hints at the same offset are shown in the order they are given. The styles of the text's spans
+/// are kept, but the byte offsets and display columns after a hint are shifted by its text, so
+/// cursors and selections should be applied before inserting hints (as
+/// [CodeView::inlay_hints](crate::widget::CodeView::inlay_hints) does).
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use ratatui::text::{Line, Span, Text};
+/// use syntect_tui::InlayHint;
+///
+/// let text = Text::from(Line::from(vec![Span::raw("let x").blue(), Span::raw(" = f(1);")]));
+/// let hints = [InlayHint::new(0, 5, ": i32"), InlayHint::new(0, 10, "n: ")];
+/// let text = syntect_tui::insert_inlay_hints(text, &hints, Style::new().italic());
+/// assert_eq!("let x: i32 = f(n: 1);", text.lines[0].to_string());
+/// assert_eq!(Span::raw(": i32").italic(), text.lines[0].spans[1]);
+/// ```
+pub fn insert_inlay_hints<'a>(
+    mut text: ratatui::text::Text<'a>,
+    hints: &[InlayHint],
+    style: ratatui::style::Style,
+) -> ratatui::text::Text<'a> {
+    for (index, line) in text.lines.iter_mut().enumerate() {
+        let spans = hints
+            .iter()
+            .filter(|hint| hint.line == index)
+            .map(|hint| (hint.column, hint.span(style)));
+        *line = insert_spans(line, spans, char::len_utf8);
+    }
+    text
+}
+
+/// Returns the display width of the hints of line `index` that are drawn before byte offset
+/// `column` of `line`, e.g. to find where a cursor is drawn once hints are inserted.
+#[cfg(feature = "widget")]
+pub(crate) fn width_before(
+    line: &ratatui::text::Line,
+    index: usize,
+    column: usize,
+    hints: &[InlayHint],
+) -> usize {
+    let length: usize = line.spans.iter().map(|span| span.content.len()).sum();
+    hints
+        .iter()
+        .filter(|hint| hint.line == index && hint.column.min(length) <= column)
+        .map(|hint| hint.text.width())
+        .sum()
+}
+
+/// Inserts `spans` into `line` before the characters at their positions, measuring characters with
+/// `measure`, e.g. in bytes or display columns. A position inside a character counts as its
+/// start, characters that measure zero (e.g. combining marks) stay with the character before
+/// them, and spans positioned past the end of the line are appended to it.
+pub(crate) fn insert_spans<'a>(
+    line: &ratatui::text::Line<'a>,
+    spans: impl IntoIterator<Item = (usize, ratatui::text::Span<'static>)>,
+    measure: impl Fn(char) -> usize,
+) -> ratatui::text::Line<'a> {
+    let mut pending: Vec<_> = spans.into_iter().collect();
+    if pending.is_empty() {
+        return line.clone();
+    }
+    pending.sort_by_key(|(position, _)| *position);
+    let mut pending = pending.into_iter().peekable();
+    let mut result = Vec::with_capacity(line.spans.len() + pending.len() * 2);
+    let mut position = 0;
+    for span in &line.spans {
+        let mut start = 0;
+        for (index, character) in span.content.char_indices() {
+            let size = measure(character);
+            while size > 0 && pending.peek().is_some_and(|(at, _)| *at < position + size) {
+                if start < index {
+                    result.push(ratatui::text::Span::styled(
+                        span.content[start..index].to_string(),
+                        span.style,
+                    ));
+                    start = index;
+                }
+                result.extend(pending.next().map(|(_, span)| span));
+            }
+            position += size;
+        }
+        if start == 0 {
+            result.push(span.clone());
+        } else {
+            result.push(ratatui::text::Span::styled(
+                span.content[start..].to_string(),
+                span.style,
+            ));
+        }
+    }
+    result.extend(pending.map(|(_, span)| span));
+    ratatui::text::Line {
+        spans: result,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Style, Stylize};
+    use ratatui::text::{Line, Span, Text};
+    use rstest::*;
+
+    fn fake_text() -> Text<'static> {
+        Text::from(vec![
+            Line::from(vec![Span::raw("ab").red(), Span::raw("日c").blue()]),
+            Line::raw("e\u{301}f"),
+        ])
+    }
+
+    #[rstest]
+    #[case::start(0, 0, "|ab日c")]
+    #[case::between_spans(0, 2, "ab|日c")]
+    #[case::inside_character(0, 3, "ab|日c")]
+    #[case::end(0, 6, "ab日c|")]
+    #[case::past_end(0, 9, "ab日c|")]
+    #[case::after_combining_mark(1, 3, "e\u{301}|f")]
+    #[case::before_combining_mark(1, 1, "e|\u{301}f")]
+    fn check_insert_inlay_hints(
+        #[case] line: usize,
+        #[case] column: usize,
+        #[case] expected: &str,
+    ) {
+        let text = insert_inlay_hints(
+            fake_text(),
+            &[InlayHint::new(line, column, "|")],
+            Style::new(),
+        );
+        assert_eq!(expected, text.lines[line].to_string());
+    }
+
+    #[test]
+    fn hints_keep_span_styles_and_order() {
+        let hints = [
+            InlayHint::new(0, 1, "2"),
+            InlayHint::new(0, 1, "1").style(Style::new().bold()),
+            InlayHint::new(1, 0, "x"),
+        ];
+        let text = insert_inlay_hints(fake_text(), &hints, Style::new().dim());
+        assert_eq!(
+            vec![
+                Span::raw("a").red(),
+                Span::raw("2").dim(),
+                Span::raw("1").dim().bold(),
+                Span::raw("b").red(),
+                Span::raw("日c").blue(),
+            ],
+            text.lines[0].spans
+        );
+    }
+}
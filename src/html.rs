@@ -0,0 +1,219 @@
+//! Export of ratatui text as inline-styled HTML.
+use crate::{from_line, SyntectTuiError};
+use std::fmt::Write;
+use syntect::highlighting::{Color, FontStyle};
+use syntect::html::IncludeBackground;
+
+/// Converts a [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html)
+/// into inline-styled HTML, equivalent to
+/// [syntect::html::styled_line_to_highlighted_html](https://docs.rs/syntect/latest/syntect/html/fn.styled_line_to_highlighted_html.html).
+///
+/// Styles are converted back into syntect styles using [from_line](crate::from_line), so the same
+/// lossy rules apply (e.g. modifiers syntect cannot represent are dropped). Spans without a
+/// foreground colour have no `color` in their style, so they keep the colour of the page around
+/// them instead of becoming transparent.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use ratatui::text::{Line, Span};
+/// use syntect::html::IncludeBackground;
+///
+/// let line = Line::from(vec![Span::styled("<b>", Style::new().fg(Color::Rgb(255, 0, 0)))]);
+/// assert_eq!(
+///     Ok("<span style=\"color:#ff0000;\">&lt;b&gt;</span>".to_string()),
+///     syntect_tui::line_to_html(&line, IncludeBackground::No)
+/// );
+/// ```
+/// # Errors
+/// Returns `SyntectTuiError::HtmlExport` if the HTML fails to be written.
+pub fn line_to_html(
+    line: &ratatui::text::Line<'_>,
+    background: IncludeBackground,
+) -> Result<String, SyntectTuiError> {
+    let mut html = String::new();
+    let mut previous: Option<syntect::highlighting::Style> = None;
+    for (style, content) in from_line(line) {
+        let unified = previous.is_some_and(|previous| {
+            style == previous
+                || (style.background == previous.background && content.trim().is_empty())
+        });
+        if !unified {
+            if previous.is_some() {
+                html.push_str("</span>");
+            }
+            previous = Some(style);
+            write_span_style(&mut html, style, background)?;
+        }
+        write_escaped(&mut html, &content);
+    }
+    if previous.is_some() {
+        html.push_str("</span>");
+    }
+    Ok(html)
+}
+
+/// Writes the opening tag of a span with `style`, like syntect does, but without a `color` if the
+/// foreground is fully transparent.
+fn write_span_style(
+    html: &mut String,
+    style: syntect::highlighting::Style,
+    background: IncludeBackground,
+) -> Result<(), SyntectTuiError> {
+    let include_background = match background {
+        IncludeBackground::Yes => true,
+        IncludeBackground::No => false,
+        IncludeBackground::IfDifferent(colour) => style.background != colour,
+    };
+    html.push_str("<span style=\"");
+    if include_background {
+        html.push_str("background-color:");
+        write_css_colour(html, style.background)?;
+        html.push(';');
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        html.push_str("text-decoration:underline;");
+    }
+    if style.font_style.contains(FontStyle::BOLD) {
+        html.push_str("font-weight:bold;");
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        html.push_str("font-style:italic;");
+    }
+    if style.foreground.a != 0 {
+        html.push_str("color:");
+        write_css_colour(html, style.foreground)?;
+        html.push(';');
+    }
+    html.push_str("\">");
+    Ok(())
+}
+
+/// Writes `colour` as a CSS hex colour, with its alpha unless it is opaque.
+fn write_css_colour(html: &mut String, colour: Color) -> Result<(), SyntectTuiError> {
+    let Color { r, g, b, a } = colour;
+    match a {
+        0xFF => write!(html, "#{r:02x}{g:02x}{b:02x}"),
+        _ => write!(html, "#{r:02x}{g:02x}{b:02x}{a:02x}"),
+    }
+    .map_err(|error| SyntectTuiError::HtmlExport {
+        message: error.to_string(),
+    })
+}
+
+/// Writes `content` with the characters HTML gives a meaning escaped.
+fn write_escaped(html: &mut String, content: &str) {
+    for character in content.chars() {
+        match character {
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '&' => html.push_str("&amp;"),
+            '\'' => html.push_str("&#39;"),
+            '"' => html.push_str("&quot;"),
+            _ => html.push(character),
+        }
+    }
+}
+
+/// Converts a [ratatui::text::Text](https://docs.rs/ratatui/latest/ratatui/text/struct.Text.html)
+/// into inline-styled HTML using [line_to_html](crate::line_to_html), joining lines with `\n`.
+///
+/// The text's own style is patched with each line's style, as it is when ratatui renders the text.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use ratatui::text::Text;
+/// use syntect::html::IncludeBackground;
+///
+/// let text = Text::from("a\nb").style(Style::new().fg(Color::Rgb(0, 0, 255)));
+/// assert_eq!(
+///     Ok("<span style=\"color:#0000ff;\">a</span>\n<span style=\"color:#0000ff;\">b</span>".to_string()),
+///     syntect_tui::text_to_html(&text, IncludeBackground::No)
+/// );
+/// ```
+/// # Errors
+/// Returns `SyntectTuiError::HtmlExport` if the HTML fails to be written.
+pub fn text_to_html(
+    text: &ratatui::text::Text<'_>,
+    background: IncludeBackground,
+) -> Result<String, SyntectTuiError> {
+    let lines = text
+        .lines
+        .iter()
+        .map(|line| {
+            let line = ratatui::text::Line {
+                style: text.style.patch(line.style),
+                ..line.clone()
+            };
+            line_to_html(&line, background)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::{Line, Span, Text};
+    use rstest::*;
+
+    #[rstest]
+    #[case::plain(Style::new(), "<span style=\"\">fn</span>")]
+    #[case::reset(Style::new().fg(Color::Reset), "<span style=\"\">fn</span>")]
+    #[case::bold(
+        Style::new().fg(Color::Rgb(1, 2, 3)).add_modifier(Modifier::BOLD),
+        "<span style=\"font-weight:bold;color:#010203;\">fn</span>"
+    )]
+    #[case::named(Style::new().fg(Color::Red), "<span style=\"color:#cd0000;\">fn</span>")]
+    fn check_line_to_html(#[case] style: Style, #[case] expected: &str) {
+        let line = Line::from(Span::styled("fn", style));
+        assert_eq!(
+            Ok(expected.to_string()),
+            line_to_html(&line, IncludeBackground::No)
+        );
+    }
+
+    #[test]
+    fn matches_syntect_html() {
+        let line = Line::from(vec![
+            Span::styled("<a href='x'>", Style::new().fg(Color::Rgb(1, 2, 3))),
+            Span::styled(" ", Style::new().fg(Color::Rgb(4, 5, 6))),
+            Span::styled(
+                "&\"",
+                Style::new().fg(Color::Rgb(1, 2, 3)).bg(Color::Rgb(7, 8, 9)),
+            ),
+        ]);
+        let segments = from_line(&line);
+        let segments: Vec<_> = segments
+            .iter()
+            .map(|(style, content)| (*style, content.as_str()))
+            .collect();
+        for background in [
+            IncludeBackground::Yes,
+            IncludeBackground::No,
+            IncludeBackground::IfDifferent(segments[0].0.background),
+        ] {
+            assert_eq!(
+                syntect::html::styled_line_to_highlighted_html(&segments, background).ok(),
+                line_to_html(&line, background).ok()
+            );
+        }
+    }
+
+    #[test]
+    fn line_styles_override_text_style() {
+        let text = Text::from(vec![
+            Line::from("a"),
+            Line::from("b").style(Style::new().fg(Color::Rgb(255, 0, 0))),
+        ])
+        .style(Style::new().fg(Color::Rgb(0, 0, 255)));
+        let expected =
+            "<span style=\"color:#0000ff;\">a</span>\n<span style=\"color:#ff0000;\">b</span>";
+        assert_eq!(
+            Ok(expected.to_string()),
+            text_to_html(&text, IncludeBackground::No)
+        );
+    }
+}
@@ -0,0 +1,508 @@
+use crate::highlight::{highlight_from_states, LineStates};
+use crate::{HighlightedText, InternedLine, Metrics, StyleTable, SyntectTuiError};
+
+/// Keeps a document highlighted as it is edited, e.g. the buffer of an editor.
+///
+/// The highlighter owns the lines of the document, their converted lines, and a checkpoint of the
+/// parser and highlighter states before each line. An edit re-highlights the lines it replaces
+/// from the checkpoint before them, then carries on past them only until the states after a line
+/// match the checkpoint that was there before the edit. From that point on the rest of the
+/// document would be highlighted exactly as before, so typing inside a function only
+/// re-highlights the edited line, while opening a block comment re-highlights every line up to
+/// where it is closed.
+///
+/// The converted lines are kept as [InternedLine]s, so a large document takes memory for each of
+/// its distinct styles rather than each of its spans, and are converted back into ratatui lines
+/// as they are requested.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::IncrementalHighlighter;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut highlighter = IncrementalHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+/// highlighter
+///     .set_text("let a = 1;\nlet b = 2;\nlet c = 3;\n")
+///     .unwrap();
+/// assert_eq!(1..2, highlighter.replace_lines(1..2, "let b = 20;\n").unwrap());
+/// assert_eq!(1..3, highlighter.replace_lines(1..2, "/* b\n").unwrap());
+/// assert_eq!("let c = 3;", highlighter.line(2).unwrap().to_string());
+/// ```
+#[derive(Clone, Debug)]
+pub struct IncrementalHighlighter<'a> {
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    source: Vec<String>,
+    lines: Vec<InternedLine>,
+    styles: StyleTable,
+    /// The states before each line, followed by the states after the last one.
+    checkpoints: Vec<LineStates>,
+    /// The sorted, disjoint ranges of lines whose converted line changed since they were last
+    /// taken, where an end of `usize::MAX` stands for the end of the document.
+    changed: Vec<std::ops::Range<usize>>,
+    /// The number of lines when the changed lines were last taken.
+    taken_line_count: usize,
+    fallback: crate::LongLineFallback,
+    translator: crate::StyleTranslator,
+    metrics: Metrics,
+}
+
+impl<'a> IncrementalHighlighter<'a> {
+    /// Creates a highlighter for an empty document highlighted using `syntax` and `theme`.
+    pub fn new(
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            syntax_set,
+            theme,
+            source: Vec::new(),
+            lines: Vec::new(),
+            styles: StyleTable::new(),
+            checkpoints: vec![syntect::easy::HighlightLines::new(syntax, theme).state()],
+            changed: Vec::new(),
+            taken_line_count: 0,
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+            metrics: Metrics::new(),
+        }
+    }
+
+    /// Replaces the whole document with `source` and highlights it.
+    ///
+    /// # Errors
+    /// Can return the same errors as [replace_lines](IncrementalHighlighter::replace_lines).
+    pub fn set_text(&mut self, source: &str) -> Result<(), SyntectTuiError> {
+        self.replace_lines(0..self.source.len(), source).map(|_| ())
+    }
+
+    /// Replaces the lines in `lines` with those of `text`, which may be more or fewer (or none, to
+    /// remove them), and re-highlights as few lines as possible.
+    ///
+    /// `lines` is clamped to the document, so an empty range at its end appends to it. A line
+    /// ending is added to the last line of `text` if lines follow it, and to the last line of the
+    /// document if lines are appended after it.
+    ///
+    /// Returns the range of lines, in the edited document, that were re-highlighted; any other
+    /// lines are unchanged.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line of the edited document they occurred on. The document is left as it was before
+    /// the edit.
+    pub fn replace_lines(
+        &mut self,
+        lines: std::ops::Range<usize>,
+        text: &str,
+    ) -> Result<std::ops::Range<usize>, SyntectTuiError> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("replace_lines", start = lines.start, end = lines.end).entered();
+        let end = lines.end.min(self.source.len());
+        let mut start = lines.start.min(end);
+        let mut inserted: Vec<String> = syntect::util::LinesWithEndings::from(text)
+            .map(str::to_string)
+            .collect();
+        if end < self.source.len() {
+            if let Some(last) = inserted.last_mut().filter(|last| !last.ends_with('\n')) {
+                last.push('\n');
+            }
+        }
+        if start > 0
+            && start == self.source.len()
+            && !inserted.is_empty()
+            && !self.source[start - 1].ends_with('\n')
+        {
+            start -= 1;
+            inserted.insert(0, format!("{}\n", self.source[start]));
+        }
+        let mut states = self.checkpoints[start].clone();
+        let mut highlighted = Vec::with_capacity(inserted.len());
+        let mut checkpoints = Vec::with_capacity(inserted.len());
+        for (offset, line) in inserted.iter().chain(&self.source[end..]).enumerate() {
+            if offset >= inserted.len() && states == self.checkpoints[end + offset - inserted.len()]
+            {
+                break;
+            }
+            let (converted, outgoing) = highlight_from_states(
+                &states,
+                self.syntax_set,
+                self.theme,
+                line,
+                &self.fallback,
+                &self.translator,
+                Some(&mut self.metrics),
+            )
+            .map_err(|error| error.at_line(start + offset))?;
+            highlighted.push(InternedLine::new(&converted, &mut self.styles));
+            checkpoints.push(outgoing.clone());
+            states = outgoing;
+        }
+        let count = highlighted.len();
+        let replaced = end + count - inserted.len();
+        if replaced - start == count {
+            for (offset, line) in highlighted.iter().enumerate() {
+                if self.lines[start + offset] != *line {
+                    self.mark_changed(start + offset..start + offset + 1);
+                }
+            }
+        } else {
+            self.mark_changed(start..usize::MAX);
+        }
+        self.source.splice(start..end, inserted);
+        self.lines.splice(start..replaced, highlighted);
+        self.checkpoints
+            .splice(start + 1..replaced + 1, checkpoints);
+        Ok(start..start + count)
+    }
+
+    /// Highlights the document with `theme` instead, e.g. as a theme picker moves between themes.
+    ///
+    /// The parser states before each line are kept, so each line is highlighted again from its
+    /// checkpoint on its own, rather than the document being highlighted from its first line, and
+    /// the styles of the previous theme are dropped. Every line is marked as changed.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line they occurred on. The document is left highlighted with the previous theme.
+    pub fn set_theme(
+        &mut self,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Result<(), SyntectTuiError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("set_theme", lines = self.lines.len()).entered();
+        let checkpoints: Vec<LineStates> = self
+            .checkpoints
+            .iter()
+            .map(|states| crate::highlight::restyle_states(states, theme))
+            .collect();
+        let mut styles = StyleTable::new();
+        let lines = self
+            .source
+            .iter()
+            .zip(&checkpoints)
+            .enumerate()
+            .map(|(index, (line, states))| {
+                let (converted, _) = highlight_from_states(
+                    states,
+                    self.syntax_set,
+                    theme,
+                    line,
+                    &self.fallback,
+                    &self.translator,
+                    Some(&mut self.metrics),
+                )
+                .map_err(|error| error.at_line(index))?;
+                Ok(InternedLine::new(&converted, &mut styles))
+            })
+            .collect::<Result<_, SyntectTuiError>>()?;
+        self.theme = theme;
+        self.checkpoints = checkpoints;
+        self.styles = styles;
+        self.lines = lines;
+        self.mark_changed(0..usize::MAX);
+        Ok(())
+    }
+
+    /// Returns the number of lines in the document.
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the measurements of the lines highlighted since the highlighter was created or its
+    /// metrics were [reset](IncrementalHighlighter::reset_metrics).
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Resets the highlighter's metrics.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = Metrics::new();
+    }
+
+    /// Returns the sorted, disjoint ranges of lines whose converted line differs from the line
+    /// that was at the same index when they were last taken, and starts tracking changes afresh,
+    /// e.g. so that a TUI can redraw only the rows that changed.
+    ///
+    /// Lines that were re-highlighted but came out the same are left out. Inserting or removing
+    /// lines moves every line after them, so those lines are all changed, along with the lines
+    /// past the end of a document that got shorter, which need to be cleared.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect::highlighting::ThemeSet;
+    /// use syntect::parsing::SyntaxSet;
+    /// use syntect_tui::IncrementalHighlighter;
+    ///
+    /// let ps = SyntaxSet::load_defaults_newlines();
+    /// let ts = ThemeSet::load_defaults();
+    /// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+    /// let mut highlighter = IncrementalHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+    /// highlighter.set_text("let a = 1;\nlet b = 2;\nlet c = 3;\n").unwrap();
+    /// assert_eq!(vec![0..3], highlighter.take_changed_lines());
+    /// highlighter.replace_lines(1..2, "let b = 20;\n").unwrap();
+    /// highlighter.replace_lines(1..2, "let b = 2;\n").unwrap();
+    /// assert_eq!(vec![1..2], highlighter.take_changed_lines());
+    /// highlighter.replace_lines(2..3, "").unwrap();
+    /// assert_eq!(vec![2..3], highlighter.take_changed_lines());
+    /// assert!(highlighter.take_changed_lines().is_empty());
+    /// ```
+    pub fn take_changed_lines(&mut self) -> Vec<std::ops::Range<usize>> {
+        let rows = self.lines.len().max(self.taken_line_count);
+        self.taken_line_count = self.lines.len();
+        std::mem::take(&mut self.changed)
+            .into_iter()
+            .map(|lines| lines.start..lines.end.min(rows))
+            .filter(|lines| !lines.is_empty())
+            .collect()
+    }
+
+    /// Adds `lines` to the changed lines, merging it with the ranges it overlaps or touches.
+    fn mark_changed(&mut self, mut lines: std::ops::Range<usize>) {
+        let first = self
+            .changed
+            .partition_point(|changed| changed.end < lines.start);
+        let last = self
+            .changed
+            .partition_point(|changed| changed.start <= lines.end);
+        if let Some(merged) = self
+            .changed
+            .get(first..last)
+            .filter(|merged| !merged.is_empty())
+        {
+            lines.start = lines.start.min(merged[0].start);
+            lines.end = lines.end.max(merged[merged.len() - 1].end);
+        }
+        self.changed.splice(first..last, [lines]);
+    }
+
+    /// Returns converted line `index`, or `None` if the document has no such line.
+    pub fn line(&self, index: usize) -> Option<ratatui::text::Line<'_>> {
+        self.lines.get(index).map(|line| line.to_line(&self.styles))
+    }
+
+    /// Returns the lines of the document, with their line endings.
+    pub fn source(&self) -> &[String] {
+        &self.source
+    }
+
+    /// Returns the converted lines in `lines`, e.g. those visible in a viewport, with the theme's
+    /// background. Returns fewer lines if the document ends before `lines` does.
+    pub fn text(&self, lines: std::ops::Range<usize>) -> HighlightedText<'_> {
+        let end = lines.end.min(self.lines.len());
+        let start = lines.start.min(end);
+        let converted = self.lines[start..end]
+            .iter()
+            .map(|line| line.to_line(&self.styles))
+            .collect();
+        HighlightedText::new(converted, self.translator.theme_background(self.theme))
+    }
+
+    /// Returns the converted lines in `lines` like [IncrementalHighlighter::text], but in lines
+    /// taken from `pool`, which can be [recycled](crate::LinePool::recycle) once drawn.
+    pub fn text_with(
+        &self,
+        lines: std::ops::Range<usize>,
+        pool: &mut crate::LinePool,
+    ) -> HighlightedText<'_> {
+        let converted = crate::intern::write_lines(&self.lines, &self.styles, lines, pool);
+        HighlightedText::new(converted, self.translator.theme_background(self.theme))
+    }
+
+    /// Returns a copy of the converted document, e.g. to
+    /// [publish](crate::HighlightedDocument::replace) it to the viewports drawing it.
+    pub fn interned_text(&self) -> crate::InternedText {
+        let background = self.translator.theme_background(self.theme);
+        crate::InternedText::from_parts(self.styles.clone(), self.lines.clone(), background)
+    }
+
+    /// Returns a snapshot of the highlighted document, which can be saved to
+    /// [restore](crate::HighlightSnapshot::restore) it later.
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> crate::HighlightSnapshot {
+        crate::HighlightSnapshot::new(&self.source.concat(), self.interned_text())
+    }
+}
+
+/// Lines already highlighted keep how they were converted until they are re-highlighted.
+impl crate::LineConversion for IncrementalHighlighter<'_> {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineConversion;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\nlet b = \"x\";\nlet c = 3;\nlet d = 4;\n";
+
+    #[rstest]
+    #[case::edited_line(1..2, "let b = 2;\n", 1..2)]
+    #[case::edited_inside_string(1..2, "let b = \"xy\";\n", 1..2)]
+    #[case::opened_comment(1..2, "/* b = \"x\";\n", 1..4)]
+    #[case::opened_string(2..3, "let c = \"3;\n", 2..4)]
+    #[case::inserted_lines(1..1, "let y = 0;\nlet z = 0;\n", 1..3)]
+    #[case::removed_lines(1..3, "", 1..1)]
+    #[case::replaced_lines(0..2, "let a = 1;", 0..1)]
+    #[case::appended_line(4..9, "let e = 5;\n", 4..5)]
+    #[case::cleared(0..4, "", 0..0)]
+    fn check_replace_lines(
+        #[case] lines: std::ops::Range<usize>,
+        #[case] text: &str,
+        #[case] expected: std::ops::Range<usize>,
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme);
+        highlighter.set_text(SOURCE).unwrap();
+        assert_eq!(expected, highlighter.replace_lines(lines, text).unwrap());
+        let mut fresh = IncrementalHighlighter::new(syntax, &ps, theme);
+        fresh.set_text(&highlighter.source().concat()).unwrap();
+        assert_eq!(fresh.text(0..usize::MAX), highlighter.text(0..usize::MAX));
+        assert_eq!(fresh.checkpoints, highlighter.checkpoints);
+    }
+
+    #[test]
+    fn converts_with_the_translator() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let quantizer =
+            crate::PaletteQuantizer::new((16..=255).map(ratatui::style::Color::Indexed));
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme)
+            .translator(crate::StyleTranslator::new().quantize_colours(quantizer));
+        highlighter.set_text(SOURCE).unwrap();
+        let text = highlighter.text(0..usize::MAX);
+        assert!(matches!(
+            text.background,
+            Some(ratatui::style::Color::Indexed(_))
+        ));
+        let colours: Vec<_> = text
+            .lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .filter_map(|span| span.style.fg)
+            .collect();
+        assert!(!colours.is_empty());
+        assert!(colours
+            .iter()
+            .all(|colour| matches!(colour, ratatui::style::Color::Indexed(_))));
+    }
+
+    #[test]
+    fn plain_translator_leaves_lines_unstyled() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme)
+            .translator(crate::StyleTranslator::new().plain(true));
+        highlighter.set_text(SOURCE).unwrap();
+        let text = highlighter.text(0..usize::MAX);
+        assert_eq!(None, text.background);
+        assert!(text.lines.iter().all(|line| {
+            line.style == ratatui::style::Style::default()
+                && line
+                    .spans
+                    .iter()
+                    .all(|span| span.style == ratatui::style::Style::default())
+        }));
+    }
+
+    #[test]
+    fn switching_themes_rehighlights_every_line() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["InspiredGitHub"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let source = "let a = 1;\n/* b\nc */\nlet d = \"e\";\n";
+        let mut highlighter =
+            IncrementalHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+        highlighter.set_text(source).unwrap();
+        highlighter.take_changed_lines();
+        highlighter.set_theme(theme).unwrap();
+        assert_eq!(vec![0..4], highlighter.take_changed_lines());
+        let mut fresh = IncrementalHighlighter::new(syntax, &ps, theme);
+        fresh.set_text(source).unwrap();
+        assert_eq!(fresh.text(0..usize::MAX), highlighter.text(0..usize::MAX));
+        assert_eq!(fresh.checkpoints, highlighter.checkpoints);
+        assert_eq!(fresh.styles, highlighter.styles);
+    }
+
+    #[test]
+    fn appending_ends_the_last_line() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme);
+        highlighter.set_text("let a = 1;").unwrap();
+        assert_eq!(0..2, highlighter.replace_lines(1..1, "let b = 2;").unwrap());
+        assert_eq!(
+            vec!["let a = 1;\n", "let b = 2;"],
+            highlighter.source().to_vec()
+        );
+        // syntect parses the first line of a document differently, so it is re-highlighted too
+        assert_eq!(0..2, highlighter.replace_lines(0..0, "let z = 0;").unwrap());
+        assert_eq!("let z = 0;\n", highlighter.source()[0]);
+    }
+
+    #[rstest]
+    #[case::unchanged(1..2, "let b = \"x\";\n", vec![])]
+    #[case::edited_line(1..2, "let b = 2;\n", vec![1..2])]
+    #[case::opened_comment(1..2, "/* b = \"x\";\n", vec![1..4])]
+    #[case::inserted_line(1..1, "let z = 0;\n", vec![1..5])]
+    #[case::removed_line(1..2, "", vec![1..4])]
+    fn check_take_changed_lines(
+        #[case] lines: std::ops::Range<usize>,
+        #[case] text: &str,
+        #[case] expected: Vec<std::ops::Range<usize>>,
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme);
+        highlighter.set_text(SOURCE).unwrap();
+        assert_eq!(vec![0..4], highlighter.take_changed_lines());
+        highlighter.replace_lines(lines, text).unwrap();
+        assert_eq!(expected, highlighter.take_changed_lines());
+    }
+
+    #[test]
+    fn changed_lines_are_merged() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme);
+        highlighter.set_text(SOURCE).unwrap();
+        highlighter.take_changed_lines();
+        highlighter.replace_lines(3..4, "let d = 0;\n").unwrap();
+        highlighter.replace_lines(0..1, "let a = 0;\n").unwrap();
+        assert_eq!(vec![0..1, 3..4], highlighter.changed);
+        highlighter.replace_lines(1..2, "let b = 0;\n").unwrap();
+        assert_eq!(vec![0..2, 3..4], highlighter.changed);
+        highlighter.replace_lines(2..3, "let c = 0;\n").unwrap();
+        assert_eq!(vec![0..4], highlighter.take_changed_lines());
+    }
+}
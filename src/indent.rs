@@ -0,0 +1,227 @@
+use crate::colour::{blend, rgb, ColourSpace};
+
+/// The symbol, indent width and style of the indent guides drawn by
+/// [show_indent_guides](crate::show_indent_guides).
+///
+/// By default guides are drawn as `│` every 4 columns in a
+/// [DIM](ratatui::style::Modifier::DIM) style that is patched over the syntax colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IndentGuides {
+    symbol: char,
+    width: usize,
+    style: ratatui::style::Style,
+}
+
+impl Default for IndentGuides {
+    fn default() -> Self {
+        Self {
+            symbol: '│',
+            width: 4,
+            style: ratatui::style::Style::new().add_modifier(ratatui::style::Modifier::DIM),
+        }
+    }
+}
+
+impl IndentGuides {
+    /// Creates the default indent guides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates indent guides in a subtle colour from `theme`.
+    ///
+    /// The theme's `guide` setting is used if it has one, or else its foreground faded most of
+    /// the way into its background, falling back to the default style if it has neither.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::{Color, Style};
+    /// use syntect::highlighting::{Color as SyntectColour, Theme};
+    /// use syntect_tui::IndentGuides;
+    ///
+    /// let mut theme = Theme::default();
+    /// theme.settings.foreground = Some(SyntectColour { r: 255, g: 255, b: 255, a: 255 });
+    /// theme.settings.background = Some(SyntectColour::BLACK);
+    /// assert_eq!(
+    ///     IndentGuides::new().style(Style::new().fg(Color::Rgb(64, 64, 64))),
+    ///     IndentGuides::from_theme(&theme)
+    /// );
+    /// ```
+    pub fn from_theme(theme: &syntect::highlighting::Theme) -> Self {
+        let settings = &theme.settings;
+        let faded = settings
+            .foreground
+            .map(|foreground| syntect::highlighting::Color {
+                a: 0x40,
+                ..foreground
+            });
+        let Some(colour) = settings.guide.or(faded) else {
+            return Self::default();
+        };
+        let colour = settings.background.map_or(colour, |background| {
+            blend(colour, background, ColourSpace::Srgb)
+        });
+        Self::default().style(ratatui::style::Style::new().fg(rgb(colour)))
+    }
+
+    /// Sets the symbol drawn for each guide.
+    pub fn symbol(mut self, symbol: char) -> Self {
+        self.symbol = symbol;
+        self
+    }
+
+    /// Sets the number of display columns per indent level, or `0` to draw no guides.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the style patched over the guides.
+    pub fn style(mut self, style: ratatui::style::Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+/// Draws indent guides in the leading whitespace of `line`, replacing the space at every
+/// [indent width](IndentGuides::width) display columns with the guide symbol, in a span with the
+/// guide style patched over the original one.
+///
+/// Leading tabs are expanded to spaces up to the next multiple of `tab_width`, or of the indent
+/// width if it is `None`, so that guides line up by display column whatever the indentation is
+/// made of. Guides are only drawn before the first other character, so a line's indentation
+/// decides how many it gets, and display columns are unaffected, so guides stay in place when the
+/// line is [sliced](crate::slice_line) for horizontal scrolling.
+///
+/// # Examples
+/// ```
+/// use ratatui::text::Line;
+/// use syntect_tui::IndentGuides;
+///
+/// let line = Line::raw("\t    x = 1;");
+/// let shown = syntect_tui::show_indent_guides(&line, &IndentGuides::default(), Some(4));
+/// assert_eq!("│   │   x = 1;", shown.to_string());
+/// ```
+pub fn show_indent_guides<'a>(
+    line: &ratatui::text::Line<'a>,
+    guides: &IndentGuides,
+    tab_width: Option<usize>,
+) -> ratatui::text::Line<'a> {
+    if guides.width == 0 {
+        return line.clone();
+    }
+    let tab_width = tab_width.unwrap_or(guides.width);
+    let mut spans = Vec::with_capacity(line.spans.len());
+    let mut column = 0;
+    let mut indenting = true;
+    for span in &line.spans {
+        let indent = span
+            .content
+            .find(|character| character != ' ' && character != '\t')
+            .unwrap_or(span.content.len());
+        if !indenting || indent == 0 {
+            indenting &= span.content.is_empty();
+            spans.push(span.clone());
+            continue;
+        }
+        let guide_style = span.style.patch(guides.style);
+        // Spaces between guides are collected and flushed as a span before each guide.
+        let mut run = String::new();
+        for character in span.content[..indent].chars() {
+            let columns = match (character, tab_width) {
+                (' ', _) => 1,
+                (_, 0) => 0,
+                (_, width) => width - column % width,
+            };
+            for next in column..column + columns {
+                if next % guides.width == 0 {
+                    if !run.is_empty() {
+                        spans.push(ratatui::text::Span::styled(
+                            std::mem::take(&mut run),
+                            span.style,
+                        ));
+                    }
+                    spans.push(ratatui::text::Span::styled(
+                        guides.symbol.to_string(),
+                        guide_style,
+                    ));
+                } else {
+                    run.push(' ');
+                }
+            }
+            column += columns;
+        }
+        run.push_str(&span.content[indent..]);
+        if !run.is_empty() {
+            spans.push(ratatui::text::Span::styled(run, span.style));
+        }
+        indenting = indent == span.content.len();
+    }
+    ratatui::text::Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Style, Stylize};
+    use ratatui::text::{Line, Span};
+    use rstest::*;
+
+    #[rstest]
+    #[case::spaces("        x", None, "│   │   x")]
+    #[case::partial_level("      x", None, "│   │ x")]
+    #[case::tabs("\t\tx", Some(2), "│   x")]
+    #[case::tab_defaults_to_indent_width("\tx", None, "│   x")]
+    #[case::tab_after_spaces("  \tx", Some(8), "│   │   x")]
+    #[case::zero_tab_width("\t x", Some(0), "│x")]
+    #[case::unindented("x  y", None, "x  y")]
+    #[case::only_whitespace("     ", None, "│   │")]
+    #[case::empty("", None, "")]
+    fn check_show_indent_guides(
+        #[case] content: &str,
+        #[case] tab_width: Option<usize>,
+        #[case] expected: &str,
+    ) {
+        let line = Line::raw(content);
+        let shown = show_indent_guides(&line, &IndentGuides::default(), tab_width);
+        assert_eq!(expected, shown.to_string());
+    }
+
+    #[test]
+    fn indentation_can_span_several_spans() {
+        let line = Line::from(vec![
+            Span::raw("  ").on_blue(),
+            Span::raw(""),
+            Span::raw("    x").red(),
+            Span::raw("    y"),
+        ]);
+        let guides = IndentGuides::new()
+            .width(2)
+            .symbol('¦')
+            .style(Style::new().gray());
+        assert_eq!(
+            vec![
+                Span::raw("¦").on_blue().gray(),
+                Span::raw(" ").on_blue(),
+                Span::raw(""),
+                Span::raw("¦").red().gray(),
+                Span::raw(" ").red(),
+                Span::raw("¦").red().gray(),
+                Span::raw(" x").red(),
+                Span::raw("    y"),
+            ],
+            show_indent_guides(&line, &guides, None).spans
+        );
+    }
+
+    #[test]
+    fn zero_width_draws_no_guides() {
+        let line = Line::raw("\t  x");
+        let guides = IndentGuides::new().width(0);
+        assert_eq!(line, show_indent_guides(&line, &guides, Some(4)));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::{HighlightedText, LinePool};
+
+/// Identifies a style in a [StyleTable].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StyleId(u32);
+
+/// Stores each distinct style once, so that converted lines can refer to their styles by a small
+/// [StyleId] rather than each holding a copy.
+///
+/// Highlighting a large document produces a span for every token but only a few dozen distinct
+/// styles, so [InternedLine]s sharing a table take far less memory than the equivalent ratatui
+/// lines.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use syntect_tui::StyleTable;
+///
+/// let mut table = StyleTable::new();
+/// let red = table.intern(Style::new().red());
+/// assert_eq!(red, table.intern(Style::new().red()));
+/// assert_ne!(red, table.intern(Style::new().blue()));
+/// assert_eq!(Some(Style::new().red()), table.get(red));
+/// assert_eq!(2, table.len());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        from = "Vec<ratatui::style::Style>",
+        into = "Vec<ratatui::style::Style>"
+    )
+)]
+pub struct StyleTable {
+    styles: Vec<ratatui::style::Style>,
+    ids: std::collections::HashMap<ratatui::style::Style, StyleId>,
+}
+
+impl StyleTable {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `style`, adding it to the table if it is not in it yet.
+    pub fn intern(&mut self, style: ratatui::style::Style) -> StyleId {
+        *self.ids.entry(style).or_insert_with(|| {
+            let id = StyleId(u32::try_from(self.styles.len()).unwrap_or(u32::MAX));
+            self.styles.push(style);
+            id
+        })
+    }
+
+    /// Returns the style with `id`, or `None` if it is not from this table.
+    pub fn get(&self, id: StyleId) -> Option<ratatui::style::Style> {
+        self.styles.get(id.0 as usize).copied()
+    }
+
+    /// Returns the number of distinct styles in the table.
+    pub fn len(&self) -> usize {
+        self.styles.len()
+    }
+
+    /// Returns whether the table has no styles.
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+}
+
+impl From<Vec<ratatui::style::Style>> for StyleTable {
+    fn from(styles: Vec<ratatui::style::Style>) -> Self {
+        let mut table = Self::new();
+        for style in styles {
+            table.styles.push(style);
+            let id = StyleId(u32::try_from(table.styles.len() - 1).unwrap_or(u32::MAX));
+            table.ids.entry(style).or_insert(id);
+        }
+        table
+    }
+}
+
+impl From<StyleTable> for Vec<ratatui::style::Style> {
+    fn from(table: StyleTable) -> Self {
+        table.styles
+    }
+}
+
+/// A converted line whose styles are interned in a [StyleTable], with the contents of all its
+/// spans in a single allocation.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Stylize;
+/// use ratatui::text::{Line, Span};
+/// use syntect_tui::{InternedLine, StyleTable};
+///
+/// let mut table = StyleTable::new();
+/// let line = Line::from(vec![Span::raw("let").blue(), Span::raw(" x")]);
+/// let interned = InternedLine::new(&line, &mut table);
+/// assert_eq!("let x", interned.content());
+/// assert_eq!(line, interned.to_line(&table));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "InternedLineParts")
+)]
+pub struct InternedLine {
+    content: Box<str>,
+    /// The style of each span and the byte offset in `content` it ends at.
+    spans: Box<[(StyleId, u32)]>,
+    style: StyleId,
+    #[cfg_attr(feature = "serde", serde(with = "serde_alignment"))]
+    alignment: Option<ratatui::layout::Alignment>,
+}
+
+/// The fields of a deserialized [InternedLine], which are checked before they are used.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct InternedLineParts {
+    content: Box<str>,
+    spans: Box<[(StyleId, u32)]>,
+    style: StyleId,
+    #[serde(with = "serde_alignment")]
+    alignment: Option<ratatui::layout::Alignment>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<InternedLineParts> for InternedLine {
+    type Error = String;
+
+    /// Checks that the spans end in order at character boundaries of the content, so that
+    /// converting the line cannot panic.
+    fn try_from(parts: InternedLineParts) -> Result<Self, Self::Error> {
+        let mut start = 0;
+        for (_, end) in parts.spans.iter() {
+            let end = *end as usize;
+            if end < start || !parts.content.is_char_boundary(end) {
+                return Err(format!("invalid span end {end} in {:?}", parts.content));
+            }
+            start = end;
+        }
+        Ok(Self {
+            content: parts.content,
+            spans: parts.spans,
+            style: parts.style,
+            alignment: parts.alignment,
+        })
+    }
+}
+
+/// Stores alignments by name, as ratatui cannot serialize them.
+#[cfg(feature = "serde")]
+mod serde_alignment {
+    use ratatui::layout::Alignment;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        alignment: &Option<Alignment>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        alignment
+            .map(|alignment| alignment.to_string())
+            .serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Alignment>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|name| name.parse().map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+impl InternedLine {
+    /// Interns the styles of `line` in `table` and copies its contents.
+    pub fn new(line: &ratatui::text::Line<'_>, table: &mut StyleTable) -> Self {
+        let mut content =
+            String::with_capacity(line.spans.iter().map(|span| span.content.len()).sum());
+        let spans = line
+            .spans
+            .iter()
+            .map(|span| {
+                content.push_str(&span.content);
+                let end = u32::try_from(content.len()).unwrap_or(u32::MAX);
+                (table.intern(span.style), end)
+            })
+            .collect();
+        Self {
+            content: content.into_boxed_str(),
+            spans,
+            style: table.intern(line.style),
+            alignment: line.alignment,
+        }
+    }
+
+    /// Returns the contents of all the line's spans.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Converts the line back into a ratatui line borrowing its contents, with its styles looked
+    /// up in `table`, which must be the table it was interned in. Styles that are not in `table`
+    /// are left empty.
+    pub fn to_line<'a>(&'a self, table: &StyleTable) -> ratatui::text::Line<'a> {
+        let mut line = ratatui::text::Line::default();
+        self.write_to(table, &mut line);
+        line
+    }
+
+    /// Converts the line back into `line` like [InternedLine::to_line], reusing the memory of
+    /// its spans, e.g. one taken from a [LinePool].
+    pub fn write_to<'a>(&'a self, table: &StyleTable, line: &mut ratatui::text::Line<'a>) {
+        let mut start = 0;
+        line.spans.clear();
+        line.spans.extend(self.spans.iter().map(|(style, end)| {
+            let content = &self.content[start..*end as usize];
+            start = *end as usize;
+            ratatui::text::Span::styled(content, table.get(*style).unwrap_or_default())
+        }));
+        line.style = table.get(self.style).unwrap_or_default();
+        line.alignment = self.alignment;
+    }
+}
+
+/// A whole document of [InternedLine]s sharing a [StyleTable], along with the background of the
+/// theme that highlighted them, e.g. to keep a large highlighted file in memory and convert only
+/// the lines being drawn.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::InternedText;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let theme = &ts.themes["base16-ocean.dark"];
+/// let source = "let x = 1;\n".repeat(1000);
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut cache = syntect_tui::HighlightCache::new(syntax, &ps, theme);
+/// let text = cache.highlight(&source).unwrap();
+/// let interned = InternedText::from(&text);
+/// assert_eq!(1000, interned.len());
+/// assert!(interned.styles().len() < 10);
+/// assert_eq!(text.lines[10..12], interned.text(10..12).lines[..]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InternedText {
+    styles: StyleTable,
+    lines: Vec<InternedLine>,
+    /// The background colour of the theme, if it has one.
+    pub background: Option<ratatui::style::Color>,
+}
+
+impl InternedText {
+    /// Creates an empty document with a background colour.
+    pub fn new(background: Option<ratatui::style::Color>) -> Self {
+        Self {
+            background,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a document from lines interned in `styles`.
+    pub(crate) fn from_parts(
+        styles: StyleTable,
+        lines: Vec<InternedLine>,
+        background: Option<ratatui::style::Color>,
+    ) -> Self {
+        Self {
+            styles,
+            lines,
+            background,
+        }
+    }
+
+    /// Interns `line` and adds it to the end of the document.
+    pub fn push(&mut self, line: &ratatui::text::Line<'_>) {
+        self.lines.push(InternedLine::new(line, &mut self.styles));
+    }
+
+    /// Returns the number of lines in the document.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns whether the document has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Returns the table of the document's styles.
+    pub fn styles(&self) -> &StyleTable {
+        &self.styles
+    }
+
+    /// Converts line `index` into a ratatui line borrowing its contents, or returns `None` if the
+    /// document has no such line.
+    pub fn line(&self, index: usize) -> Option<ratatui::text::Line<'_>> {
+        self.lines.get(index).map(|line| line.to_line(&self.styles))
+    }
+
+    /// Converts the lines in `lines`, e.g. those visible in a viewport, into highlighted text
+    /// borrowing their contents. Returns fewer lines if the document ends before `lines` does.
+    pub fn text(&self, lines: std::ops::Range<usize>) -> HighlightedText<'_> {
+        let end = lines.end.min(self.lines.len());
+        let start = lines.start.min(end);
+        HighlightedText::new(
+            self.lines[start..end]
+                .iter()
+                .map(|line| line.to_line(&self.styles))
+                .collect(),
+            self.background,
+        )
+    }
+
+    /// Converts the lines in `lines` like [InternedText::text], but into lines taken from `pool`,
+    /// which can be [recycled](LinePool::recycle) once drawn.
+    pub fn text_with(
+        &self,
+        lines: std::ops::Range<usize>,
+        pool: &mut LinePool,
+    ) -> HighlightedText<'_> {
+        HighlightedText::new(
+            write_lines(&self.lines, &self.styles, lines, pool),
+            self.background,
+        )
+    }
+}
+
+/// Converts the lines of `interned` in `lines` into lines taken from `pool`.
+pub(crate) fn write_lines<'a>(
+    interned: &'a [InternedLine],
+    table: &StyleTable,
+    lines: std::ops::Range<usize>,
+    pool: &mut LinePool,
+) -> Vec<ratatui::text::Line<'a>> {
+    let end = lines.end.min(interned.len());
+    let start = lines.start.min(end);
+    let mut written = pool.lines();
+    written.extend(interned[start..end].iter().map(|line| {
+        let mut pooled = pool.line();
+        line.write_to(table, &mut pooled);
+        pooled
+    }));
+    written
+}
+
+impl From<&HighlightedText<'_>> for InternedText {
+    fn from(text: &HighlightedText<'_>) -> Self {
+        let mut interned = Self::new(text.background);
+        for line in &text.lines {
+            interned.push(line);
+        }
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Alignment;
+    use ratatui::style::{Color, Style, Stylize};
+    use ratatui::text::{Line, Span};
+    use rstest::*;
+
+    fn fake_text() -> HighlightedText<'static> {
+        HighlightedText::new(
+            vec![
+                Line::from(vec![
+                    Span::raw("fn").blue(),
+                    Span::raw(" "),
+                    Span::raw("日本").red(),
+                ]),
+                Line::default(),
+                Line::from(vec![Span::raw("x").red(), Span::raw("")])
+                    .style(Style::new().on_black())
+                    .alignment(Alignment::Right),
+            ],
+            Some(Color::Black),
+        )
+    }
+
+    #[test]
+    fn round_trips_lines() {
+        let text = fake_text();
+        let interned = InternedText::from(&text);
+        assert_eq!(text.lines.len(), interned.len());
+        for (index, line) in text.lines.iter().enumerate() {
+            assert_eq!(Some(line), interned.line(index).as_ref());
+        }
+        assert_eq!(text, interned.text(0..usize::MAX));
+        assert_eq!(None, interned.line(3));
+    }
+
+    #[test]
+    fn styles_are_stored_once() {
+        let interned = InternedText::from(&fake_text());
+        // Blue, red, the empty style and black.
+        assert_eq!(4, interned.styles().len());
+    }
+
+    #[rstest]
+    #[case::middle(1..3, 2)]
+    #[case::past_end(2..9, 1)]
+    #[case::empty(5..9, 0)]
+    fn check_text(#[case] lines: std::ops::Range<usize>, #[case] expected: usize) {
+        let interned = InternedText::from(&fake_text());
+        let text = interned.text(lines);
+        assert_eq!(expected, text.lines.len());
+        assert_eq!(Some(Color::Black), text.background);
+    }
+
+    #[test]
+    fn unknown_styles_are_empty() {
+        let mut table = StyleTable::new();
+        let line = InternedLine::new(&Line::raw("x").red(), &mut table);
+        assert_eq!(Line::raw("x"), line.to_line(&StyleTable::new()));
+    }
+
+    #[test]
+    fn text_with_matches_text() {
+        let interned = InternedText::from(&fake_text());
+        let mut pool = LinePool::new();
+        for _ in 0..2 {
+            let text = interned.text_with(1..9, &mut pool);
+            assert_eq!(interned.text(1..9), text);
+            pool.recycle(text.lines);
+        }
+        assert_eq!(2, pool.len());
+    }
+}
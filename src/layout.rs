@@ -0,0 +1,144 @@
+use unicode_width::UnicodeWidthChar;
+
+/// Cuts a [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html)
+/// down to the display columns in `columns`, keeping the style of every span.
+///
+/// Columns are measured by display width, so wide characters (e.g. CJK ideographs) count for two
+/// columns. A wide character that straddles either edge of `columns` cannot be partially drawn, so
+/// its visible half is replaced with a space in the same style. This keeps the sliced line exactly
+/// as wide as the portion of `columns` the line covers, which is what horizontal scrolling needs.
+///
+/// If `tab_width` is set, tabs advance to the next multiple of `tab_width` columns, like
+/// [StyleTranslator::expand_tabs](crate::StyleTranslator::expand_tabs), and are replaced with the
+/// spaces they cover. Otherwise tabs, like other control characters, count as zero columns, so
+/// lines with tabs should have them expanded first.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use ratatui::text::{Line, Span};
+///
+/// let line = Line::from(vec![
+///     Span::styled("let", Style::new().blue()),
+///     Span::raw(" x = "),
+///     Span::styled("\"日本\"", Style::new().green()),
+/// ]);
+/// let sliced = syntect_tui::slice_line(&line, 2..10, None);
+/// assert_eq!(
+///     vec![
+///         Span::styled("t", Style::new().blue()),
+///         Span::raw(" x = "),
+///         Span::styled("\" ", Style::new().green()),
+///     ],
+///     sliced.spans
+/// );
+/// ```
+pub fn slice_line<'a>(
+    line: &ratatui::text::Line<'a>,
+    columns: std::ops::Range<usize>,
+    tab_width: Option<usize>,
+) -> ratatui::text::Line<'a> {
+    let mut spans = Vec::new();
+    let mut column = 0;
+    for span in &line.spans {
+        if column >= columns.end {
+            break;
+        }
+        let mut content = String::new();
+        for character in span.content.chars() {
+            let (width, drawn) = match (character, tab_width) {
+                ('\t', Some(0)) => (0, None),
+                ('\t', Some(tab_width)) => (tab_width - column % tab_width, None),
+                _ => (character.width().unwrap_or(0), Some(character)),
+            };
+            let (start, end) = (column, column + width);
+            column = end;
+            if end <= columns.start && start < columns.start {
+                continue;
+            }
+            if start >= columns.end {
+                break;
+            }
+            match drawn {
+                Some(character) if start >= columns.start && end <= columns.end => {
+                    content.push(character);
+                }
+                _ => {
+                    let visible = end.min(columns.end) - start.max(columns.start);
+                    content.extend(std::iter::repeat_n(' ', visible));
+                }
+            }
+        }
+        if !content.is_empty() {
+            spans.push(ratatui::text::Span::styled(content, span.style));
+        }
+    }
+    let mut sliced = ratatui::text::Line::from(spans).style(line.style);
+    sliced.alignment = line.alignment;
+    sliced
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::{Style, Stylize};
+    use ratatui::text::{Line, Span};
+
+    fn fake_line() -> Line<'static> {
+        Line::from(vec![
+            Span::styled("ab", Style::new().red()),
+            Span::styled("日c", Style::new().blue()),
+        ])
+    }
+
+    #[rstest]
+    #[case::everything(0..10, vec![("ab", Style::new().red()), ("日c", Style::new().blue())])]
+    #[case::nothing(5..10, vec![])]
+    #[case::first_span(0..2, vec![("ab", Style::new().red())])]
+    #[case::across_spans(1..5, vec![("b", Style::new().red()), ("日c", Style::new().blue())])]
+    #[case::wide_start(3..5, vec![(" c", Style::new().blue())])]
+    #[case::wide_end(0..3, vec![("ab", Style::new().red()), (" ", Style::new().blue())])]
+    fn check_slice_line(
+        #[case] columns: std::ops::Range<usize>,
+        #[case] expected: Vec<(&str, Style)>,
+    ) {
+        let expected: Vec<Span> = expected
+            .into_iter()
+            .map(|(content, style)| Span::styled(content, style))
+            .collect();
+        assert_eq!(expected, slice_line(&fake_line(), columns, None).spans);
+    }
+
+    #[rstest]
+    #[case::everything(0..10, Some(4), vec![("ab  ", Style::new().red()), ("日c", Style::new().blue())])]
+    #[case::after_tab(4..10, Some(4), vec![("日c", Style::new().blue())])]
+    #[case::across_tab(3..6, Some(4), vec![(" ", Style::new().red()), ("日", Style::new().blue())])]
+    #[case::wider_tab(0..10, Some(8), vec![("ab      ", Style::new().red()), ("日", Style::new().blue())])]
+    #[case::zero_tab_width(0..4, Some(0), vec![("ab", Style::new().red()), ("日", Style::new().blue())])]
+    #[case::unset_tab_width(2..4, None, vec![("\t", Style::new().red()), ("日", Style::new().blue())])]
+    fn check_slice_line_with_tab(
+        #[case] columns: std::ops::Range<usize>,
+        #[case] tab_width: Option<usize>,
+        #[case] expected: Vec<(&str, Style)>,
+    ) {
+        let line = Line::from(vec![
+            Span::styled("ab\t", Style::new().red()),
+            Span::styled("日c", Style::new().blue()),
+        ]);
+        let expected: Vec<Span> = expected
+            .into_iter()
+            .map(|(content, style)| Span::styled(content, style))
+            .collect();
+        assert_eq!(expected, slice_line(&line, columns, tab_width).spans);
+    }
+
+    #[test]
+    fn slice_line_keeps_line_style() {
+        let line = fake_line().style(Style::new().on_black()).centered();
+        let sliced = slice_line(&line, 0..1, None);
+        assert_eq!(line.style, sliced.style);
+        assert_eq!(line.alignment, sliced.alignment);
+    }
+}
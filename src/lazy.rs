@@ -0,0 +1,271 @@
+use crate::highlight::LineStates;
+use crate::SyntectTuiError;
+
+/// Highlights the lines of a large document on demand, e.g. those visible in a pager, without
+/// highlighting the whole document up front.
+///
+/// Each line's highlighting depends on the lines before it, so highlighting a range of lines
+/// first scans the lines before it, which parses them without converting them. The states are
+/// kept every [checkpoint_interval](LazyHighlighter::checkpoint_interval) lines as they are
+/// scanned, so each line is only scanned once however far the viewport jumps around, and later
+/// requests only scan forward from the nearest checkpoint before them.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::LazyHighlighter;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "/*\n".to_string() + &"let x = 1;\n".repeat(1_000) + "*/\nlet y = 2;\n";
+/// let theme = &ts.themes["base16-ocean.dark"];
+/// let mut highlighter = LazyHighlighter::new(&source, syntax, &ps, theme);
+/// assert_eq!(1_003, highlighter.line_count());
+/// let viewport = highlighter.highlight_lines(1_000..1_010).unwrap();
+/// assert_eq!(3, viewport.len());
+/// assert_eq!("let y = 2;", viewport[2].to_string());
+/// assert_ne!(viewport[1].spans[0].style, viewport[2].spans[0].style);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LazyHighlighter<'a> {
+    source: &'a str,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    /// The byte offset each line starts at.
+    line_starts: Vec<usize>,
+    /// The states before every `interval`th line, as far as the document has been scanned.
+    checkpoints: Vec<LineStates>,
+    interval: usize,
+    fallback: crate::LongLineFallback,
+    translator: crate::StyleTranslator,
+}
+
+impl<'a> LazyHighlighter<'a> {
+    /// Creates a highlighter for `source` using `syntax` and `theme`, which keeps the states
+    /// every 1000 lines.
+    pub fn new(
+        source: &'a str,
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .filter(|start| *start < source.len())
+            .collect();
+        Self {
+            source,
+            syntax_set,
+            theme,
+            line_starts,
+            checkpoints: vec![syntect::easy::HighlightLines::new(syntax, theme).state()],
+            interval: 1000,
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+        }
+    }
+
+    /// Sets how many lines apart the states are kept, which trades memory for how far a request
+    /// may have to scan. Clears the states kept so far.
+    pub fn checkpoint_interval(mut self, interval: usize) -> Self {
+        self.interval = interval.max(1);
+        self.checkpoints.truncate(1);
+        self
+    }
+
+    /// Highlights with `theme` from now on, keeping the parser states scanned so far, so switching
+    /// themes, e.g. in a theme picker, does not scan the document again.
+    pub fn set_theme(&mut self, theme: &'a syntect::highlighting::Theme) {
+        self.theme = theme;
+        for checkpoint in &mut self.checkpoints {
+            *checkpoint = crate::highlight::restyle_states(checkpoint, theme);
+        }
+    }
+
+    /// Returns the number of lines in the document.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Highlights the lines in `lines`, scanning the lines before them from the nearest
+    /// checkpoint. Returns fewer lines if the document ends before `lines` does.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line of the document they occurred on, including while scanning.
+    pub fn highlight_lines(
+        &mut self,
+        lines: std::ops::Range<usize>,
+    ) -> Result<Vec<ratatui::text::Line<'a>>, SyntectTuiError> {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("highlight_lazy", start = lines.start, end = lines.end).entered();
+        let end = lines.end.min(self.line_count());
+        let start = lines.start.min(end);
+        let checkpoint = (start / self.interval).min(self.checkpoints.len() - 1);
+        let (highlight_state, parse_state) = self.checkpoints[checkpoint].clone();
+        let mut highlighter =
+            syntect::easy::HighlightLines::from_state(self.theme, highlight_state, parse_state);
+        let mut highlighted = Vec::with_capacity(end - start);
+        for index in checkpoint * self.interval..end {
+            if index % self.interval == 0 && index / self.interval == self.checkpoints.len() {
+                let states = highlighter.state();
+                self.checkpoints.push(states.clone());
+                highlighter =
+                    syntect::easy::HighlightLines::from_state(self.theme, states.0, states.1);
+            }
+            let line = self.line(index);
+            if index < start {
+                if self.fallback.is_long(line) {
+                    continue;
+                }
+                highlighter
+                    .highlight_line(line, self.syntax_set)
+                    .map_err(|error| {
+                        SyntectTuiError::HighlightFailed {
+                            message: error.to_string(),
+                        }
+                        .at_line(index)
+                    })?;
+            } else {
+                highlighted.push(
+                    self.fallback
+                        .highlight_line(
+                            &mut highlighter,
+                            self.syntax_set,
+                            self.theme,
+                            line,
+                            &self.translator,
+                            None,
+                        )
+                        .map_err(|error| error.at_line(index))?,
+                );
+            }
+        }
+        Ok(highlighted)
+    }
+
+    /// Returns line `index` of the document with its line ending.
+    fn line(&self, index: usize) -> &'a str {
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.source.len());
+        &self.source[self.line_starts[index]..end]
+    }
+}
+
+/// Setting how long lines are converted clears the parser states kept so far, as they may have
+/// been scanned differently.
+impl crate::LineConversion for LazyHighlighter<'_> {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self.checkpoints.truncate(1);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineConversion;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\n*/\nlet c = \"\nd\";\nlet e = 5;";
+
+    #[rstest]
+    #[case::start(vec![0..2])]
+    #[case::middle(vec![3..6])]
+    #[case::end(vec![5..7])]
+    #[case::past_end(vec![6..20])]
+    #[case::backwards(vec![5..7, 1..3, 0..7])]
+    #[case::empty(vec![4..4, 2..5])]
+    fn matches_full_highlighting(#[case] requests: Vec<std::ops::Range<usize>>) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut full = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(SOURCE)
+            .map(|line| crate::highlight_line_to_line(&mut full, &ps, theme, line).unwrap())
+            .collect::<Vec<_>>();
+        let mut highlighter =
+            LazyHighlighter::new(SOURCE, syntax, &ps, theme).checkpoint_interval(2);
+        assert_eq!(7, highlighter.line_count());
+        for lines in requests {
+            let end = lines.end.min(expected.len());
+            let start = lines.start.min(end);
+            assert_eq!(
+                expected[start..end],
+                highlighter.highlight_lines(lines).unwrap()[..]
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_checkpoints_as_it_scans() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter =
+            LazyHighlighter::new(SOURCE, syntax, &ps, theme).checkpoint_interval(2);
+        highlighter.highlight_lines(3..4).unwrap();
+        assert_eq!(2, highlighter.checkpoints.len());
+        highlighter.highlight_lines(0..1).unwrap();
+        assert_eq!(2, highlighter.checkpoints.len());
+        highlighter.highlight_lines(6..7).unwrap();
+        assert_eq!(4, highlighter.checkpoints.len());
+    }
+
+    #[test]
+    fn switching_themes_keeps_checkpoints() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["InspiredGitHub"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter =
+            LazyHighlighter::new(SOURCE, syntax, &ps, &ts.themes["base16-ocean.dark"])
+                .checkpoint_interval(2);
+        highlighter.highlight_lines(6..7).unwrap();
+        highlighter.set_theme(theme);
+        assert_eq!(4, highlighter.checkpoints.len());
+        let mut fresh = LazyHighlighter::new(SOURCE, syntax, &ps, theme).checkpoint_interval(2);
+        assert_eq!(
+            fresh.highlight_lines(0..7).unwrap(),
+            highlighter.highlight_lines(0..7).unwrap()
+        );
+        assert_eq!(fresh.checkpoints, highlighter.checkpoints);
+    }
+
+    #[rstest]
+    #[case::long_line_before(3..7)]
+    #[case::long_line_inside(0..7)]
+    fn skips_long_lines_like_chunks(#[case] lines: std::ops::Range<usize>) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let fallback = crate::LongLineFallback::new().max_line_bytes(4);
+        let expected = crate::HighlightChunks::new(SOURCE, syntax, &ps, theme)
+            .long_lines(fallback)
+            .next()
+            .unwrap()
+            .unwrap();
+        let mut highlighter = LazyHighlighter::new(SOURCE, syntax, &ps, theme).long_lines(fallback);
+        assert_eq!(
+            expected[lines.clone()],
+            highlighter.highlight_lines(lines).unwrap()[..]
+        );
+    }
+}
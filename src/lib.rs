@@ -7,10 +7,232 @@
 //! Contributions welcome! Feel free to fork and submit a pull request.
 use custom_error::custom_error;
 
+mod ansi;
+mod ansi_palette;
+mod appearance;
+pub mod backend;
+#[cfg(feature = "bat")]
+mod bat;
+mod bracket;
+mod budget;
+mod builder;
+mod cache;
+mod cancel;
+mod chunks;
+mod colour;
+mod compiled;
+mod conversion;
+mod convert;
+mod diff;
+mod document;
+#[cfg(feature = "serde")]
+mod export;
+mod fallback;
+#[cfg(feature = "helix")]
+mod helix;
+mod highlight;
+mod hint;
+mod html;
+mod incremental;
+mod indent;
+mod intern;
+mod layout;
+mod lazy;
+mod line;
+mod logs;
+#[cfg(feature = "markdown")]
+mod markdown;
+mod metrics;
+mod overlay;
+mod overrides;
+mod palette;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod pool;
+mod profile;
+mod quantize;
+mod reader;
+#[cfg(feature = "reload")]
+mod reload;
+mod report;
+mod reverse;
+#[cfg(feature = "ropey")]
+mod rope;
+mod round_trip;
+mod sanitize;
+mod schedule;
+mod scope;
+#[cfg(feature = "serde")]
+mod serialize;
+mod settings;
+#[cfg(feature = "tokio")]
+mod stream;
+mod swatch;
+mod text;
+mod theme;
+#[cfg(feature = "toml")]
+mod toml_styles;
+mod translator;
+#[cfg(feature = "vscode")]
+mod vscode;
+mod whitespace;
+#[cfg(feature = "widget")]
+pub mod widget;
+mod worker;
+mod wrap;
+
+pub use ansi::{text_to_ansi_string, to_ansi_string};
+pub use ansi_palette::AnsiThemePalette;
+pub use appearance::{parse_background_response, query_background_colour, Appearance};
+#[cfg(feature = "bat")]
+pub use bat::BatAssets;
+pub use bracket::{highlight_matching_brackets, matching_bracket};
+pub use budget::HighlightCursor;
+pub use builder::{Highlighter, HighlighterBuilder};
+pub use cache::{CacheStats, HighlightCache};
+pub use cancel::CancellationToken;
+pub use chunks::{highlight_chunks, HighlightChunks};
+pub use colour::{ColourAdjustment, ColourSpace};
+pub use compiled::CompiledTheme;
+pub use conversion::LineConversion;
+pub use convert::Syntect;
+pub use diff::{highlight_diff, DiffHighlighter};
+pub use document::HighlightedDocument;
+#[cfg(feature = "serde")]
+pub use export::{ColourMode, ExportedTheme};
+pub use fallback::LongLineFallback;
+#[cfg(feature = "helix")]
+pub use helix::{helix_theme, HelixTheme};
+pub use highlight::highlight_line_to_line;
+pub use hint::{insert_inlay_hints, InlayHint};
+pub use html::{line_to_html, text_to_html};
+pub use incremental::IncrementalHighlighter;
+pub use indent::{show_indent_guides, IndentGuides};
+pub use intern::{InternedLine, InternedText, StyleId, StyleTable};
+pub use layout::slice_line;
+pub use lazy::LazyHighlighter;
+pub use line::{into_line, into_style_ranges, merge_adjacent_spans};
+pub use logs::{highlight_log, FormatDetector, JsonDetector, LogHighlighter};
+#[cfg(feature = "markdown")]
+pub use markdown::{highlight_markdown, MarkdownHighlighter};
+pub use metrics::Metrics;
+pub use overlay::{highlight_current_line, highlight_selection, Selection};
+pub use overrides::ThemeOverrides;
+pub use palette::Palette;
+#[cfg(feature = "rayon")]
+pub use parallel::{highlight_parallel, ParallelHighlighter};
+pub use pool::LinePool;
+pub use profile::TerminalProfile;
+pub use quantize::PaletteQuantizer;
+pub use reader::{highlight_reader, ReaderHighlighter};
+#[cfg(feature = "reload")]
+pub use reload::{LoadedTheme, ThemeChange, ThemeReloader};
+pub use report::{Loss, LossEntry, LossReport};
+pub use reverse::{
+    from_line, from_span, translate_colour_back, translate_font_style_back, translate_style_back,
+    ReverseOptions,
+};
+#[cfg(feature = "ropey")]
+pub use rope::{highlight_rope, RopeEdit};
+pub use round_trip::RoundTripStyle;
+pub use sanitize::ControlCharacterPolicy;
+pub use schedule::HighlightScheduler;
+pub use scope::{into_scoped_spans, ScopeOverride, ScopedSpan};
+#[cfg(feature = "serde")]
+pub use serialize::{HighlightSnapshot, SerializedLine, SerializedSpan, SerializedStyle};
+pub use settings::{translate_theme_set, translate_theme_settings, TuiTheme, TuiThemeSettings};
+#[cfg(feature = "tokio")]
+pub use stream::{AsyncHighlighter, HighlightStream};
+pub use swatch::{theme_preview, ThemePreview};
+pub use text::{apply_theme_background, theme_background, HighlightedText};
+pub use theme::build_theme;
+#[cfg(feature = "underline-color")]
+pub use translator::UnderlineColour;
+pub use translator::{AlphaStrategy, StyleTranslator};
+#[cfg(feature = "vscode")]
+pub use vscode::{vscode_theme, VsCodeTheme};
+pub use whitespace::{show_whitespace, Whitespace};
+pub use worker::{BackgroundHighlighter, HighlightBatch, HighlightWorker};
+pub use wrap::wrap_line;
+
 custom_error! {
     #[derive(PartialEq)]
+    #[non_exhaustive]
     pub SyntectTuiError
     UnknownFontStyle { bits: u8 } = "Unable to convert syntect::FontStyle into ratatui::style::Modifier: unsupported bits ({bits}) value.",
+    UnsupportedModifier { bits: u16 } = "Unable to convert ratatui::style::Modifier into syntect::FontStyle: unsupported bits ({bits}) value.",
+    InvalidScopeOperation { message: String } = "Unable to apply syntect::parsing::ScopeStackOp: {message}.",
+    InvalidScopeSelector { selector: String, message: String } = "Unable to parse scope selector \"{selector}\": {message}.",
+    HighlightFailed { message: String } = "Unable to highlight line using syntect: {message}.",
+    HtmlExport { message: String } = "Unable to export ratatui::text::Text as HTML: {message}.",
+    ThemeExport { message: String } = "Unable to export or import a translated theme: {message}.",
+    InvalidUtf8 { message: String } = "Unable to convert text that is not valid UTF-8: {message}.",
+    ThemeLoading { message: String } = "Unable to load syntect::highlighting::Theme: {message}.",
+    FileRead { path: String, message: String } = "Unable to read file \"{path}\": {message}.",
+    ReadFailed { message: String } = "Unable to read text to highlight: {message}.",
+    AssetLoading { message: String } = "Unable to load syntect::parsing::SyntaxSet: {message}.",
+    Cancelled { line: usize } = "Highlighting was cancelled before line {line}.",
+    InvalidSegment { line: Option<usize>, segment: usize, content: String, error: Box<SyntectTuiError> } = @{
+        match line {
+            Some(line) => format!("Unable to convert segment {segment} ({content:?}) of line {line}: {error}"),
+            None => format!("Unable to convert segment {segment} ({content:?}): {error}"),
+        }
+    },
+}
+
+/// The maximum number of characters of a segment's content kept by
+/// `SyntectTuiError::InvalidSegment`.
+const SEGMENT_SNIPPET_LENGTH: usize = 32;
+
+impl SyntectTuiError {
+    /// Wraps an error raised while converting a segment of a line with the segment's index and a
+    /// snippet of its content.
+    pub(crate) fn in_segment(self, segment: usize, content: &str) -> Self {
+        SyntectTuiError::InvalidSegment {
+            line: None,
+            segment,
+            content: content.chars().take(SEGMENT_SNIPPET_LENGTH).collect(),
+            error: Box::new(self),
+        }
+    }
+
+    /// Records the index of the line in which a segment failed to convert, for applications that
+    /// convert several lines at once. Errors that are not tied to a segment are returned as they
+    /// are.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect::highlighting::{FontStyle, Style};
+    ///
+    /// let style = Style {
+    ///     font_style: unsafe { FontStyle::from_bits_unchecked(0b1000) },
+    ///     ..Default::default()
+    /// };
+    /// let error = syntect_tui::into_line(vec![(Style::default(), "fn"), (style, " main")])
+    ///     .unwrap_err()
+    ///     .at_line(3);
+    /// assert_eq!(
+    ///     "Unable to convert segment 1 (\" main\") of line 3: Unable to convert syntect::FontStyle \
+    ///      into ratatui::style::Modifier: unsupported bits (8) value.",
+    ///     error.to_string()
+    /// );
+    /// ```
+    pub fn at_line(self, index: usize) -> Self {
+        match self {
+            SyntectTuiError::InvalidSegment {
+                segment,
+                content,
+                error,
+                ..
+            } => SyntectTuiError::InvalidSegment {
+                line: Some(index),
+                segment,
+                content,
+                error,
+            },
+            other => other,
+        }
+    }
 }
 
 /// Converts a line segment highlighed using [syntect::easy::HighlightLines::highlight_line](https://docs.rs/syntect/latest/syntect/easy/struct.HighlightLines.html#method.highlight_line) into a [ratatui::text::Span](https://docs.rs/ratatui/latest/ratatui/text/struct.Span.html).
@@ -32,6 +254,7 @@ custom_error! {
 /// let expected_style = ratatui::style::Style {
 ///     fg: Some(ratatui::style::Color::Rgb(255, 0, 0)),
 ///     bg: None,
+///     #[cfg(feature = "underline-color")]
 ///     underline_color: Some(ratatui::style::Color::Rgb(255, 0, 0)),
 ///     add_modifier: ratatui::style::Modifier::BOLD,
 ///     sub_modifier: ratatui::style::Modifier::empty()
@@ -70,12 +293,12 @@ custom_error! {
 /// # Errors
 /// Can return `SyntectTuiError::UnknownFontStyle` if the input [FontStyle](https://docs.rs/syntect/latest/syntect/highlighting/struct.FontStyle.html) is not supported.
 ///
-/// All explicit compositions of `BOLD`, `ITALIC` & `UNDERLINE` are supported, however, implicit bitflag coercions are not. For example, even though `FontStyle::from_bits(3)` is coerced to `Some(FontStyle::BOLD | FontStyle::ITALIC)`, we ignore this result as it would be a pain to handle all implicit coercions.
+/// Each of `BOLD`, `ITALIC` & `UNDERLINE` is translated independently, so any composition of them is supported. Any other bits (e.g. ones added by future versions of syntect) are reported along with the rest of the input's bits.
 pub fn into_span<'a>(
     (style, content): (syntect::highlighting::Style, &'a str),
 ) -> Result<ratatui::text::Span<'a>, SyntectTuiError> {
     Ok(ratatui::text::Span::styled(
-        String::from(content),
+        content,
         translate_style(style)?,
     ))
 }
@@ -97,6 +320,7 @@ pub fn into_span<'a>(
 /// let expected = ratatui::style::Style {
 ///     fg: Some(ratatui::style::Color::Rgb(255, 0, 0)),
 ///     bg: None,
+///     #[cfg(feature = "underline-color")]
 ///     underline_color: Some(ratatui::style::Color::Rgb(255, 0, 0)),
 ///     add_modifier: ratatui::style::Modifier::BOLD,
 ///     sub_modifier: ratatui::style::Modifier::empty()
@@ -107,19 +331,116 @@ pub fn into_span<'a>(
 /// # Errors
 /// Can return `SyntectTuiError::UnknownFontStyle` if the input [FontStyle](https://docs.rs/syntect/latest/syntect/highlighting/struct.FontStyle.html) is not supported.
 ///
-/// All explicit compositions of `BOLD`, `ITALIC` & `UNDERLINE` are supported, however, implicit bitflag coercions are not. For example, even though `FontStyle::from_bits(3)` is coerced to `Some(FontStyle::BOLD | FontStyle::ITALIC)`, we ignore this result as it would be a pain to handle all implicit coercions.
+/// Each of `BOLD`, `ITALIC` & `UNDERLINE` is translated independently, so any composition of them is supported. Any other bits (e.g. ones added by future versions of syntect) are reported along with the rest of the input's bits.
 pub fn translate_style(
     syntect_style: syntect::highlighting::Style,
 ) -> Result<ratatui::style::Style, SyntectTuiError> {
     Ok(ratatui::style::Style {
         fg: translate_colour(syntect_style.foreground),
         bg: translate_colour(syntect_style.background),
+        #[cfg(feature = "underline-color")]
         underline_color: translate_colour(syntect_style.foreground),
         add_modifier: translate_font_style(syntect_style.font_style)?,
         sub_modifier: ratatui::style::Modifier::empty(),
     })
 }
 
+/// A lossy counterpart to [into_span] that never fails.
+///
+/// Font style bits that ratatui cannot represent are ignored rather than causing an error, so the
+/// segment's content is always preserved, along with whatever style could be translated.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::{Color, FontStyle, Style};
+///
+/// let style = Style {
+///     foreground: Color { r: 255, g: 0, b: 0, a: 255 },
+///     background: Color { r: 0, g: 0, b: 0, a: 0 },
+///     font_style: unsafe { FontStyle::from_bits_unchecked(0b1001) }
+/// };
+/// assert!(syntect_tui::into_span((style, "fn")).is_err());
+/// let span = syntect_tui::into_span_lossy((style, "fn"));
+/// assert_eq!("fn", span.content);
+/// assert_eq!(ratatui::style::Modifier::BOLD, span.style.add_modifier);
+/// ```
+pub fn into_span_lossy(segment: (syntect::highlighting::Style, &str)) -> ratatui::text::Span<'_> {
+    StyleTranslator::default().into_span_lossy(segment)
+}
+
+/// A lossy counterpart to [translate_style] that never fails, ignoring font style bits that
+/// ratatui cannot represent.
+///
+/// # Examples
+/// ```
+/// let style = syntect::highlighting::Style {
+///     font_style: unsafe { syntect::highlighting::FontStyle::from_bits_unchecked(0b1100) },
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     ratatui::style::Modifier::ITALIC,
+///     syntect_tui::translate_style_lossy(style).add_modifier
+/// );
+/// ```
+pub fn translate_style_lossy(syntect_style: syntect::highlighting::Style) -> ratatui::style::Style {
+    StyleTranslator::default().translate_style_lossy(syntect_style)
+}
+
+/// Converts a
+/// [syntect::highlighting::StyleModifier](https://docs.rs/syntect/latest/syntect/highlighting/struct.StyleModifier.html)
+/// into a [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html)
+/// suitable for [Style::patch](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html#method.patch).
+///
+/// Fields that are `None` in the `StyleModifier` are left unset in the returned style, so patching
+/// leaves them untouched. Colours that are set but fully transparent become `Color::Reset`, so
+/// patching clears them. When a font style is set it replaces the existing one, as it does in
+/// syntect: the translated modifiers are added and the remaining supported modifiers are removed.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use ratatui::style::{Color, Modifier, Style};
+///
+/// let input = syntect::highlighting::StyleModifier {
+///     foreground: Some(syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 }),
+///     background: None,
+///     font_style: Some(syntect::highlighting::FontStyle::BOLD),
+/// };
+/// let base = Style::new().bg(Color::Black).add_modifier(Modifier::ITALIC);
+/// let patch = syntect_tui::translate_style_modifier(input).unwrap();
+/// let actual = base.patch(patch);
+/// assert_eq!(Some(Color::Rgb(255, 0, 0)), actual.fg);
+/// assert_eq!(Some(Color::Black), actual.bg);
+/// assert_eq!(Modifier::BOLD, actual.add_modifier);
+/// ```
+/// # Errors
+/// Can return `SyntectTuiError::UnknownFontStyle` if the input [FontStyle](https://docs.rs/syntect/latest/syntect/highlighting/struct.FontStyle.html) is not supported.
+pub fn translate_style_modifier(
+    syntect_style_modifier: syntect::highlighting::StyleModifier,
+) -> Result<ratatui::style::Style, SyntectTuiError> {
+    use ratatui::style::{Color, Modifier};
+    let translate_patch_colour = |colour: Option<syntect::highlighting::Color>| {
+        colour.map(|colour| translate_colour(colour).unwrap_or(Color::Reset))
+    };
+    let fg = translate_patch_colour(syntect_style_modifier.foreground);
+    let (add_modifier, sub_modifier) = match syntect_style_modifier.font_style {
+        Some(font_style) => {
+            let modifier = translate_font_style(font_style)?;
+            let supported = Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED;
+            (modifier, supported - modifier)
+        }
+        None => (Modifier::empty(), Modifier::empty()),
+    };
+    Ok(ratatui::style::Style {
+        fg,
+        bg: translate_patch_colour(syntect_style_modifier.background),
+        #[cfg(feature = "underline-color")]
+        underline_color: fg,
+        add_modifier,
+        sub_modifier,
+    })
+}
+
 /// Converts a
 /// [syntect::highlighting::Color](https://docs.rs/syntect/latest/syntect/highlighting/struct.Color.html)
 /// into a [ratatui::style::Color](https://docs.rs/ratatui/latest/ratatui/style/enum.Color.html).
@@ -167,41 +488,61 @@ pub fn translate_colour(
 /// # Errors
 /// Can return `SyntectTuiError::UnknownFontStyle` if the input [FontStyle](https://docs.rs/syntect/latest/syntect/highlighting/struct.FontStyle.html) is not supported.
 ///
-/// All explicit compositions of `BOLD`, `ITALIC` & `UNDERLINE` are supported, however, implicit bitflag coercions are not. For example, even though `FontStyle::from_bits(3)` is coerced to `Some(FontStyle::BOLD | FontStyle::ITALIC)`, we ignore this result as it would be a pain to handle all implicit coercions.
+/// Each of `BOLD`, `ITALIC` & `UNDERLINE` is translated independently, so any composition of them is supported. Any other bits (e.g. ones added by future versions of syntect) are reported along with the rest of the input's bits.
 pub fn translate_font_style(
     syntect_font_style: syntect::highlighting::FontStyle,
 ) -> Result<ratatui::style::Modifier, SyntectTuiError> {
+    let unknown = syntect_font_style.bits() & !SUPPORTED_FONT_STYLE.bits();
+    if unknown != 0 {
+        return Err(SyntectTuiError::UnknownFontStyle {
+            bits: syntect_font_style.bits(),
+        });
+    }
+    Ok(translate_known_font_style(syntect_font_style))
+}
+
+pub(crate) const SUPPORTED_FONT_STYLE: syntect::highlighting::FontStyle =
+    syntect::highlighting::FontStyle::BOLD
+        .union(syntect::highlighting::FontStyle::ITALIC)
+        .union(syntect::highlighting::FontStyle::UNDERLINE);
+
+/// Removes the font style bits that ratatui cannot represent from `syntect_style`.
+pub(crate) fn mask_font_style(
+    syntect_style: syntect::highlighting::Style,
+) -> syntect::highlighting::Style {
+    syntect::highlighting::Style {
+        font_style: syntect_style.font_style.intersection(SUPPORTED_FONT_STYLE),
+        ..syntect_style
+    }
+}
+
+/// Translates the bits of `syntect_font_style` that ratatui supports, ignoring any others.
+pub(crate) fn translate_known_font_style(
+    syntect_font_style: syntect::highlighting::FontStyle,
+) -> ratatui::style::Modifier {
     use ratatui::style::Modifier;
     use syntect::highlighting::FontStyle;
-    match syntect_font_style {
-        x if x == FontStyle::empty() => Ok(Modifier::empty()),
-        x if x == FontStyle::BOLD => Ok(Modifier::BOLD),
-        x if x == FontStyle::ITALIC => Ok(Modifier::ITALIC),
-        x if x == FontStyle::UNDERLINE => Ok(Modifier::UNDERLINED),
-        x if x == FontStyle::BOLD | FontStyle::ITALIC => Ok(Modifier::BOLD | Modifier::ITALIC),
-        x if x == FontStyle::BOLD | FontStyle::UNDERLINE => {
-            Ok(Modifier::BOLD | Modifier::UNDERLINED)
-        }
-        x if x == FontStyle::ITALIC | FontStyle::UNDERLINE => {
-            Ok(Modifier::ITALIC | Modifier::UNDERLINED)
-        }
-        x if x == FontStyle::BOLD | FontStyle::ITALIC | FontStyle::UNDERLINE => {
-            Ok(Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED)
-        }
-        unknown => Err(SyntectTuiError::UnknownFontStyle {
-            bits: unknown.bits(),
-        }),
-    }
+    [
+        (FontStyle::BOLD, Modifier::BOLD),
+        (FontStyle::ITALIC, Modifier::ITALIC),
+        (FontStyle::UNDERLINE, Modifier::UNDERLINED),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| syntect_font_style.contains(*flag))
+    .fold(Modifier::empty(), |acc, (_, modifier)| acc | modifier)
 }
 
 #[cfg(test)]
+#[allow(clippy::zero_prefixed_literal)]
 mod tests {
     use rstest::*;
 
     use super::*;
     use ratatui::style::Modifier;
     use ratatui::text::Span;
-    use syntect::highlighting::{Color as SyntectColour, FontStyle, Style as SyntectStyle};
+    use syntect::highlighting::{
+        Color as SyntectColour, FontStyle, Style as SyntectStyle, StyleModifier,
+    };
 
     fn fake_syntect_colour(r: u8, g: u8, b: u8, a: u8) -> SyntectColour {
         SyntectColour { r, g, b, a }
@@ -221,6 +562,7 @@ mod tests {
             style: ratatui::style::Style {
                 fg: Some(ratatui::style::Color::Rgb(r, g, b)),
                 bg: Some(ratatui::style::Color::Rgb(g, b, r)),
+                #[cfg(feature = "underline-color")]
                 underline_color: Some(ratatui::style::Color::Rgb(r, g, b)),
                 add_modifier: Modifier::UNDERLINED,
                 sub_modifier: Modifier::empty(),
@@ -238,11 +580,13 @@ mod tests {
             foreground: fake_syntect_colour(r, g, b, 128),
             background: fake_syntect_colour(g, b, r, 128),
         };
-        let expected = Ok(ratatui::style::Style::default()
+        let expected = ratatui::style::Style::default()
             .fg(ratatui::style::Color::Rgb(r, g, b))
             .bg(ratatui::style::Color::Rgb(g, b, r))
-            .underline_color(ratatui::style::Color::Rgb(r, g, b))
-            .add_modifier(Modifier::UNDERLINED));
+            .add_modifier(Modifier::UNDERLINED);
+        #[cfg(feature = "underline-color")]
+        let expected = expected.underline_color(ratatui::style::Color::Rgb(r, g, b));
+        let expected = Ok(expected);
         let actual = translate_style(input);
         assert_eq!(expected, actual);
     }
@@ -260,6 +604,46 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[rstest]
+    #[case::unset(
+        StyleModifier { foreground: None, background: None, font_style: None },
+        Ok(ratatui::style::Style::default())
+    )]
+    #[case::colours(
+        StyleModifier {
+            foreground: Some(fake_syntect_colour(12, 123, 234, 128)),
+            background: Some(fake_syntect_colour(12, 123, 234, 0)),
+            font_style: None
+        },
+        Ok(ratatui::style::Style {
+            fg: Some(ratatui::style::Color::Rgb(12, 123, 234)),
+            bg: Some(ratatui::style::Color::Reset),
+            #[cfg(feature = "underline-color")]
+            underline_color: Some(ratatui::style::Color::Rgb(12, 123, 234)),
+            ..Default::default()
+        })
+    )]
+    #[case::font_style(
+        StyleModifier { foreground: None, background: None, font_style: Some(FontStyle::ITALIC) },
+        Ok(ratatui::style::Style::default()
+            .add_modifier(Modifier::ITALIC)
+            .remove_modifier(Modifier::BOLD | Modifier::UNDERLINED))
+    )]
+    #[case::err(
+        StyleModifier {
+            foreground: None,
+            background: None,
+            font_style: Some(unsafe { FontStyle::from_bits_unchecked(254) })
+        },
+        Err(SyntectTuiError::UnknownFontStyle { bits: 254 })
+    )]
+    fn check_translate_style_modifier(
+        #[case] input: StyleModifier,
+        #[case] expected: Result<ratatui::style::Style, SyntectTuiError>,
+    ) {
+        assert_eq!(expected, translate_style_modifier(input));
+    }
+
     #[rstest]
     #[case::with_alpha(
         fake_syntect_colour(012, 123, 234, 128),
@@ -285,10 +669,18 @@ mod tests {
         FontStyle::BOLD | FontStyle::ITALIC | FontStyle::UNDERLINE,
         Ok(Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED)
     )]
+    #[case::implicit_composition(
+        FontStyle::from_bits(3).unwrap(),
+        Ok(Modifier::BOLD | Modifier::UNDERLINED)
+    )]
     #[case::err(
         unsafe { FontStyle::from_bits_unchecked(254) } ,
         Err(SyntectTuiError::UnknownFontStyle { bits: 254 })
     )]
+    #[case::err_with_known_bits(
+        unsafe { FontStyle::from_bits_unchecked(9) } ,
+        Err(SyntectTuiError::UnknownFontStyle { bits: 9 })
+    )]
     fn check_translate_font_style(
         #[case] input: FontStyle,
         #[case] expected: Result<Modifier, SyntectTuiError>,
@@ -0,0 +1,482 @@
+use crate::{StyleTranslator, SyntectTuiError, Whitespace};
+
+/// Converts a line highlighted using
+/// [syntect::easy::HighlightLines::highlight_line](https://docs.rs/syntect/latest/syntect/easy/struct.HighlightLines.html#method.highlight_line)
+/// into a [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html).
+///
+/// Lines produced by
+/// [syntect::util::LinesWithEndings](https://docs.rs/syntect/latest/syntect/util/struct.LinesWithEndings.html)
+/// keep their trailing `\n` or `\r\n`, which corrupts ratatui layouts when rendered, so the line
+/// ending is trimmed from the end of the converted line. Segments left empty by trimming are
+/// dropped.
+///
+/// # Examples
+/// ```
+/// use syntect::easy::HighlightLines;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::util::LinesWithEndings;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut h = HighlightLines::new(syntax, &ts.themes["base16-ocean.dark"]);
+/// let s = "pub struct Wow { hi: u64 }\nfn blah() -> u64 {}";
+/// for line in LinesWithEndings::from(s) {
+///     let segments = h.highlight_line(line, &ps).unwrap();
+///     let converted = syntect_tui::into_line(segments).unwrap();
+///     assert_eq!(line.trim_end_matches('\n').len(), converted.width());
+/// }
+/// ```
+///
+/// # Errors
+/// Can return `SyntectTuiError::InvalidSegment` wrapping `SyntectTuiError::UnknownFontStyle` under
+/// the same conditions as [into_span](crate::into_span), identifying the offending segment.
+pub fn into_line<'a, I>(segments: I) -> Result<ratatui::text::Line<'a>, SyntectTuiError>
+where
+    I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+{
+    StyleTranslator::default().into_line(segments)
+}
+
+/// Converts a highlighted line into translated styles paired with the byte range of the line that
+/// each style applies to, instead of owned span content.
+///
+/// This suits editors that keep text in their own buffer (e.g. a rope or gap buffer) and build
+/// spans lazily from it. `segments` must cover the line contiguously from its start, as the output
+/// of
+/// [syntect::easy::HighlightLines::highlight_line](https://docs.rs/syntect/latest/syntect/easy/struct.HighlightLines.html#method.highlight_line)
+/// does. Unlike [into_line](crate::into_line), no text is trimmed or rewritten, so the ranges
+/// always index into the original line.
+///
+/// # Examples
+/// ```
+/// use syntect::easy::HighlightLines;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect::highlighting::ThemeSet;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut h = HighlightLines::new(syntax, &ts.themes["base16-ocean.dark"]);
+/// let line = "fn main() {}\n";
+/// let ranges = syntect_tui::into_style_ranges(h.highlight_line(line, &ps).unwrap()).unwrap();
+/// let (style, range) = &ranges[0];
+/// assert_eq!("fn", &line[range.clone()]);
+/// assert!(style.fg.is_some());
+/// ```
+///
+/// # Errors
+/// Can return `SyntectTuiError::InvalidSegment` wrapping `SyntectTuiError::UnknownFontStyle` under
+/// the same conditions as [into_span](crate::into_span), identifying the offending segment.
+pub fn into_style_ranges<'a, I>(
+    segments: I,
+) -> Result<Vec<(ratatui::style::Style, std::ops::Range<usize>)>, SyntectTuiError>
+where
+    I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+{
+    StyleTranslator::default().into_style_ranges(segments)
+}
+
+impl StyleTranslator {
+    /// Expands tabs into spaces when converting whole lines, e.g. using
+    /// [StyleTranslator::into_line].
+    ///
+    /// Expansion is tab-stop aware: each tab is replaced by however many spaces it takes to reach
+    /// the next multiple of `width` display columns, and the spaces keep the style of the span the
+    /// tab belonged to.
+    ///
+    /// # Examples
+    /// ```
+    /// let translator = syntect_tui::StyleTranslator::new().expand_tabs(4);
+    /// let style = syntect::highlighting::Style::default();
+    /// let line = translator.into_line(vec![(style, "ab\t"), (style, "c\td")]).unwrap();
+    /// assert_eq!("ab  c   d", line.to_string());
+    /// ```
+    pub fn expand_tabs(mut self, width: usize) -> Self {
+        self.tab_width = Some(width);
+        self
+    }
+
+    /// Makes whitespace visible when converting whole lines, e.g. using
+    /// [StyleTranslator::into_line].
+    ///
+    /// See [show_whitespace](crate::show_whitespace) for details. Tabs are expanded if
+    /// [expand_tabs](StyleTranslator::expand_tabs) is also set.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect_tui::Whitespace;
+    ///
+    /// let translator = syntect_tui::StyleTranslator::new()
+    ///     .expand_tabs(4)
+    ///     .show_whitespace(Whitespace::default());
+    /// let style = syntect::highlighting::Style::default();
+    /// let line = translator.into_line(vec![(style, "\tx "), (style, "\n")]).unwrap();
+    /// assert_eq!("→   x·", line.to_string());
+    /// ```
+    pub fn show_whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.whitespace = Some(whitespace);
+        self
+    }
+
+    /// Enables or disables merging adjacent spans with identical styles when converting whole
+    /// lines, e.g. using [StyleTranslator::into_line].
+    ///
+    /// See [merge_adjacent_spans](crate::merge_adjacent_spans) for details.
+    pub fn merge_adjacent_spans(mut self, merge: bool) -> Self {
+        self.merge_spans = merge;
+        self
+    }
+
+    /// Converts a highlighted line into a
+    /// [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html)
+    /// according to this translator's options.
+    ///
+    /// See [into_line](crate::into_line) for details.
+    ///
+    /// # Errors
+    /// Can return the same errors as [into_line](crate::into_line).
+    pub fn into_line<'a, I>(&self, segments: I) -> Result<ratatui::text::Line<'a>, SyntectTuiError>
+    where
+        I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+    {
+        let mut line = ratatui::text::Line::default();
+        self.convert_line_into(segments, &mut line)?;
+        Ok(line)
+    }
+
+    /// Converts a highlighted line like [StyleTranslator::into_line], but into `line`, reusing
+    /// the memory of its spans, e.g. one taken from a [LinePool](crate::LinePool) each frame.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect::easy::HighlightLines;
+    /// use syntect::highlighting::ThemeSet;
+    /// use syntect::parsing::SyntaxSet;
+    ///
+    /// let ps = SyntaxSet::load_defaults_newlines();
+    /// let ts = ThemeSet::load_defaults();
+    /// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+    /// let mut h = HighlightLines::new(syntax, &ts.themes["base16-ocean.dark"]);
+    /// let translator = syntect_tui::StyleTranslator::new();
+    /// let mut line = ratatui::text::Line::raw("old");
+    /// translator
+    ///     .convert_line_into(h.highlight_line("fn main() {}\n", &ps).unwrap(), &mut line)
+    ///     .unwrap();
+    /// assert_eq!("fn main() {}", line.to_string());
+    /// ```
+    ///
+    /// # Errors
+    /// Can return the same errors as [into_line](crate::into_line), in which case `line` is left
+    /// empty.
+    pub fn convert_line_into<'a, I>(
+        &self,
+        segments: I,
+        line: &mut ratatui::text::Line<'a>,
+    ) -> Result<(), SyntectTuiError>
+    where
+        I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+    {
+        self.convert_line_with(segments, line, |style| self.translate_style(style))
+    }
+
+    /// Converts a highlighted line into `line` according to this translator's options,
+    /// translating its styles using `translate`.
+    pub(crate) fn convert_line_with<'a, I, F>(
+        &self,
+        segments: I,
+        line: &mut ratatui::text::Line<'a>,
+        mut translate: F,
+    ) -> Result<(), SyntectTuiError>
+    where
+        I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+        F: FnMut(syntect::highlighting::Style) -> Result<ratatui::style::Style, SyntectTuiError>,
+    {
+        let segments = segments.into_iter();
+        let mut spans = std::mem::take(&mut line.spans);
+        spans.clear();
+        spans.reserve(segments.size_hint().0);
+        line.style = ratatui::style::Style::default();
+        line.alignment = None;
+        // Consecutive segments often share a style, so the last translation is reused.
+        let mut previous: Option<(syntect::highlighting::Style, ratatui::style::Style)> = None;
+        for (index, (style, content)) in segments.enumerate() {
+            let translated = match previous {
+                Some((last, translated)) if last == style => translated,
+                _ => match translate(style) {
+                    Ok(translated) => {
+                        previous = Some((style, translated));
+                        translated
+                    }
+                    Err(error) => {
+                        spans.clear();
+                        line.spans = spans;
+                        return Err(error.in_segment(index, content));
+                    }
+                },
+            };
+            spans.push(self.styled_span(content, translated));
+        }
+        trim_line_ending(&mut spans);
+        match &self.whitespace {
+            Some(whitespace) => {
+                *line = crate::show_whitespace(&spans.into(), whitespace, self.tab_width)
+            }
+            None => {
+                if let Some(width) = self.tab_width {
+                    expand_tabs(&mut spans, width);
+                }
+                line.spans = spans;
+            }
+        }
+        if self.merge_spans {
+            merge_adjacent_spans(line);
+        }
+        Ok(())
+    }
+
+    /// Converts a highlighted line into translated styles paired with byte ranges according to
+    /// this translator's options.
+    ///
+    /// See [into_style_ranges](crate::into_style_ranges) for details.
+    ///
+    /// # Errors
+    /// Can return the same errors as [into_style_ranges](crate::into_style_ranges).
+    pub fn into_style_ranges<'a, I>(
+        &self,
+        segments: I,
+    ) -> Result<Vec<(ratatui::style::Style, std::ops::Range<usize>)>, SyntectTuiError>
+    where
+        I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+    {
+        let mut offset = 0;
+        segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, (style, content))| {
+                let range = offset..offset + content.len();
+                offset = range.end;
+                let style = self
+                    .translate_style(style)
+                    .map_err(|error| error.in_segment(index, content))?;
+                Ok((style, range))
+            })
+            .collect()
+    }
+}
+
+/// Merges neighbouring spans in `line` whose styles are equal into a single span.
+///
+/// Syntect often emits several consecutive segments that resolve to the same style. Merging them
+/// keeps `Line::spans` short, which makes rendering and diffing cheaper, without changing how the
+/// line looks.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use ratatui::text::{Line, Span};
+///
+/// let mut line = Line::from(vec![
+///     Span::styled("pub", Style::new().red()),
+///     Span::styled(" ", Style::new().red()),
+///     Span::styled("struct", Style::new().blue()),
+/// ]);
+/// syntect_tui::merge_adjacent_spans(&mut line);
+/// assert_eq!(
+///     vec![Span::styled("pub ", Style::new().red()), Span::styled("struct", Style::new().blue())],
+///     line.spans
+/// );
+/// ```
+pub fn merge_adjacent_spans(line: &mut ratatui::text::Line<'_>) {
+    let mut merged: Vec<ratatui::text::Span<'_>> = Vec::with_capacity(line.spans.len());
+    for span in line.spans.drain(..) {
+        match merged.last_mut() {
+            Some(last) if last.style == span.style => last.content.to_mut().push_str(&span.content),
+            _ => merged.push(span),
+        }
+    }
+    line.spans = merged;
+}
+
+/// Removes a single trailing `\n` or `\r\n` from `spans`, even if it is split across spans.
+fn trim_line_ending(spans: &mut Vec<ratatui::text::Span<'_>>) {
+    for suffix in ['\n', '\r'] {
+        while spans.last().is_some_and(|span| span.content.is_empty()) {
+            spans.pop();
+        }
+        if let Some(span) = spans.last_mut() {
+            if span.content.ends_with(suffix) {
+                // Slicing rather than popping keeps borrowed content borrowed.
+                match &mut span.content {
+                    std::borrow::Cow::Borrowed(content) => *content = &content[..content.len() - 1],
+                    std::borrow::Cow::Owned(content) => {
+                        content.pop();
+                    }
+                }
+            } else {
+                return;
+            }
+        }
+    }
+    while spans.last().is_some_and(|span| span.content.is_empty()) {
+        spans.pop();
+    }
+}
+
+/// Replaces every tab in `spans` with spaces up to the next multiple of `width` display columns.
+pub(crate) fn expand_tabs(spans: &mut [ratatui::text::Span<'_>], width: usize) {
+    use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+    let mut column = 0;
+    for span in spans {
+        if !span.content.contains('\t') {
+            column += span.content.width();
+            continue;
+        }
+        let mut expanded = String::with_capacity(span.content.len());
+        for character in span.content.chars() {
+            if character == '\t' {
+                let spaces = match width {
+                    0 => 0,
+                    width => width - column % width,
+                };
+                expanded.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            } else {
+                expanded.push(character);
+                column += character.width().unwrap_or(0);
+            }
+        }
+        span.content = expanded.into();
+    }
+}
+
+/// Copies the contents of any borrowed spans of `line`, so it no longer borrows the source it was
+/// converted from, e.g. to keep it between frames.
+pub(crate) fn into_owned_line(line: ratatui::text::Line<'_>) -> ratatui::text::Line<'static> {
+    ratatui::text::Line {
+        spans: line
+            .spans
+            .into_iter()
+            .map(|span| ratatui::text::Span::styled(span.content.into_owned(), span.style))
+            .collect(),
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::text::Span;
+
+    #[test]
+    fn conversion_errors_identify_segment() {
+        let style = syntect::highlighting::Style {
+            font_style: unsafe { syntect::highlighting::FontStyle::from_bits_unchecked(0b1000) },
+            ..Default::default()
+        };
+        let content = "x".repeat(40);
+        let expected = SyntectTuiError::InvalidSegment {
+            line: Some(7),
+            segment: 1,
+            content: "x".repeat(32),
+            error: Box::new(SyntectTuiError::UnknownFontStyle { bits: 8 }),
+        };
+        let segments = vec![(Default::default(), "fn"), (style, content.as_str())];
+        assert_eq!(
+            expected,
+            into_line(segments.clone()).unwrap_err().at_line(7)
+        );
+        assert_eq!(
+            expected,
+            into_style_ranges(segments).unwrap_err().at_line(7)
+        );
+    }
+
+    #[rstest]
+    #[case::no_ending(vec!["fn", " main"], vec!["fn", " main"])]
+    #[case::newline(vec!["fn", " main\n"], vec!["fn", " main"])]
+    #[case::carriage_return(vec!["fn", " main\r\n"], vec!["fn", " main"])]
+    #[case::separate_segment(vec!["fn", " main", "\n"], vec!["fn", " main"])]
+    #[case::split_ending(vec!["fn", " main\r", "\n"], vec!["fn", " main"])]
+    #[case::only_one_ending(vec!["fn\n\n"], vec!["fn\n"])]
+    #[case::empty_line(vec!["\n"], vec![])]
+    fn check_trim_line_ending(#[case] input: Vec<&str>, #[case] expected: Vec<&str>) {
+        let mut spans: Vec<Span> = input.into_iter().map(Span::raw).collect();
+        trim_line_ending(&mut spans);
+        let expected: Vec<Span> = expected.into_iter().map(Span::raw).collect();
+        assert_eq!(expected, spans);
+    }
+
+    #[rstest]
+    #[case::no_tabs(vec!["fn", " main"], vec!["fn", " main"])]
+    #[case::leading_tab(vec!["\tfn"], vec!["    fn"])]
+    #[case::tab_stops(vec!["a\tbc\td"], vec!["a   bc  d"])]
+    #[case::across_spans(vec!["ab", "\tc"], vec!["ab", "  c"])]
+    #[case::full_stop(vec!["abcd\t"], vec!["abcd    "])]
+    #[case::wide_characters(vec!["日\t"], vec!["日  "])]
+    fn check_expand_tabs(#[case] input: Vec<&str>, #[case] expected: Vec<&str>) {
+        let mut spans: Vec<Span> = input.into_iter().map(Span::raw).collect();
+        expand_tabs(&mut spans, 4);
+        let expected: Vec<Span> = expected.into_iter().map(Span::raw).collect();
+        assert_eq!(expected, spans);
+    }
+
+    #[test]
+    fn merge_adjacent_spans_only_merges_neighbours() {
+        use ratatui::style::{Style, Stylize};
+        let (red, blue) = (Style::new().red(), Style::new().blue());
+        let mut line = ratatui::text::Line::from(vec![
+            Span::styled("a", red),
+            Span::styled("b", red),
+            Span::styled("c", blue),
+            Span::styled("d", red),
+            Span::styled("e", red),
+        ]);
+        merge_adjacent_spans(&mut line);
+        let expected = vec![
+            Span::styled("ab", red),
+            Span::styled("c", blue),
+            Span::styled("de", red),
+        ];
+        assert_eq!(expected, line.spans);
+    }
+
+    #[test]
+    fn into_style_ranges_covers_every_segment() {
+        let style = syntect::highlighting::Style::default();
+        let expected_style = crate::translate_style(style).unwrap();
+        let actual = into_style_ranges(vec![(style, "fn"), (style, " 日本"), (style, "\n")]);
+        let expected = vec![
+            (expected_style, 0..2),
+            (expected_style, 2..9),
+            (expected_style, 9..10),
+        ];
+        assert_eq!(Ok(expected), actual);
+    }
+
+    #[test]
+    fn into_line_converts_every_segment() {
+        let style = syntect::highlighting::Style::default();
+        let expected = crate::into_span((style, "fn")).unwrap();
+        let actual = into_line(vec![(style, "fn"), (style, "\n")]).unwrap();
+        assert_eq!(ratatui::text::Line::from(vec![expected]), actual);
+    }
+
+    #[rstest]
+    #[case::plain(vec!["fn", " main"])]
+    #[case::line_ending(vec!["fn", " main\r\n"])]
+    #[case::separate_line_ending(vec!["fn", " main\r", "\n"])]
+    fn into_line_borrows_contents(#[case] input: Vec<&str>) {
+        let style = syntect::highlighting::Style::default();
+        let line = into_line(input.into_iter().map(|content| (style, content))).unwrap();
+        assert_eq!("fn main", line.to_string());
+        assert!(line
+            .spans
+            .iter()
+            .all(|span| matches!(span.content, std::borrow::Cow::Borrowed(_))));
+    }
+}
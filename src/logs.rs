@@ -0,0 +1,215 @@
+use crate::{HighlightedText, SyntectTuiError};
+
+/// Detects the format of a line of a log, for [LogHighlighter].
+///
+/// Closures that return the [token](https://docs.rs/syntect/latest/syntect/parsing/struct.SyntaxSet.html#method.find_syntax_by_token)
+/// of a syntax, e.g. `|line: &str| line.starts_with('<').then_some("xml")`, are detectors too.
+pub trait FormatDetector {
+    /// Returns the syntax to highlight `line` with, or `None` if the line is not in this
+    /// detector's format.
+    fn detect<'s>(
+        &self,
+        line: &str,
+        syntax_set: &'s syntect::parsing::SyntaxSet,
+    ) -> Option<&'s syntect::parsing::SyntaxReference>;
+}
+
+impl<F> FormatDetector for F
+where
+    F: Fn(&str) -> Option<&'static str>,
+{
+    fn detect<'s>(
+        &self,
+        line: &str,
+        syntax_set: &'s syntect::parsing::SyntaxSet,
+    ) -> Option<&'s syntect::parsing::SyntaxReference> {
+        self(line).and_then(|token| syntax_set.find_syntax_by_token(token))
+    }
+}
+
+/// Detects lines that are a JSON object or array, ignoring surrounding whitespace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct JsonDetector;
+
+impl FormatDetector for JsonDetector {
+    fn detect<'s>(
+        &self,
+        line: &str,
+        syntax_set: &'s syntect::parsing::SyntaxSet,
+    ) -> Option<&'s syntect::parsing::SyntaxReference> {
+        let line = line.trim();
+        let json = (line.starts_with('{') && line.ends_with('}'))
+            || (line.starts_with('[') && line.ends_with(']'));
+        json.then(|| syntax_set.find_syntax_by_extension("json"))
+            .flatten()
+    }
+}
+
+/// Highlights logs that mix formats, e.g. JSON and plain text, line by line.
+///
+/// Each line is highlighted on its own with the syntax of the first [FormatDetector] that
+/// recognises it, or the [fallback](LogHighlighter::fallback) syntax (plain text by default) if
+/// none do, so a malformed line never affects the ones after it. A new highlighter detects JSON
+/// lines with [JsonDetector].
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::LogHighlighter;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let highlighter = LogHighlighter::new(&ps, &ts.themes["base16-ocean.dark"])
+///     .detector(|line: &str| line.starts_with('<').then_some("xml"));
+/// assert_eq!("JSON", highlighter.detect(r#"{"level": "info"}"#).name);
+/// assert_eq!("XML", highlighter.detect("<event/>").name);
+/// assert_eq!("Plain Text", highlighter.detect("server started").name);
+///
+/// let text = highlighter.highlight("server started\n{\"port\": 80}\n").unwrap();
+/// assert_eq!(1, text.lines[0].spans.len());
+/// assert!(text.lines[1].spans.len() > 1);
+/// ```
+pub struct LogHighlighter<'a> {
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    detectors: Vec<Box<dyn FormatDetector + 'a>>,
+    fallback: &'a syntect::parsing::SyntaxReference,
+}
+
+impl std::fmt::Debug for LogHighlighter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogHighlighter")
+            .field("detectors", &self.detectors.len())
+            .field("fallback", &self.fallback.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Highlights a log line by line with syntaxes from `syntax_set` and colours from `theme`,
+/// detecting JSON lines.
+///
+/// See [LogHighlighter] for details.
+///
+/// # Errors
+/// Can return the same errors as [LogHighlighter::highlight].
+pub fn highlight_log<'a>(
+    log: &'a str,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+) -> Result<HighlightedText<'a>, SyntectTuiError> {
+    LogHighlighter::new(syntax_set, theme).highlight(log)
+}
+
+impl<'a> LogHighlighter<'a> {
+    /// Creates a log highlighter that uses syntaxes from `syntax_set` and colours from `theme`.
+    pub fn new(
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            syntax_set,
+            theme,
+            detectors: vec![Box::new(JsonDetector)],
+            fallback: syntax_set.find_syntax_plain_text(),
+        }
+    }
+
+    /// Adds `detector`, which is tried after the detectors already added.
+    pub fn detector(mut self, detector: impl FormatDetector + 'a) -> Self {
+        self.detectors.push(Box::new(detector));
+        self
+    }
+
+    /// Removes all detectors, including the default [JsonDetector].
+    pub fn clear_detectors(mut self) -> Self {
+        self.detectors.clear();
+        self
+    }
+
+    /// Sets the syntax of lines that no detector recognises.
+    pub fn fallback(mut self, syntax: &'a syntect::parsing::SyntaxReference) -> Self {
+        self.fallback = syntax;
+        self
+    }
+
+    /// Returns the syntax `line` is highlighted with.
+    pub fn detect(&self, line: &str) -> &'a syntect::parsing::SyntaxReference {
+        self.detectors
+            .iter()
+            .find_map(|detector| detector.detect(line, self.syntax_set))
+            .unwrap_or(self.fallback)
+    }
+
+    /// Highlights a single line of a log, e.g. as it is appended to a log pane.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight_line_to_line](crate::highlight_line_to_line).
+    pub fn highlight_line<'b>(
+        &self,
+        line: &'b str,
+    ) -> Result<ratatui::text::Line<'b>, SyntectTuiError> {
+        let mut highlighter = syntect::easy::HighlightLines::new(self.detect(line), self.theme);
+        crate::highlight_line_to_line(&mut highlighter, self.syntax_set, self.theme, line)
+    }
+
+    /// Highlights `log`, returning one line per line of the log.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line),
+    /// identifying the line of the log they occurred on.
+    pub fn highlight<'b>(&self, log: &'b str) -> Result<HighlightedText<'b>, SyntectTuiError> {
+        let lines = syntect::util::LinesWithEndings::from(log)
+            .enumerate()
+            .map(|(index, line)| {
+                self.highlight_line(line)
+                    .map_err(|error| error.at_line(index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HighlightedText::from_theme(lines, self.theme))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    #[rstest]
+    #[case::object(r#"{"a": 1}"#, Some("JSON"))]
+    #[case::array("  [1, 2]\n", Some("JSON"))]
+    #[case::prefixed(r#"INFO {"a": 1}"#, None)]
+    #[case::unbalanced("{ started", None)]
+    fn check_json_detector(#[case] line: &str, #[case] expected: Option<&str>) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let actual = JsonDetector.detect(line, &ps);
+        assert_eq!(expected, actual.map(|syntax| syntax.name.as_str()));
+    }
+
+    #[test]
+    fn detectors_are_tried_in_order() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let highlighter = LogHighlighter::new(&ps, &ts.themes["base16-ocean.dark"])
+            .clear_detectors()
+            .detector(|line: &str| line.starts_with('{').then_some("py"))
+            .detector(JsonDetector)
+            .fallback(ps.find_syntax_by_extension("sh").unwrap());
+        assert_eq!("Python", highlighter.detect("{}").name);
+        assert_eq!("JSON", highlighter.detect("[]").name);
+        assert_eq!("Bourne Again Shell (bash)", highlighter.detect("ls").name);
+    }
+
+    #[test]
+    fn lines_are_highlighted_independently() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        // The unterminated string would run on into the next line if the state were shared.
+        let text = highlight_log("[\"a]\n{\"b\": 1}\n", &ps, theme).unwrap();
+        let alone = highlight_log("{\"b\": 1}\n", &ps, theme).unwrap();
+        assert_eq!(alone.lines[0], text.lines[1]);
+    }
+}
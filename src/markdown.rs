@@ -0,0 +1,509 @@
+use crate::settings::scope_style;
+use crate::{HighlightedText, SyntectTuiError};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+/// Renders Markdown as styled text, highlighting fenced code blocks with syntect.
+///
+/// Each fenced code block is highlighted with the syntax its language tag names (see
+/// [find_syntax_by_token](https://docs.rs/syntect/latest/syntect/parsing/struct.SyntaxSet.html#method.find_syntax_by_token)),
+/// or as plain text if there is no tag or no such syntax. The rest of the document gets simple
+/// styles: headings, emphasis, strong emphasis, inline code, links and block quotes are styled
+/// like the theme's `markup.heading`, `markup.italic`, `markup.bold`, `markup.raw.inline`,
+/// `markup.underline.link` and `markup.quote` scopes, on top of bold, italic, bold, no, underlined
+/// and italic text respectively. Top-level headings are also underlined.
+///
+/// Blocks are separated by blank lines, block quotes are prefixed with `│ ` and list items with
+/// `• ` or their number, with nested lists indented by two columns. Lists are always rendered
+/// tight, and soft line breaks are joined with a space, so paragraphs should be
+/// [wrapped](crate::wrap_line) to the width they are rendered at. Raw HTML is kept as is.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Modifier;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::MarkdownHighlighter;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let markdown = "# Usage\n\nCall *main*:\n\n```rust\nfn main() {}\n```\n\n- one\n- two\n";
+/// let text = MarkdownHighlighter::new(&ps, &ts.themes["base16-ocean.dark"])
+///     .highlight(markdown)
+///     .unwrap();
+/// let lines: Vec<String> = text.lines.iter().map(ToString::to_string).collect();
+/// assert_eq!(
+///     vec!["Usage", "", "Call main:", "", "fn main() {}", "", "• one", "• two"],
+///     lines
+/// );
+/// assert!(text.lines[2].spans[1].style.add_modifier.contains(Modifier::ITALIC));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct MarkdownHighlighter<'a> {
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    heading_styles: [ratatui::style::Style; 6],
+    emphasis_style: ratatui::style::Style,
+    strong_style: ratatui::style::Style,
+    strikethrough_style: ratatui::style::Style,
+    code_style: ratatui::style::Style,
+    link_style: ratatui::style::Style,
+    quote_style: ratatui::style::Style,
+    list_marker_style: ratatui::style::Style,
+}
+
+/// Renders `markdown` as styled text, highlighting fenced code blocks with syntaxes from
+/// `syntax_set` and colours from `theme`.
+///
+/// See [MarkdownHighlighter] for details.
+///
+/// # Errors
+/// Can return the same errors as [MarkdownHighlighter::highlight].
+pub fn highlight_markdown(
+    markdown: &str,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+) -> Result<HighlightedText<'static>, SyntectTuiError> {
+    MarkdownHighlighter::new(syntax_set, theme).highlight(markdown)
+}
+
+impl<'a> MarkdownHighlighter<'a> {
+    /// Creates a Markdown highlighter that uses syntaxes from `syntax_set` and colours from
+    /// `theme`.
+    pub fn new(
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        use ratatui::style::{Modifier, Style};
+        // Only the foreground and font style of a scope are used, as a background would break
+        // up the theme background behind the text.
+        let themed = |scope: &str, fallback: Style| {
+            scope_style(theme, scope)
+                .and_then(|style| crate::translate_style(style).ok())
+                .map_or(fallback, |style| {
+                    fallback.patch(Style { bg: None, ..style })
+                })
+        };
+        let heading = themed("markup.heading", Style::new().add_modifier(Modifier::BOLD));
+        let mut heading_styles = [heading; 6];
+        heading_styles[0] = heading.add_modifier(Modifier::UNDERLINED);
+        Self {
+            syntax_set,
+            theme,
+            heading_styles,
+            emphasis_style: themed("markup.italic", Style::new().add_modifier(Modifier::ITALIC)),
+            strong_style: themed("markup.bold", Style::new().add_modifier(Modifier::BOLD)),
+            strikethrough_style: Style::new().add_modifier(Modifier::CROSSED_OUT),
+            code_style: themed("markup.raw.inline", Style::new()),
+            link_style: themed(
+                "markup.underline.link",
+                Style::new().add_modifier(Modifier::UNDERLINED),
+            ),
+            quote_style: themed("markup.quote", Style::new().add_modifier(Modifier::ITALIC)),
+            list_marker_style: themed("punctuation.definition.list_item", Style::new()),
+        }
+    }
+
+    /// Sets the style of headings of `level`, from 1 to 6. Other levels are ignored.
+    pub fn heading_style(mut self, level: usize, style: ratatui::style::Style) -> Self {
+        if let Some(heading) = level
+            .checked_sub(1)
+            .and_then(|index| self.heading_styles.get_mut(index))
+        {
+            *heading = style;
+        }
+        self
+    }
+
+    /// Sets the style of emphasised text.
+    pub fn emphasis_style(mut self, style: ratatui::style::Style) -> Self {
+        self.emphasis_style = style;
+        self
+    }
+
+    /// Sets the style of strongly emphasised text.
+    pub fn strong_style(mut self, style: ratatui::style::Style) -> Self {
+        self.strong_style = style;
+        self
+    }
+
+    /// Sets the style of struck through text.
+    pub fn strikethrough_style(mut self, style: ratatui::style::Style) -> Self {
+        self.strikethrough_style = style;
+        self
+    }
+
+    /// Sets the style of inline code.
+    pub fn code_style(mut self, style: ratatui::style::Style) -> Self {
+        self.code_style = style;
+        self
+    }
+
+    /// Sets the style of link and image text.
+    pub fn link_style(mut self, style: ratatui::style::Style) -> Self {
+        self.link_style = style;
+        self
+    }
+
+    /// Sets the style of block quotes, including their `│ ` prefix.
+    pub fn quote_style(mut self, style: ratatui::style::Style) -> Self {
+        self.quote_style = style;
+        self
+    }
+
+    /// Sets the style of list item bullets and numbers.
+    pub fn list_marker_style(mut self, style: ratatui::style::Style) -> Self {
+        self.list_marker_style = style;
+        self
+    }
+
+    /// Renders `markdown` as styled text on the theme's background.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line),
+    /// identifying the line of `markdown` they occurred on.
+    pub fn highlight(&self, markdown: &str) -> Result<HighlightedText<'static>, SyntectTuiError> {
+        let mut writer = Writer::new(self);
+        let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+        for (event, range) in Parser::new_ext(markdown, options).into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    writer.start_block();
+                    let (language, skipped) = match &kind {
+                        CodeBlockKind::Fenced(info) => (info.split_whitespace().next(), 1),
+                        CodeBlockKind::Indented => (None, 0),
+                    };
+                    let first_line = markdown[..range.start].matches('\n').count() + skipped;
+                    let syntax = language
+                        .and_then(|language| self.syntax_set.find_syntax_by_token(language))
+                        .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+                    writer.code_block = Some(CodeBlock {
+                        syntax,
+                        first_line,
+                        source: String::new(),
+                    });
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some(block) = writer.code_block.take() {
+                        writer.write_code_block(block)?;
+                    }
+                    writer.end_block();
+                }
+                Event::Text(text) => match &mut writer.code_block {
+                    Some(block) => block.source.push_str(&text),
+                    None => writer.write(&text, writer.style()),
+                },
+                event => writer.handle(event),
+            }
+        }
+        writer.flush();
+        Ok(HighlightedText::from_theme(writer.lines, self.theme))
+    }
+}
+
+/// A fenced or indented code block being collected.
+struct CodeBlock<'a> {
+    syntax: &'a syntect::parsing::SyntaxReference,
+    first_line: usize,
+    source: String,
+}
+
+/// The state of a Markdown document being rendered.
+struct Writer<'h, 'a> {
+    highlighter: &'h MarkdownHighlighter<'a>,
+    lines: Vec<ratatui::text::Line<'static>>,
+    /// The spans of the line being written, including its prefix, if one has been started.
+    line: Option<Vec<ratatui::text::Span<'static>>>,
+    /// Whether the line being written has any content besides its prefix.
+    has_content: bool,
+    styles: Vec<ratatui::style::Style>,
+    /// The next number of each enclosing list, or `None` for bulleted lists.
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    /// Whether a blank line should separate the next block from the previous one.
+    gap: bool,
+    code_block: Option<CodeBlock<'a>>,
+}
+
+impl<'h, 'a> Writer<'h, 'a> {
+    fn new(highlighter: &'h MarkdownHighlighter<'a>) -> Self {
+        let foreground = crate::translate_theme_settings(highlighter.theme)
+            .foreground
+            .unwrap_or_default();
+        Self {
+            highlighter,
+            lines: Vec::new(),
+            line: None,
+            has_content: false,
+            styles: vec![foreground],
+            lists: Vec::new(),
+            quotes: 0,
+            gap: false,
+            code_block: None,
+        }
+    }
+
+    fn handle(&mut self, event: Event) {
+        let highlighter = self.highlighter;
+        match event {
+            Event::Start(Tag::Paragraph | Tag::HtmlBlock) => self.start_block(),
+            Event::End(TagEnd::Paragraph | TagEnd::HtmlBlock) => self.end_block(),
+            Event::Start(Tag::Heading { level, .. }) => {
+                self.start_block();
+                let index = match level {
+                    HeadingLevel::H1 => 0,
+                    HeadingLevel::H2 => 1,
+                    HeadingLevel::H3 => 2,
+                    HeadingLevel::H4 => 3,
+                    HeadingLevel::H5 => 4,
+                    HeadingLevel::H6 => 5,
+                };
+                self.push_style(highlighter.heading_styles[index]);
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                self.styles.pop();
+                self.end_block();
+            }
+            Event::Start(Tag::BlockQuote(_)) => {
+                self.start_block();
+                self.quotes += 1;
+                self.push_style(highlighter.quote_style);
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                self.flush();
+                self.quotes -= 1;
+                self.styles.pop();
+                self.gap = true;
+            }
+            Event::Start(Tag::List(start)) => {
+                if self.lists.is_empty() {
+                    self.start_block();
+                } else if self.has_content {
+                    self.flush();
+                }
+                self.lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                self.flush();
+                self.lists.pop();
+                self.gap = self.lists.is_empty();
+            }
+            Event::Start(Tag::Item) => {
+                self.flush();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                let mut line = self.prefix(self.lists.len().saturating_sub(1));
+                line.push(ratatui::text::Span::styled(
+                    marker,
+                    highlighter.list_marker_style,
+                ));
+                self.line = Some(line);
+            }
+            Event::End(TagEnd::Item) => self.flush(),
+            Event::Start(Tag::Emphasis) => self.push_style(highlighter.emphasis_style),
+            Event::Start(Tag::Strong) => self.push_style(highlighter.strong_style),
+            Event::Start(Tag::Strikethrough) => self.push_style(highlighter.strikethrough_style),
+            Event::Start(Tag::Link { .. } | Tag::Image { .. }) => {
+                self.push_style(highlighter.link_style);
+            }
+            Event::End(
+                TagEnd::Emphasis
+                | TagEnd::Strong
+                | TagEnd::Strikethrough
+                | TagEnd::Link
+                | TagEnd::Image,
+            ) => {
+                self.styles.pop();
+            }
+            Event::Code(code) => {
+                let style = self.style().patch(highlighter.code_style);
+                self.write(&code, style);
+            }
+            Event::Html(html) | Event::InlineHtml(html) => self.write(&html, self.style()),
+            Event::SoftBreak => self.write(" ", self.style()),
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.start_block();
+                self.write("───", self.style());
+                self.end_block();
+            }
+            Event::TaskListMarker(checked) => {
+                let marker = if checked { "[x] " } else { "[ ] " };
+                self.write(marker, highlighter.list_marker_style);
+            }
+            _ => {}
+        }
+    }
+
+    fn style(&self) -> ratatui::style::Style {
+        self.styles.last().copied().unwrap_or_default()
+    }
+
+    fn push_style(&mut self, style: ratatui::style::Style) {
+        self.styles.push(self.style().patch(style));
+    }
+
+    /// Returns the prefix of a line nested in the current block quotes and `indent` lists.
+    fn prefix(&self, indent: usize) -> Vec<ratatui::text::Span<'static>> {
+        let mut prefix =
+            vec![ratatui::text::Span::styled("│ ", self.highlighter.quote_style); self.quotes];
+        if indent > 0 {
+            prefix.push(ratatui::text::Span::raw("  ".repeat(indent)));
+        }
+        prefix
+    }
+
+    /// Writes `text` to the current line, starting a new line at each line break.
+    fn write(&mut self, text: &str, style: ratatui::style::Style) {
+        for (index, piece) in text.split('\n').enumerate() {
+            if index > 0 {
+                self.flush();
+            }
+            if !piece.is_empty() {
+                let prefix = self.prefix(self.lists.len());
+                self.line
+                    .get_or_insert(prefix)
+                    .push(ratatui::text::Span::styled(piece.to_string(), style));
+                self.has_content = true;
+            }
+        }
+    }
+
+    fn write_code_block(&mut self, block: CodeBlock) -> Result<(), SyntectTuiError> {
+        let theme = self.highlighter.theme;
+        let mut highlighter = syntect::easy::HighlightLines::new(block.syntax, theme);
+        for (index, line) in syntect::util::LinesWithEndings::from(&block.source).enumerate() {
+            let line = crate::highlight_line_to_line(
+                &mut highlighter,
+                self.highlighter.syntax_set,
+                theme,
+                line,
+            )
+            .map_err(|error| error.at_line(block.first_line + index))?;
+            let mut spans = self.prefix(self.lists.len());
+            spans.extend(
+                line.spans
+                    .into_iter()
+                    .map(|span| ratatui::text::Span::styled(span.content.into_owned(), span.style)),
+            );
+            self.lines
+                .push(ratatui::text::Line::from(spans).style(line.style));
+        }
+        Ok(())
+    }
+
+    /// Ends the current line, if one has been started.
+    fn flush(&mut self) {
+        if let Some(spans) = self.line.take() {
+            self.lines.push(ratatui::text::Line::from(spans));
+        }
+        self.has_content = false;
+    }
+
+    /// Starts a block, separating it from the previous one with a blank line outside lists.
+    fn start_block(&mut self) {
+        if self.has_content {
+            self.flush();
+        }
+        if self.gap && self.lists.is_empty() {
+            self.lines.push(ratatui::text::Line::from(self.prefix(0)));
+        }
+        self.gap = false;
+    }
+
+    fn end_block(&mut self) {
+        self.flush();
+        self.gap = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Modifier, Style, Stylize};
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    fn render(markdown: &str) -> Vec<String> {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        highlight_markdown(markdown, &ps, &ts.themes["base16-ocean.dark"])
+            .unwrap()
+            .lines
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[rstest]
+    #[case::paragraphs("one\ntwo\n\nthree\n", &["one two", "", "three"])]
+    #[case::hard_break("one  \ntwo\n", &["one", "two"])]
+    #[case::nested_list(
+        "- a\n  - b\n  - c\n- d\n\ntext\n",
+        &["• a", "  • b", "  • c", "• d", "", "text"]
+    )]
+    #[case::ordered_list("3. a\n4. b\n", &["3. a", "4. b"])]
+    #[case::loose_list("- a\n\n- b\n", &["• a", "• b"])]
+    #[case::task_list("- [x] done\n- [ ] todo\n", &["• [x] done", "• [ ] todo"])]
+    #[case::quote("> a\n> b\n\nc\n", &["│ a b", "", "c"])]
+    #[case::nested_quote("> a\n>> b\n", &["│ a", "│ ", "│ │ b"])]
+    #[case::code_in_list("- a\n  ```\n  x\n  ```\n", &["• a", "  x"])]
+    #[case::indented_code("    let x;\n    let y;\n", &["let x;", "let y;"])]
+    #[case::rule("a\n\n---\n\nb\n", &["a", "", "───", "", "b"])]
+    #[case::html("<br>\n\ntext\n", &["<br>", "", "text"])]
+    fn check_layout(#[case] markdown: &str, #[case] expected: &[&str]) {
+        assert_eq!(expected, render(markdown));
+    }
+
+    #[test]
+    fn styles_inline_markup() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let highlighter = MarkdownHighlighter::new(&ps, &ts.themes["base16-ocean.dark"])
+            .code_style(Style::new().add_modifier(Modifier::REVERSED));
+        let text = highlighter.highlight("# T\n\n***a*** `b` ~~c~~\n").unwrap();
+        let modifiers: Vec<_> = text.lines[2]
+            .spans
+            .iter()
+            .map(|span| span.style.add_modifier)
+            .collect();
+        assert!(text.lines[0].spans[0]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD | Modifier::UNDERLINED));
+        assert!(modifiers[0].contains(Modifier::BOLD | Modifier::ITALIC));
+        assert_eq!(Modifier::REVERSED, modifiers[2]);
+        assert_eq!(Modifier::CROSSED_OUT, modifiers[4]);
+    }
+
+    #[test]
+    fn highlights_code_blocks_by_language() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let text =
+            highlight_markdown("```rust\nfn x\n```\n\n```nope\nfn x\n```\n", &ps, theme).unwrap();
+        assert!(text.lines[0].spans.len() > 1);
+        assert_eq!(1, text.lines[2].spans.len());
+        assert_ne!(text.lines[0].spans[0].style, text.lines[2].spans[0].style);
+    }
+
+    #[rstest]
+    #[case(0, None)]
+    #[case(2, Some(Style::new().italic()))]
+    #[case(7, None)]
+    fn check_heading_style(#[case] level: usize, #[case] expected: Option<Style>) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let theme = syntect::highlighting::Theme::default();
+        let highlighter =
+            MarkdownHighlighter::new(&ps, &theme).heading_style(level, Style::new().italic());
+        let text = highlighter.highlight("## a\n").unwrap();
+        let bold = Style::new().add_modifier(Modifier::BOLD);
+        assert_eq!(expected.unwrap_or(bold), text.lines[0].spans[0].style);
+    }
+}
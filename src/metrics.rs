@@ -0,0 +1,153 @@
+use std::time::{Duration, Instant};
+
+/// Measurements of the highlighting done by a document-level highlighter, e.g. a
+/// [HighlightCache] or an [IncrementalHighlighter](crate::IncrementalHighlighter), since it was
+/// created or its metrics were reset, to diagnose what makes highlighting slow.
+///
+/// The time taken by each line is split between syntect parsing and highlighting it, and
+/// converting its styles into ratatui ones, so slow highlighting can be told apart from slow
+/// conversion, e.g. by an expensive [StyleTranslator](crate::StyleTranslator). Metrics can be
+/// added together to combine several highlighters'.
+///
+/// With the `tracing` feature, document-level calls are also recorded as `tracing` spans at the
+/// debug level, to see where they fall among an application's own spans.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::HighlightCache;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut cache = HighlightCache::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+/// cache.highlight("let a = 1;\nlet b = 2;\n").unwrap();
+/// cache.highlight("let a = 1;\nlet b = 20;\n").unwrap();
+/// let metrics = cache.metrics();
+/// assert_eq!(3, metrics.lines);
+/// assert_eq!(Some(0.25), metrics.cache_hit_rate());
+/// assert!(metrics.lines_per_second().unwrap() > 0.0);
+/// ```
+///
+/// [HighlightCache]: crate::HighlightCache
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Metrics {
+    /// The number of lines highlighted and converted.
+    pub lines: u64,
+    /// The number of those lines that were too long to highlight, and were converted by a
+    /// [LongLineFallback](crate::LongLineFallback) instead.
+    pub long_lines: u64,
+    /// The time spent in syntect, parsing and highlighting lines.
+    pub highlight_time: Duration,
+    /// The time spent converting highlighted lines into ratatui lines.
+    pub conversion_time: Duration,
+    /// The number of lines reused from a cache rather than highlighted.
+    pub cache_hits: u64,
+    /// The number of lines highlighted because they were not cached.
+    pub cache_misses: u64,
+}
+
+impl Metrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total time spent highlighting and converting lines.
+    pub fn total_time(&self) -> Duration {
+        self.highlight_time + self.conversion_time
+    }
+
+    /// Returns the number of lines highlighted and converted per second of
+    /// [total time](Metrics::total_time), or `None` if no time was measured.
+    pub fn lines_per_second(&self) -> Option<f64> {
+        let seconds = self.total_time().as_secs_f64();
+        (seconds > 0.0).then(|| self.lines as f64 / seconds)
+    }
+
+    /// Returns the share of lines looked up in a cache that were reused, from `0.0` to `1.0`, or
+    /// `None` if no lines were looked up.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let lookups = self.cache_hits + self.cache_misses;
+        (lookups > 0).then(|| self.cache_hits as f64 / lookups as f64)
+    }
+
+    /// Runs `highlight`, adding the time it takes to the time spent in syntect.
+    pub(crate) fn time_highlight<T>(&mut self, highlight: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = highlight();
+        self.highlight_time += start.elapsed();
+        result
+    }
+
+    /// Runs `convert`, adding the time it takes to the time spent converting lines, and counting
+    /// a line.
+    pub(crate) fn time_conversion<T>(&mut self, convert: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = convert();
+        self.conversion_time += start.elapsed();
+        self.lines += 1;
+        result
+    }
+}
+
+impl std::ops::Add for Metrics {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl std::ops::AddAssign for Metrics {
+    fn add_assign(&mut self, other: Self) {
+        self.lines += other.lines;
+        self.long_lines += other.long_lines;
+        self.highlight_time += other.highlight_time;
+        self.conversion_time += other.conversion_time;
+        self.cache_hits += other.cache_hits;
+        self.cache_misses += other.cache_misses;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::empty(Metrics::new(), None, None)]
+    #[case::measured(
+        Metrics {
+            lines: 30,
+            long_lines: 0,
+            highlight_time: Duration::from_millis(100),
+            conversion_time: Duration::from_millis(50),
+            cache_hits: 3,
+            cache_misses: 1,
+        },
+        Some(200.0),
+        Some(0.75),
+    )]
+    fn check_rates(
+        #[case] metrics: Metrics,
+        #[case] lines_per_second: Option<f64>,
+        #[case] cache_hit_rate: Option<f64>,
+    ) {
+        assert_eq!(lines_per_second, metrics.lines_per_second());
+        assert_eq!(cache_hit_rate, metrics.cache_hit_rate());
+    }
+
+    #[test]
+    fn metrics_add_up() {
+        let mut metrics = Metrics::new();
+        metrics.time_highlight(|| ());
+        let counted = metrics.time_conversion(|| 7);
+        assert_eq!(7, counted);
+        let sum = metrics + metrics;
+        assert_eq!(2, sum.lines);
+        assert_eq!(metrics.total_time() * 2, sum.total_time());
+    }
+}
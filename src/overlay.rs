@@ -0,0 +1,395 @@
+use crate::colour::{blend, rgb, ColourSpace};
+
+/// Tints the background of line `index` of already converted text with the `line_highlight`
+/// colour of `theme`, so that the line containing the cursor looks the way the theme intends.
+///
+/// Themes usually give `line_highlight` some transparency, so it is blended over whatever it
+/// covers: spans with their own RGB background keep a tinted version of it, and the rest of the
+/// line is tinted over the line's, the text's or else the theme's background. If none of those
+/// is set the colour is used as is. Spans with a non-RGB background are left alone, since there is
+/// nothing to blend with.
+///
+/// The text is returned unchanged if the theme has no `line_highlight` or `index` is out of range.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style, Stylize};
+/// use ratatui::text::{Line, Span, Text};
+/// use syntect::highlighting::{Color as SyntectColour, Theme, ThemeSettings};
+///
+/// let theme = Theme {
+///     settings: ThemeSettings {
+///         background: Some(SyntectColour { r: 0, g: 0, b: 0, a: 255 }),
+///         line_highlight: Some(SyntectColour { r: 255, g: 255, b: 255, a: 51 }),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// let text = Text::from(vec![
+///     Line::raw("first"),
+///     Line::from(vec![Span::raw("x = "), Span::styled("1", Style::new().bg(Color::Rgb(100, 0, 0)))]),
+/// ]);
+/// let text = syntect_tui::highlight_current_line(text, 1, &theme);
+/// assert_eq!(None, text.lines[0].style.bg);
+/// assert_eq!(Some(Color::Rgb(51, 51, 51)), text.lines[1].style.bg);
+/// assert_eq!(Some(Color::Rgb(131, 51, 51)), text.lines[1].spans[1].style.bg);
+/// ```
+pub fn highlight_current_line<'a>(
+    mut text: ratatui::text::Text<'a>,
+    index: usize,
+    theme: &syntect::highlighting::Theme,
+) -> ratatui::text::Text<'a> {
+    let Some(colour) = theme.settings.line_highlight else {
+        return text;
+    };
+    let backdrop = text.style.bg.or_else(|| theme.settings.background.map(rgb));
+    if let Some(line) = text.lines.get_mut(index) {
+        tint_line(line, colour, backdrop);
+    }
+    text
+}
+
+/// Blends `colour` over the backgrounds of `line` and its spans, treating `backdrop` as the
+/// background behind a line without one.
+pub(crate) fn tint_line(
+    line: &mut ratatui::text::Line,
+    colour: syntect::highlighting::Color,
+    backdrop: Option<ratatui::style::Color>,
+) {
+    let tint = |background: Option<ratatui::style::Color>| match background {
+        Some(ratatui::style::Color::Rgb(r, g, b)) => Some(rgb(blend(
+            colour,
+            syntect::highlighting::Color { r, g, b, a: 255 },
+            ColourSpace::Srgb,
+        ))),
+        Some(background) => Some(background),
+        None => Some(rgb(colour)),
+    };
+    line.style.bg = tint(line.style.bg.or(backdrop));
+    for span in &mut line.spans {
+        if span.style.bg.is_some() {
+            span.style.bg = tint(span.style.bg);
+        }
+    }
+}
+
+/// A selected range of text, given either as byte offsets into the whole source or as
+/// `(line, column)` positions.
+///
+/// Columns are byte offsets within a line, matching the offsets syntect works with. Offsets that
+/// fall inside a multi-byte character are rounded down to its start. Ranges are half-open, so the
+/// character at the end offset or position is not selected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Selection {
+    /// Byte offsets into the source the lines were highlighted from.
+    Bytes(std::ops::Range<usize>),
+    /// `(line, column)` positions, with columns as byte offsets within their line.
+    Positions(std::ops::Range<(usize, usize)>),
+}
+
+impl Selection {
+    /// Resolves the selection into `(line, column)` positions, given the byte offset at which each
+    /// line starts in the source.
+    pub(crate) fn positions(&self, line_starts: &[usize]) -> std::ops::Range<(usize, usize)> {
+        let position = |offset: usize| {
+            let line = line_starts
+                .partition_point(|&start| start <= offset)
+                .saturating_sub(1);
+            let start = line_starts.get(line).copied().unwrap_or(0);
+            (line, offset - start.min(offset))
+        };
+        match self {
+            Selection::Bytes(bytes) => position(bytes.start)..position(bytes.end),
+            Selection::Positions(positions) => positions.clone(),
+        }
+    }
+
+    /// Returns the selected byte range within line `index`, if any of it is selected.
+    pub(crate) fn line_bytes(
+        positions: &std::ops::Range<(usize, usize)>,
+        index: usize,
+    ) -> Option<std::ops::Range<usize>> {
+        let (start, end) = (positions.start, positions.end);
+        if index < start.0 || index > end.0 || start >= end {
+            return None;
+        }
+        let from = if index == start.0 { start.1 } else { 0 };
+        let to = if index == end.0 { end.1 } else { usize::MAX };
+        Some(from..to)
+    }
+}
+
+/// Returns the byte offset at which each line starts, assuming lines were separated by a single
+/// `\n` that was trimmed when converting them.
+pub(crate) fn line_starts(lines: &[ratatui::text::Line]) -> Vec<usize> {
+    lines
+        .iter()
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line
+                .spans
+                .iter()
+                .map(|span| span.content.len())
+                .sum::<usize>()
+                + 1;
+            Some(start)
+        })
+        .collect()
+}
+
+/// Styles a selection of already converted text with the `selection` and `selection_foreground`
+/// colours of `theme`.
+///
+/// Spans are split at the selection boundaries so that only the selected characters are styled,
+/// and the selection style is patched over the style of each selected span. [Selection::Bytes]
+/// offsets are resolved assuming the lines were separated by a single `\n`; use the
+/// [code view](crate::widget) to select in sources with other line endings.
+///
+/// The text is returned unchanged if the theme has no selection colours.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style, Stylize};
+/// use ratatui::text::{Line, Span, Text};
+/// use syntect::highlighting::{Color as SyntectColour, Theme, ThemeSettings};
+/// use syntect_tui::Selection;
+///
+/// let theme = Theme {
+///     settings: ThemeSettings {
+///         selection: Some(SyntectColour { r: 0, g: 0, b: 255, a: 255 }),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// let text = Text::from(vec![
+///     Line::from(vec![Span::styled("let", Style::new().red()), Span::raw(" x")]),
+///     Line::raw("x += 1;"),
+/// ]);
+/// let text = syntect_tui::highlight_selection(text, &Selection::Bytes(1..7), &theme);
+/// let selected = Style::new().bg(Color::Rgb(0, 0, 255));
+/// assert_eq!(
+///     vec![
+///         Span::styled("l", Style::new().red()),
+///         Span::styled("et", Style::new().red().patch(selected)),
+///         Span::styled(" x", selected),
+///     ],
+///     text.lines[0].spans
+/// );
+/// assert_eq!(vec![Span::styled("x", selected), Span::raw(" += 1;")], text.lines[1].spans);
+/// ```
+pub fn highlight_selection<'a>(
+    mut text: ratatui::text::Text<'a>,
+    selection: &Selection,
+    theme: &syntect::highlighting::Theme,
+) -> ratatui::text::Text<'a> {
+    let Some(style) = crate::translate_theme_settings(theme).selection else {
+        return text;
+    };
+    let positions = selection.positions(&line_starts(&text.lines));
+    for (index, line) in text.lines.iter_mut().enumerate() {
+        if let Some(bytes) = Selection::line_bytes(&positions, index) {
+            *line = select_line(line, bytes, style);
+        }
+    }
+    text
+}
+
+/// Patches `style` over the characters of `line` within the byte range `bytes`, splitting spans
+/// at the boundaries of the range.
+pub(crate) fn select_line<'a>(
+    line: &ratatui::text::Line<'a>,
+    bytes: std::ops::Range<usize>,
+    style: ratatui::style::Style,
+) -> ratatui::text::Line<'a> {
+    let mut selected = line.clone();
+    selected.spans.clear();
+    let mut offset = 0;
+    for span in &line.spans {
+        let content = span.content.as_ref();
+        let (start, end) = (offset, offset + content.len());
+        offset = end;
+        let from = floor_char_boundary(content, bytes.start.clamp(start, end) - start);
+        let to = floor_char_boundary(content, bytes.end.clamp(start, end) - start);
+        if from >= to {
+            selected.spans.push(span.clone());
+            continue;
+        }
+        let pieces = [
+            (&content[..from], span.style),
+            (&content[from..to], span.style.patch(style)),
+            (&content[to..], span.style),
+        ];
+        selected.spans.extend(
+            pieces
+                .into_iter()
+                .filter(|(piece, _)| !piece.is_empty())
+                .map(|(piece, style)| ratatui::text::Span::styled(piece.to_string(), style)),
+        );
+    }
+    selected
+}
+
+fn floor_char_boundary(content: &str, mut index: usize) -> usize {
+    while !content.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::{Color, Style, Stylize};
+    use ratatui::text::{Line, Span, Text};
+    use syntect::highlighting::{Color as SyntectColour, Theme, ThemeSettings};
+
+    fn fake_theme(line_highlight: Option<SyntectColour>) -> Theme {
+        Theme {
+            settings: ThemeSettings {
+                background: Some(SyntectColour {
+                    r: 0,
+                    g: 0,
+                    b: 100,
+                    a: 255,
+                }),
+                line_highlight,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    const HALF_WHITE: SyntectColour = SyntectColour {
+        r: 255,
+        g: 255,
+        b: 255,
+        a: 128,
+    };
+
+    #[rstest]
+    #[case::theme_background(None, None, Some(Color::Rgb(128, 128, 178)))]
+    #[case::text_background(Some(Color::Rgb(0, 0, 0)), None, Some(Color::Rgb(128, 128, 128)))]
+    #[case::line_background(None, Some(Color::Rgb(200, 0, 0)), Some(Color::Rgb(228, 128, 128)))]
+    #[case::named_background(None, Some(Color::Red), Some(Color::Red))]
+    fn check_line_background(
+        #[case] text_background: Option<Color>,
+        #[case] line_background: Option<Color>,
+        #[case] expected: Option<Color>,
+    ) {
+        let line = Line::raw("a").style(Style {
+            bg: line_background,
+            ..Default::default()
+        });
+        let mut text = Text::from(line);
+        text.style.bg = text_background;
+        let text = highlight_current_line(text, 0, &fake_theme(Some(HALF_WHITE)));
+        assert_eq!(expected, text.lines[0].style.bg);
+    }
+
+    #[test]
+    fn highlight_current_line_tints_only_span_backgrounds() {
+        let text = Text::from(Line::from(vec![
+            Span::raw("a"),
+            Span::styled("b", Style::new().bg(Color::Rgb(0, 0, 0))),
+        ]));
+        let text = highlight_current_line(text, 0, &fake_theme(Some(HALF_WHITE)));
+        assert_eq!(None, text.lines[0].spans[0].style.bg);
+        assert_eq!(
+            Some(Color::Rgb(128, 128, 128)),
+            text.lines[0].spans[1].style.bg
+        );
+    }
+
+    #[rstest]
+    #[case::no_line_highlight(None, 0)]
+    #[case::out_of_range(Some(HALF_WHITE), 1)]
+    fn highlight_current_line_unchanged(
+        #[case] line_highlight: Option<SyntectColour>,
+        #[case] index: usize,
+    ) {
+        let text = Text::raw("a");
+        assert_eq!(
+            text.clone(),
+            highlight_current_line(text, index, &fake_theme(line_highlight))
+        );
+    }
+
+    #[rstest]
+    #[case::bytes_in_one_span(Selection::Bytes(1..2), vec![("a", false), ("b", true), ("c", false), ("de", false)])]
+    #[case::bytes_across_spans(Selection::Bytes(2..4), vec![("ab", false), ("c", true), ("d", true), ("e", false)])]
+    #[case::positions(Selection::Positions((0, 0)..(0, 5)), vec![("abc", true), ("de", true)])]
+    #[case::empty(Selection::Bytes(2..2), vec![("abc", false), ("de", false)])]
+    #[case::other_line(Selection::Positions((1, 0)..(1, 1)), vec![("abc", false), ("de", false)])]
+    fn check_highlight_selection(
+        #[case] selection: Selection,
+        #[case] expected: Vec<(&str, bool)>,
+    ) {
+        let selected = Style::new()
+            .bg(Color::Rgb(0, 0, 255))
+            .fg(Color::Rgb(255, 255, 255));
+        let theme = Theme {
+            settings: ThemeSettings {
+                selection: Some(SyntectColour {
+                    r: 0,
+                    g: 0,
+                    b: 255,
+                    a: 255,
+                }),
+                selection_foreground: Some(SyntectColour {
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let line = Line::from(vec![
+            Span::raw("abc"),
+            Span::styled("de", Style::new().italic()),
+        ]);
+        let text = Text::from(vec![line, Line::raw("")]);
+        let actual = highlight_selection(text, &selection, &theme);
+        let expected: Vec<Span> = expected
+            .into_iter()
+            .map(|(content, is_selected)| {
+                let base = if "de".contains(content) {
+                    Style::new().italic()
+                } else {
+                    Style::new()
+                };
+                let style = if is_selected {
+                    base.patch(selected)
+                } else {
+                    base
+                };
+                Span::styled(content, style)
+            })
+            .collect();
+        assert_eq!(expected, actual.lines[0].spans);
+    }
+
+    #[rstest]
+    #[case::first_line(Selection::Bytes(0..1), (0, 0)..(0, 1))]
+    #[case::later_line(Selection::Bytes(5..9), (1, 1)..(2, 1))]
+    #[case::positions(Selection::Positions((1, 2)..(3, 4)), (1, 2)..(3, 4))]
+    fn check_selection_positions(
+        #[case] selection: Selection,
+        #[case] expected: std::ops::Range<(usize, usize)>,
+    ) {
+        assert_eq!(expected, selection.positions(&[0, 4, 8]));
+    }
+
+    #[test]
+    fn select_line_rounds_to_character_boundaries() {
+        let line = Line::raw("日本");
+        let selected = select_line(&line, 1..4, Style::new().bold());
+        assert_eq!(
+            vec![Span::styled("日", Style::new().bold()), Span::raw("本")],
+            selected.spans
+        );
+    }
+}
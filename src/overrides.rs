@@ -0,0 +1,314 @@
+use crate::reverse::font_style_back;
+use crate::{ReverseOptions, SyntectTuiError};
+use std::str::FromStr;
+
+/// Changes to make to a theme, e.g. to use `base16-ocean.dark` but with grey comments and italic
+/// strings, so users can adjust a theme they like without writing a theme of their own.
+///
+/// Each override only changes what its
+/// [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html) sets:
+/// - Colours replace the theme's, converted with the [ReverseOptions]' palette, while unset and
+///   `Color::Reset` colours keep the theme's.
+/// - Added and removed modifiers are added to and removed from the font style the theme gives the
+///   selector, so making strings italic keeps them bold if they were. Only `BOLD`, `ITALIC` and
+///   `UNDERLINED` have syntect equivalents.
+///
+/// [apply](ThemeOverrides::apply) puts the overrides before the theme's own rules, so an override
+/// wins over the theme's rules for the same selector, and later overrides win over earlier ones,
+/// while the theme's rules for more specific selectors, e.g. `comment.block.documentation` for a
+/// `comment` override, still win as they would in the theme.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Modifier, Style};
+/// use syntect::highlighting::ThemeSet;
+/// use syntect_tui::{CompiledTheme, StyleTranslator, ThemeOverrides};
+///
+/// let base = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+/// let theme = ThemeOverrides::new()
+///     .scope("comment", Style::new().fg(Color::Rgb(0x77, 0x77, 0x77)))
+///     .scope("string", Style::new().add_modifier(Modifier::ITALIC))
+///     .apply(base)
+///     .unwrap();
+/// let compiled = CompiledTheme::new(&theme, StyleTranslator::new()).unwrap();
+/// assert_eq!(base.scopes.len() + 2, theme.scopes.len());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThemeOverrides {
+    options: ReverseOptions,
+    foreground: Option<ratatui::style::Color>,
+    background: Option<ratatui::style::Color>,
+    scopes: Vec<(String, ratatui::style::Style)>,
+}
+
+impl ThemeOverrides {
+    /// Creates overrides that change nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the options colours are converted with, e.g. the palette named colours resolve to.
+    pub fn reverse_options(mut self, options: ReverseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Replaces the theme's default foreground colour.
+    pub fn foreground(mut self, colour: ratatui::style::Color) -> Self {
+        self.foreground = Some(colour);
+        self
+    }
+
+    /// Replaces the theme's default background colour.
+    pub fn background(mut self, colour: ratatui::style::Color) -> Self {
+        self.background = Some(colour);
+        self
+    }
+
+    /// Overrides the style of text matching `selector`, e.g. `comment` or `source.rust string`.
+    pub fn scope(mut self, selector: impl Into<String>, style: ratatui::style::Style) -> Self {
+        self.scopes.push((selector.into(), style));
+        self
+    }
+
+    /// Parses overrides from TOML, with default `foreground` and `background` colours, a table of
+    /// `scopes` whose styles are written like [Helix](crate::HelixTheme)'s, and a `palette` of
+    /// colour names. Colours may be hex colours, the names of the palette or the names of the 16
+    /// ANSI colours, e.g. `light-red`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::{Color, Modifier, Style};
+    /// use syntect_tui::ThemeOverrides;
+    ///
+    /// let overrides = ThemeOverrides::from_toml(
+    ///     r##"
+    ///     background = "black"
+    ///
+    ///     [scopes]
+    ///     comment = "grey"
+    ///     string = { modifiers = ["italic"] }
+    ///
+    ///     [palette]
+    ///     grey = "#777777"
+    ///     "##,
+    /// )
+    /// .unwrap();
+    /// let expected = ThemeOverrides::new()
+    ///     .background(Color::Black)
+    ///     .scope("comment", Style::new().fg(Color::Rgb(0x77, 0x77, 0x77)))
+    ///     .scope("string", Style::new().add_modifier(Modifier::ITALIC));
+    /// assert_eq!(expected, overrides);
+    /// ```
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeLoading` if `source` is not valid TOML, or a style is
+    /// malformed or uses an unknown colour or modifier.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(source: &str) -> Result<Self, SyntectTuiError> {
+        use crate::toml_styles::{parse_colour, parse_style};
+        let table: toml::Table =
+            source
+                .parse()
+                .map_err(|error: toml::de::Error| SyntectTuiError::ThemeLoading {
+                    message: error.message().to_string(),
+                })?;
+        let palette = table.get("palette").and_then(toml::Value::as_table);
+        let colour = |key: &str| {
+            let Some(value) = table.get(key) else {
+                return Ok(None);
+            };
+            value
+                .as_str()
+                .and_then(|name| parse_colour(name, palette))
+                .map(Some)
+                .ok_or_else(|| SyntectTuiError::ThemeLoading {
+                    message: format!("invalid colour for \"{key}\""),
+                })
+        };
+        let scopes = table.get("scopes").and_then(toml::Value::as_table);
+        Ok(Self {
+            options: ReverseOptions::default(),
+            foreground: colour("foreground")?,
+            background: colour("background")?,
+            scopes: scopes
+                .into_iter()
+                .flatten()
+                .map(|(selector, value)| {
+                    Ok((selector.clone(), parse_style(selector, value, palette)?))
+                })
+                .collect::<Result<_, SyntectTuiError>>()?,
+        })
+    }
+
+    /// Returns `base` with the overrides applied.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::InvalidScopeSelector` if a selector cannot be parsed.
+    pub fn apply(
+        &self,
+        base: &syntect::highlighting::Theme,
+    ) -> Result<syntect::highlighting::Theme, SyntectTuiError> {
+        let highlighter = syntect::highlighting::Highlighter::new(base);
+        let mut items = Vec::with_capacity(self.scopes.len());
+        // The highlighter keeps the first of equally specific rules, so the last override goes
+        // first.
+        for (selector, style) in self.scopes.iter().rev() {
+            let selectors =
+                syntect::highlighting::ScopeSelectors::from_str(selector).map_err(|error| {
+                    SyntectTuiError::InvalidScopeSelector {
+                        selector: selector.clone(),
+                        message: error.to_string(),
+                    }
+                })?;
+            let modifier = self.options.style_modifier(*style);
+            let changes_font = !style.add_modifier.is_empty() || !style.sub_modifier.is_empty();
+            // Each selector of a list is split into its own rule, as the font style the theme
+            // gives each of them may differ.
+            for selector in selectors.selectors {
+                let font_style = changes_font.then(|| {
+                    let current = highlighter
+                        .style_for_stack(selector.path.as_slice())
+                        .font_style;
+                    (current | font_style_back(style.add_modifier))
+                        - font_style_back(style.sub_modifier)
+                });
+                items.push(syntect::highlighting::ThemeItem {
+                    scope: syntect::highlighting::ScopeSelectors {
+                        selectors: vec![selector],
+                    },
+                    style: syntect::highlighting::StyleModifier {
+                        font_style,
+                        ..modifier
+                    },
+                });
+            }
+        }
+        let colour = |colour: Option<ratatui::style::Color>| match colour {
+            None | Some(ratatui::style::Color::Reset) => None,
+            Some(colour) => Some(crate::translate_colour_back(colour, &self.options.palette)),
+        };
+        let mut theme = base.clone();
+        theme.settings.foreground = colour(self.foreground).or(theme.settings.foreground);
+        theme.settings.background = colour(self.background).or(theme.settings.background);
+        theme.scopes.splice(0..0, items);
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier, Style};
+    use rstest::*;
+    use syntect::highlighting::{FontStyle, ThemeSet};
+
+    /// Returns the style `theme` gives a scope stack written like `source.rust comment.line`.
+    fn style_for(
+        theme: &syntect::highlighting::Theme,
+        stack: &str,
+    ) -> syntect::highlighting::Style {
+        let stack = syntect::parsing::ScopeStack::from_str(stack).unwrap();
+        syntect::highlighting::Highlighter::new(theme).style_for_stack(stack.as_slice())
+    }
+
+    fn ocean() -> syntect::highlighting::Theme {
+        ThemeSet::load_defaults()
+            .themes
+            .remove("base16-ocean.dark")
+            .unwrap()
+    }
+
+    #[test]
+    fn overrides_win_over_the_theme() {
+        let base = ocean();
+        let grey = syntect::highlighting::Color {
+            r: 0x77,
+            g: 0x77,
+            b: 0x77,
+            a: 255,
+        };
+        let theme = ThemeOverrides::new()
+            .scope("comment", Style::new().fg(Color::Red))
+            .scope("comment", Style::new().fg(Color::Rgb(0x77, 0x77, 0x77)))
+            .scope(
+                "string, constant",
+                Style::new().add_modifier(Modifier::ITALIC),
+            )
+            .apply(&base)
+            .unwrap();
+        assert_eq!(
+            grey,
+            style_for(&theme, "source.rust comment.line").foreground
+        );
+        let string = style_for(&theme, "source.rust string.quoted");
+        assert_eq!(
+            style_for(&base, "source.rust string.quoted").foreground,
+            string.foreground
+        );
+        assert_eq!(FontStyle::ITALIC, string.font_style);
+        assert_eq!(
+            FontStyle::ITALIC,
+            style_for(&theme, "source.rust constant.numeric").font_style
+        );
+    }
+
+    #[rstest]
+    #[case::added(Style::new().add_modifier(Modifier::UNDERLINED), FontStyle::BOLD | FontStyle::UNDERLINE)]
+    #[case::removed(Style::new().remove_modifier(Modifier::BOLD), FontStyle::empty())]
+    #[case::unchanged(Style::new().fg(Color::Red), FontStyle::BOLD)]
+    fn modifiers_change_the_theme_font_style(#[case] style: Style, #[case] expected: FontStyle) {
+        let base =
+            crate::build_theme([("keyword", Style::new().add_modifier(Modifier::BOLD))]).unwrap();
+        let theme = ThemeOverrides::new()
+            .scope("keyword", style)
+            .apply(&base)
+            .unwrap();
+        assert_eq!(expected, style_for(&theme, "keyword.control").font_style);
+    }
+
+    #[test]
+    fn default_colours_are_replaced() {
+        let base = ocean();
+        let theme = ThemeOverrides::new()
+            .background(Color::Black)
+            .foreground(Color::Reset)
+            .apply(&base)
+            .unwrap();
+        assert_eq!(
+            Some(syntect::highlighting::Color {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 255
+            }),
+            theme.settings.background
+        );
+        assert_eq!(base.settings.foreground, theme.settings.foreground);
+    }
+
+    #[test]
+    fn invalid_selectors_are_rejected() {
+        let actual = ThemeOverrides::new()
+            .scope("a.b.c.d.e.f.g.h.i", Style::new())
+            .apply(&ocean());
+        assert!(matches!(
+            actual,
+            Err(SyntectTuiError::InvalidScopeSelector { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn rejects_invalid_toml() {
+        for source in [
+            "scopes = ",
+            "background = \"mauve\"",
+            "[scopes]\ncomment = 1",
+        ] {
+            assert!(matches!(
+                ThemeOverrides::from_toml(source),
+                Err(SyntectTuiError::ThemeLoading { .. })
+            ));
+        }
+    }
+}
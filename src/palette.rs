@@ -0,0 +1,147 @@
+/// Concrete RGB values for ratatui's named and indexed colours.
+///
+/// Named colours (e.g. `Color::Red`) and `Color::Indexed` colours are rendered using the terminal's
+/// palette, so they have no fixed RGB value. A `Palette` supplies one wherever a concrete colour
+/// is needed, e.g. when converting ratatui colours back into syntect colours. The default palette
+/// uses xterm's default colours, and individual entries can be overridden to match the user's
+/// terminal.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+///
+/// let palette = syntect_tui::Palette::default().with_colour(Color::Red, (220, 50, 47));
+/// assert_eq!(Some((220, 50, 47)), palette.rgb(Color::Red));
+/// assert_eq!(Some((220, 50, 47)), palette.rgb(Color::Indexed(1)));
+/// assert_eq!(Some((0, 255, 0)), palette.rgb(Color::LightGreen));
+/// assert_eq!(Some((1, 2, 3)), palette.rgb(Color::Rgb(1, 2, 3)));
+/// assert_eq!(None, palette.rgb(Color::Reset));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colours: [(u8, u8, u8); 256],
+}
+
+const XTERM_SYSTEM_COLOURS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const XTERM_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::xterm()
+    }
+}
+
+impl Palette {
+    /// Creates a palette with xterm's default colours.
+    pub fn xterm() -> Self {
+        let mut colours = [(0, 0, 0); 256];
+        colours[..16].copy_from_slice(&XTERM_SYSTEM_COLOURS);
+        for (offset, colour) in colours[16..232].iter_mut().enumerate() {
+            *colour = (
+                XTERM_CUBE_LEVELS[offset / 36],
+                XTERM_CUBE_LEVELS[(offset / 6) % 6],
+                XTERM_CUBE_LEVELS[offset % 6],
+            );
+        }
+        for (offset, colour) in colours[232..].iter_mut().enumerate() {
+            let level = 8 + 10 * offset as u8;
+            *colour = (level, level, level);
+        }
+        Self { colours }
+    }
+
+    /// Overrides the RGB value of a named or indexed colour.
+    ///
+    /// Named colours share their entry with the equivalent index, e.g. `Color::Red` is
+    /// `Color::Indexed(1)`. Overrides for `Color::Rgb` and `Color::Reset` are ignored.
+    pub fn with_colour(mut self, colour: ratatui::style::Color, rgb: (u8, u8, u8)) -> Self {
+        if let Some(index) = palette_index(colour) {
+            self.colours[usize::from(index)] = rgb;
+        }
+        self
+    }
+
+    /// Returns the RGB value of `colour`.
+    ///
+    /// `Color::Rgb` colours are returned as they are, while `Color::Reset` (the terminal's default
+    /// colour) has no RGB value.
+    pub fn rgb(&self, colour: ratatui::style::Color) -> Option<(u8, u8, u8)> {
+        match colour {
+            ratatui::style::Color::Rgb(r, g, b) => Some((r, g, b)),
+            other => palette_index(other).map(|index| self.colours[usize::from(index)]),
+        }
+    }
+}
+
+/// Returns the palette index of a named or indexed colour.
+pub(crate) fn palette_index(colour: ratatui::style::Color) -> Option<u8> {
+    use ratatui::style::Color;
+    match colour {
+        Color::Reset | Color::Rgb(..) => None,
+        Color::Black => Some(0),
+        Color::Red => Some(1),
+        Color::Green => Some(2),
+        Color::Yellow => Some(3),
+        Color::Blue => Some(4),
+        Color::Magenta => Some(5),
+        Color::Cyan => Some(6),
+        Color::Gray => Some(7),
+        Color::DarkGray => Some(8),
+        Color::LightRed => Some(9),
+        Color::LightGreen => Some(10),
+        Color::LightYellow => Some(11),
+        Color::LightBlue => Some(12),
+        Color::LightMagenta => Some(13),
+        Color::LightCyan => Some(14),
+        Color::White => Some(15),
+        Color::Indexed(index) => Some(index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::Color;
+
+    #[rstest]
+    #[case::named(Color::Blue, Some((0, 0, 238)))]
+    #[case::system_index(Color::Indexed(9), Some((255, 0, 0)))]
+    #[case::cube_start(Color::Indexed(16), Some((0, 0, 0)))]
+    #[case::cube(Color::Indexed(110), Some((135, 175, 215)))]
+    #[case::cube_end(Color::Indexed(231), Some((255, 255, 255)))]
+    #[case::greyscale_start(Color::Indexed(232), Some((8, 8, 8)))]
+    #[case::greyscale_end(Color::Indexed(255), Some((238, 238, 238)))]
+    #[case::rgb(Color::Rgb(1, 2, 3), Some((1, 2, 3)))]
+    #[case::reset(Color::Reset, None)]
+    fn check_xterm_palette(#[case] colour: Color, #[case] expected: Option<(u8, u8, u8)>) {
+        assert_eq!(expected, Palette::xterm().rgb(colour));
+    }
+
+    #[test]
+    fn overrides_ignore_rgb_and_reset() {
+        let palette = Palette::default()
+            .with_colour(Color::Rgb(1, 2, 3), (4, 5, 6))
+            .with_colour(Color::Reset, (4, 5, 6));
+        assert_eq!(Palette::xterm(), palette);
+    }
+}
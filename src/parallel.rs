@@ -0,0 +1,329 @@
+use crate::highlight::{advance_states, highlight_from_states, LineStates};
+use crate::{HighlightedText, SyntectTuiError};
+use rayon::prelude::*;
+
+/// A run of consecutive lines highlighted on its own, with whether the states after each of its
+/// lines are back to those at the start of a document.
+///
+/// The states themselves stay on the thread that highlighted the chunk, as syntect's parser state
+/// cannot always be moved between threads.
+struct Chunk<'s> {
+    lines: Vec<ratatui::text::Line<'s>>,
+    resets: Vec<bool>,
+}
+
+/// Highlights a whole document on a thread pool, e.g. when a large file is opened.
+///
+/// The document is split into chunks of [chunk_lines](ParallelHighlighter::chunk_lines) lines,
+/// which are highlighted in parallel on rayon's global thread pool. Every chunk but the first
+/// starts from the states at the start of a document, as the states the chunk before it ends in
+/// are not known yet, which is right for most top level code. The chunks are then stitched in
+/// order: where a chunk did not start from the states the chunk before it actually ends in, e.g.
+/// in the middle of a block comment, its lines are highlighted again from those states until
+/// both they and the chunk's own states are back to those at the start of a document, and the
+/// lines after the last that left them there are parsed again for the states the chunk ends in.
+/// The result is the same as highlighting the document line by line, but how much faster it is
+/// depends on how soon chunks catch up, e.g. a chunk starting inside a block that the syntax
+/// tracks, like a Rust function body, only catches up once the block is closed.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "/*\n".to_string() + &"let x = 1;\n".repeat(3) + "*/\nlet y = 2;\n";
+/// let text = syntect_tui::ParallelHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .chunk_lines(2)
+///     .highlight(&source)
+///     .unwrap();
+/// assert_eq!(6, text.lines.len());
+/// assert_eq!(text.lines[1].spans[0].style, text.lines[4].spans[0].style);
+/// assert_ne!(text.lines[1].spans[0].style, text.lines[5].spans[0].style);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParallelHighlighter<'a> {
+    syntax: &'a syntect::parsing::SyntaxReference,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    chunk_lines: usize,
+    cancellation: Option<&'a crate::CancellationToken>,
+    fallback: crate::LongLineFallback,
+    translator: crate::StyleTranslator,
+}
+
+/// Highlights `source` using `syntax` and `theme` on a thread pool.
+///
+/// See [ParallelHighlighter] for details.
+///
+/// # Errors
+/// Can return the same errors as [ParallelHighlighter::highlight].
+pub fn highlight_parallel<'s>(
+    source: &'s str,
+    syntax: &syntect::parsing::SyntaxReference,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+) -> Result<HighlightedText<'s>, SyntectTuiError> {
+    ParallelHighlighter::new(syntax, syntax_set, theme).highlight(source)
+}
+
+impl<'a> ParallelHighlighter<'a> {
+    /// Creates a highlighter that highlights chunks of 1000 lines using `syntax` and `theme`.
+    pub fn new(
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            syntax,
+            syntax_set,
+            theme,
+            chunk_lines: 1000,
+            cancellation: None,
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+        }
+    }
+
+    /// Sets the number of lines in each chunk. Smaller chunks spread the work more evenly, but
+    /// are more likely to start inside a construct spanning several lines.
+    pub fn chunk_lines(mut self, lines: usize) -> Self {
+        self.chunk_lines = lines.max(1);
+        self
+    }
+
+    /// Stops highlighting on every thread before their next line once `token` has been
+    /// cancelled, returning `SyntectTuiError::Cancelled`.
+    pub fn cancel_with(mut self, token: &'a crate::CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Highlights `source`, with the theme's background.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line of `source` they occurred on.
+    pub fn highlight<'s>(&self, source: &'s str) -> Result<HighlightedText<'s>, SyntectTuiError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("highlight_parallel", bytes = source.len()).entered();
+        let lines: Vec<&str> = syntect::util::LinesWithEndings::from(source).collect();
+        let chunks = lines
+            .par_chunks(self.chunk_lines)
+            .enumerate()
+            .map(|(index, chunk)| self.highlight_chunk(chunk, index * self.chunk_lines))
+            .collect::<Result<Vec<_>, _>>()?;
+        let initial = syntect::easy::HighlightLines::new(self.syntax, self.theme).state();
+        let mut highlighted = Vec::with_capacity(lines.len());
+        let mut states = initial.clone();
+        for (index, mut chunk) in chunks.into_iter().enumerate() {
+            let start = index * self.chunk_lines;
+            let chunk_source = &lines[start..start + chunk.lines.len()];
+            let mut offset = 0;
+            let mut converged = states == initial;
+            while !converged && offset < chunk_source.len() {
+                let (converted, outgoing) =
+                    self.highlight_from(&states, chunk_source, start, offset)?;
+                chunk.lines[offset] = converted;
+                converged = outgoing == initial && chunk.resets[offset];
+                states = outgoing;
+                offset += 1;
+            }
+            if converged {
+                // The rest of the chunk is highlighted as it should be, so only the states it ends
+                // in are rebuilt, by parsing on from the last of its lines that left them as at
+                // the start without converting the lines again.
+                let from = chunk.resets[offset..]
+                    .iter()
+                    .rposition(|&reset| reset)
+                    .map_or(offset, |last| offset + last + 1);
+                states = initial.clone();
+                for (line, text) in chunk_source.iter().enumerate().skip(from) {
+                    advance_states(
+                        &mut states,
+                        self.syntax_set,
+                        self.theme,
+                        text,
+                        &self.fallback,
+                    )
+                    .map_err(|error| error.at_line(start + line))?;
+                }
+            }
+            highlighted.extend(chunk.lines);
+        }
+        Ok(HighlightedText::new(
+            highlighted,
+            self.translator.theme_background(self.theme),
+        ))
+    }
+
+    /// Highlights line `offset` of the chunk of `lines` starting at line `start`, from `states`.
+    fn highlight_from(
+        &self,
+        states: &LineStates,
+        lines: &[&str],
+        start: usize,
+        offset: usize,
+    ) -> Result<(ratatui::text::Line<'static>, LineStates), SyntectTuiError> {
+        highlight_from_states(
+            states,
+            self.syntax_set,
+            self.theme,
+            lines[offset],
+            &self.fallback,
+            &self.translator,
+            None,
+        )
+        .map_err(|error| error.at_line(start + offset))
+    }
+
+    /// Highlights the lines of a chunk starting at line `start`, from the states at the start of
+    /// a document.
+    fn highlight_chunk<'s>(
+        &self,
+        lines: &[&'s str],
+        start: usize,
+    ) -> Result<Chunk<'s>, SyntectTuiError> {
+        let initial = syntect::easy::HighlightLines::new(self.syntax, self.theme).state();
+        let mut highlighter = syntect::easy::HighlightLines::new(self.syntax, self.theme);
+        let mut chunk = Chunk {
+            lines: Vec::with_capacity(lines.len()),
+            resets: Vec::with_capacity(lines.len()),
+        };
+        for (offset, line) in lines.iter().enumerate() {
+            if let Some(token) = self.cancellation {
+                token.check(start + offset)?;
+            }
+            chunk.lines.push(
+                self.fallback
+                    .highlight_line(
+                        &mut highlighter,
+                        self.syntax_set,
+                        self.theme,
+                        line,
+                        &self.translator,
+                        None,
+                    )
+                    .map_err(|error| error.at_line(start + offset))?,
+            );
+            let (highlight_state, parse_state) = highlighter.state();
+            chunk
+                .resets
+                .push((&highlight_state, &parse_state) == (&initial.0, &initial.1));
+            highlighter =
+                syntect::easy::HighlightLines::from_state(self.theme, highlight_state, parse_state);
+        }
+        Ok(chunk)
+    }
+}
+
+impl crate::LineConversion for ParallelHighlighter<'_> {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\nc\n*/\nlet d = \"\ne\";\nlet f = 6;";
+
+    #[rstest]
+    #[case::one_per_line(1)]
+    #[case::split_comment(2)]
+    #[case::split_string(3)]
+    #[case::whole_document(100)]
+    fn matches_sequential_highlighting(#[case] chunk_lines: usize) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(SOURCE)
+            .map(|line| crate::highlight_line_to_line(&mut sequential, &ps, theme, line).unwrap())
+            .collect::<Vec<_>>();
+        let text = ParallelHighlighter::new(syntax, &ps, theme)
+            .chunk_lines(chunk_lines)
+            .highlight(SOURCE)
+            .unwrap();
+        assert_eq!(HighlightedText::from_theme(expected, theme), text);
+    }
+
+    #[test]
+    fn matches_sequential_highlighting_across_blocks() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let source = "fn a() {\n    let s = \"\n}\";\n}\n/* b */\nfn c() {\n    1\n}\n";
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(source)
+            .map(|line| crate::highlight_line_to_line(&mut sequential, &ps, theme, line).unwrap())
+            .collect::<Vec<_>>();
+        for chunk_lines in 1..=5 {
+            let text = ParallelHighlighter::new(syntax, &ps, theme)
+                .chunk_lines(chunk_lines)
+                .highlight(source)
+                .unwrap();
+            assert_eq!(expected, text.lines, "chunks of {chunk_lines} lines");
+        }
+    }
+
+    #[test]
+    fn matches_sequential_highlighting_without_converging() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        // The block comment is never closed, so no chunk after it ever catches up.
+        let source = "let a = 1;\n/*\n".to_string() + &"let b = 2;\n".repeat(20);
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(source.as_str())
+            .map(|line| crate::highlight_line_to_line(&mut sequential, &ps, theme, line).unwrap())
+            .collect::<Vec<_>>();
+        for chunk_lines in [1, 3, 7] {
+            let text = ParallelHighlighter::new(syntax, &ps, theme)
+                .chunk_lines(chunk_lines)
+                .highlight(&source)
+                .unwrap();
+            assert_eq!(expected, text.lines, "chunks of {chunk_lines} lines");
+        }
+    }
+
+    #[test]
+    fn highlights_empty_source() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let text = highlight_parallel("", syntax, &ps, theme).unwrap();
+        assert!(text.lines.is_empty());
+    }
+
+    #[test]
+    fn cancelled_highlighting_fails() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let token = crate::CancellationToken::new();
+        token.cancel();
+        let result = ParallelHighlighter::new(syntax, &ps, theme)
+            .chunk_lines(2)
+            .cancel_with(&token)
+            .highlight(SOURCE);
+        assert!(matches!(result, Err(SyntectTuiError::Cancelled { .. })));
+    }
+}
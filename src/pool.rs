@@ -0,0 +1,112 @@
+/// Keeps the memory of converted lines between frames, so that converting the visible lines of a
+/// document every frame stops allocating once the pool has warmed up.
+///
+/// Lines taken from the pool are empty but keep the span capacity of lines
+/// [recycled](LinePool::recycle) before, whatever they borrowed. Lines are filled using the
+/// conversions that write into a line, e.g. [StyleTranslator::convert_line_into](crate::StyleTranslator::convert_line_into)
+/// or [InternedLine::write_to](crate::InternedLine::write_to), or pooled conversions like
+/// [InternedText::text_with](crate::InternedText::text_with), and handed back once drawn.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Stylize;
+/// use ratatui::text::{Line, Span};
+/// use syntect_tui::{HighlightedText, InternedText, LinePool};
+///
+/// let text = HighlightedText::new(
+///     vec![Line::from(vec![Span::raw("let").blue(), Span::raw(" x")]); 100],
+///     None,
+/// );
+/// let document = InternedText::from(&text);
+/// let mut pool = LinePool::new();
+/// for frame in 0..3 {
+///     let visible = document.text_with(frame..frame + 10, &mut pool);
+///     assert_eq!(10, visible.lines.len());
+///     pool.recycle(visible.lines);
+/// }
+/// assert_eq!(10, pool.len());
+/// ```
+#[derive(Debug, Default)]
+pub struct LinePool {
+    spans: Vec<Vec<ratatui::text::Span<'static>>>,
+    lines: Vec<Vec<ratatui::text::Line<'static>>>,
+}
+
+impl LinePool {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of lines' spans the pool holds.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns whether the pool holds no lines' spans.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Takes an empty line, reusing the spans of a recycled line if there is one.
+    pub fn line<'a>(&mut self) -> ratatui::text::Line<'a> {
+        ratatui::text::Line::from(self.spans.pop().unwrap_or_default())
+    }
+
+    /// Takes an empty list of lines, reusing a recycled list if there is one.
+    pub fn lines<'a>(&mut self) -> Vec<ratatui::text::Line<'a>> {
+        self.lines.pop().unwrap_or_default()
+    }
+
+    /// Hands `lines` back to the pool, keeping the memory of the list and of each line's spans.
+    pub fn recycle(&mut self, mut lines: Vec<ratatui::text::Line<'_>>) {
+        for line in lines.drain(..) {
+            self.recycle_line(line);
+        }
+        self.lines.push(forget_lifetime(lines));
+    }
+
+    /// Hands `line` back to the pool, keeping the memory of its spans.
+    pub fn recycle_line(&mut self, line: ratatui::text::Line<'_>) {
+        self.spans.push(forget_lifetime(line.spans));
+    }
+}
+
+/// Empties `items` and returns its memory as a list of items borrowing nothing.
+///
+/// Collecting a vector's own iterator into a vector of an item with the same layout reuses its
+/// memory, and as the iterator yields nothing, no item outlives what it borrowed.
+fn forget_lifetime<T, U>(mut items: Vec<T>) -> Vec<U> {
+    items.clear();
+    items.into_iter().map(|_| unreachable!()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::{Line, Span};
+
+    #[test]
+    fn recycled_memory_is_reused() {
+        let mut pool = LinePool::new();
+        let content = "x".to_string();
+        let mut lines = pool.lines();
+        lines.reserve(8);
+        let mut line = pool.line();
+        line.spans
+            .extend([Span::raw(content.as_str()), Span::raw("y")]);
+        let span_capacity = line.spans.capacity();
+        lines.push(line);
+        let line_capacity = lines.capacity();
+        pool.recycle(lines);
+        drop(content);
+        assert_eq!(1, pool.len());
+        let line: Line<'static> = pool.line();
+        assert!(line.spans.is_empty());
+        assert_eq!(span_capacity, line.spans.capacity());
+        let lines: Vec<Line<'static>> = pool.lines();
+        assert!(lines.is_empty());
+        assert_eq!(line_capacity, lines.capacity());
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::colour::{hsl_to_rgb, rgb_to_hsl};
+
+/// Adjustments that make a theme look as intended on a particular kind of terminal, applied by a
+/// [StyleTranslator](crate::StyleTranslator) when it translates the theme's colours, so the same
+/// theme looks right across terminal emulators.
+///
+/// Terminals differ in how they render the same colours, e.g. the Linux console renders dark
+/// themes much dimmer than a modern emulator, and terminals limited to the 256 indexed colours
+/// have no levels between black and `#5f5f5f`, so dark foregrounds snap to black. A profile
+/// counters this with:
+/// - a `gamma` applied to every channel of every colour, where values above `1.0` brighten the
+///   midtones,
+/// - a `minimum_lightness` foregrounds are raised to, keeping dim text readable,
+/// - a `background_blend` backgrounds are mixed towards black by, so brightened backgrounds still
+///   recede behind the text.
+///
+/// Profiles can be chosen by [name](TerminalProfile::named), e.g. from an application's config,
+/// or [detected](TerminalProfile::from_env) from the `TERM` and `COLORTERM` environment
+/// variables. The default profile leaves colours unchanged, and only RGB colours are adjusted.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+/// use syntect_tui::{StyleTranslator, TerminalProfile};
+///
+/// let profile = TerminalProfile::named("linux-console").unwrap();
+/// assert_eq!(Some(profile), TerminalProfile::detect(Some("linux"), None));
+/// let translator = StyleTranslator::new().terminal_profile(profile);
+/// let dim = syntect::highlighting::Color { r: 40, g: 40, b: 60, a: 255 };
+/// let style = syntect::highlighting::Style { foreground: dim, ..Default::default() };
+/// assert_ne!(Some(Color::Rgb(40, 40, 60)), translator.translate_style(style).unwrap().fg);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TerminalProfile {
+    /// The gamma applied to every channel, where `1.0` leaves colours unchanged, values above it
+    /// brighten them and values below it darken them.
+    pub gamma: f32,
+    /// The HSL lightness, from `0.0` to `1.0`, below which foregrounds are lightened to it.
+    pub minimum_lightness: f32,
+    /// How far backgrounds are mixed towards black, from `0.0` to `1.0`.
+    pub background_blend: f32,
+}
+
+impl Default for TerminalProfile {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+impl TerminalProfile {
+    /// The profile that leaves colours unchanged, for terminals that render RGB colours
+    /// faithfully.
+    pub const NONE: Self = Self {
+        gamma: 1.0,
+        minimum_lightness: 0.0,
+        background_blend: 0.0,
+    };
+
+    /// The profile for the Linux console, which renders colours dim and washes out dark
+    /// backgrounds.
+    pub const LINUX_CONSOLE: Self = Self {
+        gamma: 1.3,
+        minimum_lightness: 0.5,
+        background_blend: 0.5,
+    };
+
+    /// The profile for terminals limited to the 256 indexed colours, whose darkest shades above
+    /// black are too far apart for dark foregrounds.
+    pub const INDEXED_256: Self = Self {
+        gamma: 1.1,
+        minimum_lightness: 0.35,
+        background_blend: 0.0,
+    };
+
+    /// The names of the built-in profiles, as accepted by [TerminalProfile::named].
+    pub const NAMES: [&'static str; 3] = ["none", "linux-console", "256-colour"];
+
+    /// Returns the built-in profile named `name`, which is one of [TerminalProfile::NAMES], or
+    /// `None` if there is no such profile.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::NONE),
+            "linux-console" => Some(Self::LINUX_CONSOLE),
+            "256-colour" => Some(Self::INDEXED_256),
+            _ => None,
+        }
+    }
+
+    /// Detects the profile for the terminal from the `TERM` and `COLORTERM` environment
+    /// variables, or `None` if they do not identify a terminal with a built-in profile.
+    pub fn from_env() -> Option<Self> {
+        Self::detect(
+            std::env::var("TERM").ok().as_deref(),
+            std::env::var("COLORTERM").ok().as_deref(),
+        )
+    }
+
+    /// Detects the profile for a terminal with the given `TERM` and `COLORTERM` values.
+    ///
+    /// Terminals that advertise RGB colours in `COLORTERM` get [TerminalProfile::NONE], the Linux
+    /// console gets [TerminalProfile::LINUX_CONSOLE] and other terminals whose `TERM` ends in
+    /// `256color` get [TerminalProfile::INDEXED_256].
+    pub fn detect(term: Option<&str>, colorterm: Option<&str>) -> Option<Self> {
+        if colorterm.is_some_and(|colorterm| matches!(colorterm, "truecolor" | "24bit")) {
+            return Some(Self::NONE);
+        }
+        let term = term?;
+        if term == "linux" {
+            Some(Self::LINUX_CONSOLE)
+        } else if term.ends_with("256color") {
+            Some(Self::INDEXED_256)
+        } else {
+            None
+        }
+    }
+
+    /// Applies the profile to a foreground colour.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::Color;
+    /// use syntect_tui::TerminalProfile;
+    ///
+    /// let profile = TerminalProfile { minimum_lightness: 0.5, ..Default::default() };
+    /// assert_eq!(Color::Rgb(128, 128, 128), profile.apply_foreground(Color::Rgb(10, 10, 10)));
+    /// assert_eq!(Color::Black, profile.apply_foreground(Color::Black));
+    /// ```
+    pub fn apply_foreground(&self, colour: ratatui::style::Color) -> ratatui::style::Color {
+        match self.apply_gamma(colour) {
+            ratatui::style::Color::Rgb(r, g, b) if self.minimum_lightness > 0.0 => {
+                let (hue, saturation, lightness) = rgb_to_hsl(r, g, b);
+                if lightness >= self.minimum_lightness {
+                    return ratatui::style::Color::Rgb(r, g, b);
+                }
+                let (r, g, b) = hsl_to_rgb(hue, saturation, self.minimum_lightness.min(1.0));
+                ratatui::style::Color::Rgb(r, g, b)
+            }
+            other => other,
+        }
+    }
+
+    /// Applies the profile to a background colour.
+    pub fn apply_background(&self, colour: ratatui::style::Color) -> ratatui::style::Color {
+        match self.apply_gamma(colour) {
+            ratatui::style::Color::Rgb(r, g, b) if self.background_blend > 0.0 => {
+                let keep = 1.0 - self.background_blend.clamp(0.0, 1.0);
+                let channel = |channel: u8| (f32::from(channel) * keep).round() as u8;
+                ratatui::style::Color::Rgb(channel(r), channel(g), channel(b))
+            }
+            other => other,
+        }
+    }
+
+    fn apply_gamma(&self, colour: ratatui::style::Color) -> ratatui::style::Color {
+        match colour {
+            ratatui::style::Color::Rgb(r, g, b) if self.gamma != 1.0 && self.gamma > 0.0 => {
+                let channel = |channel: u8| {
+                    ((f32::from(channel) / 255.0).powf(1.0 / self.gamma) * 255.0).round() as u8
+                };
+                ratatui::style::Color::Rgb(channel(r), channel(g), channel(b))
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+    use rstest::*;
+
+    #[rstest]
+    #[case::true_colour(Some("xterm-256color"), Some("truecolor"), Some(TerminalProfile::NONE))]
+    #[case::direct(Some("linux"), Some("24bit"), Some(TerminalProfile::NONE))]
+    #[case::linux(Some("linux"), None, Some(TerminalProfile::LINUX_CONSOLE))]
+    #[case::indexed(Some("screen-256color"), Some(""), Some(TerminalProfile::INDEXED_256))]
+    #[case::unknown(Some("xterm"), None, None)]
+    #[case::unset(None, None, None)]
+    fn check_detect(
+        #[case] term: Option<&str>,
+        #[case] colorterm: Option<&str>,
+        #[case] expected: Option<TerminalProfile>,
+    ) {
+        assert_eq!(expected, TerminalProfile::detect(term, colorterm));
+    }
+
+    #[test]
+    fn names_every_profile() {
+        for name in TerminalProfile::NAMES {
+            assert!(TerminalProfile::named(name).is_some(), "{name}");
+        }
+        assert_eq!(None, TerminalProfile::named("vt100"));
+    }
+
+    #[test]
+    fn the_default_profile_changes_nothing() {
+        let profile = TerminalProfile::default();
+        for colour in [Color::Rgb(12, 34, 56), Color::Indexed(67), Color::Red] {
+            assert_eq!(colour, profile.apply_foreground(colour));
+            assert_eq!(colour, profile.apply_background(colour));
+        }
+    }
+
+    #[rstest]
+    #[case::brightens(2.0, Color::Rgb(64, 0, 255), Color::Rgb(128, 0, 255))]
+    #[case::darkens(0.5, Color::Rgb(128, 0, 255), Color::Rgb(64, 0, 255))]
+    #[case::ignores_named(2.0, Color::Blue, Color::Blue)]
+    fn check_gamma(#[case] gamma: f32, #[case] colour: Color, #[case] expected: Color) {
+        let profile = TerminalProfile {
+            gamma,
+            ..Default::default()
+        };
+        assert_eq!(expected, profile.apply_background(colour));
+    }
+
+    #[test]
+    fn blends_backgrounds_towards_black() {
+        let profile = TerminalProfile {
+            background_blend: 0.5,
+            minimum_lightness: 0.9,
+            ..Default::default()
+        };
+        assert_eq!(
+            Color::Rgb(22, 24, 30),
+            profile.apply_background(Color::Rgb(43, 48, 59))
+        );
+    }
+}
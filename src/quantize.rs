@@ -0,0 +1,349 @@
+use crate::{ColourSpace, HighlightedText, Palette};
+
+/// Snaps colours to the nearest entry of an application-provided set of colours.
+///
+/// Applications with a curated palette can use a `PaletteQuantizer` so that highlighted text only
+/// uses colours from that palette, either directly or via
+/// [StyleTranslator::quantize_colours](crate::StyleTranslator::quantize_colours). Named and
+/// indexed colours are compared using their RGB values in a [Palette](crate::Palette), and
+/// distance is measured in the quantizer's [ColourSpace](crate::ColourSpace).
+///
+/// Finding the nearest entry compares a colour with every entry, so the quantizer remembers the
+/// entry it found for each RGB value. Highlighted documents only use a few dozen distinct
+/// colours, so quantizing all their spans, e.g. with
+/// [quantize_text](PaletteQuantizer::quantize_text), costs about as much as quantizing those
+/// colours once.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+///
+/// let quantizer = syntect_tui::PaletteQuantizer::new([
+///     Color::Rgb(40, 40, 40),
+///     Color::Rgb(204, 36, 29),
+///     Color::Indexed(2),
+/// ]);
+/// assert_eq!(Color::Rgb(204, 36, 29), quantizer.quantize(Color::Rgb(255, 0, 0)));
+/// assert_eq!(Color::Indexed(2), quantizer.quantize(Color::Rgb(0, 200, 0)));
+/// assert_eq!(Color::Reset, quantizer.quantize(Color::Reset));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteQuantizer {
+    palette: Palette,
+    colours: Vec<(ratatui::style::Color, (u8, u8, u8))>,
+    colour_space: ColourSpace,
+    cache: QuantizeCache,
+}
+
+/// The entries a [PaletteQuantizer] has already found, keyed by the RGB value they were found
+/// for and the colour space distances were measured in.
+///
+/// Clones start with an empty cache, and caches are ignored when comparing quantizers. Entries
+/// are found without holding the lock, so threads sharing a quantizer only wait for each other
+/// while looking entries up or inserting them.
+#[derive(Default)]
+struct QuantizeCache(
+    std::sync::RwLock<std::collections::HashMap<QuantizeCacheKey, ratatui::style::Color>>,
+);
+
+type QuantizeCacheKey = ((u8, u8, u8), u8, u32);
+
+/// The number of entries a cache keeps before it starts over, as an image-like document could
+/// use far more colours than a theme.
+const QUANTIZE_CACHE_CAPACITY: usize = 4096;
+
+impl QuantizeCache {
+    fn key(rgb: (u8, u8, u8), colour_space: ColourSpace) -> QuantizeCacheKey {
+        match colour_space {
+            ColourSpace::Srgb => (rgb, 0, 0),
+            ColourSpace::Linear => (rgb, 1, 0),
+            ColourSpace::Gamma(gamma) => (rgb, 2, gamma.to_bits()),
+        }
+    }
+
+    fn get_or_insert_with<F>(&self, key: QuantizeCacheKey, find: F) -> ratatui::style::Color
+    where
+        F: FnOnce() -> ratatui::style::Color,
+    {
+        let cached = self
+            .0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&key)
+            .copied();
+        if let Some(colour) = cached {
+            return colour;
+        }
+        let colour = find();
+        let mut cache = self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if cache.len() >= QUANTIZE_CACHE_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(key, colour);
+        colour
+    }
+
+    fn clear(&mut self) {
+        self.0
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+}
+
+impl Clone for QuantizeCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl PartialEq for QuantizeCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Debug for QuantizeCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuantizeCache").finish_non_exhaustive()
+    }
+}
+
+impl PaletteQuantizer {
+    /// Creates a quantizer for `colours`, resolving named and indexed colours using the default
+    /// xterm [Palette](crate::Palette).
+    pub fn new<I>(colours: I) -> Self
+    where
+        I: IntoIterator<Item = ratatui::style::Color>,
+    {
+        Self::from_palette(colours, Palette::default())
+    }
+
+    /// Creates a quantizer for `colours`, resolving named and indexed colours using `palette`.
+    ///
+    /// `Color::Reset` has no RGB value, so it is ignored.
+    pub fn from_palette<I>(colours: I, palette: Palette) -> Self
+    where
+        I: IntoIterator<Item = ratatui::style::Color>,
+    {
+        let colours = colours
+            .into_iter()
+            .filter_map(|colour| palette.rgb(colour).map(|rgb| (colour, rgb)))
+            .collect();
+        Self {
+            palette,
+            colours,
+            colour_space: ColourSpace::default(),
+            cache: QuantizeCache::default(),
+        }
+    }
+
+    /// Creates a quantizer snapping colours to the entry whose RGB value is nearest, where several
+    /// entries may share a colour.
+    pub(crate) fn from_entries(colours: Vec<(ratatui::style::Color, (u8, u8, u8))>) -> Self {
+        Self {
+            palette: Palette::default(),
+            colours,
+            colour_space: ColourSpace::default(),
+            cache: QuantizeCache::default(),
+        }
+    }
+
+    /// Sets the colour space in which distances between colours are measured.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::Color;
+    /// use syntect_tui::{ColourSpace, PaletteQuantizer};
+    ///
+    /// let colours = [Color::Rgb(0, 0, 0), Color::Rgb(255, 255, 255)];
+    /// let grey = Color::Rgb(100, 100, 100);
+    /// assert_eq!(colours[0], PaletteQuantizer::new(colours).quantize(grey));
+    /// let linear = PaletteQuantizer::new(colours).colour_space(ColourSpace::Linear);
+    /// assert_eq!(colours[0], linear.quantize(grey));
+    /// assert_eq!(colours[1], linear.quantize(Color::Rgb(200, 200, 200)));
+    /// ```
+    pub fn colour_space(mut self, colour_space: ColourSpace) -> Self {
+        self.colour_space = colour_space;
+        self.cache.clear();
+        self
+    }
+
+    /// Returns the entry nearest to `colour`, or the first such entry if several are equally
+    /// near.
+    ///
+    /// `Color::Reset` and any colour quantized by an empty quantizer are returned unchanged.
+    pub fn quantize(&self, colour: ratatui::style::Color) -> ratatui::style::Color {
+        self.quantize_in(colour, self.colour_space)
+    }
+
+    /// Quantizes `colour`, measuring distances in `colour_space` rather than the quantizer's own
+    /// colour space.
+    pub(crate) fn quantize_in(
+        &self,
+        colour: ratatui::style::Color,
+        colour_space: ColourSpace,
+    ) -> ratatui::style::Color {
+        let Some(rgb) = self
+            .palette
+            .rgb(colour)
+            .filter(|_| !self.colours.is_empty())
+        else {
+            return colour;
+        };
+        self.cache
+            .get_or_insert_with(QuantizeCache::key(rgb, colour_space), || {
+                self.nearest(rgb, colour_space).unwrap_or(colour)
+            })
+    }
+
+    /// Quantizes the colours of every span of `line`, and of the line itself.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::{Color, Stylize};
+    /// use ratatui::text::{Line, Span};
+    ///
+    /// let quantizer = syntect_tui::PaletteQuantizer::new([Color::Black, Color::Red]);
+    /// let mut line = Line::from(vec![Span::raw("a").fg(Color::Rgb(200, 10, 10))]).bg(Color::Rgb(5, 5, 5));
+    /// quantizer.quantize_line(&mut line);
+    /// assert_eq!(Some(Color::Red), line.spans[0].style.fg);
+    /// assert_eq!(Some(Color::Black), line.style.bg);
+    /// ```
+    pub fn quantize_line(&self, line: &mut ratatui::text::Line<'_>) {
+        self.quantize_style(&mut line.style);
+        for span in &mut line.spans {
+            self.quantize_style(&mut span.style);
+        }
+    }
+
+    /// Quantizes the colours of every line of `text`, and its background, e.g. to show a document
+    /// highlighted in true colour in a 256 colour terminal without highlighting it again.
+    pub fn quantize_text(&self, text: &mut HighlightedText<'_>) {
+        text.background = text.background.map(|colour| self.quantize(colour));
+        for line in &mut text.lines {
+            self.quantize_line(line);
+        }
+    }
+
+    fn quantize_style(&self, style: &mut ratatui::style::Style) {
+        style.fg = style.fg.map(|colour| self.quantize(colour));
+        style.bg = style.bg.map(|colour| self.quantize(colour));
+        #[cfg(feature = "underline-color")]
+        {
+            style.underline_color = style.underline_color.map(|colour| self.quantize(colour));
+        }
+    }
+
+    /// Returns the entry nearest to `(r, g, b)` in `colour_space`, or `None` if the quantizer is
+    /// empty.
+    fn nearest(
+        &self,
+        (r, g, b): (u8, u8, u8),
+        colour_space: ColourSpace,
+    ) -> Option<ratatui::style::Color> {
+        let distance = |(er, eg, eb): (u8, u8, u8)| {
+            [(r, er), (g, eg), (b, eb)]
+                .into_iter()
+                .map(|(a, b)| (colour_space.to_linear(a) - colour_space.to_linear(b)).powi(2))
+                .sum::<f32>()
+        };
+        self.colours
+            .iter()
+            .map(|(entry, rgb)| (entry, distance(*rgb)))
+            .reduce(|nearest, candidate| {
+                if candidate.1 < nearest.1 {
+                    candidate
+                } else {
+                    nearest
+                }
+            })
+            .map(|(entry, _)| *entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::Color;
+
+    #[rstest]
+    #[case::exact(Color::Rgb(0, 0, 238), Color::Blue)]
+    #[case::nearest(Color::Rgb(250, 240, 10), Color::Rgb(255, 255, 0))]
+    #[case::named_input(Color::White, Color::Rgb(255, 255, 255))]
+    #[case::tie(Color::Rgb(0, 0, 119), Color::Black)]
+    #[case::reset(Color::Reset, Color::Reset)]
+    fn check_quantize(#[case] input: Color, #[case] expected: Color) {
+        let quantizer = PaletteQuantizer::new([
+            Color::Black,
+            Color::Reset,
+            Color::Blue,
+            Color::Rgb(255, 255, 0),
+            Color::Rgb(255, 255, 255),
+        ]);
+        assert_eq!(expected, quantizer.quantize(input));
+    }
+
+    #[test]
+    fn empty_quantizer_keeps_colours() {
+        let quantizer = PaletteQuantizer::new([]);
+        assert_eq!(Color::Rgb(1, 2, 3), quantizer.quantize(Color::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn named_entries_use_palette() {
+        let palette = Palette::default().with_colour(Color::Red, (1, 2, 3));
+        let quantizer = PaletteQuantizer::from_palette([Color::Red, Color::Green], palette);
+        assert_eq!(Color::Red, quantizer.quantize(Color::Rgb(0, 0, 0)));
+    }
+
+    #[test]
+    fn remembers_quantized_colours() {
+        let quantizer = PaletteQuantizer::new([Color::Black, Color::White]);
+        assert_eq!(Color::White, quantizer.quantize(Color::Rgb(200, 200, 200)));
+        assert_eq!(Color::White, quantizer.quantize(Color::Rgb(200, 200, 200)));
+        assert_eq!(Color::Black, quantizer.quantize(Color::Rgb(10, 10, 10)));
+        assert_eq!(
+            Color::Black,
+            quantizer.quantize_in(Color::Rgb(10, 10, 10), ColourSpace::Linear)
+        );
+        assert_eq!(3, quantizer.cache.len());
+        let quantizer = quantizer.colour_space(ColourSpace::Gamma(2.2));
+        assert_eq!(0, quantizer.cache.len());
+    }
+
+    #[test]
+    fn quantizes_text() {
+        use ratatui::style::Stylize;
+        use ratatui::text::{Line, Span};
+        let quantizer = PaletteQuantizer::new([Color::Black, Color::Red, Color::White]);
+        let mut text = HighlightedText::new(
+            vec![
+                Line::from(vec![
+                    Span::raw("a").fg(Color::Rgb(250, 250, 250)),
+                    Span::raw("b").red().on_black(),
+                ]),
+                Line::raw("c").bg(Color::Rgb(200, 0, 0)),
+            ],
+            Some(Color::Rgb(1, 1, 1)),
+        );
+        quantizer.quantize_text(&mut text);
+        assert_eq!(Some(Color::Black), text.background);
+        assert_eq!(Some(Color::White), text.lines[0].spans[0].style.fg);
+        assert_eq!(Some(Color::Red), text.lines[0].spans[1].style.fg);
+        assert_eq!(Some(Color::Red), text.lines[1].style.bg);
+    }
+}
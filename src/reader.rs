@@ -0,0 +1,347 @@
+use crate::SyntectTuiError;
+
+/// Highlights text as it is read, e.g. a log being tailed or a file too large to load, handing
+/// each line to a sink as soon as it has been converted.
+///
+/// The highlighter keeps its states between calls, so reading can carry on from where it
+/// stopped once more text is available. A line is only highlighted once its line ending has been
+/// read, and the text read after the last line ending is kept until more text completes it or
+/// [finish](ReaderHighlighter::finish) highlights it as the last line.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::ReaderHighlighter;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut highlighter = ReaderHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+/// let mut lines = Vec::new();
+/// let read = highlighter
+///     .highlight_from(&mut "let a = 1;\nlet b".as_bytes(), |_, line| lines.push(line))
+///     .unwrap();
+/// assert_eq!(1, read);
+/// highlighter
+///     .highlight_from(&mut " = 2;\nlet c = 3;".as_bytes(), |_, line| lines.push(line))
+///     .unwrap();
+/// lines.extend(highlighter.finish().unwrap());
+/// assert_eq!(3, lines.len());
+/// assert_eq!("let b = 2;", lines[1].to_string());
+/// ```
+pub struct ReaderHighlighter<'a> {
+    highlighter: syntect::easy::HighlightLines<'a>,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    fallback: crate::LongLineFallback,
+    translator: crate::StyleTranslator,
+    /// The text read after the last line ending.
+    pending: String,
+    lines: usize,
+}
+
+/// Highlights all the text of `reader` using `syntax` and `theme`, handing each line and its
+/// index to `sink` as it is read, and returns the number of lines.
+///
+/// See [ReaderHighlighter] for details.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = std::io::Cursor::new("fn main() {\n}");
+/// let mut last = None;
+/// let lines = syntect_tui::highlight_reader(
+///     source,
+///     syntax,
+///     &ps,
+///     &ts.themes["base16-ocean.dark"],
+///     |index, line| last = Some((index, line)),
+/// )
+/// .unwrap();
+/// assert_eq!(2, lines);
+/// assert_eq!(Some((1, "}".to_string())), last.map(|(i, line)| (i, line.to_string())));
+/// ```
+///
+/// # Errors
+/// Can return the same errors as [ReaderHighlighter::highlight_from] and
+/// [ReaderHighlighter::finish].
+pub fn highlight_reader<R, F>(
+    mut reader: R,
+    syntax: &syntect::parsing::SyntaxReference,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+    mut sink: F,
+) -> Result<usize, SyntectTuiError>
+where
+    R: std::io::BufRead,
+    F: FnMut(usize, ratatui::text::Line<'static>),
+{
+    let mut highlighter = ReaderHighlighter::new(syntax, syntax_set, theme);
+    highlighter.highlight_from(&mut reader, &mut sink)?;
+    let index = highlighter.lines_highlighted();
+    if let Some(line) = highlighter.finish()? {
+        sink(index, line);
+    }
+    Ok(highlighter.lines_highlighted())
+}
+
+impl<'a> ReaderHighlighter<'a> {
+    /// Creates a highlighter that highlights text using `syntax`, which must be one of the
+    /// syntaxes of `syntax_set`, and `theme`.
+    pub fn new(
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            highlighter: syntect::easy::HighlightLines::new(syntax, theme),
+            syntax_set,
+            theme,
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+            pending: String::new(),
+            lines: 0,
+        }
+    }
+
+    /// Returns the number of lines highlighted so far, which is also the index of the next line.
+    pub fn lines_highlighted(&self) -> usize {
+        self.lines
+    }
+
+    /// Reads and highlights the next line of `reader`, or returns `None` if `reader` has no
+    /// complete line left, in which case the text read is kept for the next call.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ReadFailed` if `reader` fails,
+    /// `SyntectTuiError::InvalidUtf8` if the text is not valid UTF-8, and the errors of
+    /// [highlight_line_to_line](crate::highlight_line_to_line), identifying the line they
+    /// occurred on.
+    pub fn next_line<R: std::io::BufRead>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<Option<ratatui::text::Line<'static>>, SyntectTuiError> {
+        reader
+            .read_line(&mut self.pending)
+            .map_err(|error| match error.kind() {
+                std::io::ErrorKind::InvalidData => SyntectTuiError::InvalidUtf8 {
+                    message: error.to_string(),
+                },
+                _ => SyntectTuiError::ReadFailed {
+                    message: error.to_string(),
+                },
+            })?;
+        if !self.pending.ends_with('\n') {
+            return Ok(None);
+        }
+        self.highlight_pending().map(Some)
+    }
+
+    /// Reads and highlights the complete lines of `reader`, handing each line and its index to
+    /// `sink`, and returns the number of lines read.
+    ///
+    /// # Errors
+    /// Can return the same errors as [next_line](ReaderHighlighter::next_line). The lines before
+    /// the error have already been handed to `sink`.
+    pub fn highlight_from<R, F>(
+        &mut self,
+        reader: &mut R,
+        mut sink: F,
+    ) -> Result<usize, SyntectTuiError>
+    where
+        R: std::io::BufRead,
+        F: FnMut(usize, ratatui::text::Line<'static>),
+    {
+        let start = self.lines;
+        while let Some(line) = self.next_line(reader)? {
+            sink(self.lines - 1, line);
+        }
+        Ok(self.lines - start)
+    }
+
+    /// Highlights the text read after the last line ending as a line of its own, e.g. once the
+    /// whole file has been read, or returns `None` if there is none.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line they occurred on.
+    pub fn finish(&mut self) -> Result<Option<ratatui::text::Line<'static>>, SyntectTuiError> {
+        if self.pending.is_empty() {
+            return Ok(None);
+        }
+        self.highlight_pending().map(Some)
+    }
+
+    /// Highlights the text read since the last line and starts the next line.
+    fn highlight_pending(&mut self) -> Result<ratatui::text::Line<'static>, SyntectTuiError> {
+        let line = self
+            .fallback
+            .highlight_line(
+                &mut self.highlighter,
+                self.syntax_set,
+                self.theme,
+                &self.pending,
+                &self.translator,
+                None,
+            )
+            .map(crate::line::into_owned_line)
+            .map_err(|error| error.at_line(self.lines));
+        self.pending.clear();
+        self.lines += 1;
+        line
+    }
+}
+
+impl crate::LineConversion for ReaderHighlighter<'_> {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+impl std::fmt::Debug for ReaderHighlighter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReaderHighlighter")
+            .field("pending", &self.pending)
+            .field("lines", &self.lines)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LineConversion;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\n*/\nlet c = \"\nd\";\nlet e = 5;";
+
+    /// Reads its text in chunks of a few bytes, like a file being written to.
+    struct Chunked<'s> {
+        chunks: Vec<&'s [u8]>,
+    }
+
+    impl std::io::Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let Some(chunk) = self.chunks.first_mut() else {
+                return Ok(0);
+            };
+            let read = chunk.len().min(buf.len());
+            buf[..read].copy_from_slice(&chunk[..read]);
+            *chunk = &chunk[read..];
+            if chunk.is_empty() {
+                self.chunks.remove(0);
+            }
+            Ok(read)
+        }
+    }
+
+    #[rstest]
+    #[case::whole(100)]
+    #[case::split_lines(3)]
+    #[case::bytes(1)]
+    fn matches_sequential_highlighting(#[case] chunk_bytes: usize) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(SOURCE)
+            .map(|line| crate::highlight_line_to_line(&mut sequential, &ps, theme, line).unwrap())
+            .collect::<Vec<_>>();
+        let mut highlighter = ReaderHighlighter::new(syntax, &ps, theme);
+        let mut lines = Vec::new();
+        // Each chunk is read as if the file ended after it, so lines are completed across calls.
+        for chunk in SOURCE.as_bytes().chunks(chunk_bytes) {
+            let mut reader = std::io::BufReader::new(Chunked {
+                chunks: vec![chunk],
+            });
+            highlighter
+                .highlight_from(&mut reader, |index, line| {
+                    assert_eq!(lines.len(), index);
+                    lines.push(line);
+                })
+                .unwrap();
+        }
+        assert_eq!(6, highlighter.lines_highlighted());
+        lines.extend(highlighter.finish().unwrap());
+        assert_eq!(None, highlighter.finish().unwrap());
+        assert_eq!(expected, lines);
+    }
+
+    #[test]
+    fn converts_with_translator_and_fallback() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter = ReaderHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"])
+            .long_lines(crate::LongLineFallback::new().max_line_bytes(8))
+            .translator(crate::StyleTranslator::new().plain(true));
+        let mut lines = Vec::new();
+        highlighter
+            .highlight_from(&mut SOURCE.as_bytes(), |_, line| lines.push(line))
+            .unwrap();
+        assert_eq!(1, lines[0].spans.len());
+        assert!(lines.iter().all(|line| {
+            line.style == ratatui::style::Style::default()
+                && line
+                    .spans
+                    .iter()
+                    .all(|span| span.style == ratatui::style::Style::default())
+        }));
+    }
+
+    #[test]
+    fn invalid_utf8_is_an_error() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let result = highlight_reader(
+            &b"let a = 1;\n\xff\n"[..],
+            syntax,
+            &ps,
+            &ts.themes["base16-ocean.dark"],
+            |_, _| {},
+        );
+        assert!(matches!(result, Err(SyntectTuiError::InvalidUtf8 { .. })));
+    }
+
+    #[test]
+    fn read_errors_are_returned() {
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disconnected"))
+            }
+        }
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let result = highlight_reader(
+            std::io::BufReader::new(Failing),
+            syntax,
+            &ps,
+            &ts.themes["base16-ocean.dark"],
+            |_, _| {},
+        );
+        assert_eq!(
+            Err(SyntectTuiError::ReadFailed {
+                message: "disconnected".to_string()
+            }),
+            result
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{CompiledTheme, StyleTranslator, SyntectTuiError};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+/// A theme loaded by a [ThemeReloader], along with its style table.
+#[derive(Clone, Debug)]
+pub struct LoadedTheme {
+    /// The theme, e.g. to highlight with.
+    pub theme: Arc<syntect::highlighting::Theme>,
+    /// The theme's styles, translated by the reloader's translator.
+    pub compiled: Arc<CompiledTheme>,
+}
+
+/// A change to the themes of a [ThemeReloader].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ThemeChange {
+    /// The theme with this name was loaded again, or added.
+    Reloaded(String),
+    /// The theme with this name was deleted.
+    Removed(String),
+}
+
+/// What the watcher thread found when a theme file changed.
+enum Reload {
+    Loaded(String, Result<LoadedTheme, SyntectTuiError>),
+    Removed(String),
+}
+
+/// Watches `.tmTheme` files and loads them again when they change, e.g. so that users editing
+/// their own colour scheme see their changes in a running TUI.
+///
+/// A reloader watches a single theme file or a directory of them, names themes after their file
+/// stems, like
+/// [ThemeSet::load_from_folder](https://docs.rs/syntect/latest/syntect/highlighting/struct.ThemeSet.html#method.load_from_folder),
+/// and keeps each loaded theme along with its [CompiledTheme]. Changed themes are loaded and
+/// compiled on the watcher's thread, and take effect when the application
+/// [polls](ThemeReloader::poll) the reloader, e.g. each time it draws, which reports which themes
+/// changed so it can invalidate what it highlighted with them.
+///
+/// # Examples
+/// ```no_run
+/// use syntect_tui::{StyleTranslator, ThemeChange, ThemeReloader};
+///
+/// let mut themes = ThemeReloader::watch("themes/mine.tmTheme", StyleTranslator::new()).unwrap();
+/// loop {
+///     for change in themes.poll() {
+///         match change {
+///             Ok(ThemeChange::Reloaded(name)) => println!("Re-highlighting with {name}"),
+///             Ok(ThemeChange::Removed(name)) => println!("{name} was deleted"),
+///             Err(error) => println!("{error}"),
+///         }
+///     }
+///     let theme = &themes.theme("mine").unwrap().theme;
+///     // Draw the frame using `theme`.
+/// #   break;
+/// }
+/// ```
+pub struct ThemeReloader {
+    themes: HashMap<String, LoadedTheme>,
+    receiver: Receiver<Reload>,
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl ThemeReloader {
+    /// Loads the theme file, or the theme files of the directory, at `path`, and starts watching
+    /// them, compiling the themes with `translator`.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::FileRead` if `path` cannot be watched, and
+    /// `SyntectTuiError::ThemeLoading` if any of its themes cannot be loaded.
+    pub fn watch(
+        path: impl AsRef<Path>,
+        translator: StyleTranslator,
+    ) -> Result<Self, SyntectTuiError> {
+        let path = path.as_ref().to_path_buf();
+        let watch_error = |message: String| SyntectTuiError::FileRead {
+            path: path.display().to_string(),
+            message,
+        };
+        // Editors often save by replacing files, which ends a watch on the file itself, so a
+        // single file is watched through its directory.
+        let (dir, file) = match path.is_dir() {
+            true => (path.clone(), None),
+            false => {
+                let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+                (
+                    dir.unwrap_or(Path::new(".")).to_path_buf(),
+                    Some(path.clone()),
+                )
+            }
+        };
+        let paths = match &file {
+            Some(file) => vec![file.clone()],
+            None => std::fs::read_dir(&dir)
+                .map_err(|error| watch_error(error.to_string()))?
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| is_theme_file(path))
+                .collect(),
+        };
+        let themes = paths
+            .iter()
+            .map(|path| Ok((theme_name(path), load(path, &translator)?)))
+            .collect::<Result<_, SyntectTuiError>>()?;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if event.kind.is_access() {
+                    return;
+                }
+                for changed in event.paths {
+                    let watched = match &file {
+                        Some(file) => changed.file_name() == file.file_name(),
+                        None => is_theme_file(&changed),
+                    };
+                    if !watched {
+                        continue;
+                    }
+                    let name = theme_name(&changed);
+                    let reload = match changed.exists() {
+                        true => Reload::Loaded(name, load(&changed, &translator)),
+                        false => Reload::Removed(name),
+                    };
+                    let _ = sender.send(reload);
+                }
+            })
+            .map_err(|error| watch_error(error.to_string()))?;
+        notify::Watcher::watch(&mut watcher, &dir, notify::RecursiveMode::NonRecursive)
+            .map_err(|error| watch_error(error.to_string()))?;
+        Ok(Self {
+            themes,
+            receiver,
+            _watcher: watcher,
+        })
+    }
+
+    /// Returns the loaded themes, by name.
+    pub fn themes(&self) -> &HashMap<String, LoadedTheme> {
+        &self.themes
+    }
+
+    /// Returns the loaded theme named `name`, if there is one.
+    pub fn theme(&self, name: &str) -> Option<&LoadedTheme> {
+        self.themes.get(name)
+    }
+
+    /// Applies the changes to theme files since the last call, returning each theme that
+    /// changed once, along with an error for each theme that could not be loaded, which keeps
+    /// the version loaded before.
+    pub fn poll(&mut self) -> Vec<Result<ThemeChange, SyntectTuiError>> {
+        let mut reloads: Vec<Reload> = Vec::new();
+        for reload in self.receiver.try_iter() {
+            let name = match &reload {
+                Reload::Loaded(name, _) | Reload::Removed(name) => name.clone(),
+            };
+            reloads.retain(|earlier| match earlier {
+                Reload::Loaded(earlier, _) | Reload::Removed(earlier) => *earlier != name,
+            });
+            reloads.push(reload);
+        }
+        reloads
+            .into_iter()
+            .map(|reload| match reload {
+                Reload::Loaded(name, loaded) => {
+                    self.themes.insert(name.clone(), loaded?);
+                    Ok(ThemeChange::Reloaded(name))
+                }
+                Reload::Removed(name) => {
+                    self.themes.remove(&name);
+                    Ok(ThemeChange::Removed(name))
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Debug for ThemeReloader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ThemeReloader")
+            .field("themes", &self.themes)
+            .finish_non_exhaustive()
+    }
+}
+
+fn is_theme_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("tmTheme"))
+}
+
+fn theme_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Loads and compiles the theme at `path`.
+fn load(path: &Path, translator: &StyleTranslator) -> Result<LoadedTheme, SyntectTuiError> {
+    let theme = syntect::highlighting::ThemeSet::get_theme(path).map_err(|error| {
+        SyntectTuiError::ThemeLoading {
+            message: format!("{} ({error})", path.display()),
+        }
+    })?;
+    let compiled = CompiledTheme::new(&theme, translator.clone())?;
+    Ok(LoadedTheme {
+        theme: Arc::new(theme),
+        compiled: Arc::new(compiled),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn theme_file(background: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+<key>name</key><string>Mine</string>
+<key>settings</key><array><dict><key>settings</key><dict>
+<key>background</key><string>{background}</string>
+<key>foreground</key><string>#C0C5CE</string>
+</dict></dict></array>
+</dict></plist>"#
+        )
+    }
+
+    /// Replaces the file at `path` at once, like editors do, so the watcher never sees it
+    /// half-written.
+    fn replace(path: &Path, contents: &str) {
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, contents).unwrap();
+        std::fs::rename(temporary, path).unwrap();
+    }
+
+    /// Polls `reloader` until it reports a change, or fails after a few seconds.
+    fn wait_for_change(reloader: &mut ThemeReloader) -> Vec<Result<ThemeChange, SyntectTuiError>> {
+        let start = Instant::now();
+        loop {
+            let changes = reloader.poll();
+            if !changes.is_empty() || start.elapsed() > Duration::from_secs(5) {
+                return changes;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    #[test]
+    fn reloads_changed_themes() {
+        let dir = std::env::temp_dir().join("syntect-tui-reload");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mine.tmTheme");
+        std::fs::write(&path, theme_file("#2B303B")).unwrap();
+        let mut reloader = ThemeReloader::watch(&dir, StyleTranslator::new()).unwrap();
+        let background =
+            |reloader: &ThemeReloader| reloader.theme("mine").unwrap().compiled.background();
+        assert_eq!(
+            Some(ratatui::style::Color::Rgb(43, 48, 59)),
+            background(&reloader)
+        );
+
+        replace(&path, &theme_file("#FFFFFF"));
+        let changes = wait_for_change(&mut reloader);
+        assert_eq!(vec![Ok(ThemeChange::Reloaded("mine".to_string()))], changes);
+        assert_eq!(
+            Some(ratatui::style::Color::Rgb(255, 255, 255)),
+            background(&reloader)
+        );
+
+        replace(&path, "not a theme");
+        let changes = wait_for_change(&mut reloader);
+        assert!(matches!(
+            changes[..],
+            [Err(SyntectTuiError::ThemeLoading { .. })]
+        ));
+        assert_eq!(
+            Some(ratatui::style::Color::Rgb(255, 255, 255)),
+            background(&reloader)
+        );
+
+        std::fs::remove_file(&path).unwrap();
+        let changes = wait_for_change(&mut reloader);
+        assert_eq!(vec![Ok(ThemeChange::Removed("mine".to_string()))], changes);
+        assert!(reloader.themes().is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{AlphaStrategy, StyleTranslator, SUPPORTED_FONT_STYLE};
+
+/// A piece of information dropped while converting a syntect style, see [LossReport].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Loss {
+    /// A translucent colour's alpha value was discarded, since terminals cannot render
+    /// translucency, because it is at least the translator's
+    /// [alpha threshold](StyleTranslator::alpha_threshold). Fully transparent colours are
+    /// translated into `None` and are not reported.
+    AlphaDiscarded {
+        /// The translucent colour.
+        colour: syntect::highlighting::Color,
+    },
+    /// A translucent colour below the translator's
+    /// [alpha threshold](StyleTranslator::alpha_threshold) was dropped by its
+    /// [AlphaStrategy]. Colours blended by `AlphaStrategy::Blend`, or dimmed by
+    /// `AlphaStrategy::Dim`, are not reported.
+    ColourDropped {
+        /// The translucent colour.
+        colour: syntect::highlighting::Color,
+    },
+    /// Font style bits that ratatui cannot represent were ignored.
+    FontStyleMasked {
+        /// The ignored bits.
+        bits: u8,
+    },
+    /// A colour was snapped to the nearest colour of the translator's
+    /// [PaletteQuantizer](crate::PaletteQuantizer).
+    ColourQuantized {
+        /// The colour before quantization.
+        from: ratatui::style::Color,
+        /// The colour after quantization.
+        to: ratatui::style::Color,
+    },
+}
+
+/// A [Loss] along with the index of the segment it occurred in, if it occurred while converting a
+/// line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LossEntry {
+    /// The index of the segment within its line.
+    pub segment: Option<usize>,
+    /// What was lost.
+    pub loss: Loss,
+}
+
+/// Records every place information was dropped during conversion, so applications can explain
+/// why a theme looks different in the terminal (e.g. "this theme uses translucent colours").
+///
+/// Reports are filled in by [StyleTranslator::translate_style_reporting] and
+/// [StyleTranslator::into_line_reporting], which convert lossily rather than failing, and can be
+/// reused across many conversions.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::{Color, FontStyle, Style};
+/// use syntect_tui::{Loss, LossReport, StyleTranslator};
+///
+/// let style = Style {
+///     foreground: Color { r: 255, g: 0, b: 0, a: 128 },
+///     background: Color { r: 0, g: 0, b: 0, a: 0 },
+///     font_style: FontStyle::BOLD,
+/// };
+/// let mut report = LossReport::default();
+/// let line = StyleTranslator::new().into_line_reporting(vec![(style, "fn")], &mut report);
+/// assert_eq!("fn", line.to_string());
+/// assert_eq!(
+///     vec![Loss::AlphaDiscarded { colour: style.foreground }],
+///     report.losses().collect::<Vec<_>>()
+/// );
+/// assert_eq!(Some(0), report.entries()[0].segment);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LossReport {
+    entries: Vec<LossEntry>,
+}
+
+impl LossReport {
+    /// Returns `true` if nothing has been lost.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns every recorded loss along with where it occurred.
+    pub fn entries(&self) -> &[LossEntry] {
+        &self.entries
+    }
+
+    /// Returns every recorded loss.
+    pub fn losses(&self) -> impl Iterator<Item = Loss> + '_ {
+        self.entries.iter().map(|entry| entry.loss)
+    }
+
+    /// Removes every recorded loss.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn record(&mut self, segment: Option<usize>, loss: Loss) {
+        self.entries.push(LossEntry { segment, loss });
+    }
+}
+
+impl StyleTranslator {
+    /// Converts a syntect style like [StyleTranslator::translate_style_lossy], recording anything
+    /// that was dropped in `report`.
+    pub fn translate_style_reporting(
+        &self,
+        syntect_style: syntect::highlighting::Style,
+        report: &mut LossReport,
+    ) -> ratatui::style::Style {
+        self.report_losses(&syntect_style, None, report);
+        self.translate_style_lossy(syntect_style)
+    }
+
+    /// Converts a highlighted line like [StyleTranslator::into_line], but never fails: unsupported
+    /// font style bits are ignored, and anything that was dropped is recorded in `report` along
+    /// with the index of its segment.
+    pub fn into_line_reporting<'a, I>(
+        &self,
+        segments: I,
+        report: &mut LossReport,
+    ) -> ratatui::text::Line<'a>
+    where
+        I: IntoIterator<Item = (syntect::highlighting::Style, &'a str)>,
+    {
+        let segments: Vec<_> = segments
+            .into_iter()
+            .enumerate()
+            .map(|(index, (style, content))| {
+                self.report_losses(&style, Some(index), report);
+                (crate::mask_font_style(style), content)
+            })
+            .collect();
+        // Masked font styles only contain supported bits, so conversion cannot fail.
+        self.into_line(segments).unwrap_or_default()
+    }
+
+    fn report_losses(
+        &self,
+        syntect_style: &syntect::highlighting::Style,
+        segment: Option<usize>,
+        report: &mut LossReport,
+    ) {
+        if self.is_plain() {
+            return;
+        }
+        let dimmed = self.alpha_strategy == AlphaStrategy::Dim;
+        for (colour, is_foreground) in [
+            (syntect_style.foreground, true),
+            (syntect_style.background, false),
+        ] {
+            if colour.a == 0 || colour.a == u8::MAX {
+                continue;
+            }
+            if colour.a >= self.alpha_threshold {
+                report.record(segment, Loss::AlphaDiscarded { colour });
+            } else if self.alpha_strategy == AlphaStrategy::Drop || (dimmed && !is_foreground) {
+                report.record(segment, Loss::ColourDropped { colour });
+            }
+        }
+        let bits = syntect_style.font_style.bits() & !SUPPORTED_FONT_STYLE.bits();
+        if bits != 0 {
+            report.record(segment, Loss::FontStyleMasked { bits });
+        }
+        if self.quantizer.is_some() {
+            let colours = self.translate_colours(syntect_style);
+            let quantized_fg = colours.fg.map(|colour| self.quantize(colour));
+            for (from, to) in [
+                (colours.fg, quantized_fg),
+                (colours.bg, colours.quantized_bg),
+            ] {
+                if let (Some(from), Some(to)) = (from, to) {
+                    if from != to {
+                        report.record(segment, Loss::ColourQuantized { from, to });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+    use rstest::*;
+    use syntect::highlighting::{Color as SyntectColour, FontStyle, Style as SyntectStyle};
+
+    fn fake_syntect_style(alpha: u8, font_style: FontStyle) -> SyntectStyle {
+        SyntectStyle {
+            foreground: SyntectColour {
+                r: 250,
+                g: 10,
+                b: 10,
+                a: alpha,
+            },
+            background: SyntectColour {
+                r: 0,
+                g: 0,
+                b: 0,
+                a: 0,
+            },
+            font_style,
+        }
+    }
+
+    #[test]
+    fn lossless_conversion_reports_nothing() {
+        let mut report = LossReport::default();
+        StyleTranslator::new()
+            .translate_style_reporting(fake_syntect_style(255, FontStyle::BOLD), &mut report);
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn reports_masked_font_styles_and_quantized_colours() {
+        let quantizer = crate::PaletteQuantizer::new([Color::Red]);
+        let translator = StyleTranslator::new().quantize_colours(quantizer);
+        let style = fake_syntect_style(255, unsafe { FontStyle::from_bits_unchecked(0b1001) });
+        let mut report = LossReport::default();
+        let line = translator.into_line_reporting(vec![(style, "fn")], &mut report);
+        assert_eq!(Some(Color::Red), line.spans[0].style.fg);
+        let expected = vec![
+            LossEntry {
+                segment: Some(0),
+                loss: Loss::FontStyleMasked { bits: 0b1000 },
+            },
+            LossEntry {
+                segment: Some(0),
+                loss: Loss::ColourQuantized {
+                    from: Color::Rgb(250, 10, 10),
+                    to: Color::Red,
+                },
+            },
+        ];
+        assert_eq!(expected, report.entries());
+    }
+
+    #[rstest]
+    #[case::kept(
+        StyleTranslator::new(),
+        Some(Loss::AlphaDiscarded { colour: fake_syntect_style(128, FontStyle::empty()).foreground })
+    )]
+    #[case::dropped(
+        StyleTranslator::new().alpha_threshold(200),
+        Some(Loss::ColourDropped { colour: fake_syntect_style(128, FontStyle::empty()).foreground })
+    )]
+    #[case::blended(
+        StyleTranslator::new()
+            .alpha_threshold(200)
+            .alpha_strategy(AlphaStrategy::Blend { backdrop: SyntectColour::BLACK }),
+        None
+    )]
+    #[case::dimmed(StyleTranslator::new().dim_translucent_foreground(200), None)]
+    fn check_translucent_foreground(
+        #[case] translator: StyleTranslator,
+        #[case] expected: Option<Loss>,
+    ) {
+        let mut report = LossReport::default();
+        translator.translate_style_reporting(fake_syntect_style(128, FontStyle::BOLD), &mut report);
+        assert_eq!(expected, report.losses().next());
+        assert!(report.entries().len() <= 1);
+    }
+
+    #[test]
+    fn dimmed_translucent_backgrounds_are_dropped() {
+        let colour = SyntectColour {
+            r: 1,
+            g: 2,
+            b: 3,
+            a: 128,
+        };
+        let style = SyntectStyle {
+            background: colour,
+            ..fake_syntect_style(255, FontStyle::empty())
+        };
+        let mut report = LossReport::default();
+        StyleTranslator::new()
+            .dim_translucent_foreground(200)
+            .translate_style_reporting(style, &mut report);
+        assert_eq!(
+            vec![Loss::ColourDropped { colour }],
+            report.losses().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn plain_translators_report_nothing() {
+        let mut report = LossReport::default();
+        StyleTranslator::new()
+            .plain(true)
+            .translate_style_reporting(fake_syntect_style(128, FontStyle::BOLD), &mut report);
+        assert!(report.is_empty());
+    }
+}
@@ -0,0 +1,444 @@
+//! Conversion from ratatui styles back into syntect styles.
+use crate::Palette;
+
+/// Converts a [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html)
+/// back into a
+/// [syntect::highlighting::Style](https://docs.rs/syntect/latest/syntect/highlighting/struct.Style.html),
+/// e.g. to feed styles generated in a TUI through syntect's HTML exporter.
+///
+/// ratatui styles can express more than syntect styles, so the conversion follows these rules:
+/// - Colours are converted using [translate_colour_back](crate::translate_colour_back) with the
+///   default xterm [Palette](crate::Palette).
+/// - Unset colours become fully transparent black. This mirrors
+///   [translate_colour](crate::translate_colour), which translates transparent colours into `None`.
+/// - `BOLD`, `ITALIC` and `UNDERLINED` in `add_modifier` become the equivalent font styles, while
+///   all other modifiers (and `sub_modifier`, which syntect cannot represent) are dropped.
+/// - `underline_color` is dropped, since syntect underlines always match the foreground.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use ratatui::style::{Color, Modifier, Style};
+///
+/// let input = Style::new().fg(Color::Rgb(255, 0, 0)).add_modifier(Modifier::BOLD | Modifier::DIM);
+/// let expected = syntect::highlighting::Style {
+///     foreground: syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 },
+///     background: syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 0 },
+///     font_style: syntect::highlighting::FontStyle::BOLD
+/// };
+/// assert_eq!(expected, syntect_tui::translate_style_back(input));
+/// ```
+pub fn translate_style_back(style: ratatui::style::Style) -> syntect::highlighting::Style {
+    ReverseOptions::default().translate_style_back(style)
+}
+
+/// Converts a [ratatui::text::Span](https://docs.rs/ratatui/latest/ratatui/text/struct.Span.html)
+/// back into a syntect style and its content, using [translate_style_back](crate::translate_style_back).
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+///
+/// let span = ratatui::text::Span::styled("fn", Style::new().fg(Color::Rgb(255, 0, 0)));
+/// let (style, content) = syntect_tui::from_span(&span);
+/// assert_eq!(syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 }, style.foreground);
+/// assert_eq!("fn", content);
+/// ```
+pub fn from_span(span: &ratatui::text::Span<'_>) -> (syntect::highlighting::Style, String) {
+    ReverseOptions::default().from_span(span)
+}
+
+/// Converts a [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html)
+/// back into syntect styled segments, as produced by
+/// [syntect::easy::HighlightLines::highlight_line](https://docs.rs/syntect/latest/syntect/easy/struct.HighlightLines.html#method.highlight_line).
+///
+/// The line's own style is patched with each span's style, as it is when ratatui renders the line.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use ratatui::text::{Line, Span};
+///
+/// let line = Line::from(vec![Span::raw("fn"), Span::raw(" main")])
+///     .style(Style::new().fg(Color::Rgb(255, 0, 0)));
+/// let segments = syntect_tui::from_line(&line);
+/// let red = syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 };
+/// assert!(segments.iter().all(|(style, _)| style.foreground == red));
+/// ```
+pub fn from_line(line: &ratatui::text::Line<'_>) -> Vec<(syntect::highlighting::Style, String)> {
+    ReverseOptions::default().from_line(line)
+}
+
+/// Configures how ratatui styles are converted back into syntect styles.
+///
+/// The free functions [translate_style_back](crate::translate_style_back),
+/// [from_span](crate::from_span) and [from_line](crate::from_line) use the default options: the
+/// default xterm [Palette](crate::Palette), with unset colours becoming fully transparent black.
+/// Consumers of syntect styles (e.g. its HTML exporter) usually expect fully-specified colours, so
+/// default foreground and background colours can be supplied to fill in unset and `Color::Reset`
+/// colours instead.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use syntect::highlighting::Color as SyntectColour;
+///
+/// let white = SyntectColour { r: 255, g: 255, b: 255, a: 255 };
+/// let black = SyntectColour { r: 0, g: 0, b: 0, a: 255 };
+/// let options = syntect_tui::ReverseOptions::new()
+///     .default_foreground(white)
+///     .default_background(black);
+/// let actual = options.translate_style_back(Style::new().bg(Color::Reset));
+/// assert_eq!(white, actual.foreground);
+/// assert_eq!(black, actual.background);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReverseOptions {
+    pub(crate) palette: Palette,
+    pub(crate) default_foreground: Option<syntect::highlighting::Color>,
+    pub(crate) default_background: Option<syntect::highlighting::Color>,
+}
+
+impl ReverseOptions {
+    /// Creates options equivalent to the free reverse conversion functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates options which fill unset colours with the theme's default foreground and
+    /// background colours, if it has them.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect::highlighting::ThemeSet;
+    ///
+    /// let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+    /// let options = syntect_tui::ReverseOptions::from_theme(theme);
+    /// let actual = options.translate_style_back(ratatui::style::Style::new());
+    /// assert_eq!(theme.settings.foreground, Some(actual.foreground));
+    /// assert_eq!(theme.settings.background, Some(actual.background));
+    /// ```
+    pub fn from_theme(theme: &syntect::highlighting::Theme) -> Self {
+        Self {
+            default_foreground: theme.settings.foreground,
+            default_background: theme.settings.background,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the palette used to resolve named and indexed colours.
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Sets the colour used for unset and `Color::Reset` foregrounds.
+    pub fn default_foreground(mut self, colour: syntect::highlighting::Color) -> Self {
+        self.default_foreground = Some(colour);
+        self
+    }
+
+    /// Sets the colour used for unset and `Color::Reset` backgrounds.
+    pub fn default_background(mut self, colour: syntect::highlighting::Color) -> Self {
+        self.default_background = Some(colour);
+        self
+    }
+
+    /// Converts a ratatui style back into a syntect style, following the rules of
+    /// [translate_style_back](crate::translate_style_back) but with these options.
+    pub fn translate_style_back(
+        &self,
+        style: ratatui::style::Style,
+    ) -> syntect::highlighting::Style {
+        syntect::highlighting::Style {
+            foreground: self.colour_back(style.fg, self.default_foreground),
+            background: self.colour_back(style.bg, self.default_background),
+            font_style: font_style_back(style.add_modifier),
+        }
+    }
+
+    /// Converts a ratatui span back into a syntect style and its content, following the rules of
+    /// [from_span](crate::from_span) but with these options.
+    pub fn from_span(
+        &self,
+        span: &ratatui::text::Span<'_>,
+    ) -> (syntect::highlighting::Style, String) {
+        (
+            self.translate_style_back(span.style),
+            span.content.to_string(),
+        )
+    }
+
+    /// Converts a ratatui line back into syntect styled segments, following the rules of
+    /// [from_line](crate::from_line) but with these options.
+    pub fn from_line(
+        &self,
+        line: &ratatui::text::Line<'_>,
+    ) -> Vec<(syntect::highlighting::Style, String)> {
+        line.spans
+            .iter()
+            .map(|span| {
+                (
+                    self.translate_style_back(line.style.patch(span.style)),
+                    span.content.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn colour_back(
+        &self,
+        colour: Option<ratatui::style::Color>,
+        default: Option<syntect::highlighting::Color>,
+    ) -> syntect::highlighting::Color {
+        match (colour, default) {
+            (None | Some(ratatui::style::Color::Reset), Some(default)) => default,
+            (colour, _) => colour_back(colour, &self.palette),
+        }
+    }
+}
+
+/// Converts a [ratatui::style::Modifier](https://docs.rs/ratatui/latest/ratatui/style/struct.Modifier.html)
+/// back into a
+/// [syntect::highlighting::FontStyle](https://docs.rs/syntect/latest/syntect/highlighting/struct.FontStyle.html).
+///
+/// `BOLD`, `ITALIC` and `UNDERLINED` map onto their syntect equivalents. Syntect has no way to
+/// represent any other modifier (e.g. `DIM`, `REVERSED`, `CROSSED_OUT` or `SLOW_BLINK`), so rather
+/// than silently dropping them this function returns an error. Callers who prefer to drop them can
+/// remove them from the modifier first, which is what [translate_style_back](crate::translate_style_back) does.
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// use ratatui::style::Modifier;
+/// use syntect::highlighting::FontStyle;
+///
+/// let actual = syntect_tui::translate_font_style_back(Modifier::BOLD | Modifier::ITALIC);
+/// assert_eq!(Ok(FontStyle::BOLD | FontStyle::ITALIC), actual);
+///
+/// let supported = Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED;
+/// let lossy = syntect_tui::translate_font_style_back((Modifier::BOLD | Modifier::DIM) & supported);
+/// assert_eq!(Ok(FontStyle::BOLD), lossy);
+/// ```
+/// # Errors
+/// Returns `SyntectTuiError::UnsupportedModifier` with the unsupported bits if `modifier` contains
+/// anything other than `BOLD`, `ITALIC` or `UNDERLINED`.
+pub fn translate_font_style_back(
+    modifier: ratatui::style::Modifier,
+) -> Result<syntect::highlighting::FontStyle, crate::SyntectTuiError> {
+    use ratatui::style::Modifier;
+    let unsupported = modifier - (Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED);
+    if !unsupported.is_empty() {
+        return Err(crate::SyntectTuiError::UnsupportedModifier {
+            bits: unsupported.bits(),
+        });
+    }
+    Ok(font_style_back(modifier))
+}
+
+const TRANSPARENT: syntect::highlighting::Color = syntect::highlighting::Color {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 0,
+};
+
+/// Converts a [ratatui::style::Color](https://docs.rs/ratatui/latest/ratatui/style/enum.Color.html)
+/// back into an opaque
+/// [syntect::highlighting::Color](https://docs.rs/syntect/latest/syntect/highlighting/struct.Color.html),
+/// resolving named and indexed colours using `palette`.
+///
+/// `Color::Reset` refers to the terminal's default colour, which is unknown, so it becomes fully
+/// transparent black.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+/// use syntect_tui::Palette;
+///
+/// let palette = Palette::default().with_colour(Color::Red, (220, 50, 47));
+/// assert_eq!(
+///     syntect::highlighting::Color { r: 220, g: 50, b: 47, a: 255 },
+///     syntect_tui::translate_colour_back(Color::Red, &palette)
+/// );
+/// assert_eq!(
+///     syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 0 },
+///     syntect_tui::translate_colour_back(Color::Reset, &palette)
+/// );
+/// ```
+pub fn translate_colour_back(
+    colour: ratatui::style::Color,
+    palette: &Palette,
+) -> syntect::highlighting::Color {
+    match palette.rgb(colour) {
+        Some((r, g, b)) => syntect::highlighting::Color { r, g, b, a: 255 },
+        None => TRANSPARENT,
+    }
+}
+
+pub(crate) fn colour_back(
+    colour: Option<ratatui::style::Color>,
+    palette: &Palette,
+) -> syntect::highlighting::Color {
+    colour.map_or(TRANSPARENT, |colour| translate_colour_back(colour, palette))
+}
+
+pub(crate) fn font_style_back(
+    modifier: ratatui::style::Modifier,
+) -> syntect::highlighting::FontStyle {
+    use ratatui::style::Modifier;
+    use syntect::highlighting::FontStyle;
+    [
+        (Modifier::BOLD, FontStyle::BOLD),
+        (Modifier::ITALIC, FontStyle::ITALIC),
+        (Modifier::UNDERLINED, FontStyle::UNDERLINE),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| modifier.contains(*flag))
+    .fold(FontStyle::empty(), |acc, (_, font_style)| acc | font_style)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::{Color, Modifier, Style, Stylize};
+    use syntect::highlighting::{Color as SyntectColour, FontStyle, Style as SyntectStyle};
+
+    #[rstest]
+    #[case::empty(Style::new(), SyntectStyle {
+        foreground: TRANSPARENT,
+        background: TRANSPARENT,
+        font_style: FontStyle::empty(),
+    })]
+    #[case::rgb(Style::new().fg(Color::Rgb(1, 2, 3)).bg(Color::Rgb(4, 5, 6)), SyntectStyle {
+        foreground: SyntectColour { r: 1, g: 2, b: 3, a: 255 },
+        background: SyntectColour { r: 4, g: 5, b: 6, a: 255 },
+        font_style: FontStyle::empty(),
+    })]
+    #[case::named(Style::new().fg(Color::Red).bg(Color::Reset), SyntectStyle {
+        foreground: SyntectColour { r: 205, g: 0, b: 0, a: 255 },
+        background: TRANSPARENT,
+        font_style: FontStyle::empty(),
+    })]
+    #[case::modifiers(
+        Style::new().add_modifier(
+            Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED | Modifier::REVERSED
+        ),
+        SyntectStyle {
+            foreground: TRANSPARENT,
+            background: TRANSPARENT,
+            font_style: FontStyle::BOLD | FontStyle::ITALIC | FontStyle::UNDERLINE,
+        }
+    )]
+    fn check_translate_style_back(#[case] input: Style, #[case] expected: SyntectStyle) {
+        assert_eq!(expected, translate_style_back(input));
+    }
+
+    #[rstest]
+    #[case::empty(Modifier::empty(), Ok(FontStyle::empty()))]
+    #[case::bold(Modifier::BOLD, Ok(FontStyle::BOLD))]
+    #[case::italic(Modifier::ITALIC, Ok(FontStyle::ITALIC))]
+    #[case::underlined(Modifier::UNDERLINED, Ok(FontStyle::UNDERLINE))]
+    #[case::all(
+        Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED,
+        Ok(FontStyle::BOLD | FontStyle::ITALIC | FontStyle::UNDERLINE)
+    )]
+    #[case::unsupported(
+        Modifier::BOLD | Modifier::DIM | Modifier::CROSSED_OUT,
+        Err(crate::SyntectTuiError::UnsupportedModifier {
+            bits: (Modifier::DIM | Modifier::CROSSED_OUT).bits()
+        })
+    )]
+    fn check_translate_font_style_back(
+        #[case] input: Modifier,
+        #[case] expected: Result<FontStyle, crate::SyntectTuiError>,
+    ) {
+        assert_eq!(expected, translate_font_style_back(input));
+    }
+
+    #[test]
+    fn from_line_patches_line_style() {
+        let line = ratatui::text::Line::from(vec![
+            ratatui::text::Span::raw("a"),
+            ratatui::text::Span::styled("b", Style::new().fg(Color::Rgb(1, 2, 3))),
+        ])
+        .style(Style::new().fg(Color::Rgb(4, 5, 6)).bold());
+        let actual = from_line(&line);
+        let expected = vec![
+            (
+                SyntectStyle {
+                    foreground: SyntectColour {
+                        r: 4,
+                        g: 5,
+                        b: 6,
+                        a: 255,
+                    },
+                    background: TRANSPARENT,
+                    font_style: FontStyle::BOLD,
+                },
+                String::from("a"),
+            ),
+            (
+                SyntectStyle {
+                    foreground: SyntectColour {
+                        r: 1,
+                        g: 2,
+                        b: 3,
+                        a: 255,
+                    },
+                    background: TRANSPARENT,
+                    font_style: FontStyle::BOLD,
+                },
+                String::from("b"),
+            ),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[rstest]
+    #[case::unset(None, SyntectColour { r: 1, g: 1, b: 1, a: 255 })]
+    #[case::reset(Some(Color::Reset), SyntectColour { r: 1, g: 1, b: 1, a: 255 })]
+    #[case::named(Some(Color::Red), SyntectColour { r: 220, g: 50, b: 47, a: 255 })]
+    #[case::rgb(Some(Color::Rgb(4, 5, 6)), SyntectColour { r: 4, g: 5, b: 6, a: 255 })]
+    fn check_reverse_options_colours(
+        #[case] colour: Option<Color>,
+        #[case] expected: SyntectColour,
+    ) {
+        let default = SyntectColour {
+            r: 1,
+            g: 1,
+            b: 1,
+            a: 255,
+        };
+        let options = ReverseOptions::new()
+            .palette(Palette::default().with_colour(Color::Red, (220, 50, 47)))
+            .default_foreground(default)
+            .default_background(default);
+        let style = Style {
+            fg: colour,
+            bg: colour,
+            ..Style::default()
+        };
+        let actual = options.translate_style_back(style);
+        assert_eq!(expected, actual.foreground);
+        assert_eq!(expected, actual.background);
+    }
+
+    #[test]
+    fn round_trips_translated_styles() {
+        let input = SyntectStyle {
+            foreground: SyntectColour {
+                r: 12,
+                g: 123,
+                b: 234,
+                a: 255,
+            },
+            background: TRANSPARENT,
+            font_style: FontStyle::BOLD | FontStyle::UNDERLINE,
+        };
+        let translated = crate::translate_style(input).unwrap();
+        assert_eq!(input, translate_style_back(translated));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::{HighlightedText, IncrementalHighlighter, StyleTranslator, SyntectTuiError};
+
+/// Highlights the whole of `rope` using `syntax` and `theme`, with the theme's background.
+///
+/// Lines are highlighted straight from the rope's chunks, and the converted lines borrow their
+/// contents from it, except for the few lines split across two chunks, which are copied into a
+/// single buffer to be highlighted and converted into owned lines.
+///
+/// # Examples
+/// ```
+/// use ropey::Rope;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let rope = Rope::from_str("fn main() {\n}\n");
+/// let text = syntect_tui::highlight_rope(&rope, syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .unwrap();
+/// assert_eq!(2, text.lines.len());
+/// assert_eq!("fn main() {", text.lines[0].to_string());
+/// ```
+///
+/// # Errors
+/// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+/// the line of `rope` they occurred on.
+pub fn highlight_rope<'r>(
+    rope: &'r ropey::Rope,
+    syntax: &syntect::parsing::SyntaxReference,
+    syntax_set: &syntect::parsing::SyntaxSet,
+    theme: &syntect::highlighting::Theme,
+) -> Result<HighlightedText<'r>, SyntectTuiError> {
+    StyleTranslator::default().highlight_rope(rope, syntax, syntax_set, theme)
+}
+
+impl StyleTranslator {
+    /// Highlights the whole of `rope` and converts it according to this translator's options.
+    ///
+    /// See [highlight_rope](crate::highlight_rope) for details.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight_rope](crate::highlight_rope).
+    pub fn highlight_rope<'r>(
+        &self,
+        rope: &'r ropey::Rope,
+        syntax: &syntect::parsing::SyntaxReference,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        theme: &syntect::highlighting::Theme,
+    ) -> Result<HighlightedText<'r>, SyntectTuiError> {
+        let mut highlighter = syntect::easy::HighlightLines::new(syntax, theme);
+        let mut buffer = String::new();
+        let mut lines = Vec::with_capacity(rope.len_lines());
+        for (index, line) in rope.lines().enumerate() {
+            if line.len_bytes() == 0 {
+                continue;
+            }
+            let converted = match line.as_str() {
+                Some(line) => {
+                    self.highlight_line_to_line(&mut highlighter, syntax_set, theme, line)
+                }
+                None => {
+                    buffer.clear();
+                    buffer.extend(line.chunks());
+                    self.highlight_line_to_line(&mut highlighter, syntax_set, theme, &buffer)
+                        .map(crate::line::into_owned_line)
+                }
+            };
+            lines.push(converted.map_err(|error| error.at_line(index))?);
+        }
+        Ok(HighlightedText::new(lines, self.theme_background(theme)))
+    }
+}
+
+/// The lines of a rope that an edit replaces, recorded before the edit is made, so that an
+/// [IncrementalHighlighter] can be updated once it has been, with
+/// [IncrementalHighlighter::apply_rope_edit].
+///
+/// Ropey can also break lines at characters other than `\n`, e.g. a lone `\r`, while the
+/// highlighter only breaks lines at `\n`, so the lines only match for text that only uses `\n` or
+/// `\r\n` line endings.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RopeEdit {
+    lines: std::ops::Range<usize>,
+    start: usize,
+}
+
+impl RopeEdit {
+    /// Records an edit of `rope` that will replace the characters in `chars`, which may be empty
+    /// for an insertion.
+    ///
+    /// # Panics
+    /// Panics if `chars` is out of the bounds of `rope`, like editing it would.
+    pub fn new(rope: &ropey::Rope, chars: std::ops::Range<usize>) -> Self {
+        Self {
+            lines: rope.char_to_line(chars.start)..rope.char_to_line(chars.end) + 1,
+            start: chars.start,
+        }
+    }
+}
+
+impl IncrementalHighlighter<'_> {
+    /// Replaces the whole document with the text of `rope` and highlights it.
+    ///
+    /// # Errors
+    /// Can return the same errors as [IncrementalHighlighter::set_text].
+    pub fn set_rope(&mut self, rope: &ropey::Rope) -> Result<(), SyntectTuiError> {
+        self.set_text(&rope.to_string())
+    }
+
+    /// Re-highlights the lines of `rope` touched by `edit`, once `inserted` characters have
+    /// replaced the characters it recorded, copying only those lines out of the rope.
+    ///
+    /// Returns the range of lines that were re-highlighted, like
+    /// [replace_lines](IncrementalHighlighter::replace_lines).
+    ///
+    /// # Examples
+    /// ```
+    /// use ropey::Rope;
+    /// use syntect::highlighting::ThemeSet;
+    /// use syntect::parsing::SyntaxSet;
+    /// use syntect_tui::{IncrementalHighlighter, RopeEdit};
+    ///
+    /// let ps = SyntaxSet::load_defaults_newlines();
+    /// let ts = ThemeSet::load_defaults();
+    /// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+    /// let mut highlighter = IncrementalHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+    /// let mut rope = Rope::from_str("let a = 1;\nlet b = 2;\n");
+    /// highlighter.set_rope(&rope).unwrap();
+    /// let edit = RopeEdit::new(&rope, 19..20);
+    /// rope.remove(19..20);
+    /// rope.insert(19, "20;\nlet c = 3");
+    /// assert_eq!(1..3, highlighter.apply_rope_edit(&rope, edit, 11).unwrap());
+    /// assert_eq!(rope.to_string(), highlighter.source().concat());
+    /// ```
+    ///
+    /// # Errors
+    /// Can return the same errors as [replace_lines](IncrementalHighlighter::replace_lines).
+    pub fn apply_rope_edit(
+        &mut self,
+        rope: &ropey::Rope,
+        edit: RopeEdit,
+        inserted: usize,
+    ) -> Result<std::ops::Range<usize>, SyntectTuiError> {
+        let first = edit.lines.start;
+        let last = rope.char_to_line(edit.start + inserted);
+        let end = if last + 1 < rope.len_lines() {
+            rope.line_to_char(last + 1)
+        } else {
+            rope.len_chars()
+        };
+        let text = rope.slice(rope.line_to_char(first)..end).to_string();
+        self.replace_lines(edit.lines, &text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\n*/\nlet c = \"\nd\";\nlet e = 5;";
+
+    #[rstest]
+    #[case::short(SOURCE.to_string())]
+    #[case::many_chunks(SOURCE.repeat(200))]
+    #[case::trailing_newline(SOURCE.to_string() + "\n")]
+    fn matches_highlighting_a_string(#[case] source: String) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut h = syntect::easy::HighlightLines::new(syntax, theme);
+        let expected = syntect::util::LinesWithEndings::from(&source)
+            .map(|line| crate::highlight_line_to_line(&mut h, &ps, theme, line).unwrap())
+            .collect();
+        let rope = ropey::Rope::from_str(&source);
+        assert_eq!(
+            HighlightedText::from_theme(expected, theme),
+            highlight_rope(&rope, syntax, &ps, theme).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case::typed(12..12, "x")]
+    #[case::opened_comment(0..0, "/*")]
+    #[case::joined_lines(10..12, "")]
+    #[case::split_line(4..5, "\n")]
+    #[case::appended(SOURCE.len()..SOURCE.len(), "\nlet f = 6;\n")]
+    #[case::removed_end(40..SOURCE.len(), "")]
+    #[case::replaced_all(0..SOURCE.len(), "fn main() {}\n")]
+    fn apply_rope_edit_matches_setting_the_text(
+        #[case] chars: std::ops::Range<usize>,
+        #[case] inserted: &str,
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut rope = ropey::Rope::from_str(SOURCE);
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme);
+        highlighter.set_rope(&rope).unwrap();
+        let edit = RopeEdit::new(&rope, chars.clone());
+        rope.remove(chars.clone());
+        rope.insert(chars.start, inserted);
+        highlighter
+            .apply_rope_edit(&rope, edit, inserted.chars().count())
+            .unwrap();
+        let mut fresh = IncrementalHighlighter::new(syntax, &ps, theme);
+        fresh.set_rope(&rope).unwrap();
+        assert_eq!(fresh.source(), highlighter.source());
+        assert_eq!(fresh.text(0..usize::MAX), highlighter.text(0..usize::MAX));
+    }
+}
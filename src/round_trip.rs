@@ -0,0 +1,168 @@
+use crate::reverse::{colour_back, font_style_back};
+use crate::Palette;
+
+/// A translated ratatui style that remembers the syntect style it was translated from.
+///
+/// Translation is lossy: alpha values are discarded and font style bits that ratatui cannot
+/// represent are dropped. Applications that transform styles in the TUI layer and later convert
+/// them back to syntect (e.g. for export) can use a `RoundTripStyle` to keep everything they did
+/// not change. When converting back, each field of the ratatui style that still matches its
+/// original translation is restored from the original syntect style, and only modified fields are
+/// converted using [translate_style_back](crate::translate_style_back)'s rules.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Modifier};
+/// use syntect::highlighting::{Color as SyntectColour, FontStyle, Style};
+///
+/// let original = Style {
+///     foreground: SyntectColour { r: 255, g: 0, b: 0, a: 128 },
+///     background: SyntectColour { r: 0, g: 0, b: 0, a: 0 },
+///     font_style: FontStyle::ITALIC
+/// };
+/// let mut style = syntect_tui::RoundTripStyle::new(original);
+/// style.style = style.style.bg(Color::Rgb(0, 0, 255)).add_modifier(Modifier::BOLD);
+/// let actual = style.to_syntect();
+/// assert_eq!(original.foreground, actual.foreground);
+/// assert_eq!(SyntectColour { r: 0, g: 0, b: 255, a: 255 }, actual.background);
+/// assert_eq!(FontStyle::BOLD | FontStyle::ITALIC, actual.font_style);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundTripStyle {
+    /// The ratatui style, which applications are free to modify.
+    pub style: ratatui::style::Style,
+    original: syntect::highlighting::Style,
+    translated: ratatui::style::Style,
+}
+
+impl RoundTripStyle {
+    /// Translates `original`, keeping it for converting back.
+    ///
+    /// Unlike [translate_style](crate::translate_style) this never fails: unsupported font style
+    /// bits are left out of the ratatui style, but are restored by [RoundTripStyle::to_syntect].
+    pub fn new(original: syntect::highlighting::Style) -> Self {
+        let translated = crate::translate_style_lossy(original);
+        Self {
+            style: translated,
+            original,
+            translated,
+        }
+    }
+
+    /// Returns the syntect style this style was translated from.
+    pub fn original(&self) -> syntect::highlighting::Style {
+        self.original
+    }
+
+    /// Converts the (possibly modified) ratatui style back into a syntect style, restoring
+    /// everything that has not been modified from the original.
+    pub fn to_syntect(&self) -> syntect::highlighting::Style {
+        use syntect::highlighting::FontStyle;
+        let palette = Palette::default();
+        let restore = |current, translated, original, colour| {
+            if current == translated {
+                original
+            } else {
+                colour_back(colour, &palette)
+            }
+        };
+        let font_style = if self.style.add_modifier == self.translated.add_modifier
+            && self.style.sub_modifier == self.translated.sub_modifier
+        {
+            self.original.font_style
+        } else {
+            let unsupported = self.original.font_style
+                - (FontStyle::BOLD | FontStyle::ITALIC | FontStyle::UNDERLINE);
+            unsupported | font_style_back(self.style.add_modifier - self.style.sub_modifier)
+        };
+        syntect::highlighting::Style {
+            foreground: restore(
+                self.style.fg,
+                self.translated.fg,
+                self.original.foreground,
+                self.style.fg,
+            ),
+            background: restore(
+                self.style.bg,
+                self.translated.bg,
+                self.original.background,
+                self.style.bg,
+            ),
+            font_style,
+        }
+    }
+}
+
+impl From<syntect::highlighting::Style> for RoundTripStyle {
+    fn from(value: syntect::highlighting::Style) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<RoundTripStyle> for ratatui::style::Style {
+    fn from(value: RoundTripStyle) -> Self {
+        value.style
+    }
+}
+
+impl From<RoundTripStyle> for syntect::highlighting::Style {
+    fn from(value: RoundTripStyle) -> Self {
+        value.to_syntect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier};
+    use syntect::highlighting::{Color as SyntectColour, FontStyle, Style as SyntectStyle};
+
+    fn fake_syntect_style(font_style: FontStyle) -> SyntectStyle {
+        SyntectStyle {
+            font_style,
+            foreground: SyntectColour {
+                r: 12,
+                g: 123,
+                b: 234,
+                a: 100,
+            },
+            background: SyntectColour {
+                r: 123,
+                g: 234,
+                b: 12,
+                a: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn unmodified_style_round_trips_exactly() {
+        let original = fake_syntect_style(unsafe { FontStyle::from_bits_unchecked(0b1001) });
+        let style = RoundTripStyle::new(original);
+        assert_eq!(Some(Color::Rgb(12, 123, 234)), style.style.fg);
+        assert_eq!(Modifier::BOLD, style.style.add_modifier);
+        assert_eq!(original, style.to_syntect());
+    }
+
+    #[test]
+    fn modified_fields_are_converted_back() {
+        let original = fake_syntect_style(unsafe { FontStyle::from_bits_unchecked(0b1001) });
+        let mut style = RoundTripStyle::new(original);
+        style.style = style
+            .style
+            .fg(Color::Rgb(1, 2, 3))
+            .remove_modifier(Modifier::BOLD)
+            .add_modifier(Modifier::ITALIC);
+        let expected = SyntectStyle {
+            foreground: SyntectColour {
+                r: 1,
+                g: 2,
+                b: 3,
+                a: 255,
+            },
+            background: original.background,
+            font_style: unsafe { FontStyle::from_bits_unchecked(0b1100) },
+        };
+        assert_eq!(expected, style.to_syntect());
+    }
+}
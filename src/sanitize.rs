@@ -0,0 +1,95 @@
+/// Determines how a [StyleTranslator](crate::StyleTranslator) handles control characters, such as
+/// ANSI escapes, NUL or BEL, in highlighted text.
+///
+/// Control characters are passed straight through to the terminal when rendered, so text that
+/// contains them can corrupt the display. Tabs and trailing line endings are never treated as
+/// control characters, since they are handled by [StyleTranslator::expand_tabs](crate::StyleTranslator::expand_tabs)
+/// and [into_line](crate::into_line) respectively.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlCharacterPolicy {
+    /// Control characters are left untouched. This is the default.
+    #[default]
+    Keep,
+    /// Control characters are removed.
+    Remove,
+    /// Every control character is replaced with the given character, e.g. `'\u{FFFD}'`.
+    Replace(char),
+    /// Control characters are escaped using caret notation, e.g. `^[` for ESC and `^@` for NUL.
+    /// C1 control characters are escaped with a `M-` prefix, as done by `cat -v`.
+    Escape,
+}
+
+impl ControlCharacterPolicy {
+    /// Applies this policy to `content`.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect_tui::ControlCharacterPolicy;
+    ///
+    /// let content = "\u{1b}[31mred\u{7}\n";
+    /// assert_eq!("[31mred\n", ControlCharacterPolicy::Remove.apply(content));
+    /// assert_eq!("^[[31mred^G\n", ControlCharacterPolicy::Escape.apply(content));
+    /// ```
+    pub fn apply<'a>(&self, content: &'a str) -> std::borrow::Cow<'a, str> {
+        let (body, line_ending) = split_line_ending(content);
+        if *self == Self::Keep || !body.chars().any(is_sanitized) {
+            return content.into();
+        }
+        let mut sanitized = String::with_capacity(content.len());
+        for character in body.chars() {
+            match self {
+                _ if !is_sanitized(character) => sanitized.push(character),
+                Self::Keep => sanitized.push(character),
+                Self::Remove => {}
+                Self::Replace(replacement) => sanitized.push(*replacement),
+                Self::Escape => push_escaped(&mut sanitized, character),
+            }
+        }
+        sanitized.push_str(line_ending);
+        sanitized.into()
+    }
+}
+
+fn is_sanitized(character: char) -> bool {
+    character.is_control() && character != '\t'
+}
+
+fn split_line_ending(content: &str) -> (&str, &str) {
+    let body = content
+        .strip_suffix("\r\n")
+        .or_else(|| content.strip_suffix('\n'))
+        .unwrap_or(content);
+    content.split_at(body.len())
+}
+
+fn push_escaped(escaped: &mut String, character: char) {
+    let code = u32::from(character);
+    if code >= 0x80 {
+        escaped.push_str("M-");
+    }
+    escaped.push('^');
+    escaped.push(char::from_u32((code & 0x7f) ^ 0x40).unwrap_or('?'));
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    #[rstest]
+    #[case::keep(ControlCharacterPolicy::Keep, "a\u{0}b\u{1b}c\u{7f}d\u{9b}\t\r\n")]
+    #[case::remove(ControlCharacterPolicy::Remove, "abcd\t\r\n")]
+    #[case::replace(ControlCharacterPolicy::Replace('?'), "a?b?c?d?\t\r\n")]
+    #[case::escape(ControlCharacterPolicy::Escape, "a^@b^[c^?dM-^[\t\r\n")]
+    fn check_apply(#[case] policy: ControlCharacterPolicy, #[case] expected: &str) {
+        let actual = policy.apply("a\u{0}b\u{1b}c\u{7f}d\u{9b}\t\r\n");
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn sanitizes_carriage_return_without_newline() {
+        let actual = ControlCharacterPolicy::Escape.apply("a\rb\r");
+        assert_eq!("a^Mb^M", actual);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{IncrementalHighlighter, SyntectTuiError};
+use std::time::{Duration, Instant};
+
+/// The lines of the highlighted document replaced by edits that have not been highlighted yet.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct PendingEdit {
+    /// The replaced lines, as lines of the highlighted document.
+    replaced: std::ops::Range<usize>,
+    /// The lines replacing them, with their line endings.
+    lines: Vec<String>,
+}
+
+/// Debounces the edits of an editor, so that an [IncrementalHighlighter] only re-highlights once
+/// typing pauses rather than on every keystroke.
+///
+/// Each [edit](HighlightScheduler::edit) is recorded straight away but not highlighted, and
+/// restarts the [debounce](HighlightScheduler::debounce) delay. Once the delay has passed,
+/// [poll](HighlightScheduler::poll) merges the recorded edits into a single edit and highlights
+/// it, returning the lines that are newly available to draw. Until then,
+/// [line](HighlightScheduler::line) returns the edited lines unstyled, and the other lines as they
+/// were highlighted before, so the document can still be drawn. Merging the edits means that,
+/// e.g., typing a block comment re-highlights the rest of the document once rather than on every
+/// keystroke, but edits far apart are merged with all the lines between them.
+///
+/// The time is passed to [edit](HighlightScheduler::edit) and [poll](HighlightScheduler::poll)
+/// rather than read by them, so the scheduler fits into any event loop, which can wait until
+/// [deadline](HighlightScheduler::deadline) for its next poll.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, Instant};
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::{HighlightScheduler, IncrementalHighlighter};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut highlighter = IncrementalHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+/// highlighter.set_text("let a = 1;\nlet b = 2;\n").unwrap();
+/// let mut scheduler = HighlightScheduler::new(highlighter).debounce(Duration::from_millis(100));
+/// let start = Instant::now();
+/// scheduler.edit(1..2, "let b = 20;\n", start);
+/// scheduler.edit(1..2, "let b = 200;\n", start + Duration::from_millis(50));
+/// assert_eq!(None, scheduler.poll(start + Duration::from_millis(100)).unwrap());
+/// assert_eq!("let b = 200;", scheduler.line(1).unwrap().to_string());
+/// let available = scheduler.poll(start + Duration::from_millis(150)).unwrap();
+/// assert_eq!(Some(1..2), available);
+/// assert!(scheduler.line(1).unwrap().spans.len() > 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct HighlightScheduler<'a> {
+    highlighter: IncrementalHighlighter<'a>,
+    delay: Duration,
+    pending: Option<PendingEdit>,
+    deadline: Option<Instant>,
+}
+
+impl<'a> HighlightScheduler<'a> {
+    /// Creates a scheduler for the document of `highlighter`, which highlights edits once none
+    /// have been made for 100ms.
+    pub fn new(highlighter: IncrementalHighlighter<'a>) -> Self {
+        Self {
+            highlighter,
+            delay: Duration::from_millis(100),
+            pending: None,
+            deadline: None,
+        }
+    }
+
+    /// Sets how long after the last edit the edits are highlighted.
+    pub fn debounce(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Records that the lines in `lines` were replaced with those of `text` at `now`, with the
+    /// same meaning as [IncrementalHighlighter::replace_lines], without highlighting them.
+    pub fn edit(&mut self, lines: std::ops::Range<usize>, text: &str, now: Instant) {
+        let count = self.line_count();
+        let end = lines.end.min(count);
+        let mut start = lines.start.min(end);
+        let mut inserted: Vec<String> = syntect::util::LinesWithEndings::from(text)
+            .map(str::to_string)
+            .collect();
+        if end < count {
+            if let Some(last) = inserted.last_mut().filter(|last| !last.ends_with('\n')) {
+                last.push('\n');
+            }
+        }
+        if start > 0
+            && start == count
+            && !inserted.is_empty()
+            && !self.source_line(start - 1).ends_with('\n')
+        {
+            start -= 1;
+            inserted.insert(0, format!("{}\n", self.source_line(start)));
+        }
+        let source = self.highlighter.source();
+        let pending = self.pending.get_or_insert_with(|| PendingEdit {
+            replaced: start..start,
+            lines: Vec::new(),
+        });
+        if start < pending.replaced.start {
+            pending
+                .lines
+                .splice(0..0, source[start..pending.replaced.start].iter().cloned());
+            pending.replaced.start = start;
+        }
+        let pending_end = pending.replaced.start + pending.lines.len();
+        if end > pending_end {
+            let extra = end - pending_end;
+            pending.lines.extend(
+                source[pending.replaced.end..pending.replaced.end + extra]
+                    .iter()
+                    .cloned(),
+            );
+            pending.replaced.end += extra;
+        }
+        let offset = pending.replaced.start;
+        pending.lines.splice(start - offset..end - offset, inserted);
+        self.deadline = Some(now + self.delay);
+    }
+
+    /// Highlights the recorded edits if the debounce delay has passed at `now`, returning the
+    /// range of lines that were re-highlighted, or `None` if there was nothing to highlight yet.
+    ///
+    /// # Errors
+    /// Can return the same errors as [flush](HighlightScheduler::flush).
+    pub fn poll(
+        &mut self,
+        now: Instant,
+    ) -> Result<Option<std::ops::Range<usize>>, SyntectTuiError> {
+        match self.deadline {
+            Some(deadline) if now >= deadline => self.flush(),
+            _ => Ok(None),
+        }
+    }
+
+    /// Highlights the recorded edits straight away, e.g. before saving, returning the range of
+    /// lines that were re-highlighted, or `None` if there were no edits.
+    ///
+    /// # Errors
+    /// Returns the errors of [IncrementalHighlighter::replace_lines]. The edits are kept, so the
+    /// document stays as edited.
+    pub fn flush(&mut self) -> Result<Option<std::ops::Range<usize>>, SyntectTuiError> {
+        let Some(pending) = &self.pending else {
+            return Ok(None);
+        };
+        let highlighted = self
+            .highlighter
+            .replace_lines(pending.replaced.clone(), &pending.lines.concat())?;
+        self.pending = None;
+        self.deadline = None;
+        Ok(Some(highlighted))
+    }
+
+    /// Returns when the recorded edits are due to be highlighted, or `None` if there are none.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns whether there are edits that have not been highlighted yet.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Returns the number of lines in the edited document.
+    pub fn line_count(&self) -> usize {
+        match &self.pending {
+            Some(pending) => {
+                self.highlighter.line_count() - pending.replaced.len() + pending.lines.len()
+            }
+            None => self.highlighter.line_count(),
+        }
+    }
+
+    /// Returns line `index` of the edited document, unstyled if it was edited since the last
+    /// highlighting, or `None` if the document has no such line.
+    pub fn line(&self, index: usize) -> Option<ratatui::text::Line<'_>> {
+        let Some(pending) = &self.pending else {
+            return self.highlighter.line(index);
+        };
+        if index < pending.replaced.start {
+            return self.highlighter.line(index);
+        }
+        match pending.lines.get(index - pending.replaced.start) {
+            Some(line) => Some(ratatui::text::Line::raw(
+                line.trim_end_matches(['\n', '\r']),
+            )),
+            None => self
+                .highlighter
+                .line(index - pending.lines.len() + pending.replaced.len()),
+        }
+    }
+
+    /// Returns the ranges of lines whose highlighting changed since they were last taken, as
+    /// described in [IncrementalHighlighter::take_changed_lines]. Edits are only included once
+    /// they have been highlighted.
+    pub fn take_changed_lines(&mut self) -> Vec<std::ops::Range<usize>> {
+        self.highlighter.take_changed_lines()
+    }
+
+    /// Returns the highlighter, whose document does not include the recorded edits until they
+    /// are highlighted.
+    pub fn highlighter(&self) -> &IncrementalHighlighter<'a> {
+        &self.highlighter
+    }
+
+    /// Returns the highlighter, discarding any edits that have not been highlighted.
+    pub fn into_inner(self) -> IncrementalHighlighter<'a> {
+        self.highlighter
+    }
+
+    /// Returns line `index` of the edited document with its line ending.
+    fn source_line(&self, index: usize) -> &str {
+        let source = self.highlighter.source();
+        let Some(pending) = &self.pending else {
+            return &source[index];
+        };
+        if index < pending.replaced.start {
+            return &source[index];
+        }
+        match pending.lines.get(index - pending.replaced.start) {
+            Some(line) => line,
+            None => &source[index - pending.lines.len() + pending.replaced.len()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\nlet b = \"x\";\nlet c = 3;\nlet d = 4;";
+
+    #[rstest]
+    #[case::typed_line(vec![(1..2, "let b = 2;\n"), (1..2, "let b = 20;\n")], 1..2)]
+    #[case::opened_comment(vec![(1..2, "/\n"), (1..2, "/*\n"), (1..2, "/* b\n")], 1..4)]
+    #[case::apart(vec![(0..1, "let a = 0;\n"), (3..4, "let d = 0;")], 0..4)]
+    #[case::backwards(vec![(2..3, "let c = 0;\n"), (0..1, "")], 0..2)]
+    #[case::inserted_then_removed(vec![(1..1, "let z = 0;\n"), (1..2, "")], 1..1)]
+    #[case::appended(vec![(4..4, "let e = 5;"), (5..5, "let f = 6;")], 3..6)]
+    #[case::inside_edit(vec![(1..3, "/*\nx\ny\n*/\n"), (2..3, "z\n")], 1..6)]
+    fn matches_applying_edits_directly(
+        #[case] edits: Vec<(std::ops::Range<usize>, &str)>,
+        #[case] expected: std::ops::Range<usize>,
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut direct = IncrementalHighlighter::new(syntax, &ps, theme);
+        direct.set_text(SOURCE).unwrap();
+        let mut scheduler = HighlightScheduler::new(direct.clone());
+        let now = Instant::now();
+        for (lines, text) in edits {
+            direct.replace_lines(lines.clone(), text).unwrap();
+            scheduler.edit(lines, text, now);
+            assert_eq!(direct.line_count(), scheduler.line_count());
+            for index in 0..direct.line_count() {
+                assert_eq!(
+                    direct.line(index).unwrap().to_string(),
+                    scheduler.line(index).unwrap().to_string()
+                );
+            }
+        }
+        assert_eq!(Some(expected), scheduler.flush().unwrap());
+        assert_eq!(direct.source(), scheduler.highlighter().source());
+        assert_eq!(
+            direct.text(0..usize::MAX),
+            scheduler.highlighter().text(0..usize::MAX)
+        );
+        assert_eq!(None, scheduler.flush().unwrap());
+    }
+
+    #[test]
+    fn edits_restart_the_delay() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let mut highlighter = IncrementalHighlighter::new(syntax, &ps, theme);
+        highlighter.set_text(SOURCE).unwrap();
+        let mut scheduler = HighlightScheduler::new(highlighter).debounce(Duration::from_secs(1));
+        let start = Instant::now();
+        assert_eq!(None, scheduler.deadline());
+        assert_eq!(None, scheduler.poll(start).unwrap());
+        scheduler.edit(0..1, "let a = 0;\n", start);
+        scheduler.edit(0..1, "let a = 10;\n", start + Duration::from_secs(1));
+        assert_eq!(Some(start + Duration::from_secs(2)), scheduler.deadline());
+        assert_eq!(
+            None,
+            scheduler.poll(start + Duration::from_secs(1)).unwrap()
+        );
+        assert!(scheduler.is_pending());
+        assert_eq!(
+            Some(0..1),
+            scheduler.poll(start + Duration::from_secs(2)).unwrap()
+        );
+        assert!(!scheduler.is_pending());
+        assert_eq!(None, scheduler.deadline());
+        assert_eq!("let a = 10;", scheduler.line(0).unwrap().to_string());
+        scheduler.take_changed_lines();
+        scheduler.edit(1..2, "let b = \"x\";\n", start);
+        scheduler.flush().unwrap();
+        assert!(scheduler.take_changed_lines().is_empty());
+    }
+}
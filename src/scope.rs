@@ -0,0 +1,293 @@
+use crate::{StyleTranslator, SyntectTuiError};
+
+/// A [ratatui::text::Span](https://docs.rs/ratatui/latest/ratatui/text/struct.Span.html) along
+/// with the syntect scopes that produced its style.
+///
+/// Scopes are useful wherever the flattened style is not enough, e.g. for tooltips, go-to-definition
+/// heuristics or application specific style overrides.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScopedSpan<'a> {
+    /// The scopes that applied to the span's content, outermost first.
+    pub scopes: syntect::parsing::ScopeStack,
+    /// The converted span.
+    pub span: ratatui::text::Span<'a>,
+}
+
+/// A style override applied by a [StyleTranslator] to spans whose scopes match a selector, see
+/// [StyleTranslator::override_scope].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeOverride {
+    /// Patches the theme-derived style with the given style, so only what it sets is overridden.
+    Patch(ratatui::style::Style),
+    /// Replaces the theme-derived style (and any less specific overrides) with the given style.
+    Replace(ratatui::style::Style),
+}
+
+/// Converts a line parsed using
+/// [syntect::parsing::ParseState::parse_line](https://docs.rs/syntect/latest/syntect/parsing/struct.ParseState.html#method.parse_line)
+/// into [ScopedSpan]s, styling each segment using the given
+/// [syntect::highlighting::Highlighter](https://docs.rs/syntect/latest/syntect/highlighting/struct.Highlighter.html).
+///
+/// `stack` carries the scopes that are open at the start of `line`, and is left holding the scopes
+/// that are open at its end, so the same stack should be passed in for every line of a document.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::{Highlighter, ThemeSet};
+/// use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let highlighter = Highlighter::new(&ts.themes["base16-ocean.dark"]);
+/// let mut parse_state = ParseState::new(syntax);
+/// let mut stack = ScopeStack::new();
+/// let line = "fn main() {}\n";
+/// let ops = parse_state.parse_line(line, &ps).unwrap();
+/// let spans = syntect_tui::into_scoped_spans(line, &ops, &mut stack, &highlighter).unwrap();
+/// let keyword = &spans[0];
+/// assert_eq!("fn", keyword.span.content);
+/// assert!(keyword.scopes.to_string().contains("storage.type.function"));
+/// ```
+///
+/// # Errors
+/// Can return `SyntectTuiError::UnknownFontStyle` if the theme uses an unsupported
+/// [FontStyle](https://docs.rs/syntect/latest/syntect/highlighting/struct.FontStyle.html), or
+/// `SyntectTuiError::InvalidScopeOperation` if `ops` cannot be applied to `stack`.
+pub fn into_scoped_spans<'a>(
+    line: &'a str,
+    ops: &[(usize, syntect::parsing::ScopeStackOp)],
+    stack: &mut syntect::parsing::ScopeStack,
+    highlighter: &syntect::highlighting::Highlighter,
+) -> Result<Vec<ScopedSpan<'a>>, SyntectTuiError> {
+    StyleTranslator::default().into_scoped_spans(line, ops, stack, highlighter)
+}
+
+impl StyleTranslator {
+    /// Overrides the style of spans whose scopes match `selector` when converting with
+    /// [StyleTranslator::into_scoped_spans], so applications can tweak a theme without editing it.
+    ///
+    /// Like theme rules, every matching override is applied in order of how specifically its
+    /// selector matches, with the most specific applied last. Overrides that match equally
+    /// specifically are applied in the order they were registered. Overrides are not applied in
+    /// [plain](StyleTranslator::plain) mode, so its spans stay unstyled.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::{Color, Modifier, Style};
+    /// use syntect::highlighting::{Highlighter, ThemeSet};
+    /// use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+    /// use syntect_tui::ScopeOverride;
+    ///
+    /// let translator = syntect_tui::StyleTranslator::new()
+    ///     .override_scope(
+    ///         "comment".parse().unwrap(),
+    ///         ScopeOverride::Replace(Style::new().fg(Color::DarkGray)),
+    ///     )
+    ///     .override_scope(
+    ///         "comment.line".parse().unwrap(),
+    ///         ScopeOverride::Patch(Style::new().add_modifier(Modifier::DIM)),
+    ///     );
+    /// let ps = SyntaxSet::load_defaults_newlines();
+    /// let ts = ThemeSet::load_defaults();
+    /// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+    /// let highlighter = Highlighter::new(&ts.themes["base16-ocean.dark"]);
+    /// let line = "// hi\n";
+    /// let ops = ParseState::new(syntax).parse_line(line, &ps).unwrap();
+    /// let spans = translator
+    ///     .into_scoped_spans(line, &ops, &mut ScopeStack::new(), &highlighter)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     Style::new().fg(Color::DarkGray).add_modifier(Modifier::DIM),
+    ///     spans[0].span.style
+    /// );
+    /// ```
+    pub fn override_scope(
+        mut self,
+        selector: syntect::highlighting::ScopeSelectors,
+        scope_override: ScopeOverride,
+    ) -> Self {
+        self.scope_overrides.push((selector, scope_override));
+        self
+    }
+
+    fn apply_scope_overrides(
+        &self,
+        style: ratatui::style::Style,
+        stack: &syntect::parsing::ScopeStack,
+    ) -> ratatui::style::Style {
+        let mut matches: Vec<_> = self
+            .scope_overrides
+            .iter()
+            .filter_map(|(selector, scope_override)| {
+                selector
+                    .does_match(stack.as_slice())
+                    .map(|power| (power, scope_override))
+            })
+            .collect();
+        matches.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        matches
+            .into_iter()
+            .fold(style, |style, (_, scope_override)| match scope_override {
+                ScopeOverride::Patch(patch) => style.patch(*patch),
+                ScopeOverride::Replace(replacement) => *replacement,
+            })
+    }
+
+    /// Converts a parsed line into [ScopedSpan]s according to this translator's options.
+    ///
+    /// See [into_scoped_spans](crate::into_scoped_spans) for details.
+    ///
+    /// # Errors
+    /// Can return the same errors as [into_scoped_spans](crate::into_scoped_spans).
+    pub fn into_scoped_spans<'a>(
+        &self,
+        line: &'a str,
+        ops: &[(usize, syntect::parsing::ScopeStackOp)],
+        stack: &mut syntect::parsing::ScopeStack,
+        highlighter: &syntect::highlighting::Highlighter,
+    ) -> Result<Vec<ScopedSpan<'a>>, SyntectTuiError> {
+        let mut spans = Vec::with_capacity(ops.len() + 1);
+        for (range, op) in syntect::easy::ScopeRangeIterator::new(ops, line) {
+            stack
+                .apply(op)
+                .map_err(|error| SyntectTuiError::InvalidScopeOperation {
+                    message: error.to_string(),
+                })?;
+            if range.is_empty() {
+                continue;
+            }
+            let style = highlighter.style_for_stack(stack.as_slice());
+            let mut span = self.into_span((style, &line[range]))?;
+            if !self.scope_overrides.is_empty() && !self.is_plain() {
+                span.style = self.apply_scope_overrides(span.style, stack);
+            }
+            spans.push(ScopedSpan {
+                scopes: stack.clone(),
+                span,
+            });
+        }
+        Ok(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Style, Stylize};
+    use rstest::*;
+    use syntect::highlighting::{Highlighter, ThemeSet};
+    use syntect::parsing::{ParseState, ScopeStack, ScopeStackOp, SyntaxSet};
+
+    #[test]
+    fn scoped_spans_match_highlighted_spans() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes["base16-ocean.dark"];
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let line = "pub struct Wow { hi: u64 }\n";
+        let expected: Vec<_> = syntect::easy::HighlightLines::new(syntax, theme)
+            .highlight_line(line, &ps)
+            .unwrap()
+            .into_iter()
+            .map(|segment| crate::into_span(segment).unwrap())
+            .collect();
+        let ops = ParseState::new(syntax).parse_line(line, &ps).unwrap();
+        let actual =
+            into_scoped_spans(line, &ops, &mut ScopeStack::new(), &Highlighter::new(theme))
+                .unwrap();
+        assert_eq!(
+            expected,
+            actual
+                .into_iter()
+                .map(|scoped| scoped.span)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case::unmatched(vec![("string", ScopeOverride::Replace(Style::new().red()))], None)]
+    #[case::registration_order(
+        vec![
+            ("comment", ScopeOverride::Replace(Style::new().red())),
+            ("comment", ScopeOverride::Patch(Style::new().bold())),
+        ],
+        Some(Style::new().red().bold())
+    )]
+    #[case::most_specific_last(
+        vec![
+            ("comment.line", ScopeOverride::Replace(Style::new().blue())),
+            ("comment", ScopeOverride::Replace(Style::new().red())),
+        ],
+        Some(Style::new().blue())
+    )]
+    #[case::replace_discards_patches(
+        vec![
+            ("comment.line", ScopeOverride::Replace(Style::new().blue())),
+            ("comment", ScopeOverride::Patch(Style::new().bold())),
+        ],
+        Some(Style::new().blue())
+    )]
+    fn check_override_scope(
+        #[case] overrides: Vec<(&str, ScopeOverride)>,
+        #[case] expected: Option<Style>,
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let highlighter = Highlighter::new(&ts.themes["base16-ocean.dark"]);
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let line = "// hi\n";
+        let ops = ParseState::new(syntax).parse_line(line, &ps).unwrap();
+        let translator = overrides.into_iter().fold(
+            StyleTranslator::new(),
+            |translator, (selector, scope_override)| {
+                translator.override_scope(selector.parse().unwrap(), scope_override)
+            },
+        );
+        let actual = translator
+            .into_scoped_spans(line, &ops, &mut ScopeStack::new(), &highlighter)
+            .unwrap();
+        let plain = into_scoped_spans(line, &ops, &mut ScopeStack::new(), &highlighter).unwrap();
+        assert_eq!(
+            expected.unwrap_or(plain[0].span.style),
+            actual[0].span.style
+        );
+    }
+
+    #[test]
+    fn plain_mode_ignores_overrides() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let highlighter = Highlighter::new(&ts.themes["base16-ocean.dark"]);
+        let syntax = ps.find_syntax_by_extension("rs").unwrap();
+        let line = "// hi\n";
+        let ops = ParseState::new(syntax).parse_line(line, &ps).unwrap();
+        let actual = StyleTranslator::new()
+            .override_scope(
+                "comment".parse().unwrap(),
+                ScopeOverride::Patch(Style::new().red().bold()),
+            )
+            .plain(true)
+            .into_scoped_spans(line, &ops, &mut ScopeStack::new(), &highlighter)
+            .unwrap();
+        assert!(actual
+            .iter()
+            .all(|scoped| scoped.span.style == Style::new()));
+    }
+
+    #[test]
+    fn invalid_scope_operation_is_reported() {
+        let ts = ThemeSet::load_defaults();
+        let ops = [(0, ScopeStackOp::Restore)];
+        let actual = into_scoped_spans(
+            "text",
+            &ops,
+            &mut ScopeStack::new(),
+            &Highlighter::new(&ts.themes["base16-ocean.dark"]),
+        );
+        assert!(matches!(
+            actual,
+            Err(SyntectTuiError::InvalidScopeOperation { .. })
+        ));
+    }
+}
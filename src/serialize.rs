@@ -0,0 +1,279 @@
+//! Serializable representations of converted lines, behind the `serde` feature.
+use crate::{InternedText, Palette};
+use serde::{Deserialize, Serialize};
+
+/// A serializable representation of a
+/// [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html).
+///
+/// Colours are stored as RGB triples, resolving named and indexed colours using the default xterm
+/// [Palette](crate::Palette), so that output can be rebuilt on machines with different terminal
+/// palettes. Unset and `Color::Reset` colours are both stored as `None`. Modifiers are stored by
+/// name (e.g. `"BOLD"`), and removed modifiers are not stored at all.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedStyle {
+    /// The foreground colour.
+    pub foreground: Option<[u8; 3]>,
+    /// The background colour.
+    pub background: Option<[u8; 3]>,
+    /// The names of the modifiers added by the style.
+    pub modifiers: Vec<String>,
+}
+
+/// A serializable representation of a
+/// [ratatui::text::Span](https://docs.rs/ratatui/latest/ratatui/text/struct.Span.html).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedSpan {
+    /// The span's content.
+    pub content: String,
+    /// The span's style.
+    pub style: SerializedStyle,
+}
+
+/// A serializable representation of a
+/// [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html), e.g. for
+/// caching highlighted output on disk or sending it between a client and server.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Modifier, Style};
+/// use ratatui::text::{Line, Span};
+///
+/// let line = Line::from(vec![Span::styled(
+///     "fn",
+///     Style::new().fg(Color::Rgb(255, 0, 0)).add_modifier(Modifier::BOLD),
+/// )]);
+/// let serialized = syntect_tui::SerializedLine::from(&line);
+/// assert_eq!(Some([255, 0, 0]), serialized.spans[0].style.foreground);
+/// assert_eq!(vec!["BOLD".to_string()], serialized.spans[0].style.modifiers);
+/// assert_eq!(line, Line::from(serialized));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedLine {
+    /// The line's spans.
+    pub spans: Vec<SerializedSpan>,
+    /// The line's own style.
+    pub style: SerializedStyle,
+}
+
+impl From<ratatui::style::Style> for SerializedStyle {
+    fn from(value: ratatui::style::Style) -> Self {
+        let palette = Palette::default();
+        let colour = |colour: Option<ratatui::style::Color>| {
+            colour
+                .and_then(|colour| palette.rgb(colour))
+                .map(|(r, g, b)| [r, g, b])
+        };
+        Self {
+            foreground: colour(value.fg),
+            background: colour(value.bg),
+            modifiers: value
+                .add_modifier
+                .iter_names()
+                .map(|(name, _)| name.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl From<&SerializedStyle> for ratatui::style::Style {
+    /// Rebuilds the style, ignoring any unknown modifier names.
+    fn from(value: &SerializedStyle) -> Self {
+        let colour =
+            |colour: Option<[u8; 3]>| colour.map(|[r, g, b]| ratatui::style::Color::Rgb(r, g, b));
+        ratatui::style::Style {
+            fg: colour(value.foreground),
+            bg: colour(value.background),
+            add_modifier: value
+                .modifiers
+                .iter()
+                .filter_map(|name| ratatui::style::Modifier::from_name(name))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&ratatui::text::Span<'_>> for SerializedSpan {
+    fn from(value: &ratatui::text::Span<'_>) -> Self {
+        Self {
+            content: value.content.to_string(),
+            style: value.style.into(),
+        }
+    }
+}
+
+impl From<SerializedSpan> for ratatui::text::Span<'static> {
+    fn from(value: SerializedSpan) -> Self {
+        ratatui::text::Span::styled(value.content, &value.style)
+    }
+}
+
+impl From<&ratatui::text::Line<'_>> for SerializedLine {
+    fn from(value: &ratatui::text::Line<'_>) -> Self {
+        Self {
+            spans: value.spans.iter().map(SerializedSpan::from).collect(),
+            style: value.style.into(),
+        }
+    }
+}
+
+impl From<SerializedLine> for ratatui::text::Line<'static> {
+    fn from(value: SerializedLine) -> Self {
+        ratatui::text::Line::from(
+            value
+                .spans
+                .into_iter()
+                .map(ratatui::text::Span::from)
+                .collect::<Vec<_>>(),
+        )
+        .style(&value.style)
+    }
+}
+
+/// A highlighted document saved along with a hash of the source it was highlighted from, e.g. to
+/// show a large file highlighted as soon as it is reopened.
+///
+/// A snapshot only restores the document if the source is unchanged, using a hash that is stable
+/// across builds and platforms, so a stale snapshot is never shown. Syntect's parser states cannot
+/// be saved, so an [IncrementalHighlighter] still has to highlight the document again before it
+/// can be edited, e.g. with a [BackgroundHighlighter](crate::BackgroundHighlighter) while the
+/// restored document is shown. The snapshot does not record the syntax, theme or translator it
+/// was made with, so applications that change them should store snapshots separately for each.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::{HighlightSnapshot, IncrementalHighlighter};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "fn main() {\n}\n";
+/// let mut highlighter = IncrementalHighlighter::new(syntax, &ps, &ts.themes["base16-ocean.dark"]);
+/// highlighter.set_text(source).unwrap();
+/// let json = serde_json::to_string(&highlighter.snapshot()).unwrap();
+///
+/// let snapshot: HighlightSnapshot = serde_json::from_str(&json).unwrap();
+/// assert!(snapshot.clone().restore("fn other() {}\n").is_none());
+/// let restored = snapshot.restore(source).unwrap();
+/// assert_eq!(highlighter.text(0..2), restored.text(0..2));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighlightSnapshot {
+    source_hash: u64,
+    text: InternedText,
+}
+
+impl HighlightSnapshot {
+    /// Creates a snapshot of `text`, which was highlighted from `source`.
+    pub fn new(source: &str, text: InternedText) -> Self {
+        Self {
+            source_hash: source_hash(source),
+            text,
+        }
+    }
+
+    /// Returns whether the snapshot was made from `source`.
+    pub fn matches(&self, source: &str) -> bool {
+        self.source_hash == source_hash(source)
+    }
+
+    /// Returns the highlighted document if the snapshot was made from `source`, or `None` if
+    /// `source` has changed since.
+    pub fn restore(self, source: &str) -> Option<InternedText> {
+        self.matches(source).then_some(self.text)
+    }
+}
+
+/// Hashes `source` with 64-bit FNV-1a, which unlike the standard library's hashers is the same
+/// in every build.
+fn source_hash(source: &str) -> u64 {
+    source.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier, Style};
+    use rstest::*;
+
+    #[rstest]
+    #[case::empty(Style::new(), SerializedStyle::default())]
+    #[case::named(Style::new().fg(Color::Red).bg(Color::Reset), SerializedStyle {
+        foreground: Some([205, 0, 0]),
+        background: None,
+        modifiers: vec![],
+    })]
+    #[case::modifiers(
+        Style::new().add_modifier(Modifier::BOLD | Modifier::DIM).remove_modifier(Modifier::ITALIC),
+        SerializedStyle {
+            foreground: None,
+            background: None,
+            modifiers: vec!["BOLD".to_string(), "DIM".to_string()],
+        }
+    )]
+    fn check_serialized_style(#[case] input: Style, #[case] expected: SerializedStyle) {
+        assert_eq!(expected, SerializedStyle::from(input));
+    }
+
+    #[test]
+    fn unknown_modifiers_are_ignored() {
+        let input = SerializedStyle {
+            modifiers: vec!["BOLD".to_string(), "SPARKLY".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            Style::new().add_modifier(Modifier::BOLD),
+            Style::from(&input)
+        );
+    }
+
+    #[test]
+    fn lines_round_trip_through_json() {
+        let line = ratatui::text::Line::from(vec![
+            ratatui::text::Span::styled("let", Style::new().fg(Color::Rgb(1, 2, 3))),
+            ratatui::text::Span::raw(" x"),
+        ])
+        .style(Style::new().bg(Color::Rgb(4, 5, 6)));
+        let json = serde_json::to_string(&SerializedLine::from(&line)).unwrap();
+        let actual: SerializedLine = serde_json::from_str(&json).unwrap();
+        assert_eq!(line, ratatui::text::Line::from(actual));
+    }
+
+    #[test]
+    fn source_hash_is_stable() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, source_hash(""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, source_hash("a"));
+    }
+
+    #[test]
+    fn interned_text_round_trips_through_json() {
+        let text = crate::HighlightedText::new(
+            vec![
+                ratatui::text::Line::from(vec![
+                    ratatui::text::Span::styled("日本", Style::new().fg(Color::Indexed(3))),
+                    ratatui::text::Span::raw(" x"),
+                ])
+                .alignment(ratatui::layout::Alignment::Center),
+                ratatui::text::Line::default(),
+            ],
+            Some(Color::Black),
+        );
+        let interned = InternedText::from(&text);
+        let json = serde_json::to_string(&HighlightSnapshot::new("x", interned.clone())).unwrap();
+        let snapshot: HighlightSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(Some(interned), snapshot.restore("x"));
+    }
+
+    #[rstest]
+    #[case::past_end(r#"{"content":"ab","spans":[[0,3]],"style":0,"alignment":null}"#)]
+    #[case::inside_character(r#"{"content":"日","spans":[[0,1]],"style":0,"alignment":null}"#)]
+    #[case::backwards(r#"{"content":"ab","spans":[[0,2],[0,1]],"style":0,"alignment":null}"#)]
+    #[case::unknown_alignment(r#"{"content":"ab","spans":[],"style":0,"alignment":"Diagonal"}"#)]
+    fn invalid_lines_are_rejected(#[case] json: &str) {
+        assert!(serde_json::from_str::<crate::InternedLine>(json).is_err());
+    }
+}
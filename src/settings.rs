@@ -0,0 +1,277 @@
+use crate::StyleTranslator;
+
+/// The non-token settings of a syntect theme, translated into
+/// [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html)s so
+/// that gutters, cursors and selections can be styled consistently with the highlighted text.
+///
+/// Each field is `None` if the theme sets none of its colours (or they are all transparent).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TuiThemeSettings {
+    /// The theme's default background, as a background colour.
+    pub background: Option<ratatui::style::Style>,
+    /// The theme's default foreground, as a foreground colour.
+    pub foreground: Option<ratatui::style::Style>,
+    /// The caret colour, as a background colour since terminal cursors usually fill a cell.
+    pub caret: Option<ratatui::style::Style>,
+    /// The selection background and foreground.
+    pub selection: Option<ratatui::style::Style>,
+    /// The background of the line containing the caret.
+    pub line_highlight: Option<ratatui::style::Style>,
+    /// The gutter background and foreground.
+    pub gutter: Option<ratatui::style::Style>,
+    /// The background and foreground of search matches.
+    pub find_highlight: Option<ratatui::style::Style>,
+    /// The foreground and background of the brackets next to the caret.
+    pub brackets: Option<ratatui::style::Style>,
+}
+
+/// Translates the non-token settings of a
+/// [syntect::highlighting::Theme](https://docs.rs/syntect/latest/syntect/highlighting/struct.Theme.html)
+/// into [TuiThemeSettings].
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use syntect::highlighting::ThemeSet;
+///
+/// let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+/// let settings = syntect_tui::translate_theme_settings(theme);
+/// assert_eq!(Some(Style::new().bg(Color::Rgb(43, 48, 59))), settings.background);
+/// assert!(settings.selection.is_some());
+/// ```
+pub fn translate_theme_settings(theme: &syntect::highlighting::Theme) -> TuiThemeSettings {
+    StyleTranslator::default().translate_theme_settings(theme)
+}
+
+/// A syntect theme translated into ratatui styles, so that a theme picker can list, preview and
+/// apply themes without using syntect's types.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TuiTheme {
+    /// The theme's name, if it has one.
+    pub name: Option<String>,
+    /// The theme's author, if it has one.
+    pub author: Option<String>,
+    /// The style of text that none of the theme's rules match.
+    pub default_style: ratatui::style::Style,
+    /// The style text matching each of the theme's scope selectors is highlighted with, in the
+    /// order of the theme's rules, e.g. `("comment", ...)` or `("string - string.quoted", ...)`.
+    pub tokens: Vec<(String, ratatui::style::Style)>,
+    /// The theme's non-token settings.
+    pub settings: TuiThemeSettings,
+}
+
+/// Translates every theme of a
+/// [syntect::highlighting::ThemeSet](https://docs.rs/syntect/latest/syntect/highlighting/struct.ThemeSet.html)
+/// into a [TuiTheme], keyed by the names the set gives them.
+///
+/// Styles whose font style has bits ratatui does not support are translated without them, like
+/// [translate_style_lossy](crate::translate_style_lossy).
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use syntect::highlighting::ThemeSet;
+///
+/// let themes = syntect_tui::translate_theme_set(&ThemeSet::load_defaults());
+/// let mut names: Vec<_> = themes.keys().collect();
+/// names.sort();
+/// assert_eq!("InspiredGitHub", names[0]);
+/// let ocean = &themes["base16-ocean.dark"];
+/// assert_eq!(Some(Color::Rgb(192, 197, 206)), ocean.default_style.fg);
+/// let (_, comment) = ocean.tokens.iter().find(|(selector, _)| selector == "comment").unwrap();
+/// assert_eq!(Some(Color::Rgb(101, 115, 126)), comment.fg);
+/// ```
+pub fn translate_theme_set(
+    theme_set: &syntect::highlighting::ThemeSet,
+) -> std::collections::HashMap<String, TuiTheme> {
+    StyleTranslator::default().translate_theme_set(theme_set)
+}
+
+impl StyleTranslator {
+    /// Translates the non-token settings of a theme into [TuiThemeSettings], translating colours
+    /// according to this translator's options.
+    ///
+    /// See [translate_theme_settings](crate::translate_theme_settings) for details.
+    pub fn translate_theme_settings(
+        &self,
+        theme: &syntect::highlighting::Theme,
+    ) -> TuiThemeSettings {
+        let settings = &theme.settings;
+        let style = |fg: Option<syntect::highlighting::Color>,
+                     bg: Option<syntect::highlighting::Color>| {
+            let style = ratatui::style::Style {
+                fg: fg.and_then(|colour| self.translate_colour(colour)),
+                bg: bg.and_then(|colour| self.translate_colour(colour)),
+                ..Default::default()
+            };
+            (style != ratatui::style::Style::default()).then_some(style)
+        };
+        TuiThemeSettings {
+            background: style(None, settings.background),
+            foreground: style(settings.foreground, None),
+            caret: style(None, settings.caret),
+            selection: style(settings.selection_foreground, settings.selection),
+            line_highlight: style(None, settings.line_highlight),
+            gutter: style(settings.gutter_foreground, settings.gutter),
+            find_highlight: style(settings.find_highlight_foreground, settings.find_highlight),
+            brackets: style(settings.brackets_foreground, settings.brackets_background),
+        }
+    }
+
+    /// Translates a theme into a [TuiTheme], translating its styles according to this
+    /// translator's options.
+    pub fn translate_theme(&self, theme: &syntect::highlighting::Theme) -> TuiTheme {
+        let highlighter = syntect::highlighting::Highlighter::new(theme);
+        let tokens = theme
+            .scopes
+            .iter()
+            .flat_map(|item| &item.scope.selectors)
+            .map(|selector| {
+                let style = highlighter.style_for_stack(selector.path.as_slice());
+                (selector_string(selector), self.translate_style_lossy(style))
+            })
+            .collect();
+        TuiTheme {
+            name: theme.name.clone(),
+            author: theme.author.clone(),
+            default_style: self.translate_style_lossy(highlighter.get_default()),
+            tokens,
+            settings: self.translate_theme_settings(theme),
+        }
+    }
+
+    /// Translates every theme of a theme set into a [TuiTheme], translating their styles
+    /// according to this translator's options.
+    ///
+    /// See [translate_theme_set](crate::translate_theme_set) for details.
+    pub fn translate_theme_set(
+        &self,
+        theme_set: &syntect::highlighting::ThemeSet,
+    ) -> std::collections::HashMap<String, TuiTheme> {
+        theme_set
+            .themes
+            .iter()
+            .map(|(name, theme)| (name.clone(), self.translate_theme(theme)))
+            .collect()
+    }
+}
+
+/// Writes `selector` the way themes write it, e.g. `source.rust string - string.quoted`.
+fn selector_string(selector: &syntect::highlighting::ScopeSelector) -> String {
+    std::iter::once(&selector.path)
+        .chain(&selector.excludes)
+        .map(|stack| stack.to_string().trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join(" - ")
+}
+
+/// Returns the style `theme` gives `scope`, or `None` if none of its rules match it.
+pub(crate) fn scope_style(
+    theme: &syntect::highlighting::Theme,
+    scope: &str,
+) -> Option<syntect::highlighting::Style> {
+    let stack = [syntect::parsing::Scope::new(scope).ok()?];
+    let themed = theme
+        .scopes
+        .iter()
+        .any(|item| item.scope.does_match(&stack).is_some());
+    themed.then(|| syntect::highlighting::Highlighter::new(theme).style_for_stack(&stack))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Style};
+    use syntect::highlighting::{Color as SyntectColour, Theme, ThemeSettings};
+
+    const RED: SyntectColour = SyntectColour {
+        r: 255,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+    const TRANSPARENT: SyntectColour = SyntectColour {
+        r: 0,
+        g: 0,
+        b: 0,
+        a: 0,
+    };
+
+    #[test]
+    fn empty_settings_translate_to_none() {
+        assert_eq!(
+            TuiThemeSettings::default(),
+            translate_theme_settings(&Theme::default())
+        );
+    }
+
+    #[test]
+    fn settings_are_paired_into_styles() {
+        let theme = Theme {
+            settings: ThemeSettings {
+                caret: Some(RED),
+                gutter: Some(TRANSPARENT),
+                gutter_foreground: Some(RED),
+                find_highlight: Some(RED),
+                find_highlight_foreground: Some(TRANSPARENT),
+                selection: Some(TRANSPARENT),
+                brackets_foreground: Some(RED),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let red = Color::Rgb(255, 0, 0);
+        let expected = TuiThemeSettings {
+            caret: Some(Style::new().bg(red)),
+            gutter: Some(Style::new().fg(red)),
+            find_highlight: Some(Style::new().bg(red)),
+            brackets: Some(Style::new().fg(red)),
+            ..Default::default()
+        };
+        assert_eq!(expected, translate_theme_settings(&theme));
+    }
+
+    #[test]
+    fn theme_rules_translate_into_tokens() {
+        let theme = Theme {
+            name: Some("Red".to_string()),
+            settings: ThemeSettings {
+                foreground: Some(RED),
+                ..Default::default()
+            },
+            scopes: vec![syntect::highlighting::ThemeItem {
+                scope: "string - string.quoted, source.rust comment"
+                    .parse()
+                    .unwrap(),
+                style: syntect::highlighting::StyleModifier {
+                    font_style: Some(syntect::highlighting::FontStyle::BOLD),
+                    ..Default::default()
+                },
+            }],
+            ..Default::default()
+        };
+        let mut set = syntect::highlighting::ThemeSet::new();
+        set.themes.insert("red".to_string(), theme);
+        let translated = translate_theme_set(&set).remove("red").unwrap();
+        assert_eq!(Some("Red".to_string()), translated.name);
+        let selectors: Vec<_> = translated
+            .tokens
+            .iter()
+            .map(|(selector, _)| selector)
+            .collect();
+        assert_eq!(
+            vec!["string - string.quoted", "source.rust comment"],
+            selectors
+        );
+        for (_, style) in &translated.tokens {
+            assert_eq!(Some(Color::Rgb(255, 0, 0)), style.fg);
+            assert!(style.add_modifier.contains(ratatui::style::Modifier::BOLD));
+        }
+        assert_eq!(
+            Some(Style::new().fg(Color::Rgb(255, 0, 0))),
+            translated.settings.foreground
+        );
+    }
+}
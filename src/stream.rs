@@ -0,0 +1,300 @@
+use crate::{
+    BackgroundHighlighter, CancellationToken, HighlightBatch, HighlightedText, LineConversion,
+    SyntectTuiError,
+};
+use std::sync::Arc;
+
+/// The number of batches sent ahead of those received before a stream's highlighting pauses.
+const BUFFERED_BATCHES: usize = 16;
+
+/// Highlights documents for async TUIs, without blocking the executor.
+///
+/// Highlighting runs on tokio's blocking thread pool, like a [BackgroundHighlighter] running on
+/// its own thread, and the converted lines are received in batches from a [HighlightStream].
+/// Highlighting pauses while the stream has a few batches waiting to be received, and stops once
+/// the stream is dropped or [cancelled](HighlightStream::cancel), e.g. when the task polling it
+/// is aborted.
+///
+/// # Panics
+/// [stream](AsyncHighlighter::stream) and [highlight](AsyncHighlighter::highlight) panic if they
+/// are called outside a tokio runtime.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::AsyncHighlighter;
+///
+/// let ps = Arc::new(SyntaxSet::load_defaults_newlines());
+/// let ts = ThemeSet::load_defaults();
+/// let theme = Arc::new(ts.themes["base16-ocean.dark"].clone());
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let highlighter = AsyncHighlighter::new(syntax, ps.clone(), theme).batch_lines(2);
+/// let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+/// runtime.block_on(async {
+///     let mut stream = highlighter.stream("let a = 1;\nlet b = 2;\nlet c = 3;\n".to_string());
+///     assert_eq!(0, stream.recv().await.unwrap().unwrap().start);
+///     assert_eq!(2, stream.recv().await.unwrap().unwrap().start);
+///     assert!(stream.recv().await.is_none());
+///     let text = highlighter.highlight("fn main() {}\n".to_string()).await.unwrap();
+///     assert_eq!("fn main() {}", text.lines[0].to_string());
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct AsyncHighlighter {
+    highlighter: BackgroundHighlighter,
+}
+
+impl AsyncHighlighter {
+    /// Creates a highlighter that highlights documents using `syntax`, which must be one of the
+    /// syntaxes of `syntax_set`, and `theme`, sending batches of 100 lines.
+    pub fn new(
+        syntax: &syntect::parsing::SyntaxReference,
+        syntax_set: Arc<syntect::parsing::SyntaxSet>,
+        theme: Arc<syntect::highlighting::Theme>,
+    ) -> Self {
+        BackgroundHighlighter::new(syntax, syntax_set, theme).into()
+    }
+
+    /// Sets the number of lines in each batch, as described in
+    /// [BackgroundHighlighter::batch_lines].
+    pub fn batch_lines(mut self, lines: usize) -> Self {
+        self.highlighter = self.highlighter.batch_lines(lines);
+        self
+    }
+
+    /// Starts highlighting `source` on the blocking thread pool, returning the stream of its
+    /// batches.
+    pub fn stream(&self, source: String) -> HighlightStream {
+        self.stream_with_token(source, CancellationToken::new())
+    }
+
+    /// Starts highlighting `source` on the blocking thread pool like [AsyncHighlighter::stream],
+    /// stopping before the next line once `token` has been cancelled.
+    pub fn stream_with_token(&self, source: String, token: CancellationToken) -> HighlightStream {
+        let (sender, receiver) = tokio::sync::mpsc::channel(BUFFERED_BATCHES);
+        let highlighter = self.highlighter.clone();
+        let stop = token.child();
+        let stream_stop = stop.clone();
+        tokio::task::spawn_blocking(move || {
+            for batch in highlighter.batches(&source, &stop) {
+                if stop.is_cancelled() {
+                    break;
+                }
+                let failed = batch.is_err();
+                if sender.blocking_send(batch).is_err() || failed {
+                    break;
+                }
+            }
+        });
+        HighlightStream {
+            receiver,
+            token,
+            stop: stream_stop,
+        }
+    }
+
+    /// Highlights the whole of `source` on the blocking thread pool, with the theme's background.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line of `source` they occurred on.
+    pub async fn highlight(
+        &self,
+        source: String,
+    ) -> Result<HighlightedText<'static>, SyntectTuiError> {
+        let mut stream = self.stream(source);
+        let mut lines = Vec::new();
+        while let Some(batch) = stream.recv().await {
+            lines.extend(batch?.lines);
+        }
+        let background = self
+            .highlighter
+            .translator
+            .theme_background(&self.highlighter.theme);
+        Ok(HighlightedText::new(lines, background))
+    }
+}
+
+impl LineConversion for AsyncHighlighter {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.highlighter = self.highlighter.translator(translator);
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.highlighter = self.highlighter.long_lines(fallback);
+        self
+    }
+}
+
+impl From<BackgroundHighlighter> for AsyncHighlighter {
+    fn from(highlighter: BackgroundHighlighter) -> Self {
+        Self { highlighter }
+    }
+}
+
+/// The batches of a document being highlighted by an [AsyncHighlighter], in order, ending after
+/// the whole document has been highlighted or an error.
+///
+/// Also implements [Stream](futures_core::Stream), so it can be combined with other streams, e.g.
+/// in a `tokio::select!` loop. Dropping the stream stops its highlighting, without cancelling its
+/// [token](HighlightStream::token).
+#[derive(Debug)]
+pub struct HighlightStream {
+    receiver: tokio::sync::mpsc::Receiver<Result<HighlightBatch, SyntectTuiError>>,
+    token: CancellationToken,
+    stop: CancellationToken,
+}
+
+impl HighlightStream {
+    /// Waits for the next batch, or returns `None` once every batch has been received.
+    pub async fn recv(&mut self) -> Option<Result<HighlightBatch, SyntectTuiError>> {
+        self.receiver.recv().await
+    }
+
+    /// Stops highlighting before the next line, without cancelling its
+    /// [token](HighlightStream::token) or other work sharing it. Batches already sent can still be
+    /// received.
+    pub fn cancel(&mut self) {
+        self.stop.cancel();
+        self.receiver.close();
+    }
+
+    /// Returns the token the stream was started with, which cancels its highlighting along with
+    /// any other work it was handed to.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for HighlightStream {
+    fn drop(&mut self) {
+        self.stop.cancel();
+    }
+}
+
+impl futures_core::Stream for HighlightStream {
+    type Item = Result<HighlightBatch, SyntectTuiError>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_core::Stream;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\n*/\nlet c = \"\nd\";\nlet e = 5;";
+
+    fn fake_highlighter() -> AsyncHighlighter {
+        let ps = Arc::new(SyntaxSet::load_defaults_newlines());
+        let ts = ThemeSet::load_defaults();
+        let syntax = ps.find_syntax_by_extension("rs").unwrap().clone();
+        AsyncHighlighter::new(
+            &syntax,
+            ps,
+            Arc::new(ts.themes["base16-ocean.dark"].clone()),
+        )
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[rstest]
+    #[case::one_per_batch(1, 7)]
+    #[case::uneven(3, 3)]
+    #[case::single_batch(100, 1)]
+    fn streams_sequential_highlighting(#[case] batch_lines: usize, #[case] batches: usize) {
+        let highlighter = fake_highlighter().batch_lines(batch_lines);
+        let (expected, received) = block_on(async {
+            let expected = fake_highlighter()
+                .highlight(SOURCE.to_string())
+                .await
+                .unwrap();
+            let mut stream = highlighter.stream(SOURCE.to_string());
+            let mut received = Vec::new();
+            while let Some(batch) =
+                std::future::poll_fn(|cx| std::pin::Pin::new(&mut stream).poll_next(cx)).await
+            {
+                received.push(batch.unwrap());
+            }
+            (expected, received)
+        });
+        assert_eq!(batches, received.len());
+        let mut document = Vec::new();
+        for batch in received {
+            batch.apply_to(&mut document);
+        }
+        assert_eq!(expected.lines, document);
+    }
+
+    #[test]
+    fn cancelled_stream_ends() {
+        let received = block_on(async {
+            let mut stream = fake_highlighter()
+                .batch_lines(1)
+                .stream("let x = 1;\n".repeat(10_000));
+            stream.cancel();
+            let mut received = 0;
+            while stream.recv().await.is_some() {
+                received += 1;
+            }
+            received
+        });
+        assert!(received <= BUFFERED_BATCHES);
+    }
+
+    #[test]
+    fn dropping_stream_leaves_shared_token() {
+        let token = CancellationToken::new();
+        let highlighter = fake_highlighter().batch_lines(1);
+        let received = block_on(async {
+            let dropped =
+                highlighter.stream_with_token("let x = 1;\n".repeat(10_000), token.clone());
+            let mut sibling = highlighter.stream_with_token(SOURCE.to_string(), token.clone());
+            drop(dropped);
+            let mut received = 0;
+            while let Some(batch) = sibling.recv().await {
+                batch.unwrap();
+                received += 1;
+            }
+            received
+        });
+        assert!(!token.is_cancelled());
+        assert_eq!(7, received);
+    }
+
+    #[test]
+    fn cancelling_stream_leaves_shared_token() {
+        let token = CancellationToken::new();
+        let highlighter = fake_highlighter().batch_lines(1);
+        let received = block_on(async {
+            let mut cancelled =
+                highlighter.stream_with_token("let x = 1;\n".repeat(10_000), token.clone());
+            let mut sibling = highlighter.stream_with_token(SOURCE.to_string(), token.clone());
+            cancelled.cancel();
+            let mut received = 0;
+            while let Some(batch) = sibling.recv().await {
+                batch.unwrap();
+                received += 1;
+            }
+            received
+        });
+        assert!(!token.is_cancelled());
+        assert_eq!(7, received);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::settings::scope_style;
+use crate::{HighlightedText, SyntectTuiError};
+
+/// The Rust source highlighted by a [ThemePreview] by default.
+const SAMPLE: &str = concat!(
+    "// Greets someone\n",
+    "fn greet(name: &str) -> String {\n",
+    "    let count = 42;\n",
+    "    format!(\"Hello, {name}! #{count}\")\n",
+    "}\n",
+);
+
+/// The scopes whose foregrounds are shown as the key colours of a theme, after its foreground.
+const KEY_SCOPES: [&str; 6] = [
+    "comment",
+    "keyword",
+    "storage.type",
+    "entity.name.function",
+    "string",
+    "constant.numeric",
+];
+
+/// Generates compact previews of themes, e.g. for the entries of a theme picker.
+///
+/// A preview is a header line with the theme's name and a swatch of each of its key colours (its
+/// foreground, the colours of comments, keywords, types, functions, strings and numbers, and its
+/// selection and caret colours, without repeats), followed by a small highlighted sample, much
+/// like `bat --list-themes`. The sample is a few lines of Rust by default, or plain text if the
+/// syntax set has no Rust syntax.
+///
+/// # Examples
+/// ```
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::ThemePreview;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let preview = ThemePreview::new(&ps)
+///     .source("SELECT 1;\n", ps.find_syntax_by_extension("sql").unwrap())
+///     .swatch('●');
+/// let items = ts
+///     .themes
+///     .iter()
+///     .map(|(name, theme)| preview.preview(name, theme))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(ts.themes.len(), items.len());
+/// assert!(items[0].lines[0].to_string().contains('●'));
+/// assert_eq!("SELECT 1;", items[0].lines[1].to_string().trim_end());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ThemePreview<'a> {
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    syntax: &'a syntect::parsing::SyntaxReference,
+    source: &'a str,
+    swatch: char,
+}
+
+/// Generates a preview of `theme` named `name`, highlighting a small Rust sample with syntaxes
+/// from `syntax_set`.
+///
+/// See [ThemePreview] for details.
+///
+/// # Errors
+/// Can return the same errors as [ThemePreview::preview].
+pub fn theme_preview<'a>(
+    name: &str,
+    theme: &syntect::highlighting::Theme,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+) -> Result<HighlightedText<'a>, SyntectTuiError> {
+    ThemePreview::new(syntax_set).preview(name, theme)
+}
+
+impl<'a> ThemePreview<'a> {
+    /// Creates a preview generator that highlights the default sample with syntaxes from
+    /// `syntax_set`.
+    pub fn new(syntax_set: &'a syntect::parsing::SyntaxSet) -> Self {
+        Self {
+            syntax_set,
+            syntax: syntax_set
+                .find_syntax_by_extension("rs")
+                .unwrap_or_else(|| syntax_set.find_syntax_plain_text()),
+            source: SAMPLE,
+            swatch: '█',
+        }
+    }
+
+    /// Sets the sample to highlight and its syntax, e.g. to preview themes with the language the
+    /// user is editing.
+    pub fn source(
+        mut self,
+        source: &'a str,
+        syntax: &'a syntect::parsing::SyntaxReference,
+    ) -> Self {
+        self.source = source;
+        self.syntax = syntax;
+        self
+    }
+
+    /// Sets the symbol drawn for each key colour.
+    pub fn swatch(mut self, symbol: char) -> Self {
+        self.swatch = symbol;
+        self
+    }
+
+    /// Generates a preview of `theme` with `name` in its header, with the theme's background.
+    ///
+    /// # Errors
+    /// Returns the errors of [highlight_line_to_line](crate::highlight_line_to_line), identifying
+    /// the line of the sample they occurred on.
+    pub fn preview(
+        &self,
+        name: &str,
+        theme: &syntect::highlighting::Theme,
+    ) -> Result<HighlightedText<'a>, SyntectTuiError> {
+        let mut highlighter = syntect::easy::HighlightLines::new(self.syntax, theme);
+        let sample = syntect::util::LinesWithEndings::from(self.source)
+            .enumerate()
+            .map(|(index, line)| {
+                crate::highlight_line_to_line(&mut highlighter, self.syntax_set, theme, line)
+                    .map_err(|error| error.at_line(index))
+            });
+        let lines = std::iter::once(Ok(self.header(name, theme)))
+            .chain(sample)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HighlightedText::from_theme(lines, theme))
+    }
+
+    /// Returns the header line with the theme's name and key colours.
+    fn header(
+        &self,
+        name: &str,
+        theme: &syntect::highlighting::Theme,
+    ) -> ratatui::text::Line<'static> {
+        let settings = &theme.settings;
+        let name_style = settings
+            .foreground
+            .and_then(crate::translate_colour)
+            .map_or(ratatui::style::Style::new(), |colour| {
+                ratatui::style::Style::new().fg(colour)
+            })
+            .add_modifier(ratatui::style::Modifier::BOLD);
+        let scopes = KEY_SCOPES
+            .iter()
+            .map(|scope| scope_style(theme, scope).map(|style| style.foreground));
+        let colours = std::iter::once(settings.foreground)
+            .chain(scopes)
+            .chain([settings.selection, settings.caret])
+            .flatten()
+            .filter_map(crate::translate_colour);
+        let mut spans = vec![ratatui::text::Span::styled(name.to_string(), name_style)];
+        let mut shown = Vec::new();
+        for colour in colours {
+            if shown.contains(&colour) {
+                continue;
+            }
+            shown.push(colour);
+            let symbol = if spans.len() == 1 {
+                format!("  {}", self.swatch)
+            } else {
+                self.swatch.to_string()
+            };
+            spans.push(ratatui::text::Span::styled(
+                symbol,
+                ratatui::style::Style::new().fg(colour),
+            ));
+        }
+        ratatui::text::Line::from(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier, Style};
+    use rstest::*;
+    use syntect::highlighting::{Color as SyntectColour, Theme, ThemeSet};
+    use syntect::parsing::SyntaxSet;
+
+    const RED: SyntectColour = SyntectColour {
+        r: 255,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+
+    #[rstest]
+    #[case::base16("base16-ocean.dark")]
+    #[case::solarized("Solarized (light)")]
+    fn previews_name_swatches_and_sample(#[case] name: &str) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let theme = &ts.themes[name];
+        let preview = theme_preview(name, theme, &ps).unwrap();
+        assert_eq!(1 + SAMPLE.lines().count(), preview.lines.len());
+        let header = &preview.lines[0];
+        assert_eq!(name, header.spans[0].content);
+        assert!(header.spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert!(header.spans.len() > 4);
+        assert_eq!(
+            theme.settings.background.and_then(crate::translate_colour),
+            preview.background
+        );
+        assert!(preview.lines[2].spans.len() > 1);
+    }
+
+    #[test]
+    fn repeated_colours_are_shown_once() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let mut theme = Theme::default();
+        theme.settings.foreground = Some(RED);
+        theme.settings.caret = Some(RED);
+        theme.settings.selection = Some(SyntectColour::BLACK);
+        let preview = ThemePreview::new(&ps)
+            .swatch('*')
+            .preview("red", &theme)
+            .unwrap();
+        assert_eq!("red  **", preview.lines[0].to_string());
+        assert_eq!(
+            Style::new().fg(Color::Rgb(0, 0, 0)),
+            preview.lines[0].spans[2].style
+        );
+    }
+}
@@ -0,0 +1,188 @@
+/// Converted lines of highlighted text along with the background of the theme that highlighted
+/// them.
+///
+/// Spans only colour the text they cover, so rendering converted lines on their own leaves the
+/// rest of the area in the terminal's default background. `HighlightedText` renders as a ratatui
+/// [Widget](https://docs.rs/ratatui/latest/ratatui/widgets/trait.Widget.html) that fills its
+/// whole area with the theme background, and converts into a
+/// [ratatui::text::Text](https://docs.rs/ratatui/latest/ratatui/text/struct.Text.html) styled
+/// with it for use with other widgets.
+///
+/// # Examples
+/// ```
+/// use syntect::easy::HighlightLines;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::util::LinesWithEndings;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let theme = &ts.themes["base16-ocean.dark"];
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut h = HighlightLines::new(syntax, theme);
+/// let lines = LinesWithEndings::from("fn main() {}\n")
+///     .map(|line| syntect_tui::into_line(h.highlight_line(line, &ps).unwrap()).unwrap())
+///     .collect();
+/// let highlighted = syntect_tui::HighlightedText::from_theme(lines, theme);
+/// let text: ratatui::text::Text = highlighted.into();
+/// assert_eq!(Some(ratatui::style::Color::Rgb(43, 48, 59)), text.style.bg);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HighlightedText<'a> {
+    /// The converted lines.
+    pub lines: Vec<ratatui::text::Line<'a>>,
+    /// The background colour of the theme, if it has one.
+    pub background: Option<ratatui::style::Color>,
+}
+
+impl<'a> HighlightedText<'a> {
+    /// Creates highlighted text from converted lines and a background colour.
+    pub fn new(
+        lines: Vec<ratatui::text::Line<'a>>,
+        background: Option<ratatui::style::Color>,
+    ) -> Self {
+        Self { lines, background }
+    }
+
+    /// Creates highlighted text from converted lines, taking the background colour from the
+    /// settings of the theme that highlighted them.
+    pub fn from_theme(
+        lines: Vec<ratatui::text::Line<'a>>,
+        theme: &syntect::highlighting::Theme,
+    ) -> Self {
+        Self::new(
+            lines,
+            theme.settings.background.and_then(crate::translate_colour),
+        )
+    }
+
+    /// Returns the style that fills the area behind the text.
+    pub fn style(&self) -> ratatui::style::Style {
+        match self.background {
+            Some(background) => ratatui::style::Style::new().bg(background),
+            None => ratatui::style::Style::new(),
+        }
+    }
+}
+
+/// Returns a style with the background colour of `theme`, e.g. for a
+/// [Block](https://docs.rs/ratatui/latest/ratatui/widgets/struct.Block.html) surrounding
+/// highlighted text. The style is empty if the theme has no (opaque) background.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Style};
+/// use syntect::highlighting::ThemeSet;
+///
+/// let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+/// let block = ratatui::widgets::Block::bordered().style(syntect_tui::theme_background(theme));
+/// assert_eq!(
+///     Style::new().bg(Color::Rgb(43, 48, 59)),
+///     syntect_tui::theme_background(theme)
+/// );
+/// ```
+pub fn theme_background(theme: &syntect::highlighting::Theme) -> ratatui::style::Style {
+    HighlightedText::from_theme(Vec::new(), theme).style()
+}
+
+/// Applies the background colour of `theme` to already converted text, so that the whole area it
+/// is rendered in matches the theme rather than only the highlighted spans.
+///
+/// Both the text and each of its lines are given the theme background, so it also fills the rest
+/// of each row when lines are rendered individually. Backgrounds that are already set on the text
+/// or its lines are kept.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::Color;
+/// use ratatui::text::Text;
+/// use syntect::highlighting::ThemeSet;
+///
+/// let theme = &ThemeSet::load_defaults().themes["base16-ocean.dark"];
+/// let text = syntect_tui::apply_theme_background(Text::from("a\nb"), theme);
+/// assert_eq!(Some(Color::Rgb(43, 48, 59)), text.style.bg);
+/// assert!(text.lines.iter().all(|line| line.style.bg == Some(Color::Rgb(43, 48, 59))));
+/// ```
+pub fn apply_theme_background<'a>(
+    mut text: ratatui::text::Text<'a>,
+    theme: &syntect::highlighting::Theme,
+) -> ratatui::text::Text<'a> {
+    let background = theme_background(theme);
+    text.style = background.patch(text.style);
+    for line in &mut text.lines {
+        line.style = background.patch(line.style);
+    }
+    text
+}
+
+impl<'a> From<HighlightedText<'a>> for ratatui::text::Text<'a> {
+    fn from(value: HighlightedText<'a>) -> Self {
+        let style = value.style();
+        ratatui::text::Text::from(value.lines).style(style)
+    }
+}
+
+impl ratatui::widgets::Widget for HighlightedText<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        ratatui::text::Text::from(self).render(area, buf);
+    }
+}
+
+impl ratatui::widgets::Widget for &HighlightedText<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        self.clone().render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Style, Stylize};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::Widget;
+
+    #[test]
+    fn render_fills_area_with_background() {
+        let text = HighlightedText::new(
+            vec![Line::from(Span::styled("ab", Style::new().red()))],
+            Some(Color::Blue),
+        );
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+        (&text).render(buf.area, &mut buf);
+        let mut expected = Buffer::with_lines(["ab  ", "    "]);
+        expected.set_style(expected.area, Style::new().bg(Color::Blue));
+        expected.set_style(Rect::new(0, 0, 2, 1), Style::new().red());
+        assert_eq!(expected, buf);
+    }
+
+    #[test]
+    fn apply_theme_background_keeps_existing_backgrounds() {
+        let theme = syntect::highlighting::Theme {
+            settings: syntect::highlighting::ThemeSettings {
+                background: Some(syntect::highlighting::Color {
+                    r: 1,
+                    g: 2,
+                    b: 3,
+                    a: 255,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let text = ratatui::text::Text::from(vec![
+            Line::raw("a"),
+            Line::raw("b").style(Style::new().bg(Color::Red)),
+        ]);
+        let actual = apply_theme_background(text, &theme);
+        assert_eq!(Some(Color::Rgb(1, 2, 3)), actual.lines[0].style.bg);
+        assert_eq!(Some(Color::Red), actual.lines[1].style.bg);
+    }
+
+    #[test]
+    fn into_text_without_background() {
+        let text: ratatui::text::Text = HighlightedText::new(vec![Line::raw("ab")], None).into();
+        assert_eq!(ratatui::text::Text::raw("ab"), text);
+    }
+}
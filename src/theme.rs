@@ -0,0 +1,166 @@
+//! Compilation of ratatui styles into syntect themes.
+use crate::reverse::font_style_back;
+use crate::{ReverseOptions, SyntectTuiError};
+use std::str::FromStr;
+
+/// Builds a [syntect::highlighting::Theme](https://docs.rs/syntect/latest/syntect/highlighting/struct.Theme.html)
+/// from scope selectors and
+/// [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html)s, so
+/// highlighting can be defined in Rust rather than shipped as `.tmTheme` files.
+///
+/// This uses the default [ReverseOptions](crate::ReverseOptions); see
+/// [ReverseOptions::build_theme](crate::ReverseOptions::build_theme) for details.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Color, Modifier, Style};
+/// use syntect::easy::HighlightLines;
+/// use syntect::parsing::SyntaxSet;
+///
+/// let theme = syntect_tui::build_theme([
+///     ("storage.type", Style::new().fg(Color::Rgb(255, 0, 0))),
+///     ("entity.name.function", Style::new().add_modifier(Modifier::BOLD)),
+/// ])
+/// .unwrap();
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let mut h = HighlightLines::new(syntax, &theme);
+/// let segments = h.highlight_line("fn main() {}", &ps).unwrap();
+/// let (style, _) = segments.iter().find(|(_, content)| *content == "fn").unwrap();
+/// assert_eq!(
+///     Some(Color::Rgb(255, 0, 0)),
+///     syntect_tui::translate_style(*style).unwrap().fg
+/// );
+/// ```
+/// # Errors
+/// Returns `SyntectTuiError::InvalidScopeSelector` if a selector cannot be parsed.
+pub fn build_theme<I, S>(styles: I) -> Result<syntect::highlighting::Theme, SyntectTuiError>
+where
+    I: IntoIterator<Item = (S, ratatui::style::Style)>,
+    S: AsRef<str>,
+{
+    ReverseOptions::default().build_theme(styles)
+}
+
+impl ReverseOptions {
+    /// Builds a syntect theme from scope selectors and ratatui styles.
+    ///
+    /// Each style becomes a theme item that only overrides what the style sets:
+    /// - Colours are converted using the palette, while unset and `Color::Reset` colours are left
+    ///   to the theme's defaults.
+    /// - If the style adds or removes any modifiers, the font style is replaced with the `BOLD`,
+    ///   `ITALIC` and `UNDERLINED` modifiers it adds.
+    ///
+    /// The default foreground and background colours become the theme's default colours.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::{Color, Style};
+    ///
+    /// let white = syntect::highlighting::Color { r: 255, g: 255, b: 255, a: 255 };
+    /// let theme = syntect_tui::ReverseOptions::new()
+    ///     .default_foreground(white)
+    ///     .build_theme([("comment", Style::new().fg(Color::DarkGray))])
+    ///     .unwrap();
+    /// assert_eq!(Some(white), theme.settings.foreground);
+    /// assert_eq!(1, theme.scopes.len());
+    /// ```
+    /// # Errors
+    /// Returns `SyntectTuiError::InvalidScopeSelector` if a selector cannot be parsed.
+    pub fn build_theme<I, S>(
+        &self,
+        styles: I,
+    ) -> Result<syntect::highlighting::Theme, SyntectTuiError>
+    where
+        I: IntoIterator<Item = (S, ratatui::style::Style)>,
+        S: AsRef<str>,
+    {
+        let scopes = styles
+            .into_iter()
+            .map(|(selector, style)| {
+                let scope = syntect::highlighting::ScopeSelectors::from_str(selector.as_ref())
+                    .map_err(|error| SyntectTuiError::InvalidScopeSelector {
+                        selector: selector.as_ref().to_string(),
+                        message: error.to_string(),
+                    })?;
+                Ok(syntect::highlighting::ThemeItem {
+                    scope,
+                    style: self.style_modifier(style),
+                })
+            })
+            .collect::<Result<Vec<_>, SyntectTuiError>>()?;
+        Ok(syntect::highlighting::Theme {
+            settings: syntect::highlighting::ThemeSettings {
+                foreground: self.default_foreground,
+                background: self.default_background,
+                ..Default::default()
+            },
+            scopes,
+            ..Default::default()
+        })
+    }
+
+    pub(crate) fn style_modifier(
+        &self,
+        style: ratatui::style::Style,
+    ) -> syntect::highlighting::StyleModifier {
+        let colour = |colour| match colour {
+            None | Some(ratatui::style::Color::Reset) => None,
+            Some(colour) => Some(crate::translate_colour_back(colour, &self.palette)),
+        };
+        let font_style = if style.add_modifier.is_empty() && style.sub_modifier.is_empty() {
+            None
+        } else {
+            Some(font_style_back(style.add_modifier))
+        };
+        syntect::highlighting::StyleModifier {
+            foreground: colour(style.fg),
+            background: colour(style.bg),
+            font_style,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Modifier, Style};
+    use rstest::*;
+    use syntect::highlighting::{Color as SyntectColour, FontStyle, StyleModifier};
+
+    #[rstest]
+    #[case::empty(Style::new(), StyleModifier {
+        foreground: None,
+        background: None,
+        font_style: None,
+    })]
+    #[case::colours(Style::new().fg(Color::Rgb(1, 2, 3)).bg(Color::Reset), StyleModifier {
+        foreground: Some(SyntectColour { r: 1, g: 2, b: 3, a: 255 }),
+        background: None,
+        font_style: None,
+    })]
+    #[case::added_modifiers(Style::new().add_modifier(Modifier::BOLD | Modifier::DIM), StyleModifier {
+        foreground: None,
+        background: None,
+        font_style: Some(FontStyle::BOLD),
+    })]
+    #[case::removed_modifiers(Style::new().remove_modifier(Modifier::ITALIC), StyleModifier {
+        foreground: None,
+        background: None,
+        font_style: Some(FontStyle::empty()),
+    })]
+    fn check_build_theme(#[case] style: Style, #[case] expected: StyleModifier) {
+        let theme = build_theme([("source string", style)]).unwrap();
+        assert_eq!(1, theme.scopes.len());
+        assert_eq!(expected, theme.scopes[0].style);
+    }
+
+    #[test]
+    fn build_theme_rejects_invalid_selectors() {
+        let actual = build_theme([("a.b.c.d.e.f.g.h.i", Style::new())]);
+        assert!(matches!(
+            actual,
+            Err(SyntectTuiError::InvalidScopeSelector { .. })
+        ));
+    }
+}
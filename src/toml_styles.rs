@@ -0,0 +1,129 @@
+//! Styles written in TOML, as Helix themes and theme overrides write them, e.g.
+//! `comment = { fg = "gray", modifiers = ["italic"] }`.
+use crate::SyntectTuiError;
+use ratatui::style::{Color, Modifier, Style};
+
+/// Parses the style of `scope`, which is either a foreground colour, or a table of `fg` and `bg`
+/// colours, `modifiers` and an `underline`, resolving colour names with `palette`.
+pub(crate) fn parse_style(
+    scope: &str,
+    value: &toml::Value,
+    palette: Option<&toml::Table>,
+) -> Result<Style, SyntectTuiError> {
+    let invalid = |message: String| SyntectTuiError::ThemeLoading {
+        message: format!("invalid style for \"{scope}\": {message}"),
+    };
+    let colour = |value: &toml::Value| {
+        let name = value
+            .as_str()
+            .ok_or_else(|| invalid(format!("expected a colour, found a {}", value.type_str())))?;
+        parse_colour(name, palette).ok_or_else(|| invalid(format!("unknown colour \"{name}\"")))
+    };
+    let table = match value {
+        toml::Value::String(_) => return Ok(Style::new().fg(colour(value)?)),
+        toml::Value::Table(table) => table,
+        value => {
+            return Err(invalid(format!(
+                "expected a string or a table, found a {}",
+                value.type_str()
+            )))
+        }
+    };
+    let mut style = Style::new();
+    for (key, value) in table {
+        match key.as_str() {
+            "fg" => style.fg = Some(colour(value)?),
+            "bg" => style.bg = Some(colour(value)?),
+            "modifiers" => {
+                let modifiers = value.as_array().ok_or_else(|| {
+                    invalid(format!("expected modifiers, found a {}", value.type_str()))
+                })?;
+                for modifier in modifiers {
+                    let name = modifier.as_str().unwrap_or_default();
+                    style = style.add_modifier(
+                        parse_modifier(name)
+                            .ok_or_else(|| invalid(format!("unknown modifier \"{name}\"")))?,
+                    );
+                }
+            }
+            "underline" => {
+                style = style.add_modifier(Modifier::UNDERLINED);
+                #[cfg(feature = "underline-color")]
+                if let Some(underline) = value.get("color") {
+                    style = style.underline_color(colour(underline)?);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(style)
+}
+
+/// Resolves a colour from the palette, or as a hex colour or the name of an ANSI colour.
+pub(crate) fn parse_colour(name: &str, palette: Option<&toml::Table>) -> Option<Color> {
+    let name = match palette.and_then(|palette| palette.get(name)) {
+        Some(value) => value.as_str()?,
+        None => name,
+    };
+    if let Some(hex) = name.strip_prefix('#') {
+        let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        if hex.len() != 6 {
+            return None;
+        }
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    // Helix's gray is the bright black of the ANSI colours, and its light gray the white.
+    Some(match name {
+        "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "light-gray" => Color::Gray,
+        "white" => Color::White,
+        _ => return None,
+    })
+}
+
+fn parse_modifier(name: &str) -> Option<Modifier> {
+    Some(match name {
+        "bold" => Modifier::BOLD,
+        "dim" => Modifier::DIM,
+        "italic" => Modifier::ITALIC,
+        "underlined" => Modifier::UNDERLINED,
+        "slow_blink" => Modifier::SLOW_BLINK,
+        "rapid_blink" => Modifier::RAPID_BLINK,
+        "reversed" => Modifier::REVERSED,
+        "hidden" => Modifier::HIDDEN,
+        "crossed_out" => Modifier::CROSSED_OUT,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::hex("#ff8000", Some(Color::Rgb(255, 128, 0)))]
+    #[case::palette("purple", Some(Color::Rgb(180, 142, 173)))]
+    #[case::ansi("light-gray", Some(Color::Gray))]
+    #[case::default("default", Some(Color::Reset))]
+    #[case::short_hex("#fff", None)]
+    #[case::unknown("mauve", None)]
+    fn check_parse_colour(#[case] name: &str, #[case] expected: Option<Color>) {
+        let palette: toml::Table = "purple = \"#b48ead\"".parse().unwrap();
+        assert_eq!(expected, parse_colour(name, Some(&palette)));
+    }
+}
@@ -0,0 +1,1136 @@
+use crate::{
+    colour, ColourAdjustment, ColourSpace, ControlCharacterPolicy, PaletteQuantizer, ScopeOverride,
+    SyntectTuiError, TerminalProfile, Whitespace,
+};
+
+type StylePredicate = std::sync::Arc<dyn Fn(&syntect::highlighting::Style) -> bool + Send + Sync>;
+
+/// A configurable counterpart to the free conversion functions in this crate.
+///
+/// A default `StyleTranslator` behaves exactly like [translate_style](crate::translate_style) and
+/// [into_span](crate::into_span). Options are set using chained builder methods, so applications
+/// can configure a translator once and reuse it wherever they convert highlighted text.
+///
+/// The document-level highlighters take a translator to convert their lines with too, set with
+/// [LineConversion::translator](crate::LineConversion::translator).
+///
+/// # Examples
+/// Basic usage:
+/// ```
+/// let translator = syntect_tui::StyleTranslator::new();
+/// let input = syntect::highlighting::Style {
+///     foreground: syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 },
+///     background: syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 0 },
+///     font_style: syntect::highlighting::FontStyle::BOLD
+/// };
+/// assert_eq!(
+///     syntect_tui::translate_style(input),
+///     translator.translate_style(input)
+/// );
+/// ```
+#[derive(Clone)]
+pub struct StyleTranslator {
+    plain: bool,
+    font_style_overrides: Vec<(syntect::highlighting::FontStyle, ratatui::style::Modifier)>,
+    modifier_rules: Vec<(StylePredicate, ratatui::style::Modifier)>,
+    pub(crate) alpha_threshold: u8,
+    pub(crate) alpha_strategy: AlphaStrategy,
+    colour_adjustment: ColourAdjustment,
+    terminal_profile: TerminalProfile,
+    minimum_contrast: Option<f32>,
+    pub(crate) quantizer: Option<PaletteQuantizer>,
+    colour_space: Option<ColourSpace>,
+    pub(crate) tab_width: Option<usize>,
+    pub(crate) merge_spans: bool,
+    pub(crate) whitespace: Option<Whitespace>,
+    control_characters: ControlCharacterPolicy,
+    pub(crate) scope_overrides: Vec<(syntect::highlighting::ScopeSelectors, ScopeOverride)>,
+    #[cfg(feature = "underline-color")]
+    underline_colour: UnderlineColour,
+    #[cfg(feature = "underline-color")]
+    underline_colour_rules: Vec<(StylePredicate, UnderlineColour)>,
+    memo: StyleMemo,
+}
+
+impl Default for StyleTranslator {
+    fn default() -> Self {
+        Self {
+            plain: false,
+            font_style_overrides: Vec::new(),
+            modifier_rules: Vec::new(),
+            alpha_threshold: 1,
+            alpha_strategy: AlphaStrategy::Drop,
+            colour_adjustment: ColourAdjustment::default(),
+            terminal_profile: TerminalProfile::default(),
+            minimum_contrast: None,
+            quantizer: None,
+            colour_space: None,
+            tab_width: None,
+            merge_spans: false,
+            whitespace: None,
+            control_characters: ControlCharacterPolicy::Keep,
+            scope_overrides: Vec::new(),
+            #[cfg(feature = "underline-color")]
+            underline_colour: UnderlineColour::Foreground,
+            #[cfg(feature = "underline-color")]
+            underline_colour_rules: Vec::new(),
+            memo: StyleMemo::default(),
+        }
+    }
+}
+
+impl std::fmt::Debug for StyleTranslator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("StyleTranslator");
+        debug
+            .field("plain", &self.plain)
+            .field("font_style_overrides", &self.font_style_overrides)
+            .field("modifier_rules", &self.modifier_rules.len())
+            .field("alpha_threshold", &self.alpha_threshold)
+            .field("alpha_strategy", &self.alpha_strategy)
+            .field("colour_adjustment", &self.colour_adjustment)
+            .field("terminal_profile", &self.terminal_profile)
+            .field("minimum_contrast", &self.minimum_contrast)
+            .field("quantizer", &self.quantizer)
+            .field("colour_space", &self.colour_space)
+            .field("tab_width", &self.tab_width)
+            .field("merge_spans", &self.merge_spans)
+            .field("whitespace", &self.whitespace)
+            .field("control_characters", &self.control_characters)
+            .field("scope_overrides", &self.scope_overrides);
+        #[cfg(feature = "underline-color")]
+        debug
+            .field("underline_colour", &self.underline_colour)
+            .field("underline_colour_rules", &self.underline_colour_rules.len());
+        debug.finish()
+    }
+}
+
+/// The translations a [StyleTranslator] has already made, keyed by the colours and font style
+/// they were made from, as a theme only has a few dozen distinct styles.
+///
+/// Translations depend on the translator's options, so the memo is cleared whenever an option
+/// that affects them is set, and clones start with an empty memo since they may be reconfigured.
+/// Lookups only take a read lock, so threads sharing a translator, e.g. when highlighting in
+/// parallel, only wait for each other on the few styles they have not seen yet.
+#[derive(Default)]
+struct StyleMemo(std::sync::RwLock<std::collections::HashMap<StyleMemoKey, ratatui::style::Style>>);
+
+type StyleMemoKey = (
+    syntect::highlighting::Color,
+    syntect::highlighting::Color,
+    u8,
+);
+
+/// The number of translations a memo keeps before it starts over, in case a long-lived
+/// translator sees the styles of many themes.
+const STYLE_MEMO_CAPACITY: usize = 1024;
+
+impl StyleMemo {
+    fn key(style: &syntect::highlighting::Style) -> StyleMemoKey {
+        (style.foreground, style.background, style.font_style.bits())
+    }
+
+    fn get(&self, style: &syntect::highlighting::Style) -> Option<ratatui::style::Style> {
+        let memo = self
+            .0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        memo.get(&Self::key(style)).copied()
+    }
+
+    fn insert(&self, style: syntect::highlighting::Style, translated: ratatui::style::Style) {
+        let mut memo = self
+            .0
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if memo.len() >= STYLE_MEMO_CAPACITY {
+            memo.clear();
+        }
+        memo.insert(Self::key(&style), translated);
+    }
+
+    fn clear(&mut self) {
+        self.0
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.0
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+}
+
+impl Clone for StyleMemo {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// The colours a [StyleTranslator] translates a style into, see
+/// [StyleTranslator::translate_colours].
+pub(crate) struct TranslatedColours {
+    /// The foreground, before quantization.
+    pub(crate) fg: Option<ratatui::style::Color>,
+    /// The background, before quantization.
+    pub(crate) bg: Option<ratatui::style::Color>,
+    pub(crate) quantized_bg: Option<ratatui::style::Color>,
+    /// The modifier the foreground's [AlphaStrategy] adds.
+    pub(crate) fg_modifier: ratatui::style::Modifier,
+}
+
+/// Determines how a [StyleTranslator] handles colours whose alpha value is below its
+/// [alpha threshold](StyleTranslator::alpha_threshold).
+///
+/// Terminals cannot render translucency, so every strategy trades accuracy for predictability in a
+/// different way. Fully transparent colours (i.e. an alpha value of `0`) remain colourless under
+/// every strategy except for foregrounds under `Dim`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlphaStrategy {
+    /// Translucent colours are dropped, i.e. translated into `None`. This is the default.
+    Drop,
+    /// Translucent colours are composited over an opaque colour. Foregrounds are composited over
+    /// their background (itself composited over `backdrop`), while backgrounds are composited
+    /// over `backdrop`, which is typically the theme or terminal background.
+    Blend {
+        backdrop: syntect::highlighting::Color,
+    },
+    /// Translucent foregrounds keep their RGB value and gain the `DIM` modifier, while
+    /// translucent backgrounds are dropped.
+    Dim,
+}
+
+/// Determines which colour a [StyleTranslator] uses for a translated style's `underline_color`.
+///
+/// Syntect styles have no separate underline colour, so by default the foreground is used.
+#[cfg(feature = "underline-color")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnderlineColour {
+    /// The underline matches the translated foreground. This is the default.
+    #[default]
+    Foreground,
+    /// The underline matches the translated background.
+    Background,
+    /// The underline is drawn in the given colour, translated like any other syntect colour.
+    Fixed(syntect::highlighting::Color),
+    /// The underline colour is left unset, so the terminal decides.
+    Unset,
+}
+
+impl StyleTranslator {
+    /// Creates a translator that behaves like the free conversion functions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a translator that honours the user's colour preferences.
+    ///
+    /// Plain mode (see [StyleTranslator::plain]) is enabled if the
+    /// [`NO_COLOR`](https://no-color.org) environment variable is set to a non-empty value, or if
+    /// `TERM` is set to `dumb`. The document highlighters, e.g.
+    /// [IncrementalHighlighter](crate::IncrementalHighlighter), honour it when given the
+    /// translator with their `translator` method.
+    pub fn from_env() -> Self {
+        Self::new().plain(colour_disabled(
+            std::env::var_os("NO_COLOR"),
+            std::env::var_os("TERM"),
+        ))
+    }
+
+    /// Enables or disables plain mode.
+    ///
+    /// In plain mode every syntect style is translated into an empty
+    /// [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html),
+    /// so highlighted text is rendered unstyled while going through the same code path as
+    /// styled text, and lines highlighted with the translator get no theme background. Plain mode
+    /// never fails, even for unsupported font styles.
+    ///
+    /// # Examples
+    /// ```
+    /// let translator = syntect_tui::StyleTranslator::new().plain(true);
+    /// let input = syntect::highlighting::Style {
+    ///     foreground: syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 },
+    ///     background: syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 0 },
+    ///     font_style: syntect::highlighting::FontStyle::BOLD
+    /// };
+    /// let expected = ratatui::text::Span::raw("hello");
+    /// let actual = translator.into_span((input, "hello")).unwrap();
+    /// assert_eq!(expected, actual);
+    /// ```
+    pub fn plain(mut self, plain: bool) -> Self {
+        self.plain = plain;
+        self.memo.clear();
+        self
+    }
+
+    /// Returns `true` if this translator is in plain mode.
+    pub fn is_plain(&self) -> bool {
+        self.plain
+    }
+
+    /// Overrides the [ratatui::style::Modifier](https://docs.rs/ratatui/latest/ratatui/style/struct.Modifier.html)
+    /// that a single syntect font style flag is translated into.
+    ///
+    /// `font_style` should be exactly one of `BOLD`, `ITALIC` or `UNDERLINE`; overrides for
+    /// compositions of flags are never applied. Flags without an override keep their built-in
+    /// translation, and registering the same flag twice keeps the latest override.
+    ///
+    /// # Examples
+    /// Rendering italics as dim text for terminal fonts without an italic face:
+    /// ```
+    /// use ratatui::style::Modifier;
+    /// use syntect::highlighting::FontStyle;
+    ///
+    /// let translator = syntect_tui::StyleTranslator::new()
+    ///     .map_font_style(FontStyle::ITALIC, Modifier::DIM);
+    /// let actual = translator.translate_font_style(FontStyle::BOLD | FontStyle::ITALIC).unwrap();
+    /// assert_eq!(Modifier::BOLD | Modifier::DIM, actual);
+    /// ```
+    pub fn map_font_style(
+        mut self,
+        font_style: syntect::highlighting::FontStyle,
+        modifier: ratatui::style::Modifier,
+    ) -> Self {
+        self.font_style_overrides
+            .retain(|(existing, _)| *existing != font_style);
+        self.font_style_overrides.push((font_style, modifier));
+        self.memo.clear();
+        self
+    }
+
+    /// Adds `modifier` to every translated style whose source syntect style satisfies `predicate`.
+    ///
+    /// Syntect only knows about bold, italic and underlined text, so rules are how applications
+    /// opt into richer terminal modifiers such as `CROSSED_OUT`, `DIM` or `REVERSED`. Rules are
+    /// additive: every matching rule contributes its modifier on top of the translated font style.
+    ///
+    /// # Examples
+    /// Dimming text drawn in a theme's comment colour:
+    /// ```
+    /// use ratatui::style::Modifier;
+    /// use syntect::highlighting::{Color, FontStyle, Style};
+    ///
+    /// let comment = Color { r: 101, g: 115, b: 126, a: 255 };
+    /// let translator = syntect_tui::StyleTranslator::new()
+    ///     .add_modifier_when(move |style| style.foreground == comment, Modifier::DIM);
+    /// let input = Style {
+    ///     foreground: comment,
+    ///     background: Color { r: 0, g: 0, b: 0, a: 0 },
+    ///     font_style: FontStyle::ITALIC
+    /// };
+    /// let actual = translator.translate_style(input).unwrap();
+    /// assert_eq!(Modifier::ITALIC | Modifier::DIM, actual.add_modifier);
+    /// ```
+    pub fn add_modifier_when<F>(mut self, predicate: F, modifier: ratatui::style::Modifier) -> Self
+    where
+        F: Fn(&syntect::highlighting::Style) -> bool + Send + Sync + 'static,
+    {
+        self.modifier_rules
+            .push((std::sync::Arc::new(predicate), modifier));
+        self.memo.clear();
+        self
+    }
+
+    /// Renders foregrounds whose alpha value is below `threshold` as dimmed RGB text.
+    ///
+    /// By default a foreground with an alpha value of `0` is dropped and every other alpha value
+    /// is ignored. With this option enabled, translucent foregrounds keep their RGB value and gain
+    /// the `DIM` modifier instead, so faded text in a theme still reads as faded in the terminal.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::{Color, Modifier};
+    ///
+    /// let translator = syntect_tui::StyleTranslator::new().dim_translucent_foreground(128);
+    /// let input = syntect::highlighting::Style {
+    ///     foreground: syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 64 },
+    ///     background: syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 0 },
+    ///     font_style: syntect::highlighting::FontStyle::empty()
+    /// };
+    /// let actual = translator.translate_style(input).unwrap();
+    /// assert_eq!(Some(Color::Rgb(255, 0, 0)), actual.fg);
+    /// assert_eq!(Modifier::DIM, actual.add_modifier);
+    /// ```
+    pub fn dim_translucent_foreground(self, threshold: u8) -> Self {
+        self.alpha_threshold(threshold)
+            .alpha_strategy(AlphaStrategy::Dim)
+    }
+
+    /// Sets the alpha value below which a syntect colour is considered translucent.
+    ///
+    /// Translucent colours are translated according to the translator's [AlphaStrategy]. The
+    /// default threshold is `1`, so only fully transparent colours are considered translucent.
+    pub fn alpha_threshold(mut self, threshold: u8) -> Self {
+        self.alpha_threshold = threshold;
+        self.memo.clear();
+        self
+    }
+
+    /// Sets how translucent colours (see [StyleTranslator::alpha_threshold]) are translated.
+    ///
+    /// # Examples
+    /// Blending a translucent foreground into its background:
+    /// ```
+    /// use ratatui::style::Color;
+    /// use syntect_tui::AlphaStrategy;
+    ///
+    /// let backdrop = syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 255 };
+    /// let translator = syntect_tui::StyleTranslator::new()
+    ///     .alpha_threshold(255)
+    ///     .alpha_strategy(AlphaStrategy::Blend { backdrop });
+    /// let input = syntect::highlighting::Style {
+    ///     foreground: syntect::highlighting::Color { r: 255, g: 255, b: 255, a: 128 },
+    ///     background: syntect::highlighting::Color { r: 0, g: 0, b: 255, a: 255 },
+    ///     font_style: syntect::highlighting::FontStyle::empty()
+    /// };
+    /// let actual = translator.translate_style(input).unwrap();
+    /// assert_eq!(Some(Color::Rgb(128, 128, 255)), actual.fg);
+    /// assert_eq!(Some(Color::Rgb(0, 0, 255)), actual.bg);
+    /// ```
+    pub fn alpha_strategy(mut self, strategy: AlphaStrategy) -> Self {
+        self.alpha_strategy = strategy;
+        self.memo.clear();
+        self
+    }
+
+    /// Sets the [ColourAdjustment] applied to every colour this translator produces.
+    ///
+    /// # Examples
+    /// Brightening a theme that is too dark:
+    /// ```
+    /// use ratatui::style::Color;
+    ///
+    /// let translator = syntect_tui::StyleTranslator::new().adjust_colours(
+    ///     syntect_tui::ColourAdjustment { brightness: 1.5, ..Default::default() }
+    /// );
+    /// let input = syntect::highlighting::Color { r: 0, g: 0, b: 128, a: 255 };
+    /// assert_eq!(Some(Color::Rgb(0, 0, 192)), translator.translate_colour(input));
+    /// ```
+    pub fn adjust_colours(mut self, adjustment: ColourAdjustment) -> Self {
+        self.colour_adjustment = adjustment;
+        self.memo.clear();
+        self
+    }
+
+    /// Adjusts translated colours for the terminal they are shown on with `profile`, e.g. one
+    /// [detected](TerminalProfile::from_env) from the environment.
+    ///
+    /// The profile is applied after any [ColourAdjustment], and before
+    /// [minimum contrast](StyleTranslator::minimum_contrast) and quantization.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::Color;
+    /// use syntect_tui::{StyleTranslator, TerminalProfile};
+    ///
+    /// let profile = TerminalProfile { background_blend: 0.5, ..Default::default() };
+    /// let translator = StyleTranslator::new().terminal_profile(profile);
+    /// let input = syntect::highlighting::Color { r: 40, g: 40, b: 60, a: 255 };
+    /// assert_eq!(Some(Color::Rgb(20, 20, 30)), translator.translate_colour(input));
+    /// ```
+    pub fn terminal_profile(mut self, profile: TerminalProfile) -> Self {
+        self.terminal_profile = profile;
+        self.memo.clear();
+        self
+    }
+
+    /// Ensures translated foregrounds meet a minimum
+    /// [WCAG contrast ratio](https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio) against their
+    /// background.
+    ///
+    /// Foregrounds that fall short are lightened or darkened (whichever can achieve more contrast)
+    /// by the smallest amount that meets `ratio`. Ratios range from `1.0` to `21.0`; WCAG
+    /// recommends at least `4.5` for body text. The check only applies when both the foreground
+    /// and background translate into RGB colours, since the terminal's own background is unknown.
+    ///
+    /// # Examples
+    /// ```
+    /// let translator = syntect_tui::StyleTranslator::new().minimum_contrast(4.5);
+    /// let input = syntect::highlighting::Style {
+    ///     foreground: syntect::highlighting::Color { r: 40, g: 40, b: 60, a: 255 },
+    ///     background: syntect::highlighting::Color { r: 30, g: 30, b: 30, a: 255 },
+    ///     font_style: syntect::highlighting::FontStyle::empty()
+    /// };
+    /// let actual = translator.translate_style(input).unwrap();
+    /// assert_ne!(Some(ratatui::style::Color::Rgb(40, 40, 60)), actual.fg);
+    /// ```
+    pub fn minimum_contrast(mut self, ratio: f32) -> Self {
+        self.minimum_contrast = Some(ratio);
+        self.memo.clear();
+        self
+    }
+
+    /// Snaps every colour this translator produces to the nearest colour of `quantizer`, e.g. to
+    /// restrict highlighted text to an application's own palette.
+    ///
+    /// Quantization is applied last, after any [ColourAdjustment] and
+    /// [minimum contrast](StyleTranslator::minimum_contrast), so it may reduce contrast again.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::style::Color;
+    ///
+    /// let quantizer = syntect_tui::PaletteQuantizer::new([Color::Black, Color::Rgb(204, 36, 29)]);
+    /// let translator = syntect_tui::StyleTranslator::new().quantize_colours(quantizer);
+    /// let input = syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 };
+    /// assert_eq!(Some(Color::Rgb(204, 36, 29)), translator.translate_colour(input));
+    /// ```
+    pub fn quantize_colours(mut self, quantizer: PaletteQuantizer) -> Self {
+        self.quantizer = Some(quantizer);
+        self.memo.clear();
+        self
+    }
+
+    /// Sets the [ColourSpace] in which translucent colours are blended (see
+    /// [AlphaStrategy::Blend]) and distances are measured when
+    /// [quantizing colours](StyleTranslator::quantize_colours), overriding the quantizer's own
+    /// colour space. Defaults to [ColourSpace::Srgb].
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect_tui::{AlphaStrategy, ColourSpace};
+    ///
+    /// let black = syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 255 };
+    /// let translator = syntect_tui::StyleTranslator::new()
+    ///     .alpha_strategy(AlphaStrategy::Blend { backdrop: black })
+    ///     .alpha_threshold(255)
+    ///     .colour_space(ColourSpace::Linear);
+    /// let white = syntect::highlighting::Color { r: 255, g: 255, b: 255, a: 128 };
+    /// assert_eq!(
+    ///     Some(ratatui::style::Color::Rgb(188, 188, 188)),
+    ///     translator.translate_colour(white)
+    /// );
+    /// ```
+    pub fn colour_space(mut self, colour_space: ColourSpace) -> Self {
+        self.colour_space = Some(colour_space);
+        self.memo.clear();
+        self
+    }
+
+    /// Sets how control characters in highlighted text are handled by
+    /// [StyleTranslator::into_span] and [StyleTranslator::into_line].
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect_tui::ControlCharacterPolicy;
+    ///
+    /// let translator = syntect_tui::StyleTranslator::new()
+    ///     .sanitize_control_characters(ControlCharacterPolicy::Escape);
+    /// let style = syntect::highlighting::Style::default();
+    /// let span = translator.into_span((style, "\u{1b}[2J")).unwrap();
+    /// assert_eq!("^[[2J", span.content);
+    /// ```
+    pub fn sanitize_control_characters(mut self, policy: ControlCharacterPolicy) -> Self {
+        self.control_characters = policy;
+        self
+    }
+
+    /// Sets which colour is used for every translated style's `underline_color`.
+    #[cfg(feature = "underline-color")]
+    pub fn underline_colour(mut self, underline_colour: UnderlineColour) -> Self {
+        self.underline_colour = underline_colour;
+        self.memo.clear();
+        self
+    }
+
+    /// Uses `underline_colour` for translated styles whose source syntect style satisfies
+    /// `predicate`, overriding [StyleTranslator::underline_colour].
+    ///
+    /// When several rules match, the most recently added one wins.
+    ///
+    /// # Examples
+    /// Drawing red undercurls beneath text styled like a theme's invalid tokens:
+    /// ```
+    /// use ratatui::style::{Color, Modifier};
+    /// use syntect::highlighting::FontStyle;
+    /// use syntect_tui::UnderlineColour;
+    ///
+    /// let red = syntect::highlighting::Color { r: 255, g: 0, b: 0, a: 255 };
+    /// let translator = syntect_tui::StyleTranslator::new().underline_colour_when(
+    ///     |style| style.font_style.contains(FontStyle::UNDERLINE),
+    ///     UnderlineColour::Fixed(red),
+    /// );
+    /// let input = syntect::highlighting::Style {
+    ///     foreground: syntect::highlighting::Color { r: 255, g: 255, b: 255, a: 255 },
+    ///     background: syntect::highlighting::Color { r: 0, g: 0, b: 0, a: 0 },
+    ///     font_style: FontStyle::UNDERLINE
+    /// };
+    /// let actual = translator.translate_style(input).unwrap();
+    /// assert_eq!(Some(Color::Rgb(255, 255, 255)), actual.fg);
+    /// assert_eq!(Some(Color::Rgb(255, 0, 0)), actual.underline_color);
+    /// ```
+    #[cfg(feature = "underline-color")]
+    pub fn underline_colour_when<F>(
+        mut self,
+        predicate: F,
+        underline_colour: UnderlineColour,
+    ) -> Self
+    where
+        F: Fn(&syntect::highlighting::Style) -> bool + Send + Sync + 'static,
+    {
+        self.underline_colour_rules
+            .push((std::sync::Arc::new(predicate), underline_colour));
+        self.memo.clear();
+        self
+    }
+
+    /// Converts a
+    /// [syntect::highlighting::Color](https://docs.rs/syntect/latest/syntect/highlighting/struct.Color.html)
+    /// into a [ratatui::style::Color](https://docs.rs/ratatui/latest/ratatui/style/enum.Color.html)
+    /// according to this translator's options.
+    ///
+    /// The colour is translated as a background would be, so translucent colours are handled by
+    /// the translator's [AlphaStrategy] without ever producing a modifier.
+    pub fn translate_colour(
+        &self,
+        syntect_color: syntect::highlighting::Color,
+    ) -> Option<ratatui::style::Color> {
+        self.translate_background(syntect_color).map(|colour| {
+            let colour = self.colour_adjustment.apply(colour);
+            self.quantize(self.terminal_profile.apply_background(colour))
+        })
+    }
+
+    /// Converts a
+    /// [syntect::highlighting::FontStyle](https://docs.rs/syntect/latest/syntect/highlighting/struct.FontStyle.html)
+    /// into a [ratatui::style::Modifier](https://docs.rs/ratatui/latest/ratatui/style/struct.Modifier.html),
+    /// applying any overrides registered with [StyleTranslator::map_font_style].
+    ///
+    /// # Errors
+    /// Can return `SyntectTuiError::UnknownFontStyle` under the same conditions as
+    /// [translate_font_style](crate::translate_font_style).
+    pub fn translate_font_style(
+        &self,
+        syntect_font_style: syntect::highlighting::FontStyle,
+    ) -> Result<ratatui::style::Modifier, SyntectTuiError> {
+        use syntect::highlighting::FontStyle;
+        let default = crate::translate_font_style(syntect_font_style)?;
+        if self.font_style_overrides.is_empty() {
+            return Ok(default);
+        }
+        [FontStyle::BOLD, FontStyle::ITALIC, FontStyle::UNDERLINE]
+            .into_iter()
+            .filter(|flag| syntect_font_style.contains(*flag))
+            .try_fold(ratatui::style::Modifier::empty(), |acc, flag| {
+                let modifier = match self.font_style_override(flag) {
+                    Some(modifier) => modifier,
+                    None => crate::translate_font_style(flag)?,
+                };
+                Ok(acc | modifier)
+            })
+    }
+
+    fn translate_foreground(
+        &self,
+        syntect_style: &syntect::highlighting::Style,
+    ) -> (Option<ratatui::style::Color>, ratatui::style::Modifier) {
+        let colour = syntect_style.foreground;
+        let colour = match self.alpha_strategy {
+            _ if colour.a >= self.alpha_threshold => crate::translate_colour(colour),
+            AlphaStrategy::Drop => None,
+            AlphaStrategy::Dim => {
+                return (Some(colour::rgb(colour)), ratatui::style::Modifier::DIM);
+            }
+            AlphaStrategy::Blend { backdrop } => {
+                let backdrop = colour::blend(
+                    syntect_style.background,
+                    backdrop,
+                    self.colour_space.unwrap_or_default(),
+                );
+                self.translate_blended(colour, backdrop)
+            }
+        };
+        (colour, ratatui::style::Modifier::empty())
+    }
+
+    /// Translates the colours of `syntect_style`, keeping them as they are before quantization so
+    /// that [LossReport](crate::LossReport)s can tell what quantization changed.
+    pub(crate) fn translate_colours(
+        &self,
+        syntect_style: &syntect::highlighting::Style,
+    ) -> TranslatedColours {
+        let (fg, fg_modifier) = self.translate_foreground(syntect_style);
+        let bg = self
+            .translate_background(syntect_style.background)
+            .map(|colour| {
+                let colour = self.colour_adjustment.apply(colour);
+                self.terminal_profile.apply_background(colour)
+            });
+        let quantized_bg = bg.map(|colour| self.quantize(colour));
+        let fg = self.enforce_contrast(
+            fg.map(|colour| {
+                let colour = self.colour_adjustment.apply(colour);
+                self.terminal_profile.apply_foreground(colour)
+            }),
+            quantized_bg,
+        );
+        TranslatedColours {
+            fg,
+            bg,
+            quantized_bg,
+            fg_modifier,
+        }
+    }
+
+    fn translate_background(
+        &self,
+        syntect_color: syntect::highlighting::Color,
+    ) -> Option<ratatui::style::Color> {
+        match self.alpha_strategy {
+            _ if syntect_color.a >= self.alpha_threshold => crate::translate_colour(syntect_color),
+            AlphaStrategy::Blend { backdrop } => self.translate_blended(syntect_color, backdrop),
+            AlphaStrategy::Drop | AlphaStrategy::Dim => None,
+        }
+    }
+
+    fn translate_blended(
+        &self,
+        syntect_color: syntect::highlighting::Color,
+        backdrop: syntect::highlighting::Color,
+    ) -> Option<ratatui::style::Color> {
+        (syntect_color.a > 0).then(|| {
+            colour::rgb(colour::blend(
+                syntect_color,
+                backdrop,
+                self.colour_space.unwrap_or_default(),
+            ))
+        })
+    }
+
+    pub(crate) fn quantize(&self, colour: ratatui::style::Color) -> ratatui::style::Color {
+        match &self.quantizer {
+            Some(quantizer) => match self.colour_space {
+                Some(colour_space) => quantizer.quantize_in(colour, colour_space),
+                None => quantizer.quantize(colour),
+            },
+            None => colour,
+        }
+    }
+
+    fn enforce_contrast(
+        &self,
+        fg: Option<ratatui::style::Color>,
+        bg: Option<ratatui::style::Color>,
+    ) -> Option<ratatui::style::Color> {
+        use ratatui::style::Color;
+        match (self.minimum_contrast, fg, bg) {
+            (Some(minimum), Some(Color::Rgb(fr, fg, fb)), Some(Color::Rgb(br, bg, bb))) => {
+                let (r, g, b) = colour::enforce_contrast((fr, fg, fb), (br, bg, bb), minimum);
+                Some(Color::Rgb(r, g, b))
+            }
+            _ => fg,
+        }
+    }
+
+    #[cfg(feature = "underline-color")]
+    fn translate_underline_colour(
+        &self,
+        syntect_style: &syntect::highlighting::Style,
+        fg: Option<ratatui::style::Color>,
+        bg: Option<ratatui::style::Color>,
+    ) -> Option<ratatui::style::Color> {
+        let underline_colour = self
+            .underline_colour_rules
+            .iter()
+            .rev()
+            .find(|(predicate, _)| predicate(syntect_style))
+            .map_or(self.underline_colour, |(_, underline_colour)| {
+                *underline_colour
+            });
+        match underline_colour {
+            UnderlineColour::Foreground => fg,
+            UnderlineColour::Background => bg,
+            UnderlineColour::Fixed(colour) => self.translate_colour(colour),
+            UnderlineColour::Unset => None,
+        }
+    }
+
+    fn rule_modifiers(
+        &self,
+        syntect_style: &syntect::highlighting::Style,
+    ) -> ratatui::style::Modifier {
+        self.modifier_rules
+            .iter()
+            .filter(|(predicate, _)| predicate(syntect_style))
+            .fold(ratatui::style::Modifier::empty(), |acc, (_, modifier)| {
+                acc | *modifier
+            })
+    }
+
+    fn font_style_override(
+        &self,
+        flag: syntect::highlighting::FontStyle,
+    ) -> Option<ratatui::style::Modifier> {
+        self.font_style_overrides
+            .iter()
+            .find(|(font_style, _)| *font_style == flag)
+            .map(|(_, modifier)| *modifier)
+    }
+
+    /// Converts a
+    /// [syntect::highlighting::Style](https://docs.rs/syntect/latest/syntect/highlighting/struct.Style.html)
+    /// into a [ratatui::style::Style](https://docs.rs/ratatui/latest/ratatui/style/struct.Style.html)
+    /// according to this translator's options.
+    ///
+    /// # Errors
+    /// Can return `SyntectTuiError::UnknownFontStyle` under the same conditions as
+    /// [translate_style](crate::translate_style).
+    pub fn translate_style(
+        &self,
+        syntect_style: syntect::highlighting::Style,
+    ) -> Result<ratatui::style::Style, SyntectTuiError> {
+        if let Some(style) = self.memo.get(&syntect_style) {
+            return Ok(style);
+        }
+        let style = self.translate_style_uncached(syntect_style)?;
+        self.memo.insert(syntect_style, style);
+        Ok(style)
+    }
+
+    fn translate_style_uncached(
+        &self,
+        syntect_style: syntect::highlighting::Style,
+    ) -> Result<ratatui::style::Style, SyntectTuiError> {
+        if self.plain {
+            return Ok(ratatui::style::Style::default());
+        }
+        let colours = self.translate_colours(&syntect_style);
+        let (bg, fg_modifier) = (colours.quantized_bg, colours.fg_modifier);
+        let fg = colours.fg.map(|colour| self.quantize(colour));
+        Ok(ratatui::style::Style {
+            fg,
+            bg,
+            #[cfg(feature = "underline-color")]
+            underline_color: self.translate_underline_colour(&syntect_style, fg, bg),
+            add_modifier: self.translate_font_style(syntect_style.font_style)?
+                | self.rule_modifiers(&syntect_style)
+                | fg_modifier,
+            sub_modifier: ratatui::style::Modifier::empty(),
+        })
+    }
+
+    /// Converts a highlighted line segment into a
+    /// [ratatui::text::Span](https://docs.rs/ratatui/latest/ratatui/text/struct.Span.html)
+    /// according to this translator's options.
+    ///
+    /// # Errors
+    /// Can return `SyntectTuiError::UnknownFontStyle` under the same conditions as
+    /// [into_span](crate::into_span).
+    pub fn into_span<'a>(
+        &self,
+        (style, content): (syntect::highlighting::Style, &'a str),
+    ) -> Result<ratatui::text::Span<'a>, SyntectTuiError> {
+        Ok(self.styled_span(content, self.translate_style(style)?))
+    }
+
+    /// Returns a span of `content` with its control characters handled according to this
+    /// translator's options, which borrows `content` unless they were changed.
+    pub(crate) fn styled_span<'a>(
+        &self,
+        content: &'a str,
+        style: ratatui::style::Style,
+    ) -> ratatui::text::Span<'a> {
+        ratatui::text::Span::styled(self.control_characters.apply(content), style)
+    }
+
+    /// A lossy counterpart to [StyleTranslator::translate_style] that never fails.
+    ///
+    /// See [translate_style_lossy](crate::translate_style_lossy) for details.
+    pub fn translate_style_lossy(
+        &self,
+        syntect_style: syntect::highlighting::Style,
+    ) -> ratatui::style::Style {
+        // Masked font styles only contain supported bits, so translation cannot fail.
+        self.translate_style(crate::mask_font_style(syntect_style))
+            .unwrap_or_default()
+    }
+
+    /// A lossy counterpart to [StyleTranslator::into_span] that never fails.
+    ///
+    /// See [into_span_lossy](crate::into_span_lossy) for details.
+    pub fn into_span_lossy<'a>(
+        &self,
+        (style, content): (syntect::highlighting::Style, &'a str),
+    ) -> ratatui::text::Span<'a> {
+        self.styled_span(content, self.translate_style_lossy(style))
+    }
+}
+
+fn colour_disabled(no_color: Option<std::ffi::OsString>, term: Option<std::ffi::OsString>) -> bool {
+    no_color.is_some_and(|value| !value.is_empty()) || term.is_some_and(|value| value == "dumb")
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::{Color, Modifier};
+    use syntect::highlighting::{Color as SyntectColour, FontStyle, Style as SyntectStyle};
+
+    fn fake_syntect_style(font_style: FontStyle) -> SyntectStyle {
+        SyntectStyle {
+            font_style,
+            foreground: SyntectColour {
+                r: 12,
+                g: 123,
+                b: 234,
+                a: 255,
+            },
+            background: SyntectColour {
+                r: 123,
+                g: 234,
+                b: 12,
+                a: 255,
+            },
+        }
+    }
+
+    #[test]
+    fn lossy_conversion_masks_unknown_font_style_bits() {
+        let translator = StyleTranslator::new()
+            .sanitize_control_characters(ControlCharacterPolicy::Remove)
+            .map_font_style(FontStyle::BOLD, Modifier::REVERSED);
+        let style = fake_syntect_style(unsafe { FontStyle::from_bits_unchecked(0b1001) });
+        assert!(translator.into_span((style, "a\u{7}b")).is_err());
+        let span = translator.into_span_lossy((style, "a\u{7}b"));
+        assert_eq!("ab", span.content);
+        assert_eq!(
+            translator
+                .translate_style(fake_syntect_style(FontStyle::BOLD))
+                .unwrap(),
+            span.style
+        );
+        assert_eq!(Modifier::REVERSED, span.style.add_modifier);
+    }
+
+    #[test]
+    fn default_translator_matches_free_functions() {
+        let input = fake_syntect_style(FontStyle::BOLD);
+        assert_eq!(
+            crate::translate_style(input),
+            StyleTranslator::new().translate_style(input)
+        );
+    }
+
+    #[rstest]
+    #[case::supported(FontStyle::BOLD | FontStyle::ITALIC)]
+    #[case::unsupported(unsafe { FontStyle::from_bits_unchecked(254) })]
+    fn plain_translator_ignores_style(#[case] font_style: FontStyle) {
+        let translator = StyleTranslator::new().plain(true);
+        let actual = translator.into_span((fake_syntect_style(font_style), "plain"));
+        assert_eq!(Ok(ratatui::text::Span::raw("plain")), actual);
+    }
+
+    #[rstest]
+    #[case::unmapped(FontStyle::BOLD, Ok(Modifier::BOLD))]
+    #[case::mapped(FontStyle::ITALIC, Ok(Modifier::DIM))]
+    #[case::mixed(
+        FontStyle::BOLD | FontStyle::ITALIC | FontStyle::UNDERLINE,
+        Ok(Modifier::BOLD | Modifier::DIM | Modifier::UNDERLINED)
+    )]
+    #[case::err(
+        unsafe { FontStyle::from_bits_unchecked(254) },
+        Err(SyntectTuiError::UnknownFontStyle { bits: 254 })
+    )]
+    fn check_mapped_font_style(
+        #[case] input: FontStyle,
+        #[case] expected: Result<Modifier, SyntectTuiError>,
+    ) {
+        let translator = StyleTranslator::new()
+            .map_font_style(FontStyle::ITALIC, Modifier::REVERSED)
+            .map_font_style(FontStyle::ITALIC, Modifier::DIM);
+        assert_eq!(expected, translator.translate_font_style(input));
+    }
+
+    #[rstest]
+    #[case::no_match(FontStyle::empty(), Modifier::empty())]
+    #[case::single_match(FontStyle::ITALIC, Modifier::ITALIC | Modifier::DIM)]
+    #[case::multiple_matches(
+        FontStyle::BOLD | FontStyle::ITALIC,
+        Modifier::BOLD | Modifier::ITALIC | Modifier::DIM | Modifier::CROSSED_OUT
+    )]
+    fn check_modifier_rules(#[case] font_style: FontStyle, #[case] expected: Modifier) {
+        let translator = StyleTranslator::new()
+            .add_modifier_when(
+                |style| style.font_style.contains(FontStyle::ITALIC),
+                Modifier::DIM,
+            )
+            .add_modifier_when(
+                |style| style.font_style.contains(FontStyle::BOLD),
+                Modifier::CROSSED_OUT,
+            );
+        let actual = translator.translate_style(fake_syntect_style(font_style));
+        assert_eq!(Ok(expected), actual.map(|style| style.add_modifier));
+    }
+
+    #[rstest]
+    #[case::transparent(0, Some(Color::Rgb(12, 123, 234)), Modifier::DIM)]
+    #[case::translucent(127, Some(Color::Rgb(12, 123, 234)), Modifier::DIM)]
+    #[case::threshold(128, Some(Color::Rgb(12, 123, 234)), Modifier::empty())]
+    #[case::opaque(255, Some(Color::Rgb(12, 123, 234)), Modifier::empty())]
+    fn check_dim_translucent_foreground(
+        #[case] alpha: u8,
+        #[case] expected_fg: Option<Color>,
+        #[case] expected_modifier: Modifier,
+    ) {
+        let translator = StyleTranslator::new().dim_translucent_foreground(128);
+        let mut input = fake_syntect_style(FontStyle::empty());
+        input.foreground.a = alpha;
+        let actual = translator.translate_style(input).unwrap();
+        assert_eq!(expected_fg, actual.fg);
+        #[cfg(feature = "underline-color")]
+        assert_eq!(expected_fg, actual.underline_color);
+        assert_eq!(expected_modifier, actual.add_modifier);
+    }
+
+    #[rstest]
+    #[case::drop(AlphaStrategy::Drop, None, None)]
+    #[case::dim(AlphaStrategy::Dim, Some(Color::Rgb(12, 123, 234)), None)]
+    #[case::blend(
+        AlphaStrategy::Blend { backdrop: SyntectColour { r: 0, g: 0, b: 0, a: 255 } },
+        Some(Color::Rgb(37, 120, 120)),
+        Some(Color::Rgb(62, 117, 6))
+    )]
+    fn check_alpha_strategy(
+        #[case] strategy: AlphaStrategy,
+        #[case] expected_fg: Option<Color>,
+        #[case] expected_bg: Option<Color>,
+    ) {
+        let translator = StyleTranslator::new()
+            .alpha_threshold(200)
+            .alpha_strategy(strategy);
+        let mut input = fake_syntect_style(FontStyle::empty());
+        input.foreground.a = 128;
+        input.background.a = 128;
+        let actual = translator.translate_style(input).unwrap();
+        assert_eq!(expected_fg, actual.fg);
+        assert_eq!(expected_bg, actual.bg);
+    }
+
+    #[test]
+    fn default_alpha_threshold_only_drops_transparent_colours() {
+        let mut input = fake_syntect_style(FontStyle::empty());
+        input.foreground.a = 1;
+        input.background.a = 0;
+        let actual = StyleTranslator::new().translate_style(input).unwrap();
+        assert_eq!(Some(Color::Rgb(12, 123, 234)), actual.fg);
+        assert_eq!(None, actual.bg);
+    }
+
+    #[test]
+    fn colour_adjustment_applies_to_all_colours() {
+        let translator = StyleTranslator::new().adjust_colours(ColourAdjustment {
+            saturation: 0.0,
+            ..Default::default()
+        });
+        let actual = translator
+            .translate_style(fake_syntect_style(FontStyle::empty()))
+            .unwrap();
+        assert_eq!(Some(Color::Rgb(123, 123, 123)), actual.fg);
+        assert_eq!(Some(Color::Rgb(123, 123, 123)), actual.bg);
+        #[cfg(feature = "underline-color")]
+        assert_eq!(Some(Color::Rgb(123, 123, 123)), actual.underline_color);
+    }
+
+    #[rstest]
+    #[case::transparent_background(0, Some(Color::Rgb(12, 123, 234)))]
+    #[case::opaque_background(255, Some(Color::Rgb(0, 0, 0)))]
+    fn check_minimum_contrast(#[case] background_alpha: u8, #[case] expected: Option<Color>) {
+        let translator = StyleTranslator::new().minimum_contrast(21.0);
+        let mut input = fake_syntect_style(FontStyle::empty());
+        input.background.a = background_alpha;
+        let actual = translator.translate_style(input).unwrap();
+        assert_eq!(expected, actual.fg);
+        #[cfg(feature = "underline-color")]
+        assert_eq!(expected, actual.underline_color);
+    }
+
+    #[cfg(feature = "underline-color")]
+    #[rstest]
+    #[case::foreground(UnderlineColour::Foreground, Some(Color::Rgb(12, 123, 234)))]
+    #[case::background(UnderlineColour::Background, Some(Color::Rgb(123, 234, 12)))]
+    #[case::fixed(
+        UnderlineColour::Fixed(SyntectColour { r: 1, g: 2, b: 3, a: 255 }),
+        Some(Color::Rgb(1, 2, 3))
+    )]
+    #[case::unset(UnderlineColour::Unset, None)]
+    fn check_underline_colour(
+        #[case] underline_colour: UnderlineColour,
+        #[case] expected: Option<Color>,
+    ) {
+        let translator = StyleTranslator::new().underline_colour(underline_colour);
+        let actual = translator.translate_style(fake_syntect_style(FontStyle::UNDERLINE));
+        assert_eq!(expected, actual.unwrap().underline_color);
+    }
+
+    #[cfg(feature = "underline-color")]
+    #[rstest]
+    #[case::no_match(FontStyle::empty(), Some(Color::Rgb(12, 123, 234)))]
+    #[case::latest_match_wins(FontStyle::UNDERLINE, None)]
+    #[case::earlier_match(FontStyle::BOLD, Some(Color::Rgb(123, 234, 12)))]
+    fn check_underline_colour_rules(
+        #[case] font_style: FontStyle,
+        #[case] expected: Option<Color>,
+    ) {
+        let translator = StyleTranslator::new()
+            .underline_colour_when(
+                |style| !style.font_style.is_empty(),
+                UnderlineColour::Background,
+            )
+            .underline_colour_when(
+                |style| style.font_style.contains(FontStyle::UNDERLINE),
+                UnderlineColour::Unset,
+            );
+        let actual = translator.translate_style(fake_syntect_style(font_style));
+        assert_eq!(expected, actual.unwrap().underline_color);
+    }
+
+    #[rstest]
+    #[case::unset(None, None, false)]
+    #[case::no_color(Some("1"), None, true)]
+    #[case::empty_no_color(Some(""), None, false)]
+    #[case::dumb_term(None, Some("dumb"), true)]
+    #[case::other_term(None, Some("xterm-256color"), false)]
+    fn check_colour_disabled(
+        #[case] no_color: Option<&str>,
+        #[case] term: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let actual = colour_disabled(no_color.map(Into::into), term.map(Into::into));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn translations_are_memoized_until_reconfigured() {
+        let translator = StyleTranslator::new();
+        let style = fake_syntect_style(FontStyle::BOLD);
+        let expected = translator.translate_style(style).unwrap();
+        assert_eq!(expected, translator.translate_style(style).unwrap());
+        translator
+            .translate_style(fake_syntect_style(FontStyle::ITALIC))
+            .unwrap();
+        assert_eq!(2, translator.memo.len());
+        assert_eq!(0, translator.clone().memo.len());
+        let translator = translator.plain(true);
+        assert_eq!(0, translator.memo.len());
+        assert_eq!(
+            ratatui::style::Style::default(),
+            translator.translate_style(style).unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_translations_are_not_memoized() {
+        let translator = StyleTranslator::new();
+        let style = fake_syntect_style(unsafe { FontStyle::from_bits_unchecked(0b1000) });
+        assert!(translator.translate_style(style).is_err());
+        assert!(translator.translate_style(style).is_err());
+        assert_eq!(0, translator.memo.len());
+    }
+}
@@ -0,0 +1,467 @@
+use crate::SyntectTuiError;
+use serde_json::Value;
+use std::str::FromStr;
+use syntect::highlighting::{Color, FontStyle, StyleModifier, ThemeItem, ThemeSettings};
+
+/// The TextMate scopes VS Code falls back to for semantic token types, by type.
+const SEMANTIC_SCOPES: [(&str, &str); 23] = [
+    ("namespace", "entity.name.namespace"),
+    ("type", "entity.name.type"),
+    ("class", "entity.name.type.class"),
+    ("enum", "entity.name.type.enum"),
+    ("interface", "entity.name.type.interface"),
+    ("struct", "entity.name.type.struct"),
+    ("typeParameter", "entity.name.type.parameter"),
+    ("parameter", "variable.parameter"),
+    ("variable", "variable.other.readwrite"),
+    ("property", "variable.other.property"),
+    ("enumMember", "variable.other.enummember"),
+    ("event", "variable.other.event"),
+    ("function", "entity.name.function"),
+    ("method", "entity.name.function.member"),
+    ("macro", "entity.name.function.preprocessor"),
+    ("label", "entity.name.label"),
+    ("comment", "comment"),
+    ("string", "string"),
+    ("keyword", "keyword"),
+    ("number", "constant.numeric"),
+    ("regexp", "constant.regexp"),
+    ("operator", "keyword.operator"),
+    ("decorator", "entity.name.function"),
+];
+
+/// A [VS Code](https://code.visualstudio.com) colour theme, parsed from its JSON, to highlight
+/// with the themes users have installed in their editor.
+///
+/// [to_theme](VsCodeTheme::to_theme) converts the theme's `tokenColors` rules into a syntect
+/// theme's rules, in order, followed by its `semanticTokenColors` for plain token types, e.g.
+/// `function` but not `function.declaration` or `function:rust`, as the TextMate scopes VS Code
+/// falls back to for them. The theme's default colours, caret, selection, current line, gutter,
+/// search matches and matching brackets come from the editor's `colors`. Comments and trailing
+/// commas are allowed, as VS Code allows them.
+///
+/// Themes that include another theme only hold what they change, and are completed by
+/// [including](VsCodeTheme::include) the theme they name in `include`, once the application has
+/// found it.
+///
+/// # Examples
+/// ```
+/// use syntect_tui::VsCodeTheme;
+///
+/// let theme = VsCodeTheme::from_json(
+///     r##"{
+///         // Comments are allowed.
+///         "name": "Ocean",
+///         "colors": { "editor.background": "#2b303b" },
+///         "tokenColors": [
+///             { "scope": ["comment", "punctuation.definition.comment"],
+///               "settings": { "foreground": "#65737e", "fontStyle": "italic" } },
+///         ],
+///         "semanticTokenColors": { "function": "#8fa1b3" }
+///     }"##,
+/// )
+/// .unwrap()
+/// .to_theme()
+/// .unwrap();
+/// let base = syntect::highlighting::Color { r: 43, g: 48, b: 59, a: 255 };
+/// assert_eq!(Some("Ocean".to_string()), theme.name);
+/// assert_eq!(Some(base), theme.settings.background);
+/// assert_eq!(2, theme.scopes.len());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VsCodeTheme {
+    json: serde_json::Map<String, Value>,
+}
+
+/// Parses a [VS Code](https://code.visualstudio.com) colour theme and converts it into a
+/// [syntect::highlighting::Theme](https://docs.rs/syntect/latest/syntect/highlighting/struct.Theme.html).
+///
+/// See [VsCodeTheme] for details. The theme is converted as it is, without the theme it includes,
+/// if any.
+///
+/// # Errors
+/// Can return the same errors as [VsCodeTheme::from_json] and [VsCodeTheme::to_theme].
+pub fn vscode_theme(source: &str) -> Result<syntect::highlighting::Theme, SyntectTuiError> {
+    VsCodeTheme::from_json(source)?.to_theme()
+}
+
+impl VsCodeTheme {
+    /// Parses a VS Code colour theme from its JSON, which may have comments and trailing commas.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeLoading` if `source` is not a JSON object.
+    pub fn from_json(source: &str) -> Result<Self, SyntectTuiError> {
+        let json = serde_json::from_str(&strip_jsonc(source)).map_err(|error| {
+            SyntectTuiError::ThemeLoading {
+                message: error.to_string(),
+            }
+        })?;
+        Ok(Self { json })
+    }
+
+    /// Returns the path of the theme this theme includes, relative to this theme's file, if any.
+    pub fn includes(&self) -> Option<&str> {
+        self.json.get("include")?.as_str()
+    }
+
+    /// Completes this theme with `parent`: its `tokenColors` rules come before this theme's,
+    /// while this theme's editor colours and semantic token colours replace those of `parent`.
+    /// The theme includes what `parent` includes.
+    ///
+    /// # Examples
+    /// ```
+    /// use syntect_tui::VsCodeTheme;
+    ///
+    /// let parent = VsCodeTheme::from_json(
+    ///     r##"{ "tokenColors": [{ "scope": "comment", "settings": { "foreground": "#777" } }] }"##,
+    /// )
+    /// .unwrap();
+    /// let child = VsCodeTheme::from_json(
+    ///     r##"{ "include": "./parent.json",
+    ///           "tokenColors": [{ "scope": "string", "settings": { "foreground": "#0f0" } }] }"##,
+    /// )
+    /// .unwrap();
+    /// let theme = child.include(parent);
+    /// assert_eq!(None, theme.includes());
+    /// assert_eq!(2, theme.to_theme().unwrap().scopes.len());
+    /// ```
+    pub fn include(self, parent: VsCodeTheme) -> Self {
+        let mut json = parent.json;
+        for (key, value) in self.json {
+            match (json.get_mut(&key), value) {
+                (Some(Value::Array(rules)), Value::Array(more)) if key == "tokenColors" => {
+                    rules.extend(more);
+                }
+                (Some(Value::Object(colours)), Value::Object(more)) => colours.extend(more),
+                (_, value) if key != "include" => {
+                    json.insert(key, value);
+                }
+                _ => {}
+            }
+        }
+        Self { json }
+    }
+
+    /// Converts the theme into a syntect theme.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::ThemeLoading` if a rule or colour is malformed, and
+    /// `SyntectTuiError::InvalidScopeSelector` if a rule's scope cannot be parsed.
+    pub fn to_theme(&self) -> Result<syntect::highlighting::Theme, SyntectTuiError> {
+        let colours = self.json.get("colors").and_then(Value::as_object);
+        let colour = |key: &str| {
+            colours
+                .and_then(|colours| colours.get(key))
+                .map(|value| parse_colour(key, value))
+                .transpose()
+        };
+        let mut settings = ThemeSettings {
+            foreground: colour("editor.foreground")?,
+            background: colour("editor.background")?,
+            caret: colour("editorCursor.foreground")?,
+            selection: colour("editor.selectionBackground")?,
+            selection_foreground: colour("editor.selectionForeground")?,
+            line_highlight: colour("editor.lineHighlightBackground")?,
+            gutter: colour("editorGutter.background")?,
+            gutter_foreground: colour("editorLineNumber.foreground")?,
+            find_highlight: colour("editor.findMatchHighlightBackground")?,
+            brackets_background: colour("editorBracketMatch.background")?,
+            ..Default::default()
+        };
+        let mut scopes = Vec::new();
+        // Themes may give their token colours as a path to a tmTheme, which is not followed.
+        let rules = self.json.get("tokenColors").and_then(Value::as_array);
+        for rule in rules.into_iter().flatten() {
+            let style = parse_style("tokenColors", rule.get("settings").unwrap_or(&Value::Null))?;
+            let selector = match rule.get("scope") {
+                None => {
+                    settings.foreground = style.foreground.or(settings.foreground);
+                    settings.background = style.background.or(settings.background);
+                    continue;
+                }
+                Some(Value::String(scope)) => scope.clone(),
+                Some(Value::Array(scopes)) => scopes
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                Some(scope) => {
+                    return Err(SyntectTuiError::ThemeLoading {
+                        message: format!("expected a scope, found {scope}"),
+                    })
+                }
+            };
+            scopes.push(theme_item(&selector, style)?);
+        }
+        let semantic = self
+            .json
+            .get("semanticTokenColors")
+            .and_then(Value::as_object);
+        for (token, value) in semantic.into_iter().flatten() {
+            let scope = SEMANTIC_SCOPES
+                .iter()
+                .find_map(|(semantic, scope)| (semantic == token).then_some(*scope));
+            if let Some(scope) = scope {
+                scopes.push(theme_item(scope, parse_style(token, value)?)?);
+            }
+        }
+        Ok(syntect::highlighting::Theme {
+            name: self
+                .json
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            settings,
+            scopes,
+            ..Default::default()
+        })
+    }
+}
+
+fn theme_item(selector: &str, style: StyleModifier) -> Result<ThemeItem, SyntectTuiError> {
+    let scope = syntect::highlighting::ScopeSelectors::from_str(selector).map_err(|error| {
+        SyntectTuiError::InvalidScopeSelector {
+            selector: selector.to_string(),
+            message: error.to_string(),
+        }
+    })?;
+    Ok(ThemeItem { scope, style })
+}
+
+/// Parses the settings of a token colour rule, or a semantic token colour, which may be a colour
+/// on its own or use `bold`, `italic` and `underline` flags.
+fn parse_style(key: &str, value: &Value) -> Result<StyleModifier, SyntectTuiError> {
+    if value.is_string() {
+        return Ok(StyleModifier {
+            foreground: Some(parse_colour(key, value)?),
+            ..Default::default()
+        });
+    }
+    let colour = |name| {
+        value
+            .get(name)
+            .map(|colour| parse_colour(key, colour))
+            .transpose()
+    };
+    let mut font_style = value
+        .get("fontStyle")
+        .and_then(Value::as_str)
+        .map(parse_font_style);
+    for (flag, style) in [
+        ("bold", FontStyle::BOLD),
+        ("italic", FontStyle::ITALIC),
+        ("underline", FontStyle::UNDERLINE),
+    ] {
+        if let Some(enabled) = value.get(flag).and_then(Value::as_bool) {
+            font_style
+                .get_or_insert_with(FontStyle::empty)
+                .set(style, enabled);
+        }
+    }
+    Ok(StyleModifier {
+        foreground: colour("foreground")?,
+        background: colour("background")?,
+        font_style,
+    })
+}
+
+/// Parses a font style such as `bold italic`, where an empty string resets the font style.
+/// Strikethrough has no syntect equivalent and is ignored.
+fn parse_font_style(value: &str) -> FontStyle {
+    value
+        .split_whitespace()
+        .fold(FontStyle::empty(), |font_style, word| match word {
+            "bold" => font_style | FontStyle::BOLD,
+            "italic" => font_style | FontStyle::ITALIC,
+            "underline" => font_style | FontStyle::UNDERLINE,
+            _ => font_style,
+        })
+}
+
+/// Parses a `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` colour.
+fn parse_colour(key: &str, value: &Value) -> Result<Color, SyntectTuiError> {
+    let invalid = || SyntectTuiError::ThemeLoading {
+        message: format!("invalid colour for \"{key}\": {value}"),
+    };
+    let hex = value
+        .as_str()
+        .and_then(|colour| colour.strip_prefix('#'))
+        .filter(|hex| hex.is_ascii())
+        .ok_or_else(invalid)?;
+    let digits = match hex.len() {
+        3 | 4 => 1,
+        6 | 8 => 2,
+        _ => return Err(invalid()),
+    };
+    let mut channels = (0..hex.len()).step_by(digits).map(|start| {
+        let channel = u8::from_str_radix(&hex[start..start + digits], 16).ok()?;
+        Some(if digits == 1 { channel * 17 } else { channel })
+    });
+    let mut channel = || channels.next().flatten().ok_or_else(invalid);
+    Ok(Color {
+        r: channel()?,
+        g: channel()?,
+        b: channel()?,
+        a: channels
+            .next()
+            .map_or(Some(255), |alpha| alpha)
+            .ok_or_else(invalid)?,
+    })
+}
+
+/// Removes the comments and trailing commas of JSON with comments, which VS Code's themes are
+/// written in.
+fn strip_jsonc(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+    while let Some(char) = chars.next() {
+        if in_string {
+            stripped.push(char);
+            match char {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (char, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                stripped.push(char);
+            }
+            ('/', Some('/')) => while chars.next_if(|next| *next != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            ('}' | ']', _) => {
+                let trimmed = stripped.trim_end().len();
+                if stripped[..trimmed].ends_with(',') {
+                    stripped.truncate(trimmed - 1);
+                }
+                stripped.push(char);
+            }
+            _ => stripped.push(char),
+        }
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case::short("#f80", Some((255, 136, 0, 255)))]
+    #[case::short_alpha("#f808", Some((255, 136, 0, 136)))]
+    #[case::long("#2b303b", Some((43, 48, 59, 255)))]
+    #[case::long_alpha("#2b303b80", Some((43, 48, 59, 128)))]
+    #[case::no_hash("2b303b", None)]
+    #[case::not_hex("#2b303g", None)]
+    #[case::wrong_length("#2b303", None)]
+    fn check_parse_colour(#[case] colour: &str, #[case] expected: Option<(u8, u8, u8, u8)>) {
+        let actual = parse_colour("key", &Value::from(colour)).ok();
+        assert_eq!(expected.map(|(r, g, b, a)| Color { r, g, b, a }), actual);
+    }
+
+    #[rstest]
+    #[case::line_comment("{\"a\": 1 // one\n}", "{\"a\": 1 \n}")]
+    #[case::block_comment("{/* a */\"a\": 1}", "{\"a\": 1}")]
+    #[case::trailing_commas("{\"a\": [1, 2,], }", "{\"a\": [1, 2]}")]
+    #[case::inside_strings("{\"a\": \"// /* ,}\"}", "{\"a\": \"// /* ,}\"}")]
+    #[case::escaped_quote("{\"a\": \"\\\"//\"}", "{\"a\": \"\\\"//\"}")]
+    fn check_strip_jsonc(#[case] source: &str, #[case] expected: &str) {
+        assert_eq!(expected, strip_jsonc(source));
+    }
+
+    #[test]
+    fn converts_rules_and_settings() {
+        let theme = vscode_theme(
+            r##"{
+                "colors": { "editor.foreground": "#c0c5ce", "editorCursor.foreground": "#fff" },
+                "tokenColors": [
+                    { "settings": { "background": "#2b303b" } },
+                    { "scope": "keyword, storage", "settings": { "fontStyle": "bold strikethrough" } },
+                    { "scope": "markup.italic", "settings": { "fontStyle": "" } }
+                ],
+                "semanticTokenColors": {
+                    "variable.readonly": "#ff0000",
+                    "property": { "foreground": "#bf616a", "italic": true }
+                }
+            }"##,
+        )
+        .unwrap();
+        let colour = |r, g, b| Some(Color { r, g, b, a: 255 });
+        assert_eq!(colour(192, 197, 206), theme.settings.foreground);
+        assert_eq!(colour(43, 48, 59), theme.settings.background);
+        assert_eq!(colour(255, 255, 255), theme.settings.caret);
+        let styles: Vec<_> = theme.scopes.iter().map(|item| item.style).collect();
+        assert_eq!(
+            vec![
+                StyleModifier {
+                    font_style: Some(FontStyle::BOLD),
+                    ..Default::default()
+                },
+                StyleModifier {
+                    font_style: Some(FontStyle::empty()),
+                    ..Default::default()
+                },
+                StyleModifier {
+                    foreground: colour(191, 97, 106),
+                    font_style: Some(FontStyle::ITALIC),
+                    ..Default::default()
+                },
+            ],
+            styles
+        );
+        assert_eq!(2, theme.scopes[0].scope.selectors.len());
+    }
+
+    #[test]
+    fn included_rules_come_first() {
+        let parent = VsCodeTheme::from_json(
+            r##"{ "include": "base.json", "colors": { "editor.background": "#000", "editor.foreground": "#fff" },
+                  "tokenColors": [{ "scope": "comment", "settings": { "foreground": "#777" } }] }"##,
+        )
+        .unwrap();
+        let child = VsCodeTheme::from_json(
+            r##"{ "include": "parent.json", "colors": { "editor.background": "#111" },
+                  "tokenColors": [{ "scope": "comment", "settings": { "foreground": "#888" } }] }"##,
+        )
+        .unwrap();
+        let theme = child.include(parent);
+        assert_eq!(Some("base.json"), theme.includes());
+        let theme = theme.to_theme().unwrap();
+        let colour = |level| {
+            Some(Color {
+                r: level,
+                g: level,
+                b: level,
+                a: 255,
+            })
+        };
+        assert_eq!(colour(0x11), theme.settings.background);
+        assert_eq!(colour(0xff), theme.settings.foreground);
+        assert_eq!(colour(0x88), theme.scopes[1].style.foreground);
+    }
+
+    #[rstest]
+    #[case::not_json("{")]
+    #[case::not_an_object("[]")]
+    #[case::invalid_colour(r#"{ "colors": { "editor.background": "blue" } }"#)]
+    #[case::invalid_scope(r#"{ "tokenColors": [{ "scope": 1, "settings": {} }] }"#)]
+    fn rejects_invalid_themes(#[case] source: &str) {
+        assert!(matches!(
+            vscode_theme(source),
+            Err(SyntectTuiError::ThemeLoading { .. })
+        ));
+    }
+}
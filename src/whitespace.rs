@@ -0,0 +1,229 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The symbols and style used to make whitespace visible, e.g. by
+/// [show_whitespace](crate::show_whitespace).
+///
+/// By default tabs are shown as `→`, trailing spaces as `·` and no-break spaces as `⍽`, while
+/// other spaces are left alone, all in a [DIM](ratatui::style::Modifier::DIM) style that is
+/// patched over the syntax colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Whitespace {
+    tab: char,
+    trailing_space: char,
+    space: Option<char>,
+    no_break_space: char,
+    style: ratatui::style::Style,
+}
+
+impl Default for Whitespace {
+    fn default() -> Self {
+        Self {
+            tab: '→',
+            trailing_space: '·',
+            space: None,
+            no_break_space: '⍽',
+            style: ratatui::style::Style::new().add_modifier(ratatui::style::Modifier::DIM),
+        }
+    }
+}
+
+impl Whitespace {
+    /// Creates the default whitespace symbols and style.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the symbol shown at the start of a tab.
+    pub fn tab(mut self, symbol: char) -> Self {
+        self.tab = symbol;
+        self
+    }
+
+    /// Sets the symbol shown for spaces at the end of a line.
+    pub fn trailing_space(mut self, symbol: char) -> Self {
+        self.trailing_space = symbol;
+        self
+    }
+
+    /// Sets the symbol shown for all other spaces, or `None` to leave them alone.
+    pub fn space(mut self, symbol: Option<char>) -> Self {
+        self.space = symbol;
+        self
+    }
+
+    /// Sets the symbol shown for no-break spaces (`U+00A0`).
+    pub fn no_break_space(mut self, symbol: char) -> Self {
+        self.no_break_space = symbol;
+        self
+    }
+
+    /// Sets the style patched over the symbols.
+    pub fn style(mut self, style: ratatui::style::Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the symbol to show for `character` at a byte offset that is `trailing` or not.
+    fn symbol(&self, character: char, trailing: bool) -> Option<char> {
+        match character {
+            '\t' => Some(self.tab),
+            ' ' if trailing => Some(self.trailing_space),
+            ' ' => self.space,
+            '\u{a0}' => Some(self.no_break_space),
+            _ => None,
+        }
+    }
+}
+
+/// Makes the whitespace of `line` visible, replacing each whitespace character with its symbol
+/// from `whitespace` in a span with the whitespace style patched over the original one.
+///
+/// If `tab_width` is set, tabs are also expanded like
+/// [StyleTranslator::expand_tabs](crate::StyleTranslator::expand_tabs), with the tab symbol in
+/// the first column and spaces up to the next tab stop, so display columns are unaffected either
+/// way. Spaces count as trailing if only whitespace follows them on the line.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Modifier, Style, Stylize};
+/// use ratatui::text::{Line, Span};
+/// use syntect_tui::Whitespace;
+///
+/// let line = Line::from(vec![Span::styled("\tx = 1;  ", Style::new().bold())]);
+/// let shown = syntect_tui::show_whitespace(&line, &Whitespace::default(), Some(4));
+/// assert_eq!("→   x = 1;··", shown.to_string());
+/// assert_eq!(
+///     Style::new().bold().add_modifier(Modifier::DIM),
+///     shown.spans[0].style
+/// );
+/// assert_eq!(Style::new().bold(), shown.spans[1].style);
+/// ```
+pub fn show_whitespace<'a>(
+    line: &ratatui::text::Line<'a>,
+    whitespace: &Whitespace,
+    tab_width: Option<usize>,
+) -> ratatui::text::Line<'a> {
+    let trailing = line.to_string().trim_end().len();
+    let mut spans = Vec::with_capacity(line.spans.len());
+    let mut offset = 0;
+    let mut column = 0;
+    for span in &line.spans {
+        let start = offset;
+        offset += span.content.len();
+        let shown = |(index, character): (usize, char)| {
+            whitespace.symbol(character, start + index >= trailing)
+        };
+        if !span
+            .content
+            .char_indices()
+            .any(|item| shown(item).is_some())
+        {
+            column += span.content.width();
+            spans.push(span.clone());
+            continue;
+        }
+        let symbol_style = span.style.patch(whitespace.style);
+        // Runs of characters are collected and flushed as a span whenever the style changes.
+        let mut run = String::new();
+        let mut run_is_symbol = false;
+        for (index, character) in span.content.char_indices() {
+            let symbol = shown((index, character));
+            if symbol.is_some() != run_is_symbol && !run.is_empty() {
+                let style = if run_is_symbol {
+                    symbol_style
+                } else {
+                    span.style
+                };
+                spans.push(ratatui::text::Span::styled(std::mem::take(&mut run), style));
+            }
+            run_is_symbol = symbol.is_some();
+            match (symbol, character, tab_width) {
+                (Some(symbol), '\t', Some(width)) => {
+                    let columns = match width {
+                        0 => 0,
+                        width => width - column % width,
+                    };
+                    if columns > 0 {
+                        run.push(symbol);
+                        run.extend(std::iter::repeat_n(' ', columns - 1));
+                    }
+                    column += columns;
+                }
+                (Some(symbol), _, _) => {
+                    run.push(symbol);
+                    column += character.width().unwrap_or(1);
+                }
+                (None, _, _) => {
+                    run.push(character);
+                    column += character.width().unwrap_or(0);
+                }
+            }
+        }
+        if !run.is_empty() {
+            let style = if run_is_symbol {
+                symbol_style
+            } else {
+                span.style
+            };
+            spans.push(ratatui::text::Span::styled(run, style));
+        }
+    }
+    ratatui::text::Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Style, Stylize};
+    use ratatui::text::{Line, Span};
+    use rstest::*;
+
+    #[rstest]
+    #[case::trailing("a b  ", None, "a b··")]
+    #[case::trailing_across_spans("a ", None, "a·")]
+    #[case::tab_stops("ab\tc\t", Some(4), "ab→ c→  ")]
+    #[case::unexpanded_tab("a\tb", None, "a→b")]
+    #[case::zero_tab_width("a\tb", Some(0), "ab")]
+    #[case::no_break_space("a\u{a0}b", None, "a⍽b")]
+    #[case::wide_characters("日\tx", Some(4), "日→ x")]
+    #[case::only_whitespace("  ", None, "··")]
+    fn check_show_whitespace(
+        #[case] content: &str,
+        #[case] tab_width: Option<usize>,
+        #[case] expected: &str,
+    ) {
+        let line = Line::from(vec![Span::raw(content), Span::raw(" ")]);
+        let shown = show_whitespace(&line, &Whitespace::default(), tab_width);
+        assert_eq!(format!("{expected}·"), shown.to_string());
+    }
+
+    #[test]
+    fn shows_all_spaces_when_enabled() {
+        let line = Line::raw("a b ");
+        let whitespace = Whitespace::new().space(Some('_')).trailing_space('$');
+        assert_eq!(
+            "a_b$",
+            show_whitespace(&line, &whitespace, None).to_string()
+        );
+    }
+
+    #[test]
+    fn keeps_styles_and_untouched_spans() {
+        let line = Line::from(vec![Span::raw("a").red(), Span::raw("\tb").blue()]).centered();
+        let whitespace = Whitespace::new().style(Style::new().on_black());
+        let shown = show_whitespace(&line, &whitespace, None);
+        assert_eq!(
+            vec![
+                Span::raw("a").red(),
+                Span::raw("→").blue().on_black(),
+                Span::raw("b").blue(),
+            ],
+            shown.spans
+        );
+        assert_eq!(line.alignment, shown.alignment);
+    }
+}
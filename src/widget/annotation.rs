@@ -0,0 +1,229 @@
+use unicode_width::UnicodeWidthStr;
+
+/// A caller-supplied annotation of a line of source code, e.g. the commit hash, author and age
+/// reported by `git blame`, as a list of fields rendered in aligned columns.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Annotation {
+    /// The fields of the annotation, from left to right.
+    pub fields: Vec<String>,
+}
+
+impl Annotation {
+    /// Creates an annotation from its fields, from left to right.
+    pub fn new<S: Into<String>>(fields: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            fields: fields.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A column of per-line [Annotation]s, e.g. a blame column.
+///
+/// Like a [Gutter](crate::widget::Gutter), the column can be rendered on its own or attached to
+/// a [CodeView](crate::widget::CodeView) using
+/// [CodeView::annotations](crate::widget::CodeView::annotations), which keeps it aligned with the
+/// view's scroll position. Each field gets its own column, as wide as its widest value, and
+/// columns are separated and followed by a single space. By default an annotation equal to the
+/// one on the row above is left blank, so runs of lines from the same commit show it once.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::widgets::Widget;
+/// use syntect_tui::widget::{Annotation, Annotations};
+///
+/// let first = Annotation::new(["1a2b", "ana", "2d"]);
+/// let second = Annotation::new(["3c4d", "bo", "1w"]);
+/// let annotations = Annotations::new([Some(first.clone()), Some(first), Some(second), None]);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, annotations.width(), 4));
+/// (&annotations).render(buf.area, &mut buf);
+/// assert_eq!(
+///     Buffer::with_lines(["1a2b ana 2d ", "            ", "3c4d bo  1w ", "            "]),
+///     buf
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotations {
+    annotations: Vec<Option<Annotation>>,
+    scroll: usize,
+    style: ratatui::style::Style,
+    field_styles: Vec<ratatui::style::Style>,
+    collapse: bool,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Self {
+            annotations: Vec::new(),
+            scroll: 0,
+            style: ratatui::style::Style::new(),
+            field_styles: Vec::new(),
+            collapse: true,
+        }
+    }
+}
+
+impl Annotations {
+    /// Creates a column annotating each line with the corresponding item of `annotations`, where
+    /// `None` leaves a line blank.
+    pub fn new(annotations: impl IntoIterator<Item = Option<Annotation>>) -> Self {
+        Self {
+            annotations: annotations.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the index of the line shown in the first row.
+    pub fn scroll(mut self, offset: usize) -> Self {
+        self.scroll = offset;
+        self
+    }
+
+    /// Sets the style of the whole column.
+    pub fn style(mut self, style: ratatui::style::Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the style patched over field `index` of every annotation, e.g. to dim the age.
+    pub fn field_style(mut self, index: usize, style: ratatui::style::Style) -> Self {
+        if self.field_styles.len() <= index {
+            self.field_styles
+                .resize(index + 1, ratatui::style::Style::new());
+        }
+        self.field_styles[index] = style;
+        self
+    }
+
+    /// Sets whether an annotation equal to the one on the row above is left blank. Defaults to
+    /// `true`.
+    pub fn collapse(mut self, collapse: bool) -> Self {
+        self.collapse = collapse;
+        self
+    }
+
+    /// Returns the annotation of line `line`, if any.
+    pub fn annotation(&self, line: usize) -> Option<&Annotation> {
+        self.annotations.get(line).and_then(Option::as_ref)
+    }
+
+    /// Returns the width of the column, including its padding, or 0 if no line has any fields.
+    pub fn width(&self) -> u16 {
+        let widths = self.field_widths();
+        let width: usize = widths.iter().map(|width| width + 1).sum();
+        u16::try_from(width).unwrap_or(u16::MAX)
+    }
+
+    /// Returns the width of the widest value of each field.
+    fn field_widths(&self) -> Vec<usize> {
+        let mut widths = Vec::new();
+        for annotation in self.annotations.iter().flatten() {
+            if widths.len() < annotation.fields.len() {
+                widths.resize(annotation.fields.len(), 0);
+            }
+            for (width, field) in widths.iter_mut().zip(&annotation.fields) {
+                *width = (*width).max(field.width());
+            }
+        }
+        widths
+    }
+
+    /// Renders the annotation of the line shown in each row, collapsing runs of equal
+    /// annotations if enabled.
+    pub(crate) fn render_rows(
+        &self,
+        rows: impl Iterator<Item = usize>,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+    ) {
+        buf.set_style(area, self.style);
+        let widths = self.field_widths();
+        let mut previous = None;
+        for (y, line) in (area.top()..area.bottom()).zip(rows) {
+            let annotation = self.annotation(line);
+            let repeated = self.collapse && previous.is_some() && previous == annotation;
+            previous = annotation;
+            let Some(annotation) = annotation.filter(|_| !repeated) else {
+                continue;
+            };
+            let mut x = area.x;
+            for (index, (field, width)) in annotation.fields.iter().zip(&widths).enumerate() {
+                let available = usize::from(area.right().saturating_sub(x));
+                let style = self.field_styles.get(index).copied().unwrap_or_default();
+                buf.set_stringn(x, y, field, (*width).min(available), style);
+                x = x.saturating_add(u16::try_from(width + 1).unwrap_or(u16::MAX));
+            }
+        }
+    }
+}
+
+impl ratatui::widgets::Widget for &Annotations {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        self.render_rows(self.scroll.., area, buf);
+    }
+}
+
+impl ratatui::widgets::Widget for Annotations {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Style, Stylize};
+    use ratatui::widgets::Widget;
+    use rstest::*;
+
+    fn fake_annotations() -> Annotations {
+        let first = Annotation::new(["a1", "ana"]);
+        Annotations::new([
+            Some(first.clone()),
+            Some(first.clone()),
+            None,
+            Some(first),
+            Some(Annotation::new(["b2"])),
+        ])
+    }
+
+    #[rstest]
+    #[case::collapsed(fake_annotations(), ["a1 ana ", "       ", "       ", "a1 ana "])]
+    #[case::expanded(fake_annotations().collapse(false), ["a1 ana ", "a1 ana ", "       ", "a1 ana "])]
+    #[case::scrolled(fake_annotations().scroll(1), ["a1 ana ", "       ", "a1 ana ", "b2     "])]
+    fn check_render(#[case] annotations: Annotations, #[case] expected: [&str; 4]) {
+        let mut buf = Buffer::empty(Rect::new(0, 0, annotations.width(), 4));
+        annotations.render(buf.area, &mut buf);
+        assert_eq!(Buffer::with_lines(expected), buf);
+    }
+
+    #[rstest]
+    #[case(Annotations::default(), 0)]
+    #[case(fake_annotations(), 7)]
+    #[case(Annotations::new([Some(Annotation::new(["日本"]))]), 5)]
+    fn check_width(#[case] annotations: Annotations, #[case] expected: u16) {
+        assert_eq!(expected, annotations.width());
+    }
+
+    #[test]
+    fn truncates_to_area() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        fake_annotations().render(buf.area, &mut buf);
+        assert_eq!(Buffer::with_lines(["a1 a"]), buf);
+    }
+
+    #[test]
+    fn styles_fields() {
+        let annotations = fake_annotations()
+            .style(Style::new().on_black())
+            .field_style(1, Style::new().dim());
+        let mut buf = Buffer::empty(Rect::new(0, 0, annotations.width(), 1));
+        annotations.render(buf.area, &mut buf);
+        assert_eq!(Style::new().on_black(), Style::new().bg(buf[(0, 0)].bg));
+        assert!(buf[(3, 0)].modifier.contains(ratatui::style::Modifier::DIM));
+        assert!(!buf[(0, 0)].modifier.contains(ratatui::style::Modifier::DIM));
+    }
+}
@@ -0,0 +1,120 @@
+use unicode_width::UnicodeWidthChar;
+
+/// The shape of the cursor drawn by a [CodeView](super::CodeView).
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::style::Modifier;
+/// use ratatui::text::Line;
+/// use ratatui::widgets::Widget;
+/// use syntect_tui::widget::{CodeView, CursorShape};
+///
+/// let view = CodeView::new(vec![Line::raw("ab")])
+///     .cursor(0, 1)
+///     .cursor_shape(CursorShape::Underline);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+/// view.render(buf.area, &mut buf);
+/// assert_eq!("b", buf[(1, 0)].symbol());
+/// assert!(buf[(1, 0)].modifier.contains(Modifier::UNDERLINED));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    /// Styles the character under the cursor with the cursor style, or a space if the cursor is
+    /// past the end of the line.
+    #[default]
+    Block,
+    /// Draws a thin bar at the left of the cell under the cursor, in the background colour of the
+    /// cursor style, or its foreground if it has none. The bar replaces the character, which
+    /// applications can still show with the terminal's own cursor at
+    /// [cursor_position](super::CodeView::cursor_position).
+    Bar,
+    /// Underlines the character under the cursor, keeping it readable, with the underline
+    /// coloured with the background of the cursor style. Without the `underline-color` feature
+    /// the underline cannot be coloured, so the character is also drawn
+    /// [REVERSED](ratatui::style::Modifier::REVERSED) to keep the cursor visible.
+    Underline,
+}
+
+/// The symbol a [CursorShape::Bar] cursor is drawn with.
+pub(crate) const BAR_SYMBOL: &str = "▏";
+
+/// Returns the byte range of the character of `line` at byte offset `column`, rounding down to
+/// the start of a multi-byte character. Returns `None` if `column` is past the end of the line.
+pub(crate) fn character_at(
+    line: &ratatui::text::Line,
+    column: usize,
+) -> Option<std::ops::Range<usize>> {
+    let content: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    content
+        .char_indices()
+        .take_while(|(start, _)| *start <= column)
+        .last()
+        .filter(|(start, character)| column < start + character.len_utf8())
+        .map(|(start, character)| start..start + character.len_utf8())
+}
+
+/// Returns the display column at which byte offset `column` of `line` is drawn, counting wide
+/// characters as two columns and, if `tab_width` is set, expanding tabs to the next tab stop.
+/// Offsets past the end of the line are drawn just after it.
+pub(crate) fn display_column(
+    line: &ratatui::text::Line,
+    column: usize,
+    tab_width: Option<usize>,
+) -> usize {
+    let mut display = 0;
+    let mut offset = 0;
+    for character in line.spans.iter().flat_map(|span| span.content.chars()) {
+        if offset + character.len_utf8() > column {
+            return display;
+        }
+        offset += character.len_utf8();
+        display += match (character, tab_width) {
+            ('\t', Some(0)) => 0,
+            ('\t', Some(width)) => width - display % width,
+            _ => character.width().unwrap_or(0),
+        };
+    }
+    display
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::text::{Line, Span};
+
+    fn fake_line() -> Line<'static> {
+        Line::from(vec![Span::raw("a\t"), Span::raw("日b")])
+    }
+
+    #[rstest]
+    #[case::start(0, Some(0..1))]
+    #[case::across_spans(2, Some(2..5))]
+    #[case::inside_character(3, Some(2..5))]
+    #[case::end(6, None)]
+    fn check_character_at(#[case] column: usize, #[case] expected: Option<std::ops::Range<usize>>) {
+        assert_eq!(expected, character_at(&fake_line(), column));
+    }
+
+    #[rstest]
+    #[case::start(0, None, 0)]
+    #[case::raw_tab(2, None, 1)]
+    #[case::expanded_tab(2, Some(4), 4)]
+    #[case::after_wide_character(5, Some(4), 6)]
+    #[case::inside_wide_character(3, Some(4), 4)]
+    #[case::past_end(8, Some(4), 7)]
+    fn check_display_column(
+        #[case] column: usize,
+        #[case] tab_width: Option<usize>,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(expected, display_column(&fake_line(), column, tab_width));
+    }
+}
@@ -0,0 +1,273 @@
+/// The severity of a [Diagnostic], from most to least severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Information,
+    /// A hint, e.g. a suggested refactoring.
+    Hint,
+}
+
+impl Severity {
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A message about a line of source code, e.g. reported by a compiler or language server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The index of the line the diagnostic applies to.
+    pub line: usize,
+    /// The byte range within the line the diagnostic applies to, or `None` for the whole line.
+    pub columns: Option<std::ops::Range<usize>>,
+    /// The severity of the diagnostic.
+    pub severity: Severity,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic that applies to the whole of line `line`.
+    pub fn new(line: usize, severity: Severity) -> Self {
+        Self {
+            line,
+            columns: None,
+            severity,
+        }
+    }
+
+    /// Narrows the diagnostic down to the byte range `columns` of its line.
+    pub fn columns(mut self, columns: std::ops::Range<usize>) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+}
+
+/// A set of [Diagnostic]s, rendered as a one column wide sign column that marks each line with
+/// the symbol of its most severe diagnostic.
+///
+/// Like a [Gutter](crate::widget::Gutter), the sign column can be rendered on its own or attached
+/// to a [CodeView](crate::widget::CodeView) using
+/// [CodeView::diagnostics](crate::widget::CodeView::diagnostics), which also underlines the
+/// affected ranges (see [underline](Diagnostics::underline)). By default errors, warnings,
+/// information and hints are marked `E`, `W`, `I` and `H` in red, yellow, blue and cyan.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::widgets::Widget;
+/// use syntect_tui::widget::{Diagnostic, Diagnostics, Severity};
+///
+/// let diagnostics = Diagnostics::new([
+///     Diagnostic::new(1, Severity::Warning),
+///     Diagnostic::new(1, Severity::Error).columns(4..5),
+///     Diagnostic::new(2, Severity::Hint),
+/// ])
+/// .symbol(Severity::Hint, '?');
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 1, 3));
+/// (&diagnostics).render(buf.area, &mut buf);
+/// let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+/// assert_eq!(" E?", symbols);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+    scroll: usize,
+    symbols: [char; 4],
+    styles: [ratatui::style::Style; 4],
+    underline: bool,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        use ratatui::style::{Color, Style};
+        Self {
+            diagnostics: Vec::new(),
+            scroll: 0,
+            symbols: ['E', 'W', 'I', 'H'],
+            styles: [
+                Style::new().fg(Color::Red),
+                Style::new().fg(Color::Yellow),
+                Style::new().fg(Color::Blue),
+                Style::new().fg(Color::Cyan),
+            ],
+            underline: true,
+        }
+    }
+}
+
+impl Diagnostics {
+    /// Creates a set of diagnostics.
+    pub fn new(diagnostics: impl IntoIterator<Item = Diagnostic>) -> Self {
+        Self {
+            diagnostics: diagnostics.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the index of the line shown in the first row.
+    pub fn scroll(mut self, offset: usize) -> Self {
+        self.scroll = offset;
+        self
+    }
+
+    /// Sets the symbol that marks lines whose most severe diagnostic has `severity`.
+    pub fn symbol(mut self, severity: Severity, symbol: char) -> Self {
+        self.symbols[severity.index()] = symbol;
+        self
+    }
+
+    /// Sets the style of the symbol for `severity`. The foreground colour of the style is also
+    /// used as the colour of the underline.
+    pub fn style(mut self, severity: Severity, style: ratatui::style::Style) -> Self {
+        self.styles[severity.index()] = style;
+        self
+    }
+
+    /// Sets whether a [CodeView](crate::widget::CodeView) underlines the ranges affected by
+    /// diagnostics, in the colour of their severity where the `underline-color` feature is
+    /// enabled. Enabled by default.
+    pub fn underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    /// Returns the diagnostics.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the most severe diagnostic of line `line`, if it has any.
+    pub fn most_severe(&self, line: usize) -> Option<Severity> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.line == line)
+            .map(|diagnostic| diagnostic.severity)
+            .min()
+    }
+
+    /// Returns the byte ranges of line `line` to underline, least severe first so that more
+    /// severe underlines are drawn over them, along with their style.
+    pub(crate) fn underlines(
+        &self,
+        line: usize,
+    ) -> Vec<(std::ops::Range<usize>, ratatui::style::Style)> {
+        if !self.underline {
+            return Vec::new();
+        }
+        let mut diagnostics: Vec<_> = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.line == line)
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| std::cmp::Reverse(diagnostic.severity));
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| {
+                let style =
+                    ratatui::style::Style::new().add_modifier(ratatui::style::Modifier::UNDERLINED);
+                #[cfg(feature = "underline-color")]
+                let style = match self.styles[diagnostic.severity.index()].fg {
+                    Some(colour) => style.underline_color(colour),
+                    None => style,
+                };
+                (diagnostic.columns.clone().unwrap_or(0..usize::MAX), style)
+            })
+            .collect()
+    }
+}
+
+impl ratatui::widgets::Widget for &Diagnostics {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        self.render_rows((self.scroll..).map(|line| line..line + 1), area, buf);
+    }
+}
+
+impl Diagnostics {
+    /// Renders the sign of each row, where each row shows a range of lines and is marked with
+    /// the most severe diagnostic of any of them.
+    pub(crate) fn render_rows(
+        &self,
+        rows: impl Iterator<Item = std::ops::Range<usize>>,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+    ) {
+        for (y, lines) in (area.top()..area.bottom()).zip(rows) {
+            let severity = self
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| lines.contains(&diagnostic.line))
+                .map(|diagnostic| diagnostic.severity)
+                .min();
+            if let Some(severity) = severity.filter(|_| area.width > 0) {
+                buf[(area.x, y)]
+                    .set_char(self.symbols[severity.index()])
+                    .set_style(self.styles[severity.index()]);
+            }
+        }
+    }
+}
+
+impl ratatui::widgets::Widget for Diagnostics {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::{Modifier, Style, Stylize};
+
+    fn fake_diagnostics() -> Diagnostics {
+        Diagnostics::new([
+            Diagnostic::new(0, Severity::Hint),
+            Diagnostic::new(0, Severity::Warning).columns(2..4),
+            Diagnostic::new(2, Severity::Information),
+        ])
+    }
+
+    #[rstest]
+    #[case::most_severe(0, Some(Severity::Warning))]
+    #[case::none(1, None)]
+    #[case::single(2, Some(Severity::Information))]
+    fn check_most_severe(#[case] line: usize, #[case] expected: Option<Severity>) {
+        assert_eq!(expected, fake_diagnostics().most_severe(line));
+    }
+
+    #[test]
+    fn underlines_most_severe_last() {
+        let ranges: Vec<_> = fake_diagnostics()
+            .underlines(0)
+            .into_iter()
+            .map(|(range, style)| {
+                assert!(style.add_modifier.contains(Modifier::UNDERLINED));
+                range
+            })
+            .collect();
+        assert_eq!(vec![0..usize::MAX, 2..4], ranges);
+    }
+
+    #[test]
+    fn underlines_disabled() {
+        assert!(fake_diagnostics().underline(false).underlines(0).is_empty());
+    }
+
+    #[test]
+    fn render_scrolled() {
+        use ratatui::widgets::Widget;
+        let diagnostics = fake_diagnostics()
+            .scroll(1)
+            .style(Severity::Information, Style::new().bold());
+        let mut buf = ratatui::buffer::Buffer::empty(ratatui::layout::Rect::new(0, 0, 1, 2));
+        diagnostics.render(buf.area, &mut buf);
+        let mut expected = ratatui::buffer::Buffer::with_lines([" ", "I"]);
+        expected.set_style(ratatui::layout::Rect::new(0, 1, 1, 1), Style::new().bold());
+        assert_eq!(expected, buf);
+    }
+}
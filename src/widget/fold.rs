@@ -0,0 +1,191 @@
+use super::CodeViewState;
+
+/// A row of a [CodeView](super::CodeView) as rendered: either a line, or the placeholder of a
+/// folded range of lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Row {
+    Line(usize),
+    Fold(std::ops::Range<usize>),
+}
+
+impl Row {
+    /// Returns the lines shown by the row.
+    pub(super) fn lines(&self) -> std::ops::Range<usize> {
+        match self {
+            Row::Line(line) => *line..*line + 1,
+            Row::Fold(lines) => lines.clone(),
+        }
+    }
+}
+
+impl CodeViewState {
+    /// Folds `lines`, collapsing them into a single placeholder row such as `⋯ 3 lines`.
+    ///
+    /// Folds that overlap `lines` are merged into it. Scrolling, paging and
+    /// [scroll_to_line](CodeViewState::scroll_to_line) count a fold as a single row, and the
+    /// [offset](CodeViewState::offset) of a view scrolled into a fold moves to its first line.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::StatefulWidget;
+    /// use syntect_tui::widget::{CodeView, CodeViewState, Gutter};
+    ///
+    /// let view = CodeView::new((0..6).map(|n| Line::raw(format!("line {n}"))).collect())
+    ///     .gutter(Gutter::default());
+    /// let mut state = CodeViewState::default();
+    /// state.fold(1..4);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 11, 2));
+    /// (&view).render(buf.area, &mut buf, &mut state);
+    /// let rows: Vec<String> = (0..2)
+    ///     .map(|y| (0..11).map(|x| buf[(x, y)].symbol()).collect())
+    ///     .collect();
+    /// assert_eq!(["1 line 0   ", "2 ⋯ 3 lines"], rows.as_slice());
+    /// state.scroll_down(2);
+    /// assert_eq!(4, state.offset());
+    /// ```
+    pub fn fold(&mut self, lines: std::ops::Range<usize>) {
+        if lines.is_empty() {
+            return;
+        }
+        let mut merged = lines;
+        self.folds.retain(|fold| {
+            let overlaps = fold.start < merged.end && merged.start < fold.end;
+            if overlaps {
+                merged = merged.start.min(fold.start)..merged.end.max(fold.end);
+            }
+            !overlaps
+        });
+        self.folds.push(merged);
+        self.folds.sort_by_key(|fold| fold.start);
+        self.clamp();
+    }
+
+    /// Unfolds the fold containing `line`, if any.
+    pub fn unfold(&mut self, line: usize) {
+        self.folds.retain(|fold| !fold.contains(&line));
+    }
+
+    /// Unfolds every fold.
+    pub fn unfold_all(&mut self) {
+        self.folds.clear();
+    }
+
+    /// Returns the folded ranges of lines, in order.
+    pub fn folds(&self) -> &[std::ops::Range<usize>] {
+        &self.folds
+    }
+
+    /// Returns the row that `line` is rendered in, counting from the first line.
+    pub(super) fn row_of(&self, line: usize) -> usize {
+        let hidden: usize = self
+            .folds
+            .iter()
+            .map(|fold| (fold.start + 1..fold.end.min(line + 1)).len())
+            .sum();
+        line - hidden
+    }
+
+    /// Returns the first line rendered in `row`.
+    pub(super) fn line_of(&self, row: usize) -> usize {
+        let mut line = row;
+        for fold in &self.folds {
+            if fold.start >= line {
+                break;
+            }
+            line += fold.len() - 1;
+        }
+        line
+    }
+
+    /// Returns the rows of `line_count` lines, with folded lines collapsed.
+    pub(super) fn rows(&self, line_count: usize) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut folds = self.folds.iter().peekable();
+        let mut line = 0;
+        while line < line_count {
+            match folds.next_if(|fold| fold.start <= line) {
+                Some(fold) => {
+                    rows.push(Row::Fold(line..fold.end.min(line_count)));
+                    line = fold.end;
+                }
+                None => {
+                    rows.push(Row::Line(line));
+                    line += 1;
+                }
+            }
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+
+    fn fake_state() -> CodeViewState {
+        let mut state = CodeViewState::default();
+        state.fold(2..5);
+        state.fold(6..8);
+        state
+    }
+
+    #[rstest]
+    #[case::before(1, 1)]
+    #[case::fold_start(2, 2)]
+    #[case::inside_fold(4, 2)]
+    #[case::between(5, 3)]
+    #[case::second_fold(7, 4)]
+    #[case::after(8, 5)]
+    fn check_row_of(#[case] line: usize, #[case] expected: usize) {
+        assert_eq!(expected, fake_state().row_of(line));
+    }
+
+    #[rstest]
+    #[case::before(1, 1)]
+    #[case::first_fold(2, 2)]
+    #[case::between(3, 5)]
+    #[case::second_fold(4, 6)]
+    #[case::after(5, 8)]
+    fn check_line_of(#[case] row: usize, #[case] expected: usize) {
+        assert_eq!(expected, fake_state().line_of(row));
+    }
+
+    #[test]
+    fn rows_collapse_folds() {
+        assert_eq!(
+            vec![
+                Row::Line(0),
+                Row::Line(1),
+                Row::Fold(2..5),
+                Row::Line(5),
+                Row::Fold(6..7),
+            ],
+            fake_state().rows(7)
+        );
+    }
+
+    #[rstest]
+    #[case::overlapping(3..7, vec![2..8])]
+    #[case::separate(0..1, vec![0..1, 2..5, 6..8])]
+    #[case::empty(1..1, vec![2..5, 6..8])]
+    fn check_fold(
+        #[case] lines: std::ops::Range<usize>,
+        #[case] expected: Vec<std::ops::Range<usize>>,
+    ) {
+        let mut state = fake_state();
+        state.fold(lines);
+        assert_eq!(expected, state.folds());
+    }
+
+    #[test]
+    fn unfold_removes_containing_fold() {
+        let mut state = fake_state();
+        state.unfold(3);
+        assert_eq!(std::slice::from_ref(&(6..8)), state.folds());
+    }
+}
@@ -0,0 +1,158 @@
+/// A widget that renders line numbers alongside highlighted source code.
+///
+/// A gutter can be rendered on its own next to any widget, or attached to a
+/// [CodeView](crate::widget::CodeView) using [CodeView::gutter](crate::widget::CodeView::gutter),
+/// which keeps it aligned with the view's scroll position. Numbers are right-aligned, one-based
+/// and followed by a single space of padding.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::widgets::Widget;
+/// use syntect_tui::widget::Gutter;
+///
+/// let gutter = Gutter::new(12).scroll(8).relative_to(9);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, gutter.width(), 4));
+/// (&gutter).render(buf.area, &mut buf);
+/// assert_eq!(Buffer::with_lines([" 1 ", "10 ", " 1 ", " 2 "]), buf);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Gutter {
+    line_count: usize,
+    rows: GutterRows,
+    minimum_width: u16,
+    current_line: Option<usize>,
+    style: ratatui::style::Style,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum GutterRows {
+    Scrolled(usize),
+    Explicit(Vec<Option<usize>>),
+}
+
+impl Default for GutterRows {
+    fn default() -> Self {
+        GutterRows::Scrolled(0)
+    }
+}
+
+impl Gutter {
+    /// Creates a gutter for content with `line_count` lines.
+    pub fn new(line_count: usize) -> Self {
+        Self {
+            line_count,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a gutter styled with the `gutter` and `gutter_foreground` settings of `theme`.
+    pub fn from_theme(line_count: usize, theme: &syntect::highlighting::Theme) -> Self {
+        let style = crate::translate_theme_settings(theme)
+            .gutter
+            .unwrap_or_default();
+        Self::new(line_count).style(style)
+    }
+
+    /// Sets the index of the line shown in the first row.
+    pub fn scroll(mut self, offset: usize) -> Self {
+        self.rows = GutterRows::Scrolled(offset);
+        self
+    }
+
+    /// Sets the line shown in each row explicitly, e.g. for wrapped content where continuation
+    /// rows (`None`) have no number.
+    pub fn rows(mut self, rows: Vec<Option<usize>>) -> Self {
+        self.rows = GutterRows::Explicit(rows);
+        self
+    }
+
+    /// Sets the minimum number of digits to leave room for.
+    pub fn minimum_width(mut self, width: u16) -> Self {
+        self.minimum_width = width;
+        self
+    }
+
+    /// Shows line numbers relative to `line`, while `line` itself shows its absolute number.
+    pub fn relative_to(mut self, line: usize) -> Self {
+        self.current_line = Some(line);
+        self
+    }
+
+    /// Sets the style of the gutter.
+    pub fn style(mut self, style: ratatui::style::Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the width of the gutter, including its padding.
+    pub fn width(&self) -> u16 {
+        let digits = self.line_count.max(1).ilog10() as u16 + 1;
+        digits.max(self.minimum_width) + 1
+    }
+
+    pub(crate) fn with_line_count(mut self, line_count: usize) -> Self {
+        self.line_count = line_count;
+        self
+    }
+
+    fn label(&self, line: usize) -> String {
+        match self.current_line {
+            Some(current) if current != line => current.abs_diff(line).to_string(),
+            _ => (line + 1).to_string(),
+        }
+    }
+}
+
+impl ratatui::widgets::Widget for &Gutter {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        buf.set_style(area, self.style);
+        let digits = usize::from(self.width().saturating_sub(1));
+        let rows: Box<dyn Iterator<Item = Option<usize>>> = match &self.rows {
+            GutterRows::Scrolled(offset) => Box::new((*offset..).map(Some)),
+            GutterRows::Explicit(rows) => Box::new(rows.iter().copied()),
+        };
+        for (y, line) in (area.top()..area.bottom()).zip(rows) {
+            if let Some(line) = line.filter(|line| *line < self.line_count) {
+                let label = format!("{:>digits$} ", self.label(line));
+                buf.set_stringn(
+                    area.x,
+                    y,
+                    label,
+                    usize::from(area.width),
+                    ratatui::style::Style::new(),
+                );
+            }
+        }
+    }
+}
+
+impl ratatui::widgets::Widget for Gutter {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::Widget;
+    use rstest::*;
+
+    #[rstest]
+    #[case::absolute(Gutter::new(3), ["1 ", "2 ", "3 ", "  "])]
+    #[case::minimum_width(Gutter::new(3).minimum_width(3).scroll(1), ["  2 ", "  3 ", "    ", "    "])]
+    #[case::wrapped(
+        Gutter::new(3).rows(vec![Some(0), None, Some(1), Some(2)]),
+        ["1 ", "  ", "2 ", "3 "]
+    )]
+    #[case::relative(Gutter::new(3).relative_to(1), ["1 ", "2 ", "1 ", "  "])]
+    fn check_render(#[case] gutter: Gutter, #[case] expected: [&str; 4]) {
+        let mut buf = Buffer::empty(Rect::new(0, 0, gutter.width(), 4));
+        gutter.render(buf.area, &mut buf);
+        assert_eq!(Buffer::with_lines(expected), buf);
+    }
+}
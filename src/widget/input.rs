@@ -0,0 +1,517 @@
+use unicode_width::UnicodeWidthStr;
+
+use super::cursor;
+
+/// The contents and cursor of an [InputLine], which are edited by the app, e.g. in response to
+/// key presses, and the scroll position kept in view when rendering it.
+///
+/// The cursor is a byte offset into the contents and always lies on a character boundary.
+/// Contents may span several lines, separated by `\n`, e.g. for a statement that is continued on
+/// the next line.
+///
+/// # Examples
+/// ```
+/// use syntect_tui::widget::InputLineState;
+///
+/// let mut state = InputLineState::new("SELECT 1");
+/// state.move_start();
+/// state.delete_forward();
+/// state.insert_str("s");
+/// assert_eq!("sELECT 1", state.content());
+/// assert_eq!(1, state.cursor());
+/// assert_eq!("sELECT 1", state.take());
+/// assert_eq!("", state.content());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InputLineState {
+    content: String,
+    cursor: usize,
+    offset: (usize, usize),
+    cursor_position: Option<ratatui::layout::Position>,
+}
+
+impl InputLineState {
+    /// Creates a state with `content` and the cursor at its end.
+    pub fn new(content: impl Into<String>) -> Self {
+        let content = content.into();
+        Self {
+            cursor: content.len(),
+            content,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the contents.
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Returns the byte offset of the cursor within the contents.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces the contents, e.g. with an entry from the history, and moves the cursor to the
+    /// end.
+    pub fn set_content(&mut self, content: impl Into<String>) {
+        self.content = content.into();
+        self.cursor = self.content.len();
+    }
+
+    /// Moves the cursor to byte offset `cursor`, rounded down to a character boundary and
+    /// clamped to the end of the contents.
+    pub fn set_cursor(&mut self, cursor: usize) {
+        let mut cursor = cursor.min(self.content.len());
+        while !self.content.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        self.cursor = cursor;
+    }
+
+    /// Clears the contents and returns them, e.g. to run them when Enter is pressed.
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        std::mem::take(&mut self.content)
+    }
+
+    /// Inserts `character` at the cursor and moves the cursor after it.
+    pub fn insert_char(&mut self, character: char) {
+        self.content.insert(self.cursor, character);
+        self.cursor += character.len_utf8();
+    }
+
+    /// Inserts `text` at the cursor, e.g. when pasting, and moves the cursor after it.
+    pub fn insert_str(&mut self, text: &str) {
+        self.content.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Deletes the character before the cursor, as Backspace does. Returns `false` if the cursor
+    /// is at the start.
+    pub fn delete_backward(&mut self) -> bool {
+        let Some(character) = self.content[..self.cursor].chars().next_back() else {
+            return false;
+        };
+        self.cursor -= character.len_utf8();
+        self.content.remove(self.cursor);
+        true
+    }
+
+    /// Deletes the character under the cursor, as Delete does. Returns `false` if the cursor is
+    /// at the end.
+    pub fn delete_forward(&mut self) -> bool {
+        if self.cursor == self.content.len() {
+            return false;
+        }
+        self.content.remove(self.cursor);
+        true
+    }
+
+    /// Moves the cursor one character to the left, onto the previous line at the start of one.
+    pub fn move_left(&mut self) {
+        if let Some(character) = self.content[..self.cursor].chars().next_back() {
+            self.cursor -= character.len_utf8();
+        }
+    }
+
+    /// Moves the cursor one character to the right, onto the next line at the end of one.
+    pub fn move_right(&mut self) {
+        if let Some(character) = self.content[self.cursor..].chars().next() {
+            self.cursor += character.len_utf8();
+        }
+    }
+
+    /// Moves the cursor to the start of its line.
+    pub fn move_start(&mut self) {
+        self.cursor = self.line_start();
+    }
+
+    /// Moves the cursor to the end of its line.
+    pub fn move_end(&mut self) {
+        self.cursor = self.content[self.cursor..]
+            .find('\n')
+            .map_or(self.content.len(), |end| self.cursor + end);
+    }
+
+    /// Moves the cursor to the same byte offset on the previous line, or the end of it if it is
+    /// shorter. Returns `false` if the cursor is on the first line, e.g. to recall history
+    /// instead.
+    pub fn move_up(&mut self) -> bool {
+        let start = self.line_start();
+        if start == 0 {
+            return false;
+        }
+        let previous = self.content[..start - 1]
+            .rfind('\n')
+            .map_or(0, |end| end + 1);
+        let column = (self.cursor - start).min(start - 1 - previous);
+        self.set_cursor(previous + column);
+        true
+    }
+
+    /// Moves the cursor to the same byte offset on the next line, or the end of it if it is
+    /// shorter. Returns `false` if the cursor is on the last line.
+    pub fn move_down(&mut self) -> bool {
+        let Some(end) = self.content[self.cursor..].find('\n') else {
+            return false;
+        };
+        let next = self.cursor + end + 1;
+        let next_end = self.content[next..]
+            .find('\n')
+            .map_or(self.content.len(), |end| next + end);
+        let column = self.cursor - self.line_start();
+        self.set_cursor(next + column.min(next_end - next));
+        true
+    }
+
+    /// Returns the position the cursor was drawn at by the last render, e.g. to place the
+    /// terminal's own cursor there with
+    /// [Frame::set_cursor_position](ratatui::Frame::set_cursor_position).
+    pub fn cursor_position(&self) -> Option<ratatui::layout::Position> {
+        self.cursor_position
+    }
+
+    /// Returns the byte offset of the start of the cursor's line.
+    fn line_start(&self) -> usize {
+        self.content[..self.cursor]
+            .rfind('\n')
+            .map_or(0, |end| end + 1)
+    }
+
+    /// Returns the line the cursor is on and its byte offset within that line.
+    fn line_and_column(&self) -> (usize, usize) {
+        let before = &self.content[..self.cursor];
+        (
+            before.matches('\n').count(),
+            self.cursor - self.line_start(),
+        )
+    }
+}
+
+/// A widget for typing code, e.g. the input line of a shell, database client or language REPL,
+/// that highlights its contents afresh each time it is rendered.
+///
+/// The contents and cursor live in an [InputLineState], which the app edits as keys are pressed.
+/// The first line is preceded by the [prompt](InputLine::prompt) and any further lines by the
+/// [continuation prompt](InputLine::continuation_prompt). The area is filled with the theme
+/// background, the cursor is drawn as a block in the [cursor style](InputLine::cursor_style),
+/// and the contents are scrolled to keep it in view.
+///
+/// Contents that fail to highlight are shown unhighlighted rather than not at all, since they are
+/// usually incomplete while being typed.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::{Position, Rect};
+/// use ratatui::style::{Color, Style};
+/// use ratatui::widgets::StatefulWidget;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::widget::{InputLine, InputLineState};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("py").unwrap();
+/// let input = InputLine::new(syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .prompt(">>> ", Style::new().fg(Color::Green));
+/// let mut state = InputLineState::new("print(1)");
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 14, 1));
+/// input.render(buf.area, &mut buf, &mut state);
+/// let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+/// assert_eq!(">>> print(1)  ", symbols);
+/// assert_eq!(Color::Green, buf[(0, 0)].fg);
+/// assert_eq!(Some(Position::new(12, 0)), state.cursor_position());
+/// ```
+#[derive(Clone, Debug)]
+pub struct InputLine<'a> {
+    syntax: &'a syntect::parsing::SyntaxReference,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    prompt: ratatui::text::Span<'a>,
+    continuation_prompt: Option<ratatui::text::Span<'a>>,
+    cursor_style: Option<ratatui::style::Style>,
+    tab_width: usize,
+}
+
+impl<'a> InputLine<'a> {
+    /// Creates an input line that highlights its contents using `syntax` and `theme`, with no
+    /// prompt.
+    ///
+    /// The cursor uses the `caret` setting of the theme.
+    pub fn new(
+        syntax: &'a syntect::parsing::SyntaxReference,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        Self {
+            syntax,
+            syntax_set,
+            theme,
+            prompt: ratatui::text::Span::raw(""),
+            continuation_prompt: None,
+            cursor_style: crate::translate_theme_settings(theme).caret,
+            tab_width: 4,
+        }
+    }
+
+    /// Sets the prompt before the first line and its style, which is patched over the theme
+    /// background.
+    pub fn prompt(
+        mut self,
+        prompt: impl Into<std::borrow::Cow<'a, str>>,
+        style: ratatui::style::Style,
+    ) -> Self {
+        self.prompt = ratatui::text::Span::styled(prompt, style);
+        self
+    }
+
+    /// Sets the prompt before every line after the first, e.g. `... `. Defaults to spaces as wide
+    /// as the [prompt](InputLine::prompt).
+    pub fn continuation_prompt(
+        mut self,
+        prompt: impl Into<std::borrow::Cow<'a, str>>,
+        style: ratatui::style::Style,
+    ) -> Self {
+        self.continuation_prompt = Some(ratatui::text::Span::styled(prompt, style));
+        self
+    }
+
+    /// Sets the style of the cursor. Without a style, the cursor is drawn with
+    /// [REVERSED](ratatui::style::Modifier::REVERSED).
+    pub fn cursor_style(mut self, style: ratatui::style::Style) -> Self {
+        self.cursor_style = Some(style);
+        self
+    }
+
+    /// Sets the number of display columns tabs are expanded to. Defaults to 4.
+    pub fn tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width;
+        self
+    }
+
+    /// Highlights `content`, returning one line per line of it, including an empty last line if
+    /// it ends with a newline.
+    fn highlight<'b>(&self, content: &'b str) -> Vec<ratatui::text::Line<'b>> {
+        let mut highlighter = syntect::easy::HighlightLines::new(self.syntax, self.theme);
+        let mut lines: Vec<_> = syntect::util::LinesWithEndings::from(content)
+            .map(|line| {
+                crate::highlight_line_to_line(&mut highlighter, self.syntax_set, self.theme, line)
+                    .unwrap_or_else(|_| ratatui::text::Line::raw(line.trim_end_matches('\n')))
+            })
+            .collect();
+        if content.is_empty() || content.ends_with('\n') {
+            lines.push(ratatui::text::Line::default());
+        }
+        lines
+    }
+
+    /// Returns the prompt before line `index`.
+    fn prompt_for(&self, index: usize) -> ratatui::text::Span<'a> {
+        match (index, &self.continuation_prompt) {
+            (0, _) => self.prompt.clone(),
+            (_, Some(prompt)) => prompt.clone(),
+            (_, None) => ratatui::text::Span::styled(
+                " ".repeat(self.prompt.content.width()),
+                self.prompt.style,
+            ),
+        }
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for InputLine<'_> {
+    type State = InputLineState;
+
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        ratatui::widgets::StatefulWidget::render(&self, area, buf, state);
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for &InputLine<'_> {
+    type State = InputLineState;
+
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        use ratatui::widgets::Widget;
+        buf.set_style(area, crate::theme_background(self.theme));
+        state.cursor_position = None;
+        if area.is_empty() {
+            return;
+        }
+        let (cursor_line, cursor_column) = state.line_and_column();
+        let mut lines = self.highlight(&state.content);
+        let cursor_line_text = lines[cursor_line].clone();
+        let style = self.cursor_style.unwrap_or(
+            ratatui::style::Style::new().add_modifier(ratatui::style::Modifier::REVERSED),
+        );
+        let line = &mut lines[cursor_line];
+        match cursor::character_at(line, cursor_column) {
+            Some(bytes) => *line = crate::overlay::select_line(line, bytes, style),
+            None => line.spans.push(ratatui::text::Span::styled(" ", style)),
+        }
+        // Keep the cursor in view, scrolling no further than needed.
+        let (height, width) = (usize::from(area.height), usize::from(area.width));
+        let x = self.prompt_for(cursor_line).content.width()
+            + cursor::display_column(&cursor_line_text, cursor_column, Some(self.tab_width));
+        let (rows, columns) = &mut state.offset;
+        *rows = (*rows).clamp((cursor_line + 1).saturating_sub(height), cursor_line);
+        *columns = (*columns).clamp((x + 1).saturating_sub(width), x);
+        for (row, (index, mut line)) in lines.into_iter().enumerate().skip(*rows).enumerate() {
+            if row >= height {
+                break;
+            }
+            crate::line::expand_tabs(&mut line.spans, self.tab_width);
+            line.spans.insert(0, self.prompt_for(index));
+            let line = crate::slice_line(&line, *columns..*columns + width, None);
+            let row_area = ratatui::layout::Rect {
+                y: area.y + row as u16,
+                height: 1,
+                ..area
+            };
+            line.render(row_area, buf);
+        }
+        state.cursor_position = Some(ratatui::layout::Position::new(
+            area.x + (x - *columns) as u16,
+            area.y + (cursor_line - *rows) as u16,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::{Position, Rect};
+    use ratatui::style::{Color, Style};
+    use ratatui::widgets::StatefulWidget;
+    use rstest::*;
+    use syntect::highlighting::Theme;
+    use syntect::parsing::SyntaxSet;
+
+    fn symbols(buf: &Buffer) -> Vec<String> {
+        buf.content
+            .chunks(usize::from(buf.area.width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    #[rstest]
+    #[case::empty("", 0, (0, 0))]
+    #[case::end_of_line("ab\ncd", 2, (0, 2))]
+    #[case::second_line("ab\ncd", 4, (1, 1))]
+    #[case::trailing_newline("ab\n", 3, (1, 0))]
+    fn check_line_and_column(
+        #[case] content: &str,
+        #[case] cursor: usize,
+        #[case] expected: (usize, usize),
+    ) {
+        let mut state = InputLineState::new(content);
+        state.set_cursor(cursor);
+        assert_eq!(expected, state.line_and_column());
+    }
+
+    #[test]
+    fn edits_around_multi_byte_characters() {
+        let mut state = InputLineState::new("a日");
+        state.move_left();
+        assert_eq!(1, state.cursor());
+        state.insert_char('é');
+        assert_eq!("aé日", state.content());
+        assert!(state.delete_backward());
+        assert!(state.delete_forward());
+        assert!(!state.delete_forward());
+        assert_eq!("a", state.content());
+        state.set_cursor(0);
+        assert!(!state.delete_backward());
+        state.move_right();
+        state.move_right();
+        assert_eq!(1, state.cursor());
+        state.insert_str("日本");
+        state.set_cursor(3);
+        assert_eq!(1, state.cursor());
+    }
+
+    #[rstest]
+    #[case::up_to_shorter_line("a\nbcd", 5, true, 1)]
+    #[case::up_keeps_column("abc\nd", 5, true, 1)]
+    #[case::up_from_first_line("abc\nd", 2, true, 2)]
+    #[case::down_to_shorter_line("abc\nd", 3, false, 5)]
+    #[case::down_keeps_column("abc\ndef\n", 1, false, 5)]
+    #[case::down_from_last_line("abc", 1, false, 1)]
+    fn check_vertical_moves(
+        #[case] content: &str,
+        #[case] cursor: usize,
+        #[case] up: bool,
+        #[case] expected: usize,
+    ) {
+        let mut state = InputLineState::new(content);
+        state.set_cursor(cursor);
+        if up {
+            state.move_up();
+        } else {
+            state.move_down();
+        }
+        assert_eq!(expected, state.cursor());
+    }
+
+    #[test]
+    fn home_and_end_stay_on_the_line() {
+        let mut state = InputLineState::new("ab\ncd\nef");
+        state.set_cursor(4);
+        state.move_start();
+        assert_eq!(3, state.cursor());
+        state.move_end();
+        assert_eq!(5, state.cursor());
+    }
+
+    #[rstest]
+    #[case::continuation("ab\nc", 4, 3, &["> ab", "  c ", "    "], (3, 1))]
+    #[case::scrolled_right("ab\ncdefgh", 9, 3, &["    ", "fgh ", "    "], (3, 1))]
+    #[case::scrolled_down("ab\nc\nd", 0, 1, &["> ab"], (2, 0))]
+    #[case::scrolled_to_last_line("ab\nc\nd", 7, 2, &["  c ", "  d "], (3, 1))]
+    #[case::trailing_newline("ab\n", 3, 2, &["> ab", "    "], (2, 1))]
+    fn check_render(
+        #[case] content: &str,
+        #[case] cursor: usize,
+        #[case] height: u16,
+        #[case] expected: &[&str],
+        #[case] position: (u16, u16),
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let theme = Theme::default();
+        let input = InputLine::new(ps.find_syntax_plain_text(), &ps, &theme)
+            .prompt("> ", Style::new().fg(Color::Green))
+            .cursor_style(Style::new().bg(Color::Red));
+        let mut state = InputLineState::new(content);
+        state.set_cursor(cursor);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, height));
+        (&input).render(buf.area, &mut buf, &mut state);
+        assert_eq!(expected.to_vec(), symbols(&buf));
+        let position = Position::from(position);
+        assert_eq!(Some(position), state.cursor_position());
+        assert_eq!(Color::Red, buf[position].bg);
+    }
+
+    #[test]
+    fn scrolling_is_kept_between_renders() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let theme = Theme::default();
+        let input = InputLine::new(ps.find_syntax_plain_text(), &ps, &theme)
+            .continuation_prompt(". ", Style::new());
+        let mut state = InputLineState::new("a\nb\nc");
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+        (&input).render(buf.area, &mut buf, &mut state);
+        state.move_up();
+        input.render(buf.area, &mut buf, &mut state);
+        assert_eq!(vec![". b ", ". c "], symbols(&buf));
+    }
+}
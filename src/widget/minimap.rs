@@ -0,0 +1,298 @@
+use unicode_width::UnicodeWidthChar;
+
+use crate::HighlightedText;
+
+/// The symbols a [Minimap] draws its dots with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MinimapSymbols {
+    /// Braille patterns, with a grid of 2×4 dots per cell.
+    #[default]
+    Braille,
+    /// Half blocks (`▀`, `▄` and `█`), with a grid of 1×2 dots per cell.
+    Blocks,
+}
+
+impl MinimapSymbols {
+    /// Returns the number of dots across and down each cell.
+    fn dots(self) -> (usize, usize) {
+        match self {
+            MinimapSymbols::Braille => (2, 4),
+            MinimapSymbols::Blocks => (1, 2),
+        }
+    }
+
+    /// Returns the symbol with the dots in `dots` set, indexed row by row.
+    fn symbol(self, dots: &[bool]) -> char {
+        match self {
+            MinimapSymbols::Braille => {
+                const BITS: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+                let bits: u32 = BITS
+                    .iter()
+                    .zip(dots)
+                    .filter(|(_, set)| **set)
+                    .map(|(bit, _)| bit)
+                    .sum();
+                char::from_u32(0x2800 + bits).unwrap_or(' ')
+            }
+            MinimapSymbols::Blocks => match dots {
+                [true, true] => '█',
+                [true, false] => '▀',
+                [false, true] => '▄',
+                _ => ' ',
+            },
+        }
+    }
+}
+
+/// A widget that renders a downsampled overview of highlighted text in a narrow column, for
+/// navigating large files.
+///
+/// Each cell covers a small grid of characters, drawn as a dot for every character that is not
+/// whitespace, and is coloured with the most common foreground colour of the characters it
+/// covers. The lines of the [viewport](Minimap::viewport), e.g. those visible in a
+/// [CodeView](crate::widget::CodeView), are marked with the
+/// [viewport style](Minimap::viewport_style), and the minimap scrolls to keep them in view if the
+/// text is too long to fit.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::style::{Color, Style, Stylize};
+/// use ratatui::text::{Line, Span};
+/// use ratatui::widgets::Widget;
+/// use syntect_tui::widget::{Minimap, MinimapSymbols};
+/// use syntect_tui::HighlightedText;
+///
+/// let text = HighlightedText::new(
+///     vec![
+///         Line::from(vec![Span::styled("fn", Style::new().blue()), Span::raw(" a")]),
+///         Line::styled("  b", Style::new().green()),
+///     ],
+///     None,
+/// );
+/// let minimap = Minimap::new(&text)
+///     .symbols(MinimapSymbols::Blocks)
+///     .viewport(1..2)
+///     .viewport_style(Style::new().bg(Color::DarkGray));
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+/// minimap.render(buf.area, &mut buf);
+/// let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+/// assert_eq!("▀▀▄▀", symbols);
+/// assert_eq!(Color::Blue, buf[(0, 0)].fg);
+/// assert_eq!(Color::DarkGray, buf[(0, 0)].bg);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Minimap<'a> {
+    text: &'a HighlightedText<'a>,
+    symbols: MinimapSymbols,
+    scale: usize,
+    viewport: Option<std::ops::Range<usize>>,
+    viewport_style: ratatui::style::Style,
+}
+
+impl<'a> Minimap<'a> {
+    /// Creates a minimap of `text`, filled with its background.
+    pub fn new(text: &'a HighlightedText<'a>) -> Self {
+        Self {
+            text,
+            symbols: MinimapSymbols::Braille,
+            scale: 1,
+            viewport: None,
+            viewport_style: ratatui::style::Style::new().bg(ratatui::style::Color::DarkGray),
+        }
+    }
+
+    /// Sets the symbols the dots are drawn with.
+    pub fn symbols(mut self, symbols: MinimapSymbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Sets the number of display columns each dot covers, to fit wider text into the minimap.
+    /// A `scale` of zero is treated as one.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Marks `lines` as the lines currently in view.
+    pub fn viewport(mut self, lines: std::ops::Range<usize>) -> Self {
+        self.viewport = Some(lines);
+        self
+    }
+
+    /// Sets the style of the rows covering the [viewport](Minimap::viewport), e.g. the
+    /// `line_highlight` of [translate_theme_settings](crate::translate_theme_settings). Defaults
+    /// to a dark grey background.
+    pub fn viewport_style(mut self, style: ratatui::style::Style) -> Self {
+        self.viewport_style = style;
+        self
+    }
+
+    /// Returns the index of the first row of cells to render in an area `height` rows high.
+    fn first_row(&self, height: usize) -> usize {
+        let (_, down) = self.symbols.dots();
+        let rows = self.text.lines.len().div_ceil(down);
+        match &self.viewport {
+            Some(viewport) if rows > height => {
+                let centre = (viewport.start + viewport.end) / 2 / down;
+                centre.saturating_sub(height / 2).min(rows - height)
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Returns the foreground colour of each display column of `line`, or `None` for whitespace.
+fn columns(line: &ratatui::text::Line) -> Vec<Option<ratatui::style::Color>> {
+    let mut columns = Vec::new();
+    for span in &line.spans {
+        let colour = span
+            .style
+            .fg
+            .or(line.style.fg)
+            .unwrap_or(ratatui::style::Color::Reset);
+        for character in span.content.chars() {
+            let visible = (!character.is_whitespace()).then_some(colour);
+            let width = character.width().unwrap_or(0);
+            columns.extend(std::iter::repeat_n(visible, width));
+        }
+    }
+    columns
+}
+
+impl ratatui::widgets::Widget for Minimap<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+impl ratatui::widgets::Widget for &Minimap<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        buf.set_style(area, self.text.style());
+        let (across, down) = self.symbols.dots();
+        let first_row = self.first_row(usize::from(area.height));
+        for (y, row) in (area.top()..area.bottom()).zip(first_row..) {
+            if row * down >= self.text.lines.len() {
+                break;
+            }
+            let columns: Vec<_> = (row * down..(row + 1) * down)
+                .map(|index| self.text.lines.get(index).map(columns).unwrap_or_default())
+                .collect();
+            let in_viewport = self.viewport.as_ref().is_some_and(|viewport| {
+                viewport.start < (row + 1) * down && row * down < viewport.end
+            });
+            if in_viewport {
+                let row_area = ratatui::layout::Rect {
+                    y,
+                    height: 1,
+                    ..area
+                };
+                buf.set_style(row_area, self.viewport_style);
+            }
+            for (x, cell) in (area.left()..area.right()).zip(0..) {
+                let mut dots = Vec::with_capacity(across * down);
+                let mut counts: Vec<(ratatui::style::Color, usize)> = Vec::new();
+                for line in &columns {
+                    for dot in 0..across {
+                        let start = (cell * across + dot) * self.scale;
+                        let colours = line.iter().skip(start).take(self.scale).flatten();
+                        let mut set = false;
+                        for colour in colours {
+                            set = true;
+                            match counts.iter_mut().find(|(counted, _)| counted == colour) {
+                                Some((_, count)) => *count += 1,
+                                None => counts.push((*colour, 1)),
+                            }
+                        }
+                        dots.push(set);
+                    }
+                }
+                let dominant = counts
+                    .iter()
+                    .rev()
+                    .max_by_key(|(_, count)| *count)
+                    .map(|(colour, _)| *colour);
+                if let Some(colour) = dominant {
+                    buf[(x, y)]
+                        .set_char(self.symbols.symbol(&dots))
+                        .set_fg(colour);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Style};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::Widget;
+
+    fn fake_text(count: usize) -> HighlightedText<'static> {
+        HighlightedText::new(
+            (0..count)
+                .map(|n| Line::styled(format!("{n}"), Style::new().fg(Color::Red)))
+                .collect(),
+            Some(Color::Black),
+        )
+    }
+
+    #[rstest]
+    #[case::full(&[true; 8], '⣿')]
+    #[case::empty(&[false; 8], '⠀')]
+    #[case::top_left(&[true, false, false, false, false, false, false, false], '⠁')]
+    #[case::bottom_right(&[false, false, false, false, false, false, false, true], '⢀')]
+    fn check_braille_symbol(#[case] dots: &[bool], #[case] expected: char) {
+        assert_eq!(expected, MinimapSymbols::Braille.symbol(dots));
+    }
+
+    #[rstest]
+    #[case::fits(4, Some(0..2), 0)]
+    #[case::no_viewport(40, None, 0)]
+    #[case::centred(40, Some(20..24), 4)]
+    #[case::end(40, Some(36..40), 8)]
+    fn check_first_row(
+        #[case] count: usize,
+        #[case] viewport: Option<std::ops::Range<usize>>,
+        #[case] expected: usize,
+    ) {
+        let text = fake_text(count);
+        let mut minimap = Minimap::new(&text);
+        if let Some(viewport) = viewport {
+            minimap = minimap.viewport(viewport);
+        }
+        assert_eq!(expected, minimap.first_row(2));
+    }
+
+    #[test]
+    fn dominant_colour_wins() {
+        let text = HighlightedText::new(
+            vec![Line::from(vec![
+                Span::styled("a", Style::new().fg(Color::Blue)),
+                Span::styled("bc", Style::new().fg(Color::Green)),
+            ])],
+            None,
+        );
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+        Minimap::new(&text).scale(2).render(buf.area, &mut buf);
+        assert_eq!("⠉", buf[(0, 0)].symbol());
+        assert_eq!(Color::Green, buf[(0, 0)].fg);
+    }
+
+    #[test]
+    fn render_fills_background() {
+        let text = fake_text(1);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+        Minimap::new(&text).render(buf.area, &mut buf);
+        assert!(buf.content.iter().all(|cell| cell.bg == Color::Black));
+        assert_eq!("⠁", buf[(0, 0)].symbol());
+        assert_eq!(" ", buf[(1, 0)].symbol());
+    }
+}
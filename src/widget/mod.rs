@@ -0,0 +1,1446 @@
+//! Ready-made ratatui widgets for rendering highlighted source code, behind the `widget` feature.
+use crate::{HighlightedText, IndentGuides, InlayHint, Selection, SyntectTuiError, Whitespace};
+
+mod annotation;
+mod cursor;
+mod diagnostic;
+mod fold;
+mod gutter;
+mod input;
+mod minimap;
+mod paragraph;
+mod preview;
+mod split_diff;
+
+pub use annotation::{Annotation, Annotations};
+pub use cursor::CursorShape;
+pub use diagnostic::{Diagnostic, Diagnostics, Severity};
+pub use gutter::Gutter;
+pub use input::{InputLine, InputLineState};
+pub use minimap::{Minimap, MinimapSymbols};
+pub use paragraph::HighlightedParagraph;
+pub use preview::{BinaryView, FilePreview};
+pub use split_diff::SplitDiffView;
+
+use fold::Row;
+
+/// A widget that renders highlighted source code with vertical and horizontal scrolling.
+///
+/// The whole area is filled with the theme background, and lines are rendered starting from the
+/// [scroll offset](CodeView::scroll) and [column offset](CodeView::scroll_columns). Lines wider
+/// than the area are clipped.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::widgets::Widget;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::widget::CodeView;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "fn main() {\n    println!(\"hi\");\n}\n";
+/// let view = CodeView::highlight(source, syntax, &ps, &ts.themes["base16-ocean.dark"])
+///     .unwrap()
+///     .scroll(1);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+/// (&view).render(buf.area, &mut buf);
+/// assert_eq!("    printl", buf.content[..10].iter().map(|cell| cell.symbol()).collect::<String>());
+/// assert_eq!("}", buf[(0, 1)].symbol());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeView<'a> {
+    text: HighlightedText<'a>,
+    scroll: usize,
+    column: usize,
+    overflow_indicators: Option<(char, char)>,
+    gutter: Option<Gutter>,
+    current_line: Option<usize>,
+    line_highlight: Option<syntect::highlighting::Color>,
+    selection: Option<Selection>,
+    selection_style: Option<ratatui::style::Style>,
+    line_starts: Vec<usize>,
+    cursor: Option<(usize, usize)>,
+    cursor_shape: CursorShape,
+    cursor_style: Option<ratatui::style::Style>,
+    bracket_style: Option<ratatui::style::Style>,
+    tab_width: Option<usize>,
+    matches: Vec<Selection>,
+    current_match: Option<usize>,
+    match_style: Option<ratatui::style::Style>,
+    current_match_style: Option<ratatui::style::Style>,
+    diagnostics: Option<Diagnostics>,
+    annotations: Option<Annotations>,
+    whitespace: Option<Whitespace>,
+    show_whitespace: bool,
+    indent_guides: Option<IndentGuides>,
+    inlay_hints: Vec<InlayHint>,
+    hint_style: ratatui::style::Style,
+    fold_style: ratatui::style::Style,
+}
+
+impl<'a> CodeView<'a> {
+    /// Creates a view of already converted lines, with no background.
+    pub fn new(lines: Vec<ratatui::text::Line<'a>>) -> Self {
+        Self::from(HighlightedText::new(lines, None))
+    }
+
+    /// Highlights `source` using `syntax` and `theme`, and creates a view of it.
+    ///
+    /// # Errors
+    /// Can return the same errors as [highlight_line_to_line](crate::highlight_line_to_line).
+    pub fn highlight(
+        source: &'a str,
+        syntax: &syntect::parsing::SyntaxReference,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        theme: &syntect::highlighting::Theme,
+    ) -> Result<Self, SyntectTuiError> {
+        let mut highlighter = syntect::easy::HighlightLines::new(syntax, theme);
+        let mut line_starts = Vec::new();
+        let lines = syntect::util::LinesWithEndings::from(source)
+            .inspect(|line| line_starts.push(line.as_ptr() as usize - source.as_ptr() as usize))
+            .enumerate()
+            .map(|(index, line)| {
+                crate::highlight_line_to_line(&mut highlighter, syntax_set, theme, line)
+                    .map_err(|error| error.at_line(index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let settings = crate::translate_theme_settings(theme);
+        Ok(Self {
+            line_highlight: theme.settings.line_highlight,
+            selection_style: settings.selection,
+            cursor_style: settings.caret,
+            bracket_style: settings.brackets,
+            match_style: settings.find_highlight,
+            fold_style: settings.gutter.unwrap_or_default(),
+            line_starts,
+            ..Self::from(HighlightedText::from_theme(lines, theme))
+        })
+    }
+
+    /// Sets the index of the first line to render.
+    pub fn scroll(mut self, offset: usize) -> Self {
+        self.scroll = offset;
+        self
+    }
+
+    /// Sets the display column at which lines start to be rendered.
+    ///
+    /// Lines are sliced by display width using [slice_line](crate::slice_line), so span styles are
+    /// kept, wide characters cut by either edge become spaces and, with a
+    /// [tab width](CodeView::tab_width), tabs keep their tab stops.
+    pub fn scroll_columns(mut self, offset: usize) -> Self {
+        self.column = offset;
+        self
+    }
+
+    /// Marks lines that continue past the left or right edge of the view by drawing `left` or
+    /// `right` over their first or last visible column.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    ///
+    /// let view = CodeView::new(vec![Line::raw("let x = 1;"), Line::raw("x")])
+    ///     .scroll_columns(2)
+    ///     .overflow_indicators('<', '>');
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 5, 2));
+    /// view.render(buf.area, &mut buf);
+    /// assert_eq!(Buffer::with_lines(["< x >", "<    "]), buf);
+    /// ```
+    pub fn overflow_indicators(mut self, left: char, right: char) -> Self {
+        self.overflow_indicators = Some((left, right));
+        self
+    }
+
+    /// Renders `gutter` to the left of the code, numbering the view's lines and following its
+    /// scroll position.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::{CodeView, Gutter};
+    ///
+    /// let lines = (0..10).map(|n| Line::raw(format!("line {n}"))).collect();
+    /// let view = CodeView::new(lines).gutter(Gutter::default()).scroll(8);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 9, 3));
+    /// view.render(buf.area, &mut buf);
+    /// assert_eq!(Buffer::with_lines([" 9 line 8", "10 line 9", "         "]), buf);
+    /// ```
+    pub fn gutter(mut self, gutter: Gutter) -> Self {
+        self.gutter = Some(gutter);
+        self
+    }
+
+    /// Marks line `index` as the current line, e.g. the line containing the cursor, tinting its
+    /// background with the [line highlight colour](CodeView::line_highlight).
+    ///
+    /// The tint is blended over the token backgrounds of the line as described in
+    /// [highlight_current_line](crate::highlight_current_line), and covers the whole row even if
+    /// the line is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::style::Color;
+    /// use ratatui::widgets::Widget;
+    /// use syntect::highlighting::ThemeSet;
+    /// use syntect::parsing::SyntaxSet;
+    /// use syntect_tui::widget::CodeView;
+    ///
+    /// let ps = SyntaxSet::load_defaults_newlines();
+    /// let ts = ThemeSet::load_defaults();
+    /// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+    /// let view = CodeView::highlight("fn main() {\n}\n", syntax, &ps, &ts.themes["base16-ocean.dark"])
+    ///     .unwrap()
+    ///     .current_line(1);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+    /// view.render(buf.area, &mut buf);
+    /// assert_eq!(Color::Rgb(43, 48, 59), buf[(3, 0)].bg);
+    /// assert_eq!(Color::Rgb(54, 61, 72), buf[(3, 1)].bg);
+    /// ```
+    pub fn current_line(mut self, index: usize) -> Self {
+        self.current_line = Some(index);
+        self
+    }
+
+    /// Sets the colour used to tint the [current line](CodeView::current_line), blended using its
+    /// alpha value.
+    ///
+    /// Views created by [highlight](CodeView::highlight) use the `line_highlight` setting of the
+    /// theme.
+    pub fn line_highlight(mut self, colour: syntect::highlighting::Color) -> Self {
+        self.line_highlight = Some(colour);
+        self
+    }
+
+    /// Selects a range of the source, styling it with the
+    /// [selection style](CodeView::selection_style).
+    ///
+    /// Spans are split at the selection boundaries, so only the selected characters are styled.
+    /// [Selection::Bytes] offsets are resolved against the source given to
+    /// [highlight](CodeView::highlight), whatever its line endings; for views created from
+    /// converted lines, lines are assumed to have been separated by a single `\n`.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    /// use syntect_tui::Selection;
+    ///
+    /// let view = CodeView::new(vec![Line::raw("abc"), Line::raw("def")])
+    ///     .selection_style(Style::new().bg(Color::Blue))
+    ///     .selection(Selection::Positions((0, 2)..(1, 1)));
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 3, 2));
+    /// view.render(buf.area, &mut buf);
+    /// let mut expected = Buffer::with_lines(["abc", "def"]);
+    /// expected.set_style(Rect::new(2, 0, 1, 1), Style::new().bg(Color::Blue));
+    /// expected.set_style(Rect::new(0, 1, 1, 1), Style::new().bg(Color::Blue));
+    /// assert_eq!(expected, buf);
+    /// ```
+    pub fn selection(mut self, selection: Selection) -> Self {
+        self.selection = Some(selection);
+        self
+    }
+
+    /// Sets the style patched over the [selection](CodeView::selection).
+    ///
+    /// Views created by [highlight](CodeView::highlight) use the `selection` and
+    /// `selection_foreground` settings of the theme.
+    pub fn selection_style(mut self, style: ratatui::style::Style) -> Self {
+        self.selection_style = Some(style);
+        self
+    }
+
+    /// Highlights search matches, e.g. the byte ranges found by a regex search of the source,
+    /// with the [match style](CodeView::match_style) layered over the syntax colours.
+    ///
+    /// Matches are split out of their spans like a [selection](CodeView::selection), which is
+    /// drawn over them. The [current match](CodeView::current_match) is drawn with the
+    /// [current match style](CodeView::current_match_style) instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    /// use syntect_tui::Selection;
+    ///
+    /// let source = "let a = 1;\nlet b = a;\n";
+    /// let lines = source.lines().map(Line::raw).collect();
+    /// let matches = source.match_indices('a').map(|(start, _)| Selection::Bytes(start..start + 1));
+    /// let view = CodeView::new(lines)
+    ///     .match_style(Style::new().bg(Color::Yellow))
+    ///     .current_match_style(Style::new().bg(Color::Red))
+    ///     .matches(matches)
+    ///     .current_match(1);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+    /// view.render(buf.area, &mut buf);
+    /// assert_eq!(Color::Yellow, buf[(4, 0)].bg);
+    /// assert_eq!(Color::Red, buf[(8, 1)].bg);
+    /// ```
+    pub fn matches(mut self, matches: impl IntoIterator<Item = Selection>) -> Self {
+        self.matches = matches.into_iter().collect();
+        self
+    }
+
+    /// Sets the index of the current search match, which is drawn with the
+    /// [current match style](CodeView::current_match_style).
+    pub fn current_match(mut self, index: usize) -> Self {
+        self.current_match = Some(index);
+        self
+    }
+
+    /// Sets the style patched over [search matches](CodeView::matches).
+    ///
+    /// Views created by [highlight](CodeView::highlight) use the `find_highlight` and
+    /// `find_highlight_foreground` settings of the theme.
+    pub fn match_style(mut self, style: ratatui::style::Style) -> Self {
+        self.match_style = Some(style);
+        self
+    }
+
+    /// Sets the style patched over the [current match](CodeView::current_match). Defaults to the
+    /// [match style](CodeView::match_style) with [REVERSED](ratatui::style::Modifier::REVERSED)
+    /// added.
+    pub fn current_match_style(mut self, style: ratatui::style::Style) -> Self {
+        self.current_match_style = Some(style);
+        self
+    }
+
+    /// Scrolls so that search match `index` starts on the first rendered line, and makes it the
+    /// current match. Does nothing if there is no such match.
+    ///
+    /// Use [CodeViewState::scroll_to_match] to scroll as little as possible instead.
+    pub fn scroll_to_match(self, index: usize) -> Self {
+        match self.match_position(index) {
+            Some((line, _)) => self.scroll(line).current_match(index),
+            None => self,
+        }
+    }
+
+    /// Returns the `(line, column)` position at which search match `index` starts, if there is
+    /// such a match.
+    pub fn match_position(&self, index: usize) -> Option<(usize, usize)> {
+        let found = self.matches.get(index)?;
+        Some(found.positions(&self.line_starts()).start)
+    }
+
+    /// Draws a cursor at byte offset `column` of line `line`, e.g. for editor-style apps.
+    ///
+    /// The cursor is drawn in the [cursor shape](CodeView::cursor_shape) using the
+    /// [cursor style](CodeView::cursor_style), and is positioned by display width so it lands on
+    /// the right cell after wide characters and, with a [tab width](CodeView::tab_width), tabs.
+    /// A cursor at or past the end of the line is drawn after its last character.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::{Position, Rect};
+    /// use ratatui::style::Modifier;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    ///
+    /// let view = CodeView::new(vec![Line::raw("日本")]).cursor(0, 3);
+    /// assert_eq!(Some(Position::new(2, 0)), view.cursor_position(Rect::new(0, 0, 5, 1)));
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 5, 1));
+    /// view.render(buf.area, &mut buf);
+    /// assert_eq!(Modifier::REVERSED, buf[(2, 0)].modifier);
+    /// ```
+    pub fn cursor(mut self, line: usize, column: usize) -> Self {
+        self.cursor = Some((line, column));
+        self
+    }
+
+    /// Sets the shape of the [cursor](CodeView::cursor).
+    pub fn cursor_shape(mut self, shape: CursorShape) -> Self {
+        self.cursor_shape = shape;
+        self
+    }
+
+    /// Sets the style of the [cursor](CodeView::cursor).
+    ///
+    /// Views created by [highlight](CodeView::highlight) use the `caret` setting of the theme.
+    /// Without a style, block cursors are drawn with
+    /// [REVERSED](ratatui::style::Modifier::REVERSED), and bar and underline cursors in the
+    /// default colour.
+    pub fn cursor_style(mut self, style: ratatui::style::Style) -> Self {
+        self.cursor_style = Some(style);
+        self
+    }
+
+    /// Sets the style patched over the bracket at the [cursor](CodeView::cursor) and the bracket
+    /// matching it, as found by [matching_bracket](crate::matching_bracket).
+    ///
+    /// Views created by [highlight](CodeView::highlight) use the `brackets_foreground` and
+    /// `brackets_background` settings of the theme, if it has them. Brackets are not highlighted
+    /// without a style.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::style::{Color, Style};
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    ///
+    /// let view = CodeView::new(vec![Line::raw("f(x[0])")])
+    ///     .bracket_style(Style::new().bg(Color::Green))
+    ///     .cursor(0, 7);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 8, 1));
+    /// view.render(buf.area, &mut buf);
+    /// assert_eq!(Color::Green, buf[(1, 0)].bg);
+    /// assert_eq!(Color::Green, buf[(6, 0)].bg);
+    /// assert_eq!(Color::Reset, buf[(3, 0)].bg);
+    /// ```
+    pub fn bracket_style(mut self, style: ratatui::style::Style) -> Self {
+        self.bracket_style = Some(style);
+        self
+    }
+
+    /// Expands tabs to the next multiple of `width` display columns when rendering.
+    ///
+    /// Terminals draw tabs inconsistently, so this should be set for sources that may contain
+    /// them unless the lines were converted with
+    /// [expand_tabs](crate::StyleTranslator::expand_tabs). Cursor and selection positions still
+    /// refer to the unexpanded lines.
+    pub fn tab_width(mut self, width: usize) -> Self {
+        self.tab_width = Some(width);
+        self
+    }
+
+    /// Returns the position at which the [cursor](CodeView::cursor) is drawn when the view is
+    /// rendered in `area`, e.g. to place the terminal's own cursor there with
+    /// [Frame::set_cursor_position](ratatui::Frame::set_cursor_position).
+    ///
+    /// Returns `None` if there is no cursor or it is scrolled out of view.
+    pub fn cursor_position(
+        &self,
+        area: ratatui::layout::Rect,
+    ) -> Option<ratatui::layout::Position> {
+        let [_, _, _, area] = self.split_margins(area);
+        self.cursor_cell(&self.rows(self.scroll, area), self.column, area)
+    }
+
+    /// Attaches `diagnostics` to the view, marking lines with their most severe diagnostic in a
+    /// sign column to the left of the [gutter](CodeView::gutter), and underlining the ranges they
+    /// affect unless [disabled](Diagnostics::underline).
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::style::Modifier;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::{CodeView, Diagnostic, Diagnostics, Gutter, Severity};
+    ///
+    /// let diagnostics = Diagnostics::new([Diagnostic::new(1, Severity::Error).columns(4..5)]);
+    /// let view = CodeView::new(vec![Line::raw("let x = 1;"), Line::raw("let y = x;")])
+    ///     .gutter(Gutter::default())
+    ///     .diagnostics(diagnostics);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+    /// view.render(buf.area, &mut buf);
+    /// let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+    /// assert_eq!(" 1 let xE2 let y", symbols);
+    /// assert!(buf[(7, 1)].modifier.contains(Modifier::UNDERLINED));
+    /// assert!(!buf[(6, 1)].modifier.contains(Modifier::UNDERLINED));
+    /// ```
+    pub fn diagnostics(mut self, diagnostics: Diagnostics) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Shows `annotations` in a column between the sign column and the gutter, aligned with the
+    /// rendered lines. The annotations' own scroll offset is ignored.
+    ///
+    /// A row standing in for [folded lines](CodeViewState::fold) shows the annotation of the
+    /// first of them.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::{Annotation, Annotations, CodeView};
+    ///
+    /// let commit = Some(Annotation::new(["a1", "ana"]));
+    /// let annotations = Annotations::new([commit.clone(), commit.clone(), commit]);
+    /// let view = CodeView::new(vec![Line::raw("a"), Line::raw("b"), Line::raw("c")])
+    ///     .annotations(annotations)
+    ///     .scroll(1);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+    /// view.render(buf.area, &mut buf);
+    /// assert_eq!(Buffer::with_lines(["a1 ana b", "       c"]), buf);
+    /// ```
+    pub fn annotations(mut self, annotations: Annotations) -> Self {
+        self.annotations = Some(annotations);
+        self
+    }
+
+    /// Sets the symbols and style used when [showing whitespace](CodeView::show_whitespace).
+    /// Defaults to [Whitespace::default].
+    pub fn whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.whitespace = Some(whitespace);
+        self
+    }
+
+    /// Shows or hides whitespace, e.g. to toggle it from a key binding, as described in
+    /// [show_whitespace](crate::show_whitespace). Tabs are shown with their
+    /// [tab width](CodeView::tab_width), so display columns and the cursor are unaffected.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    ///
+    /// let view = CodeView::new(vec![Line::raw("\tx; ")]).tab_width(2);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 6, 1));
+    /// (&view).render(buf.area, &mut buf);
+    /// assert_eq!(Buffer::with_lines(["  x;  "]), buf);
+    /// view.show_whitespace(true).render(buf.area, &mut buf);
+    /// let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+    /// assert_eq!("→ x;· ", symbols);
+    /// ```
+    pub fn show_whitespace(mut self, show: bool) -> Self {
+        self.show_whitespace = show;
+        self
+    }
+
+    /// Draws indent guides in the leading whitespace of each line, as described in
+    /// [show_indent_guides](crate::show_indent_guides), e.g. with
+    /// [IndentGuides::from_theme] for a colour that suits the theme.
+    ///
+    /// Leading tabs are expanded with the [tab width](CodeView::tab_width), which should be set
+    /// for sources indented with tabs so the cursor stays in line with the guides. Guides are
+    /// drawn before the whitespace is [shown](CodeView::show_whitespace), and scroll horizontally
+    /// with the code.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    /// use syntect_tui::IndentGuides;
+    ///
+    /// let view = CodeView::new(vec![Line::raw("\t\tx")])
+    ///     .tab_width(2)
+    ///     .indent_guides(IndentGuides::new().width(2))
+    ///     .scroll_columns(1);
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+    /// view.render(buf.area, &mut buf);
+    /// let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+    /// assert_eq!(" │ x", symbols);
+    /// ```
+    pub fn indent_guides(mut self, guides: IndentGuides) -> Self {
+        self.indent_guides = Some(guides);
+        self
+    }
+
+    /// Shows virtual text inside lines, e.g. inlay type hints or parameter names, in the
+    /// [hint style](CodeView::hint_style), as described in
+    /// [insert_inlay_hints](crate::insert_inlay_hints).
+    ///
+    /// Hints are inserted after the selection, search matches and cursor are applied, and the
+    /// [cursor position](CodeView::cursor_position) accounts for them, so columns keep referring
+    /// to the source without the hints.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::{Position, Rect};
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::Widget;
+    /// use syntect_tui::widget::CodeView;
+    /// use syntect_tui::InlayHint;
+    ///
+    /// let view = CodeView::new(vec![Line::raw("let x = 1;")])
+    ///     .inlay_hints([InlayHint::new(0, 5, ": i32")])
+    ///     .cursor(0, 8);
+    /// let area = Rect::new(0, 0, 15, 1);
+    /// let mut buf = Buffer::empty(area);
+    /// (&view).render(area, &mut buf);
+    /// let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+    /// assert_eq!("let x: i32 = 1;", symbols);
+    /// assert_eq!(Some(Position::new(13, 0)), view.cursor_position(area));
+    /// ```
+    pub fn inlay_hints(mut self, hints: impl IntoIterator<Item = InlayHint>) -> Self {
+        self.inlay_hints = hints.into_iter().collect();
+        self
+    }
+
+    /// Sets the style of [inlay hints](CodeView::inlay_hints), which each hint's own style is
+    /// patched over. Defaults to [DIM](ratatui::style::Modifier::DIM) and
+    /// [ITALIC](ratatui::style::Modifier::ITALIC).
+    pub fn hint_style(mut self, style: ratatui::style::Style) -> Self {
+        self.hint_style = style;
+        self
+    }
+
+    /// Sets the style of the placeholder rows that stand in for
+    /// [folded lines](CodeViewState::fold).
+    ///
+    /// Views created by [highlight](CodeView::highlight) use the `gutter` and `gutter_foreground`
+    /// settings of the theme, and other views are [DIM](ratatui::style::Modifier::DIM).
+    pub fn fold_style(mut self, style: ratatui::style::Style) -> Self {
+        self.fold_style = style;
+        self
+    }
+
+    /// Returns the index of the first line to render.
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll
+    }
+
+    /// Returns the number of lines in the view, e.g. to clamp scrolling.
+    pub fn line_count(&self) -> usize {
+        self.text.lines.len()
+    }
+}
+
+impl<'a> From<HighlightedText<'a>> for CodeView<'a> {
+    fn from(text: HighlightedText<'a>) -> Self {
+        Self {
+            text,
+            scroll: 0,
+            column: 0,
+            overflow_indicators: None,
+            gutter: None,
+            current_line: None,
+            line_highlight: None,
+            selection: None,
+            selection_style: None,
+            line_starts: Vec::new(),
+            cursor: None,
+            cursor_shape: CursorShape::Block,
+            cursor_style: None,
+            bracket_style: None,
+            tab_width: None,
+            matches: Vec::new(),
+            current_match: None,
+            match_style: None,
+            current_match_style: None,
+            diagnostics: None,
+            annotations: None,
+            whitespace: None,
+            show_whitespace: false,
+            indent_guides: None,
+            inlay_hints: Vec::new(),
+            hint_style: ratatui::style::Style::new()
+                .add_modifier(ratatui::style::Modifier::DIM | ratatui::style::Modifier::ITALIC),
+            fold_style: ratatui::style::Style::new().add_modifier(ratatui::style::Modifier::DIM),
+        }
+    }
+}
+
+impl ratatui::widgets::Widget for CodeView<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+impl ratatui::widgets::Widget for &CodeView<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        let rows = self.rows(self.scroll, area);
+        self.render_from(&rows, self.column, None, area, buf);
+    }
+}
+
+impl CodeView<'_> {
+    fn render_from(
+        &self,
+        rows: &[Row],
+        column: usize,
+        selected_lines: Option<&std::ops::Range<usize>>,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+    ) {
+        use ratatui::widgets::Widget;
+        let [signs_area, annotations_area, gutter_area, area] = self.split_margins(area);
+        if let Some(diagnostics) = &self.diagnostics {
+            diagnostics.render_rows(rows.iter().map(Row::lines), signs_area, buf);
+        }
+        if let Some(annotations) = &self.annotations {
+            annotations.render_rows(
+                rows.iter().map(|row| row.lines().start),
+                annotations_area,
+                buf,
+            );
+        }
+        if let Some(gutter) = &self.gutter {
+            gutter
+                .clone()
+                .with_line_count(self.line_count())
+                .rows(rows.iter().map(|row| Some(row.lines().start)).collect())
+                .render(gutter_area, buf);
+        }
+        let width = usize::from(area.width);
+        let layers = self.layers(selected_lines);
+        let visible: Vec<_> = rows
+            .iter()
+            .map(|row| match row {
+                Row::Line(index) => self.prepare_line(*index, &self.text.lines[*index], &layers),
+                Row::Fold(lines) => self.fold_placeholder(lines.len()),
+            })
+            .collect();
+        let lines = visible
+            .iter()
+            .map(|line| crate::slice_line(line, column..column + width, self.tab_width))
+            .collect();
+        HighlightedText::new(lines, self.text.background).render(area, buf);
+        let current = self
+            .current_line
+            .filter(|_| self.line_highlight.is_some())
+            .and_then(|line| rows.iter().position(|row| *row == Row::Line(line)));
+        if let Some(row) = current {
+            // Empty lines are not rendered at all, so fill the row with the tint explicitly.
+            let line = crate::slice_line(&visible[row], column..column + width, self.tab_width);
+            let row = ratatui::layout::Rect {
+                y: area.y + row as u16,
+                height: 1,
+                ..area
+            };
+            buf.set_style(row, line.style);
+            line.render(row, buf);
+        }
+        if let Some(position) = self.cursor_cell(rows, column, area) {
+            match self.cursor_shape {
+                CursorShape::Block => {}
+                CursorShape::Bar => {
+                    let colour = self.cursor_style.and_then(|style| style.bg.or(style.fg));
+                    let cell = &mut buf[position];
+                    cell.set_symbol(cursor::BAR_SYMBOL);
+                    if let Some(colour) = colour {
+                        cell.set_fg(colour);
+                    }
+                }
+                CursorShape::Underline => {
+                    // The character under the cursor is kept, so only its underline is set.
+                    let style = ratatui::style::Style::new()
+                        .add_modifier(ratatui::style::Modifier::UNDERLINED);
+                    #[cfg(feature = "underline-color")]
+                    let style = match self.cursor_style.and_then(|style| style.bg) {
+                        Some(colour) => style.underline_color(colour),
+                        None => style,
+                    };
+                    #[cfg(not(feature = "underline-color"))]
+                    let style = style.add_modifier(ratatui::style::Modifier::REVERSED);
+                    buf[position].set_style(style);
+                }
+            }
+        }
+        if let Some((left, right)) = self.overflow_indicators.filter(|_| width > 0) {
+            for (y, line) in (area.top()..area.bottom()).zip(&visible) {
+                let line_width = line.width();
+                if column > 0 && line_width > 0 {
+                    buf[(area.left(), y)].set_char(left);
+                }
+                if line_width > column + width {
+                    buf[(area.right() - 1, y)].set_char(right);
+                }
+            }
+        }
+    }
+
+    /// Returns the rows rendered in `area` when scrolled to line `scroll`, without folds.
+    fn rows(&self, scroll: usize, area: ratatui::layout::Rect) -> Vec<Row> {
+        (scroll..self.line_count())
+            .take(usize::from(area.height))
+            .map(Row::Line)
+            .collect()
+    }
+
+    /// Returns the placeholder row for `count` folded lines.
+    fn fold_placeholder(&self, count: usize) -> ratatui::text::Line<'static> {
+        let label = match count {
+            1 => "⋯ 1 line".to_string(),
+            count => format!("⋯ {count} lines"),
+        };
+        ratatui::text::Line::from(ratatui::text::Span::styled(label, self.fold_style))
+    }
+
+    /// Splits the sign column, annotation column and gutter, if any, off the left of `area`,
+    /// returning their areas and the area left for the code.
+    fn split_margins(&self, area: ratatui::layout::Rect) -> [ratatui::layout::Rect; 4] {
+        let signs = u16::from(self.diagnostics.is_some());
+        let annotations = self.annotations.as_ref().map_or(0, Annotations::width);
+        let gutter = self.gutter.as_ref().map_or(0, |gutter| {
+            gutter.clone().with_line_count(self.line_count()).width()
+        });
+        ratatui::layout::Layout::horizontal([
+            ratatui::layout::Constraint::Length(signs),
+            ratatui::layout::Constraint::Length(annotations),
+            ratatui::layout::Constraint::Length(gutter),
+            ratatui::layout::Constraint::Fill(1),
+        ])
+        .areas(area)
+    }
+
+    /// Returns the byte offset at which each line starts in the source.
+    fn line_starts(&self) -> std::borrow::Cow<'_, [usize]> {
+        if self.line_starts.is_empty() {
+            std::borrow::Cow::Owned(crate::overlay::line_starts(&self.text.lines))
+        } else {
+            std::borrow::Cow::Borrowed(&self.line_starts)
+        }
+    }
+
+    /// Resolves the search matches, the selection and the lines selected on the
+    /// [state](CodeViewState::select) into `(line, column)` ranges along with the style to patch
+    /// over them, in the order they are layered.
+    fn layers(
+        &self,
+        selected_lines: Option<&std::ops::Range<usize>>,
+    ) -> Vec<(std::ops::Range<(usize, usize)>, ratatui::style::Style)> {
+        let brackets = self
+            .cursor
+            .zip(self.bracket_style)
+            .and_then(|(cursor, style)| {
+                crate::matching_bracket(&self.text.lines, cursor).map(|pair| (pair, style))
+            });
+        if self.selection.is_none()
+            && selected_lines.is_none()
+            && self.matches.is_empty()
+            && brackets.is_none()
+        {
+            return Vec::new();
+        }
+        let line_starts = self.line_starts();
+        let mut layers = Vec::new();
+        if let Some(style) = self.match_style {
+            let current_style = self
+                .current_match_style
+                .unwrap_or(style.add_modifier(ratatui::style::Modifier::REVERSED));
+            for (index, found) in self.matches.iter().enumerate() {
+                let style = if self.current_match == Some(index) {
+                    current_style
+                } else {
+                    style
+                };
+                layers.push((found.positions(&line_starts), style));
+            }
+        }
+        if let Some(style) = self.selection_style {
+            let selected_lines =
+                selected_lines.map(|lines| Selection::Positions((lines.start, 0)..(lines.end, 0)));
+            for selection in self.selection.iter().chain(&selected_lines) {
+                layers.push((selection.positions(&line_starts), style));
+            }
+        }
+        if let Some(((bracket, matching), style)) = brackets {
+            for (line, column) in [bracket, matching] {
+                layers.push(((line, column)..(line, column + 1), style));
+            }
+        }
+        layers
+    }
+
+    /// Applies the current line tint, diagnostic underlines, search matches, selection, matching
+    /// brackets and block cursor to line `index`, draws its indent guides, expands its tabs and
+    /// inserts its inlay hints.
+    fn prepare_line<'b>(
+        &self,
+        index: usize,
+        original: &ratatui::text::Line<'b>,
+        layers: &[(std::ops::Range<(usize, usize)>, ratatui::style::Style)],
+    ) -> ratatui::text::Line<'b> {
+        let mut line = original.clone();
+        if let Some(colour) = self
+            .line_highlight
+            .filter(|_| self.current_line == Some(index))
+        {
+            crate::overlay::tint_line(&mut line, colour, self.text.background);
+        }
+        if let Some(diagnostics) = &self.diagnostics {
+            for (bytes, style) in diagnostics.underlines(index) {
+                line = crate::overlay::select_line(&line, bytes, style);
+            }
+        }
+        for (positions, style) in layers {
+            if let Some(bytes) = Selection::line_bytes(positions, index) {
+                line = crate::overlay::select_line(&line, bytes, *style);
+            }
+        }
+        let mut past_end = None;
+        if let Some((_, column)) = self
+            .cursor
+            .filter(|(line, _)| *line == index && self.cursor_shape == CursorShape::Block)
+        {
+            let style = self.cursor_style.unwrap_or(
+                ratatui::style::Style::new().add_modifier(ratatui::style::Modifier::REVERSED),
+            );
+            match cursor::character_at(&line, column) {
+                Some(bytes) => line = crate::overlay::select_line(&line, bytes, style),
+                None => past_end = Some(ratatui::text::Span::styled(" ", style)),
+            }
+        }
+        if let Some(guides) = &self.indent_guides {
+            line = crate::show_indent_guides(&line, guides, self.tab_width);
+        }
+        if self.show_whitespace {
+            let whitespace = self.whitespace.unwrap_or_default();
+            line = crate::show_whitespace(&line, &whitespace, self.tab_width);
+        } else if let Some(width) = self.tab_width {
+            crate::line::expand_tabs(&mut line.spans, width);
+        }
+        // Hints go in last, by display column, so they are not taken for whitespace.
+        let hints = self
+            .inlay_hints
+            .iter()
+            .filter(|hint| hint.line == index)
+            .map(|hint| {
+                let column = cursor::display_column(original, hint.column, self.tab_width);
+                (column, hint.span(self.hint_style))
+            });
+        line = crate::hint::insert_spans(&line, hints, |character| {
+            unicode_width::UnicodeWidthChar::width(character).unwrap_or(0)
+        });
+        // The cursor past the end of the line is not trailing whitespace.
+        line.spans.extend(past_end);
+        line
+    }
+
+    /// Returns the cell of `area` that the cursor is drawn in, if it is in view.
+    fn cursor_cell(
+        &self,
+        rows: &[Row],
+        column: usize,
+        area: ratatui::layout::Rect,
+    ) -> Option<ratatui::layout::Position> {
+        let (index, byte) = self.cursor?;
+        let line = self.text.lines.get(index)?;
+        let row = rows.iter().position(|row| *row == Row::Line(index))?;
+        let x = (cursor::display_column(line, byte, self.tab_width)
+            + crate::hint::width_before(line, index, byte, &self.inlay_hints))
+        .checked_sub(column)?;
+        (row < usize::from(area.height) && x < usize::from(area.width))
+            .then(|| ratatui::layout::Position::new(area.x + x as u16, area.y + row as u16))
+    }
+}
+
+/// The scroll position and selection of an interactive [CodeView], used when rendering it as a
+/// [StatefulWidget](ratatui::widgets::StatefulWidget).
+///
+/// Rendering records the size of the viewport and the number of lines, so that paging and
+/// [scroll_to_line](CodeViewState::scroll_to_line) can keep lines in view, and clamps the offset
+/// so the view never scrolls past its last line.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::text::Line;
+/// use ratatui::widgets::StatefulWidget;
+/// use syntect_tui::widget::{CodeView, CodeViewState};
+///
+/// let view = CodeView::new((0..10).map(|n| Line::raw(n.to_string())).collect());
+/// let mut state = CodeViewState::default();
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 2, 3));
+/// (&view).render(buf.area, &mut buf, &mut state);
+/// state.page_down();
+/// assert_eq!(3, state.offset());
+/// state.scroll_to_line(9);
+/// (&view).render(buf.area, &mut buf, &mut state);
+/// assert_eq!(7, state.offset());
+/// assert_eq!("7", buf[(0, 0)].symbol());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CodeViewState {
+    offset: usize,
+    column: usize,
+    selection: Option<std::ops::Range<usize>>,
+    viewport_height: usize,
+    line_count: usize,
+    cursor_position: Option<ratatui::layout::Position>,
+    folds: Vec<std::ops::Range<usize>>,
+}
+
+impl CodeViewState {
+    /// Returns the index of the first visible line.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the display column at which lines start to be rendered.
+    pub fn column_offset(&self) -> usize {
+        self.column
+    }
+
+    /// Scrolls left by `columns`.
+    pub fn scroll_left(&mut self, columns: usize) {
+        self.column = self.column.saturating_sub(columns);
+    }
+
+    /// Scrolls right by `columns`.
+    pub fn scroll_right(&mut self, columns: usize) {
+        self.column = self.column.saturating_add(columns);
+    }
+
+    /// Returns the height of the viewport the view was last rendered in.
+    pub fn viewport_height(&self) -> usize {
+        self.viewport_height
+    }
+
+    /// Returns the position at which the view's [cursor](CodeView::cursor) was last rendered, if
+    /// it was in view, e.g. to place the terminal's own cursor there.
+    pub fn cursor_position(&self) -> Option<ratatui::layout::Position> {
+        self.cursor_position
+    }
+
+    /// Returns the selected range of lines, if any.
+    pub fn selection(&self) -> Option<std::ops::Range<usize>> {
+        self.selection.clone()
+    }
+
+    /// Selects a range of lines, which are drawn in the view's
+    /// [selection style](CodeView::selection_style) along with its own
+    /// [selection](CodeView::selection) when the view is rendered with this state.
+    pub fn select(&mut self, lines: std::ops::Range<usize>) {
+        self.selection = Some(lines);
+    }
+
+    /// Clears the selection.
+    pub fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    /// Scrolls up by `lines` rows, counting each [fold](CodeViewState::fold) as one row.
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.offset = self.line_of(self.row_of(self.offset).saturating_sub(lines));
+    }
+
+    /// Scrolls down by `lines` rows, counting each [fold](CodeViewState::fold) as one row.
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.offset = self.line_of(self.row_of(self.offset).saturating_add(lines));
+        self.clamp();
+    }
+
+    /// Scrolls up by the height of the viewport.
+    pub fn page_up(&mut self) {
+        self.scroll_up(self.viewport_height.max(1));
+    }
+
+    /// Scrolls down by the height of the viewport.
+    pub fn page_down(&mut self) {
+        self.scroll_down(self.viewport_height.max(1));
+    }
+
+    /// Scrolls as little as possible to bring `line` into view.
+    pub fn scroll_to_line(&mut self, line: usize) {
+        let row = self.row_of(line);
+        let top = self.row_of(self.offset);
+        let height = self.viewport_height.max(1);
+        if row < top {
+            self.offset = self.line_of(row);
+        } else if row >= top + height {
+            self.offset = self.line_of(row + 1 - height);
+        }
+        self.clamp();
+    }
+
+    /// Scrolls as little as possible to bring search match `index` of `view` into view. Does
+    /// nothing if there is no such match.
+    ///
+    /// # Examples
+    /// ```
+    /// use ratatui::buffer::Buffer;
+    /// use ratatui::layout::Rect;
+    /// use ratatui::text::Line;
+    /// use ratatui::widgets::StatefulWidget;
+    /// use syntect_tui::widget::{CodeView, CodeViewState};
+    /// use syntect_tui::Selection;
+    ///
+    /// let view = CodeView::new((0..10).map(|n| Line::raw(n.to_string())).collect())
+    ///     .matches([Selection::Positions((2, 0)..(2, 1)), Selection::Positions((8, 0)..(8, 1))]);
+    /// let mut state = CodeViewState::default();
+    /// let mut buf = Buffer::empty(Rect::new(0, 0, 1, 3));
+    /// (&view).render(buf.area, &mut buf, &mut state);
+    /// state.scroll_to_match(&view, 1);
+    /// assert_eq!(6, state.offset());
+    /// ```
+    pub fn scroll_to_match(&mut self, view: &CodeView, index: usize) {
+        if let Some((line, _)) = view.match_position(index) {
+            self.scroll_to_line(line);
+        }
+    }
+
+    fn clamp(&mut self) {
+        let mut row = self.row_of(self.offset);
+        if self.line_count > 0 {
+            let row_count = self.rows(self.line_count).len();
+            row = row.min(row_count.saturating_sub(self.viewport_height.max(1)));
+        }
+        self.offset = self.line_of(row);
+    }
+
+    fn update(&mut self, area: ratatui::layout::Rect, line_count: usize) {
+        self.viewport_height = usize::from(area.height);
+        self.line_count = line_count;
+        self.clamp();
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for CodeView<'_> {
+    type State = CodeViewState;
+
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        ratatui::widgets::StatefulWidget::render(&self, area, buf, state);
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for &CodeView<'_> {
+    type State = CodeViewState;
+
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        state.update(area, self.line_count());
+        let rows: Vec<_> = state
+            .rows(self.line_count())
+            .into_iter()
+            .skip(state.row_of(state.offset))
+            .take(usize::from(area.height))
+            .collect();
+        self.render_from(&rows, state.column, state.selection.as_ref(), area, buf);
+        let [_, _, _, area] = self.split_margins(area);
+        state.cursor_position = self.cursor_cell(&rows, state.column, area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Modifier, Style};
+    use ratatui::text::Line;
+    use ratatui::widgets::Widget;
+    use rstest::*;
+
+    #[rstest]
+    #[case::top(0, ["a ", "b "])]
+    #[case::scrolled(2, ["c ", "  "])]
+    #[case::past_end(5, ["  ", "  "])]
+    fn check_render(#[case] scroll: usize, #[case] expected: [&str; 2]) {
+        let view =
+            CodeView::new(vec![Line::raw("a"), Line::raw("b"), Line::raw("c")]).scroll(scroll);
+        let mut by_ref = Buffer::empty(Rect::new(0, 0, 2, 2));
+        (&view).render(by_ref.area, &mut by_ref);
+        let mut owned = Buffer::empty(Rect::new(0, 0, 2, 2));
+        view.render(owned.area, &mut owned);
+        assert_eq!(Buffer::with_lines(expected), by_ref);
+        assert_eq!(by_ref, owned);
+    }
+
+    #[rstest]
+    #[case::unscrolled(0, "abc ")]
+    #[case::aligned(3, "日 日 ")]
+    #[case::wide_character_cut(4, " 日  ")]
+    #[case::past_end(10, "    ")]
+    fn check_horizontal_scroll(#[case] column: usize, #[case] expected: &str) {
+        use ratatui::style::Stylize;
+        let line = Line::from(vec![
+            ratatui::text::Span::raw("ab"),
+            ratatui::text::Span::styled("c日日", Style::new().red()),
+        ]);
+        let view = CodeView::new(vec![line]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        view.clone()
+            .scroll_columns(column)
+            .render(buf.area, &mut buf);
+        let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(expected, symbols);
+        let mut state = CodeViewState::default();
+        state.scroll_right(column);
+        let mut stateful = Buffer::empty(buf.area);
+        ratatui::widgets::StatefulWidget::render(&view, stateful.area, &mut stateful, &mut state);
+        assert_eq!(buf, stateful);
+    }
+
+    #[rstest]
+    #[case::visible(1, 0, Some(1))]
+    #[case::scrolled(2, 1, Some(1))]
+    #[case::scrolled_out(0, 1, None)]
+    fn check_current_line(
+        #[case] current_line: usize,
+        #[case] scroll: usize,
+        #[case] expected_row: Option<u16>,
+    ) {
+        let view = CodeView::new(vec![Line::raw("a"), Line::raw(""), Line::raw("c")])
+            .line_highlight(syntect::highlighting::Color {
+                r: 255,
+                g: 0,
+                b: 0,
+                a: 255,
+            })
+            .current_line(current_line)
+            .scroll(scroll);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+        view.render(buf.area, &mut buf);
+        let mut expected = buf.clone();
+        expected.set_style(expected.area, Style::reset());
+        if let Some(row) = expected_row {
+            expected.set_style(
+                Rect::new(0, row, 2, 1),
+                Style::new().bg(Color::Rgb(255, 0, 0)),
+            );
+        }
+        assert_eq!(expected, buf);
+    }
+
+    #[rstest]
+    #[case::bytes_in_crlf_source(Selection::Bytes(5..6), 0, (1, 1))]
+    #[case::positions(Selection::Positions((0, 0)..(0, 1)), 0, (0, 0))]
+    #[case::scrolled_horizontally(Selection::Bytes(6..7), 1, (1, 1))]
+    fn check_selection(
+        #[case] selection: Selection,
+        #[case] column: usize,
+        #[case] expected: (u16, u16),
+    ) {
+        let ps = syntect::parsing::SyntaxSet::load_defaults_newlines();
+        let theme = syntect::highlighting::Theme::default();
+        let view = CodeView::highlight("ab\r\ncde\r\n", ps.find_syntax_plain_text(), &ps, &theme)
+            .unwrap()
+            .selection_style(Style::new().bg(Color::Blue))
+            .selection(selection)
+            .scroll_columns(column);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+        view.render(buf.area, &mut buf);
+        let selected: Vec<_> = (0..buf.content.len())
+            .filter(|&index| buf.content[index].bg == Color::Blue)
+            .map(|index| buf.pos_of(index))
+            .collect();
+        assert_eq!(vec![expected], selected);
+    }
+
+    #[rstest]
+    #[case::block(CursorShape::Block, 0, 0, (0, 0))]
+    #[case::block_after_tab(CursorShape::Block, 0, 2, (2, 0))]
+    #[case::block_past_end(CursorShape::Block, 1, 0, (0, 1))]
+    #[case::bar_after_tab(CursorShape::Bar, 0, 2, (2, 0))]
+    #[case::underline_after_tab(CursorShape::Underline, 0, 2, (2, 0))]
+    fn check_cursor(
+        #[case] shape: CursorShape,
+        #[case] line: usize,
+        #[case] column: usize,
+        #[case] expected: (u16, u16),
+    ) {
+        let view = CodeView::new(vec![Line::raw("a\tb"), Line::raw("")])
+            .tab_width(2)
+            .cursor_style(Style::new().bg(Color::Red))
+            .cursor_shape(shape)
+            .cursor(line, column);
+        let area = Rect::new(0, 0, 4, 2);
+        let mut buf = Buffer::empty(area);
+        (&view).render(area, &mut buf);
+        let position = ratatui::layout::Position::from(expected);
+        assert_eq!(Some(position), view.cursor_position(area));
+        match shape {
+            CursorShape::Block => assert_eq!(Color::Red, buf[position].bg),
+            CursorShape::Bar => {
+                assert_eq!(cursor::BAR_SYMBOL, buf[position].symbol());
+                assert_eq!(Color::Red, buf[position].fg);
+            }
+            CursorShape::Underline => {
+                assert_eq!("b", buf[position].symbol());
+                assert!(buf[position]
+                    .modifier
+                    .contains(ratatui::style::Modifier::UNDERLINED));
+                #[cfg(feature = "underline-color")]
+                assert_eq!(Color::Red, buf[position].underline_color);
+                #[cfg(not(feature = "underline-color"))]
+                assert!(buf[position]
+                    .modifier
+                    .contains(ratatui::style::Modifier::REVERSED));
+            }
+        }
+        let mut state = CodeViewState::default();
+        ratatui::widgets::StatefulWidget::render(&view, area, &mut buf, &mut state);
+        assert_eq!(Some(position), state.cursor_position());
+    }
+
+    #[test]
+    fn draws_lines_selected_on_the_state() {
+        let view = CodeView::new(["ab", "cd", "ef", "gh"].map(Line::raw).to_vec())
+            .selection_style(Style::new().bg(Color::Blue));
+        let mut state = CodeViewState::default();
+        state.select(1..3);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 4));
+        ratatui::widgets::StatefulWidget::render(&view, buf.area, &mut buf, &mut state);
+        let selected: Vec<_> = buf
+            .content
+            .iter()
+            .map(|cell| cell.bg == Color::Blue)
+            .collect();
+        assert_eq!(
+            vec![false, false, true, true, true, true, false, false],
+            selected
+        );
+    }
+
+    #[rstest]
+    #[case::matches_only(None, [Color::Yellow, Color::Red, Color::Reset])]
+    #[case::selection_over_matches(Some(1..3), [Color::Yellow, Color::Blue, Color::Blue])]
+    fn check_matches(
+        #[case] selection: Option<std::ops::Range<usize>>,
+        #[case] expected: [ratatui::style::Color; 3],
+    ) {
+        let mut view = CodeView::new(vec![Line::raw("aac")])
+            .match_style(Style::new().bg(Color::Yellow))
+            .current_match_style(Style::new().bg(Color::Red))
+            .selection_style(Style::new().bg(Color::Blue))
+            .matches([Selection::Bytes(0..1), Selection::Bytes(1..2)])
+            .current_match(1);
+        if let Some(selection) = selection {
+            view = view.selection(Selection::Bytes(selection));
+        }
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        view.render(buf.area, &mut buf);
+        let actual: Vec<_> = buf.content.iter().map(|cell| cell.bg).collect();
+        assert_eq!(expected.to_vec(), actual);
+    }
+
+    #[rstest]
+    #[case::on_open((0, 1), [Color::Reset, Color::Green, Color::Green, Color::Reset])]
+    #[case::unmatched((0, 0), [Color::Reset; 4])]
+    fn check_matching_brackets(
+        #[case] cursor: (usize, usize),
+        #[case] expected: [ratatui::style::Color; 4],
+    ) {
+        let view = CodeView::new(vec![Line::raw("f(x"), Line::raw(")")])
+            .bracket_style(Style::new().bg(Color::Green))
+            .cursor_shape(CursorShape::Underline)
+            .cursor(cursor.0, cursor.1);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 2));
+        view.render(buf.area, &mut buf);
+        let actual: Vec<_> = buf.content.iter().map(|cell| cell.bg).collect();
+        assert_eq!(expected.to_vec(), actual);
+    }
+
+    #[rstest]
+    #[case::after_tab(2, 2, "a→|b·   ", 3)]
+    #[case::trailing_whitespace(3, 3, "a→b|·   ", 4)]
+    #[case::past_end(9, 4, "a→b·|   ", 5)]
+    #[case::after_cursor(0, 1, "|a→b·   ", 2)]
+    fn check_inlay_hints(
+        #[case] hint: usize,
+        #[case] cursor: usize,
+        #[case] expected: &str,
+        #[case] x: u16,
+    ) {
+        let view = CodeView::new(vec![Line::raw("a\tb ")])
+            .tab_width(2)
+            .show_whitespace(true)
+            .inlay_hints([InlayHint::new(0, hint, "|")])
+            .cursor(0, cursor);
+        let area = Rect::new(0, 0, 8, 1);
+        let mut buf = Buffer::empty(area);
+        (&view).render(area, &mut buf);
+        let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(expected, symbols);
+        let position = ratatui::layout::Position::new(x, 0);
+        assert_eq!(Some(position), view.cursor_position(area));
+    }
+
+    #[rstest]
+    #[case::second_line(1, 1, Some(1))]
+    #[case::missing(2, 0, None)]
+    fn check_scroll_to_match(
+        #[case] index: usize,
+        #[case] expected_scroll: usize,
+        #[case] expected_current: Option<usize>,
+    ) {
+        let view = CodeView::new(vec![Line::raw("ab"), Line::raw("cd")])
+            .matches([Selection::Bytes(0..1), Selection::Bytes(4..5)])
+            .scroll_to_match(index);
+        assert_eq!(expected_scroll, view.scroll_offset());
+        assert_eq!(expected_current, view.current_match);
+    }
+
+    #[test]
+    fn state_scrolls_over_folds() {
+        let view = CodeView::new((0..10).map(|n| Line::raw(n.to_string())).collect())
+            .diagnostics(Diagnostics::new([Diagnostic::new(4, Severity::Error)]));
+        let mut state = CodeViewState::default();
+        state.fold(2..6);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+        ratatui::widgets::StatefulWidget::render(&view, buf.area, &mut buf, &mut state);
+        let symbols: String = buf.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(" 0         1        E⋯ 4 lines", symbols);
+        state.scroll_to_line(8);
+        assert_eq!(6, state.offset());
+        state.scroll_up(1);
+        assert_eq!(2, state.offset());
+    }
+
+    #[test]
+    fn cursor_out_of_view() {
+        let view = CodeView::new(vec![Line::raw("abc")])
+            .cursor(0, 2)
+            .scroll_columns(3);
+        assert_eq!(None, view.cursor_position(Rect::new(0, 0, 4, 1)));
+    }
+
+    #[test]
+    fn cursor_past_end_is_not_trailing_whitespace() {
+        let view = CodeView::new(vec![Line::raw("a ")])
+            .cursor(0, 2)
+            .show_whitespace(true);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        view.render(buf.area, &mut buf);
+        assert_eq!(["a", "·", " "], [0, 1, 2].map(|x| buf[(x, 0)].symbol()));
+        assert!(buf[(2, 0)].modifier.contains(Modifier::REVERSED));
+    }
+
+    #[rstest]
+    #[case::scroll_down(|state: &mut CodeViewState| state.scroll_down(3), 3)]
+    #[case::scroll_down_clamped(|state: &mut CodeViewState| state.scroll_down(20), 7)]
+    #[case::scroll_up_clamped(|state: &mut CodeViewState| state.scroll_up(20), 0)]
+    #[case::page_down_twice(|state: &mut CodeViewState| { state.page_down(); state.page_down() }, 6)]
+    #[case::scroll_to_visible_line(|state: &mut CodeViewState| state.scroll_to_line(2), 0)]
+    #[case::scroll_to_line_below(|state: &mut CodeViewState| state.scroll_to_line(5), 3)]
+    fn check_state_scrolling(#[case] action: fn(&mut CodeViewState), #[case] expected: usize) {
+        let view = CodeView::new((0..10).map(|n| Line::raw(n.to_string())).collect());
+        let mut state = CodeViewState::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 3));
+        ratatui::widgets::StatefulWidget::render(&view, buf.area, &mut buf, &mut state);
+        action(&mut state);
+        assert_eq!(expected, state.offset());
+        ratatui::widgets::StatefulWidget::render(view, buf.area, &mut buf, &mut state);
+        assert_eq!(expected.to_string(), buf[(0, 0)].symbol());
+    }
+}
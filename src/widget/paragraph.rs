@@ -0,0 +1,184 @@
+use crate::HighlightedText;
+
+/// A [Paragraph](ratatui::widgets::Paragraph) of highlighted text, with its wrapping, alignment,
+/// scrolling and block options.
+///
+/// Paragraphs only style the cells their text covers, so rendering highlighted text in a plain
+/// `Paragraph` leaves the area around short lines and wrapped rows in the terminal's default
+/// background. `HighlightedParagraph` fills its whole area, including its block, with the theme
+/// background, then renders the text with its span styles intact however it is wrapped, trimmed,
+/// aligned or scrolled.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::{Alignment, Rect};
+/// use ratatui::style::{Color, Stylize};
+/// use ratatui::text::{Line, Span};
+/// use ratatui::widgets::{Widget, Wrap};
+/// use syntect_tui::widget::HighlightedParagraph;
+/// use syntect_tui::HighlightedText;
+///
+/// let text = HighlightedText::new(
+///     vec![Line::from(vec![Span::raw("let").blue(), Span::raw(" answer = 42;")])],
+///     Some(Color::Black),
+/// );
+/// let paragraph = HighlightedParagraph::new(text)
+///     .wrap(Wrap { trim: true })
+///     .alignment(Alignment::Right);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 12, 3));
+/// paragraph.render(buf.area, &mut buf);
+/// let rows: Vec<String> = buf
+///     .content
+///     .chunks(12)
+///     .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+///     .collect();
+/// assert_eq!(vec!["let answer =", "         42;", "            "], rows);
+/// assert_eq!(Color::Blue, buf[(0, 0)].fg);
+/// assert!(buf.content.iter().all(|cell| cell.bg == Color::Black));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HighlightedParagraph<'a> {
+    paragraph: ratatui::widgets::Paragraph<'a>,
+    background: ratatui::style::Style,
+}
+
+impl<'a> HighlightedParagraph<'a> {
+    /// Creates a paragraph of `text`, which is not wrapped, left aligned and not scrolled.
+    pub fn new(text: HighlightedText<'a>) -> Self {
+        let background = text.style();
+        Self {
+            paragraph: ratatui::widgets::Paragraph::new(ratatui::text::Text::from(text))
+                .style(background),
+            background,
+        }
+    }
+
+    /// Surrounds the paragraph with `block`, which is filled with the theme background unless it
+    /// has a background of its own.
+    pub fn block(mut self, block: ratatui::widgets::Block<'a>) -> Self {
+        self.paragraph = self.paragraph.block(block);
+        self
+    }
+
+    /// Sets the style of the whole area, which is patched over the theme background, e.g. to
+    /// dim the paragraph when it is not focused.
+    pub fn style(mut self, style: ratatui::style::Style) -> Self {
+        self.paragraph = self.paragraph.style(self.background.patch(style));
+        self
+    }
+
+    /// Wraps lines wider than the area, as described in [Wrap](ratatui::widgets::Wrap).
+    pub fn wrap(mut self, wrap: ratatui::widgets::Wrap) -> Self {
+        self.paragraph = self.paragraph.wrap(wrap);
+        self
+    }
+
+    /// Scrolls the paragraph down by `y` rows and, if it is not wrapped, right by `x` columns.
+    pub fn scroll(mut self, (y, x): (u16, u16)) -> Self {
+        self.paragraph = self.paragraph.scroll((y, x));
+        self
+    }
+
+    /// Sets the alignment of lines that have no alignment of their own.
+    pub fn alignment(mut self, alignment: ratatui::layout::Alignment) -> Self {
+        self.paragraph = self.paragraph.alignment(alignment);
+        self
+    }
+}
+
+impl<'a> From<HighlightedText<'a>> for HighlightedParagraph<'a> {
+    fn from(text: HighlightedText<'a>) -> Self {
+        Self::new(text)
+    }
+}
+
+impl ratatui::widgets::Widget for HighlightedParagraph<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        self.paragraph.render(area, buf);
+    }
+}
+
+impl ratatui::widgets::Widget for &HighlightedParagraph<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        self.clone().render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::{Alignment, Rect};
+    use ratatui::style::{Color, Modifier, Style, Stylize};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Widget, Wrap};
+    use rstest::*;
+
+    fn fake_text() -> HighlightedText<'static> {
+        HighlightedText::new(
+            vec![
+                Line::from(vec![Span::raw("  ab").red(), Span::raw(" cd").bold()]),
+                Line::raw("e").alignment(Alignment::Left),
+            ],
+            Some(Color::Blue),
+        )
+    }
+
+    fn symbols(buf: &Buffer) -> Vec<String> {
+        buf.content
+            .chunks(usize::from(buf.area.width))
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    #[rstest]
+    #[case::clipped(None, (0, 0), Alignment::Left, ["  ab ", "e    ", "     "])]
+    #[case::wrapped(Some(false), (0, 0), Alignment::Left, ["  ab ", "cd   ", "e    "])]
+    #[case::trimmed(Some(true), (0, 0), Alignment::Left, ["ab cd", "e    ", "     "])]
+    #[case::centred(Some(false), (0, 0), Alignment::Center, ["  ab ", " cd  ", "e    "])]
+    #[case::scrolled_down(Some(false), (1, 0), Alignment::Left, ["cd   ", "e    ", "     "])]
+    #[case::scrolled_right(None, (0, 2), Alignment::Left, ["ab cd", "     ", "     "])]
+    fn check_render(
+        #[case] trim: Option<bool>,
+        #[case] scroll: (u16, u16),
+        #[case] alignment: Alignment,
+        #[case] expected: [&str; 3],
+    ) {
+        let mut paragraph = HighlightedParagraph::new(fake_text())
+            .scroll(scroll)
+            .alignment(alignment);
+        if let Some(trim) = trim {
+            paragraph = paragraph.wrap(Wrap { trim });
+        }
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 3));
+        (&paragraph).render(buf.area, &mut buf);
+        assert_eq!(expected.to_vec(), symbols(&buf));
+        assert!(buf.content.iter().all(|cell| cell.bg == Color::Blue));
+    }
+
+    #[test]
+    fn keeps_span_styles_when_wrapped() {
+        let paragraph = HighlightedParagraph::from(fake_text()).wrap(Wrap { trim: false });
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 2));
+        paragraph.render(buf.area, &mut buf);
+        assert_eq!(Color::Red, buf[(2, 0)].fg);
+        assert!(buf[(0, 1)].modifier.contains(Modifier::BOLD));
+        assert!(!buf[(2, 1)].modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn block_and_style_are_filled_with_background() {
+        let paragraph = HighlightedParagraph::new(fake_text())
+            .block(Block::bordered())
+            .style(Style::new().dim());
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 4));
+        paragraph.render(buf.area, &mut buf);
+        assert_eq!("┌────┐", symbols(&buf)[0]);
+        assert!(buf.content.iter().all(|cell| cell.bg == Color::Blue));
+        assert!(buf
+            .content
+            .iter()
+            .all(|cell| cell.modifier.contains(Modifier::DIM)));
+    }
+}
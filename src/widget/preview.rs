@@ -0,0 +1,411 @@
+use super::{CodeView, CodeViewState};
+use crate::{HighlightedText, SyntectTuiError};
+
+/// The number of bytes shown in each row of a hex dump.
+const HEX_ROW_BYTES: usize = 16;
+
+/// How a [FilePreview] shows a binary file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BinaryView {
+    /// A one line notice giving the size of the file.
+    #[default]
+    Notice,
+    /// A hex dump with an offset, 16 bytes in hex and their printable ASCII characters per row.
+    Hex,
+}
+
+/// A widget that previews a file, e.g. in the preview pane of a fuzzy finder.
+///
+/// The syntax is detected from the file name's extension, or else from the whole file name
+/// (e.g. `Makefile`), or else from the first line of the file (e.g. a shebang), falling back to
+/// plain text. A file that is not valid UTF-8 or contains a NUL byte is treated as binary and
+/// shown as described by [binary_view](FilePreview::binary_view).
+///
+/// Highlighting is lazy: rendering only highlights the file up to the last visible line, and the
+/// highlighted lines and parser state are kept so that scrolling further down carries on from
+/// there. Keep the preview around between frames for as long as the same file is shown.
+///
+/// Like [CodeView], the preview can be scrolled with [scroll](FilePreview::scroll) or rendered
+/// with a [CodeViewState].
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::widgets::Widget;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::widget::{BinaryView, FilePreview};
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let theme = &ts.themes["base16-ocean.dark"];
+/// let preview = FilePreview::new(b"#!/bin/sh\necho hi\n".as_slice(), "run", &ps, theme).scroll(1);
+/// assert_eq!("Bourne Again Shell (bash)", preview.syntax().name);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 7, 1));
+/// (&preview).render(buf.area, &mut buf);
+/// assert_eq!("echo hi", buf.content.iter().map(|cell| cell.symbol()).collect::<String>());
+///
+/// let binary = FilePreview::new(b"\x7fELF\0".as_slice(), "a.out", &ps, theme).binary_view(BinaryView::Hex);
+/// assert!(binary.is_binary());
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 32, 1));
+/// binary.render(buf.area, &mut buf);
+/// assert_eq!("00000000  7f 45 4c 46 00        ", buf.content.iter().map(|cell| cell.symbol()).collect::<String>());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FilePreview<'a> {
+    content: Content<'a>,
+    syntax: &'a syntect::parsing::SyntaxReference,
+    syntax_set: &'a syntect::parsing::SyntaxSet,
+    theme: &'a syntect::highlighting::Theme,
+    scroll: usize,
+    binary_view: BinaryView,
+    line_count: usize,
+    highlighted: std::cell::RefCell<Highlighted>,
+}
+
+#[derive(Clone, Debug)]
+enum Content<'a> {
+    Text(std::borrow::Cow<'a, str>),
+    Binary(std::borrow::Cow<'a, [u8]>),
+}
+
+/// The lines highlighted so far, and the state to carry on from.
+#[derive(Clone, Debug, Default)]
+struct Highlighted {
+    lines: Vec<ratatui::text::Line<'static>>,
+    /// The byte offset of the next line to highlight.
+    position: usize,
+    states: Option<(
+        syntect::highlighting::HighlightState,
+        syntect::parsing::ParseState,
+    )>,
+}
+
+impl<'a> FilePreview<'a> {
+    /// Creates a preview of the contents `bytes` of a file named `file_name`, which is only used
+    /// to detect the syntax.
+    pub fn new(
+        bytes: impl Into<std::borrow::Cow<'a, [u8]>>,
+        file_name: impl AsRef<std::path::Path>,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Self {
+        let bytes = bytes.into();
+        let content = if bytes.contains(&0) {
+            Content::Binary(bytes)
+        } else {
+            match bytes {
+                std::borrow::Cow::Borrowed(bytes) => std::str::from_utf8(bytes)
+                    .map_or(Content::Binary(bytes.into()), |text| {
+                        Content::Text(text.into())
+                    }),
+                std::borrow::Cow::Owned(bytes) => String::from_utf8(bytes).map_or_else(
+                    |error| Content::Binary(error.into_bytes().into()),
+                    |text| Content::Text(text.into()),
+                ),
+            }
+        };
+        let syntax = match &content {
+            Content::Text(text) => {
+                crate::diff::syntax_for_path(syntax_set, &file_name.as_ref().to_string_lossy())
+                    .or_else(|| {
+                        let first_line = text.lines().next()?;
+                        syntax_set.find_syntax_by_first_line(first_line)
+                    })
+            }
+            Content::Binary(_) => None,
+        }
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+        let line_count = match &content {
+            Content::Text(text) => syntect::util::LinesWithEndings::from(text).count(),
+            Content::Binary(_) => 1,
+        };
+        Self {
+            content,
+            syntax,
+            syntax_set,
+            theme,
+            scroll: 0,
+            binary_view: BinaryView::Notice,
+            line_count,
+            highlighted: std::cell::RefCell::default(),
+        }
+    }
+
+    /// Reads the file at `path` and creates a preview of it.
+    ///
+    /// # Errors
+    /// Returns `SyntectTuiError::FileRead` if the file cannot be read.
+    pub fn open(
+        path: impl AsRef<std::path::Path>,
+        syntax_set: &'a syntect::parsing::SyntaxSet,
+        theme: &'a syntect::highlighting::Theme,
+    ) -> Result<Self, SyntectTuiError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|error| SyntectTuiError::FileRead {
+            path: path.display().to_string(),
+            message: error.to_string(),
+        })?;
+        Ok(Self::new(bytes, path, syntax_set, theme))
+    }
+
+    /// Sets the index of the first line to render.
+    pub fn scroll(mut self, offset: usize) -> Self {
+        self.scroll = offset;
+        self
+    }
+
+    /// Sets how a binary file is shown.
+    pub fn binary_view(mut self, view: BinaryView) -> Self {
+        self.binary_view = view;
+        if let Content::Binary(bytes) = &self.content {
+            self.line_count = match view {
+                BinaryView::Notice => 1,
+                BinaryView::Hex => bytes.len().div_ceil(HEX_ROW_BYTES),
+            };
+        }
+        self
+    }
+
+    /// Returns the syntax the file is highlighted with, which is plain text for binary files.
+    pub fn syntax(&self) -> &'a syntect::parsing::SyntaxReference {
+        self.syntax
+    }
+
+    /// Returns whether the file is treated as binary.
+    pub fn is_binary(&self) -> bool {
+        matches!(self.content, Content::Binary(_))
+    }
+
+    /// Returns the number of lines in the preview, e.g. to clamp scrolling.
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    /// Returns lines `lines` of the preview, highlighting the file up to them if needed.
+    fn lines(&self, lines: std::ops::Range<usize>) -> Vec<ratatui::text::Line<'static>> {
+        let lines = lines.start.min(self.line_count)..lines.end.min(self.line_count);
+        match (&self.content, self.binary_view) {
+            (Content::Text(text), _) => {
+                self.highlight_to(text, lines.end);
+                self.highlighted.borrow().lines[lines].to_vec()
+            }
+            (Content::Binary(bytes), BinaryView::Notice) => {
+                let notice = ratatui::text::Line::styled(
+                    format!("Binary file ({} bytes)", bytes.len()),
+                    ratatui::style::Modifier::DIM,
+                );
+                lines.map(|_| notice.clone()).collect()
+            }
+            (Content::Binary(bytes), BinaryView::Hex) => lines
+                .map(|row| hex_row(row * HEX_ROW_BYTES, bytes.chunks(HEX_ROW_BYTES).nth(row)))
+                .collect(),
+        }
+    }
+
+    /// Highlights `text` up to line `end`, carrying on from the lines already highlighted. A
+    /// line that fails to highlight is kept unstyled.
+    fn highlight_to(&self, text: &str, end: usize) {
+        let mut highlighted = self.highlighted.borrow_mut();
+        if highlighted.lines.len() >= end {
+            return;
+        }
+        let mut highlighter = match highlighted.states.take() {
+            Some((highlight_state, parse_state)) => {
+                syntect::easy::HighlightLines::from_state(self.theme, highlight_state, parse_state)
+            }
+            None => syntect::easy::HighlightLines::new(self.syntax, self.theme),
+        };
+        let remaining = syntect::util::LinesWithEndings::from(&text[highlighted.position..]);
+        for line in remaining.take(end - highlighted.lines.len()) {
+            highlighted.position += line.len();
+            let converted =
+                crate::highlight_line_to_line(&mut highlighter, self.syntax_set, self.theme, line)
+                    .unwrap_or_else(|_| {
+                        ratatui::text::Line::raw(line.trim_end_matches(['\r', '\n']))
+                    });
+            highlighted
+                .lines
+                .push(crate::line::into_owned_line(converted));
+        }
+        highlighted.states = Some(highlighter.state());
+    }
+
+    fn render_lines(
+        &self,
+        lines: std::ops::Range<usize>,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+    ) {
+        use ratatui::widgets::Widget;
+        let text = HighlightedText::from_theme(self.lines(lines), self.theme);
+        CodeView::from(text).render(area, buf);
+    }
+}
+
+/// Formats a row of a hex dump starting at `offset`.
+fn hex_row(offset: usize, bytes: Option<&[u8]>) -> ratatui::text::Line<'static> {
+    let bytes = bytes.unwrap_or_default();
+    let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    let ascii: String = bytes
+        .iter()
+        .map(|&byte| match byte {
+            0x20..=0x7e => char::from(byte),
+            _ => '.',
+        })
+        .collect();
+    let width = HEX_ROW_BYTES * 3 - 1;
+    ratatui::text::Line::raw(format!("{offset:08x}  {:width$}  |{ascii}|", hex.join(" ")))
+}
+
+impl ratatui::widgets::Widget for FilePreview<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+impl ratatui::widgets::Widget for &FilePreview<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        self.render_lines(
+            self.scroll..self.scroll + usize::from(area.height),
+            area,
+            buf,
+        );
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for FilePreview<'_> {
+    type State = CodeViewState;
+
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        ratatui::widgets::StatefulWidget::render(&self, area, buf, state);
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for &FilePreview<'_> {
+    type State = CodeViewState;
+
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        state.update(area, self.line_count);
+        self.render_lines(
+            state.offset..state.offset + usize::from(area.height),
+            area,
+            buf,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidget;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    #[rstest]
+    #[case::extension(b"fn main() {}", "main.rs", "Rust", false)]
+    #[case::file_name(b"all:", "Makefile", "Makefile", false)]
+    #[case::first_line(b"#!/usr/bin/env python\n", "script", "Python", false)]
+    #[case::unknown(b"text", "notes", "Plain Text", false)]
+    #[case::nul(b"a\0b", "main.rs", "Plain Text", true)]
+    #[case::invalid_utf8(b"\xff\xfe", "main.rs", "Plain Text", true)]
+    fn check_detection(
+        #[case] bytes: &[u8],
+        #[case] file_name: &str,
+        #[case] expected_syntax: &str,
+        #[case] expected_binary: bool,
+    ) {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let preview = FilePreview::new(bytes, file_name, &ps, &ts.themes["base16-ocean.dark"]);
+        assert_eq!(expected_syntax, preview.syntax().name);
+        assert_eq!(expected_binary, preview.is_binary());
+    }
+
+    #[rstest]
+    #[case(
+        0,
+        None,
+        "00000000                                                   ||"
+    )]
+    #[case(
+        16,
+        Some(b"Hi!\n".as_slice()),
+        "00000010  48 69 21 0a                                      |Hi!.|"
+    )]
+    fn check_hex_row(#[case] offset: usize, #[case] bytes: Option<&[u8]>, #[case] expected: &str) {
+        assert_eq!(expected, hex_row(offset, bytes).to_string());
+    }
+
+    #[test]
+    fn highlights_lazily() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let source = "/*\n*/\nfn a() {}\n".repeat(10);
+        let preview = FilePreview::new(
+            source.as_bytes(),
+            "a.rs",
+            &ps,
+            &ts.themes["base16-ocean.dark"],
+        );
+        let mut state = CodeViewState::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 9, 2));
+        (&preview).render(buf.area, &mut buf, &mut state);
+        assert_eq!(2, preview.highlighted.borrow().lines.len());
+        state.scroll_down(4);
+        (&preview).render(buf.area, &mut buf, &mut state);
+        assert_eq!(6, preview.highlighted.borrow().lines.len());
+        // Carrying on from the saved state matches highlighting the whole file at once.
+        let expected = CodeView::highlight(
+            &source,
+            ps.find_syntax_by_extension("rs").unwrap(),
+            &ps,
+            &ts.themes["base16-ocean.dark"],
+        )
+        .unwrap();
+        assert_eq!(expected.text.lines[4..6], preview.lines(4..6));
+    }
+
+    #[test]
+    fn clamps_scrolling() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let preview =
+            FilePreview::new(vec![0xff; 40], "blob", &ps, &ts.themes["base16-ocean.dark"])
+                .binary_view(BinaryView::Hex);
+        let mut state = CodeViewState::default();
+        state.scroll_down(10);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+        (&preview).render(buf.area, &mut buf, &mut state);
+        assert_eq!(1, state.offset());
+        assert_eq!(
+            "00000010",
+            buf.content[..8]
+                .iter()
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        );
+    }
+
+    #[test]
+    fn open_reports_missing_files() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let result = FilePreview::open("/does/not/exist", &ps, &ts.themes["base16-ocean.dark"]);
+        assert!(matches!(result, Err(SyntectTuiError::FileRead { .. })));
+    }
+}
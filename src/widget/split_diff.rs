@@ -0,0 +1,381 @@
+use super::{CodeView, CodeViewState};
+use crate::colour::{blend, rgb, ColourSpace};
+use crate::diff::{DiffHighlighter, DiffLine};
+use crate::{HighlightedText, SyntectTuiError};
+
+/// A widget that renders a unified diff side by side, with the old file on the left and the new
+/// file on the right.
+///
+/// Both panes are highlighted by a [DiffHighlighter], so each keeps its own syntax highlighting
+/// state. Runs of removed lines are paired with the added lines that follow them, so changed
+/// lines line up across the panes and the shorter run is padded with empty rows. Within a pair,
+/// the bytes between the common prefix and suffix are emphasised with a stronger tint. Context
+/// lines and hunk headers appear in both panes.
+///
+/// Both panes share one scroll offset: rendered with a [CodeViewState], scrolling the state
+/// scrolls them together.
+///
+/// # Examples
+/// ```
+/// use ratatui::buffer::Buffer;
+/// use ratatui::layout::Rect;
+/// use ratatui::widgets::Widget;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::widget::SplitDiffView;
+///
+/// let ps = SyntaxSet::load_defaults_newlines();
+/// let ts = ThemeSet::load_defaults();
+/// let diff = "@@ -1,2 +1,2 @@\n a\n-b\n+c\n";
+/// let view = SplitDiffView::highlight(diff, &ps, &ts.themes["base16-ocean.dark"])
+///     .unwrap()
+///     .scroll(1);
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 7, 2));
+/// view.render(buf.area, &mut buf);
+/// let row = |y: usize| buf.content[y * 7..(y + 1) * 7].iter().map(|cell| cell.symbol()).collect::<String>();
+/// assert_eq!(" a │ a ", row(0));
+/// assert_eq!("-b │+c ", row(1));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SplitDiffView<'a> {
+    old: CodeView<'a>,
+    new: CodeView<'a>,
+    divider: char,
+    divider_style: ratatui::style::Style,
+}
+
+impl<'a> SplitDiffView<'a> {
+    /// Highlights `diff` with syntaxes from `syntax_set` and colours from `theme`, and creates a
+    /// side-by-side view of it.
+    ///
+    /// # Errors
+    /// Can return the same errors as [DiffHighlighter::highlight].
+    pub fn highlight(
+        diff: &'a str,
+        syntax_set: &syntect::parsing::SyntaxSet,
+        theme: &syntect::highlighting::Theme,
+    ) -> Result<Self, SyntectTuiError> {
+        Self::from_highlighter(&DiffHighlighter::new(syntax_set, theme), diff)
+    }
+
+    /// Highlights `diff` with `highlighter`, and creates a side-by-side view of it.
+    ///
+    /// # Errors
+    /// Can return the same errors as [DiffHighlighter::highlight].
+    pub fn from_highlighter(
+        highlighter: &DiffHighlighter,
+        diff: &'a str,
+    ) -> Result<Self, SyntectTuiError> {
+        let theme = highlighter.theme();
+        let background = theme.settings.background;
+        let (added, removed) = highlighter.tints();
+        let emphasis = |tint: syntect::highlighting::Color| {
+            let tint = syntect::highlighting::Color {
+                a: tint.a.saturating_mul(2),
+                ..tint
+            };
+            let colour = background.map_or(tint, |background| {
+                blend(tint, background, ColourSpace::Srgb)
+            });
+            ratatui::style::Style::new().bg(rgb(colour))
+        };
+        let emphasis = [emphasis(removed), emphasis(added)];
+        let mut old = Vec::new();
+        let mut new = Vec::new();
+        let mut removed_run = Vec::new();
+        let mut added_run = Vec::new();
+        for (kind, line) in highlighter.highlight_lines(diff)? {
+            let side = match kind {
+                DiffLine::Removed => Some(false),
+                DiffLine::Added => Some(true),
+                DiffLine::Header if line.to_string().starts_with("--- ") => Some(false),
+                DiffLine::Header if line.to_string().starts_with("+++ ") => Some(true),
+                DiffLine::Header | DiffLine::Context => None,
+            };
+            match side {
+                Some(false) => {
+                    // A removed line after added lines starts a new change.
+                    if !added_run.is_empty() {
+                        pair_runs(
+                            &mut removed_run,
+                            &mut added_run,
+                            [&mut old, &mut new],
+                            emphasis,
+                        );
+                    }
+                    removed_run.push((kind, line));
+                }
+                Some(true) => added_run.push((kind, line)),
+                None => {
+                    pair_runs(
+                        &mut removed_run,
+                        &mut added_run,
+                        [&mut old, &mut new],
+                        emphasis,
+                    );
+                    old.push(line.clone());
+                    new.push(line);
+                }
+            }
+        }
+        pair_runs(
+            &mut removed_run,
+            &mut added_run,
+            [&mut old, &mut new],
+            emphasis,
+        );
+        Ok(Self {
+            old: CodeView::from(HighlightedText::from_theme(old, theme)),
+            new: CodeView::from(HighlightedText::from_theme(new, theme)),
+            divider: '│',
+            divider_style: crate::translate_theme_settings(theme)
+                .gutter
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Sets the index of the first row to render in both panes.
+    pub fn scroll(mut self, offset: usize) -> Self {
+        self.old = self.old.scroll(offset);
+        self.new = self.new.scroll(offset);
+        self
+    }
+
+    /// Sets the character drawn between the panes.
+    pub fn divider(mut self, divider: char) -> Self {
+        self.divider = divider;
+        self
+    }
+
+    /// Sets the style of the divider between the panes. Defaults to the theme's gutter style.
+    pub fn divider_style(mut self, style: ratatui::style::Style) -> Self {
+        self.divider_style = style;
+        self
+    }
+
+    /// Returns the pane showing the old file.
+    pub fn old_pane(&self) -> &CodeView<'a> {
+        &self.old
+    }
+
+    /// Returns the pane showing the new file.
+    pub fn new_pane(&self) -> &CodeView<'a> {
+        &self.new
+    }
+
+    /// Returns the number of rows in each pane, e.g. to clamp scrolling.
+    pub fn row_count(&self) -> usize {
+        self.old.line_count()
+    }
+
+    /// Splits `area` into the old pane, the divider and the new pane.
+    fn split(&self, area: ratatui::layout::Rect) -> [ratatui::layout::Rect; 3] {
+        ratatui::layout::Layout::horizontal([
+            ratatui::layout::Constraint::Fill(1),
+            ratatui::layout::Constraint::Length(1),
+            ratatui::layout::Constraint::Fill(1),
+        ])
+        .areas(area)
+    }
+
+    fn render_divider(&self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        buf.set_style(area, self.divider_style);
+        for position in area.positions() {
+            buf[position].set_char(self.divider);
+        }
+    }
+}
+
+/// Appends runs of removed and added lines to the old and new panes, pairing them up row by row
+/// and padding the shorter run with empty lines. Paired changed lines have their changed bytes
+/// emphasised with the old and new `emphasis` styles.
+fn pair_runs<'a>(
+    removed: &mut Vec<(DiffLine, ratatui::text::Line<'a>)>,
+    added: &mut Vec<(DiffLine, ratatui::text::Line<'a>)>,
+    [old, new]: [&mut Vec<ratatui::text::Line<'a>>; 2],
+    [old_emphasis, new_emphasis]: [ratatui::style::Style; 2],
+) {
+    let rows = removed.len().max(added.len());
+    let mut removed = removed.drain(..);
+    let mut added = added.drain(..);
+    for _ in 0..rows {
+        match (removed.next(), added.next()) {
+            (Some((DiffLine::Removed, left)), Some((DiffLine::Added, right))) => {
+                let (left_bytes, right_bytes) =
+                    changed_bytes(&left.to_string(), &right.to_string());
+                old.push(crate::overlay::select_line(&left, left_bytes, old_emphasis));
+                new.push(crate::overlay::select_line(
+                    &right,
+                    right_bytes,
+                    new_emphasis,
+                ));
+            }
+            (left, right) => {
+                old.push(left.map(|(_, line)| line).unwrap_or_default());
+                new.push(right.map(|(_, line)| line).unwrap_or_default());
+            }
+        }
+    }
+}
+
+/// Returns the bytes of `old` and `new` between their common prefix and suffix, skipping the
+/// leading diff marker. Returns empty ranges if the lines have nothing in common, as emphasising
+/// the whole of both lines would add nothing to the line tint.
+fn changed_bytes(old: &str, new: &str) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+    let marker = |line: &str| line.chars().next().map_or(0, char::len_utf8);
+    let (old_start, new_start) = (marker(old), marker(new));
+    let (old_body, new_body) = (&old[old_start..], &new[new_start..]);
+    let prefix: usize = old_body
+        .chars()
+        .zip(new_body.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let suffix: usize = old_body[prefix..]
+        .chars()
+        .rev()
+        .zip(new_body[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    if prefix == 0 && suffix == 0 {
+        return (0..0, 0..0);
+    }
+    (
+        old_start + prefix..old.len() - suffix,
+        new_start + prefix..new.len() - suffix,
+    )
+}
+
+impl ratatui::widgets::Widget for SplitDiffView<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+impl ratatui::widgets::Widget for &SplitDiffView<'_> {
+    fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        let [old, divider, new] = self.split(area);
+        (&self.old).render(old, buf);
+        self.render_divider(divider, buf);
+        (&self.new).render(new, buf);
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for SplitDiffView<'_> {
+    type State = CodeViewState;
+
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        ratatui::widgets::StatefulWidget::render(&self, area, buf, state);
+    }
+}
+
+impl ratatui::widgets::StatefulWidget for &SplitDiffView<'_> {
+    type State = CodeViewState;
+
+    /// Renders both panes from the same state, so they scroll together.
+    fn render(
+        self,
+        area: ratatui::layout::Rect,
+        buf: &mut ratatui::buffer::Buffer,
+        state: &mut Self::State,
+    ) {
+        let [old, divider, new] = self.split(area);
+        ratatui::widgets::StatefulWidget::render(&self.old, old, buf, state);
+        self.render_divider(divider, buf);
+        ratatui::widgets::StatefulWidget::render(&self.new, new, buf, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::StatefulWidget;
+    use rstest::rstest;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    fn rows(view: &CodeView) -> Vec<String> {
+        view.text.lines.iter().map(ToString::to_string).collect()
+    }
+
+    #[rstest]
+    #[case("-abc", "+abd", 3..4, 3..4)]
+    #[case("-let x = 1;", "+let y = 1;", 5..6, 5..6)]
+    #[case("-ab", "+aXb", 2..2, 2..3)]
+    #[case("-abc", "+xyz", 0..0, 0..0)]
+    #[case("-日本", "+日語", 4..7, 4..7)]
+    fn check_changed_bytes(
+        #[case] old: &str,
+        #[case] new: &str,
+        #[case] expected_old: std::ops::Range<usize>,
+        #[case] expected_new: std::ops::Range<usize>,
+    ) {
+        assert_eq!((expected_old, expected_new), changed_bytes(old, new));
+    }
+
+    #[test]
+    fn aligns_changed_lines() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let diff = "--- a/x\n+++ b/x\n@@ -1,4 +1,4 @@\n a\n-b\n-c\n+d\n e\n+f\n";
+        let view = SplitDiffView::highlight(diff, &ps, &ts.themes["base16-ocean.dark"]).unwrap();
+        assert_eq!(
+            vec!["--- a/x", "@@ -1,4 +1,4 @@", " a", "-b", "-c", " e", ""],
+            rows(view.old_pane())
+        );
+        assert_eq!(
+            vec!["+++ b/x", "@@ -1,4 +1,4 @@", " a", "+d", "", " e", "+f"],
+            rows(view.new_pane())
+        );
+        assert_eq!(7, view.row_count());
+    }
+
+    #[test]
+    fn emphasises_changed_bytes() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let view = SplitDiffView::highlight(
+            "@@ -1 +1 @@\n-let x = 1;\n+let y = 1;\n",
+            &ps,
+            &ts.themes["base16-ocean.dark"],
+        )
+        .unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 25, 2));
+        ratatui::widgets::Widget::render(&view, buf.area, &mut buf);
+        let (plain, changed) = (buf[(1, 1)].bg, buf[(5, 1)].bg);
+        assert_ne!(plain, changed);
+        assert_ne!(buf[(13 + 1, 1)].bg, buf[(13 + 5, 1)].bg);
+        assert_eq!(plain, buf[(6, 1)].bg);
+    }
+
+    #[test]
+    fn panes_scroll_together() {
+        let ps = SyntaxSet::load_defaults_newlines();
+        let ts = ThemeSet::load_defaults();
+        let diff = "@@ -1,3 +1,3 @@\n a\n-b\n+c\n d\n";
+        let view = SplitDiffView::highlight(diff, &ps, &ts.themes["base16-ocean.dark"]).unwrap();
+        let mut state = CodeViewState::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 2));
+        (&view).render(buf.area, &mut buf, &mut state);
+        state.scroll_down(2);
+        (&view).render(buf.area, &mut buf, &mut state);
+        assert_eq!(2, state.offset());
+        assert_eq!(
+            ["-", "│", "+"],
+            [
+                buf[(0, 0)].symbol(),
+                buf[(2, 0)].symbol(),
+                buf[(3, 0)].symbol()
+            ]
+        );
+        assert_eq!("d", buf[(4, 1)].symbol());
+    }
+}
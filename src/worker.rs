@@ -0,0 +1,350 @@
+use crate::{CancellationToken, LineConversion, SyntectTuiError};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+/// A run of consecutive converted lines sent by a [HighlightWorker].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HighlightBatch {
+    /// The index of the first line of the batch in the document.
+    pub start: usize,
+    /// The converted lines.
+    pub lines: Vec<ratatui::text::Line<'static>>,
+}
+
+impl HighlightBatch {
+    /// Replaces the lines of `document` that the batch covers with its lines, e.g. to fill in the
+    /// colours of lines shown unhighlighted until now, adding lines to the end of `document` if it
+    /// is too short.
+    pub fn apply_to(self, document: &mut Vec<ratatui::text::Line<'static>>) {
+        let end = self.start + self.lines.len();
+        if document.len() < end {
+            document.resize(end, ratatui::text::Line::default());
+        }
+        document.splice(self.start..end, self.lines);
+    }
+}
+
+/// Highlights documents on background threads, sending the converted lines back over a channel
+/// in batches as they are highlighted.
+///
+/// A TUI can show a large document unhighlighted straight away, then apply the batches a
+/// [HighlightWorker] has received each time it draws, so colours fill in from the top without
+/// the highlighting ever blocking the UI.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use syntect::highlighting::ThemeSet;
+/// use syntect::parsing::SyntaxSet;
+/// use syntect_tui::BackgroundHighlighter;
+///
+/// let ps = Arc::new(SyntaxSet::load_defaults_newlines());
+/// let ts = ThemeSet::load_defaults();
+/// let theme = Arc::new(ts.themes["base16-ocean.dark"].clone());
+/// let syntax = ps.find_syntax_by_extension("rs").unwrap();
+/// let source = "let x = 1;\n".repeat(250);
+/// let worker = BackgroundHighlighter::new(syntax, ps.clone(), theme)
+///     .batch_lines(100)
+///     .spawn(source.clone());
+/// let mut document: Vec<_> = source.lines().map(|line| line.to_string().into()).collect();
+/// let mut batches = 0;
+/// for batch in worker {
+///     batch.unwrap().apply_to(&mut document);
+///     batches += 1;
+/// }
+/// assert_eq!(3, batches);
+/// assert!(document.iter().all(|line| line.spans.len() > 1));
+/// ```
+#[derive(Clone, Debug)]
+pub struct BackgroundHighlighter {
+    syntax_name: String,
+    syntax_set: Arc<syntect::parsing::SyntaxSet>,
+    pub(crate) theme: Arc<syntect::highlighting::Theme>,
+    batch_lines: usize,
+    fallback: crate::LongLineFallback,
+    pub(crate) translator: crate::StyleTranslator,
+}
+
+impl BackgroundHighlighter {
+    /// Creates a highlighter that highlights documents using `syntax`, which must be one of the
+    /// syntaxes of `syntax_set`, and `theme`, sending batches of 100 lines.
+    pub fn new(
+        syntax: &syntect::parsing::SyntaxReference,
+        syntax_set: Arc<syntect::parsing::SyntaxSet>,
+        theme: Arc<syntect::highlighting::Theme>,
+    ) -> Self {
+        Self {
+            syntax_name: syntax.name.clone(),
+            syntax_set,
+            theme,
+            batch_lines: 100,
+            fallback: crate::LongLineFallback::new(),
+            translator: crate::StyleTranslator::new(),
+        }
+    }
+
+    /// Sets the number of lines in each batch. Smaller batches arrive sooner, but cost more to
+    /// send and apply.
+    pub fn batch_lines(mut self, lines: usize) -> Self {
+        self.batch_lines = lines.max(1);
+        self
+    }
+
+    /// Starts highlighting `source` on a new thread, returning the worker that receives its
+    /// batches.
+    pub fn spawn(&self, source: String) -> HighlightWorker {
+        self.spawn_with_token(source, CancellationToken::new())
+    }
+
+    /// Starts highlighting `source` on a new thread like [BackgroundHighlighter::spawn], stopping
+    /// before the next line once `token` has been cancelled, e.g. to cancel several workers
+    /// together.
+    pub fn spawn_with_token(&self, source: String, token: CancellationToken) -> HighlightWorker {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let finished = Arc::new(AtomicBool::new(false));
+        let highlighter = self.clone();
+        let stop = token.child();
+        let (worker_stop, done) = (stop.clone(), finished.clone());
+        std::thread::spawn(move || {
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::debug_span!("highlight_background", bytes = source.len()).entered();
+            for batch in highlighter.batches(&source, &stop) {
+                if stop.is_cancelled() {
+                    break;
+                }
+                let failed = batch.is_err();
+                if sender.send(batch).is_err() || failed {
+                    break;
+                }
+            }
+            done.store(true, Ordering::Release);
+        });
+        HighlightWorker {
+            receiver,
+            token,
+            stop: worker_stop,
+            finished,
+        }
+    }
+
+    /// Returns the batches of `source`, highlighting each as it is requested, and ending after
+    /// the first error, or once `token` has been cancelled.
+    pub(crate) fn batches<'s>(
+        &'s self,
+        source: &'s str,
+        token: &'s CancellationToken,
+    ) -> impl Iterator<Item = Result<HighlightBatch, SyntectTuiError>> + 's {
+        let syntax = self
+            .syntax_set
+            .find_syntax_by_name(&self.syntax_name)
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+        let mut start = 0;
+        crate::HighlightChunks::new(source, syntax, &self.syntax_set, &self.theme)
+            .chunk_lines(self.batch_lines)
+            .long_lines(self.fallback)
+            .translator(self.translator.clone())
+            .cancel_with(token)
+            .map(move |chunk| {
+                let lines: Vec<_> = chunk?
+                    .into_iter()
+                    .map(crate::line::into_owned_line)
+                    .collect();
+                let batch = HighlightBatch { start, lines };
+                start += batch.lines.len();
+                Ok(batch)
+            })
+    }
+}
+
+impl LineConversion for BackgroundHighlighter {
+    fn translator(mut self, translator: crate::StyleTranslator) -> Self {
+        self.translator = translator;
+        self
+    }
+
+    fn long_lines(mut self, fallback: crate::LongLineFallback) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+/// Receives the batches of a document being highlighted by a [BackgroundHighlighter].
+///
+/// Batches arrive in order, each after the last, until the whole document has been highlighted
+/// or an error is sent. Iterating over a worker blocks until each batch arrives, while
+/// [try_recv](HighlightWorker::try_recv) only returns the batches that have already arrived, e.g.
+/// to apply them before drawing a frame. Dropping the worker stops its highlighting, without
+/// cancelling its [token](HighlightWorker::token).
+#[derive(Debug)]
+pub struct HighlightWorker {
+    receiver: Receiver<Result<HighlightBatch, SyntectTuiError>>,
+    token: CancellationToken,
+    stop: CancellationToken,
+    finished: Arc<AtomicBool>,
+}
+
+impl HighlightWorker {
+    /// Returns the next batch if it has arrived, or `None` if it has not or the document has
+    /// been highlighted.
+    pub fn try_recv(&self) -> Option<Result<HighlightBatch, SyntectTuiError>> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Returns whether the worker has stopped sending batches, because the whole document has
+    /// been highlighted or highlighting failed or was cancelled. Batches already sent can still be
+    /// received.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Stops highlighting before the next line, without cancelling its
+    /// [token](HighlightWorker::token) or other work sharing it. Batches already sent can still be
+    /// received.
+    pub fn cancel(&self) {
+        self.stop.cancel();
+    }
+
+    /// Returns the token the worker was started with, which cancels its highlighting along with
+    /// any other work it was handed to.
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Iterator for HighlightWorker {
+    type Item = Result<HighlightBatch, SyntectTuiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for HighlightWorker {
+    fn drop(&mut self) {
+        self.stop.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Line;
+    use rstest::*;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    const SOURCE: &str = "let a = 1;\n/*\nb\n*/\nlet c = \"\nd\";\nlet e = 5;";
+
+    fn fake_highlighter() -> BackgroundHighlighter {
+        let ps = Arc::new(SyntaxSet::load_defaults_newlines());
+        let ts = ThemeSet::load_defaults();
+        let syntax = ps.find_syntax_by_extension("rs").unwrap().clone();
+        BackgroundHighlighter::new(
+            &syntax,
+            ps,
+            Arc::new(ts.themes["base16-ocean.dark"].clone()),
+        )
+    }
+
+    #[rstest]
+    #[case::one_per_batch(1, vec![0, 1, 2, 3, 4, 5, 6])]
+    #[case::uneven(3, vec![0, 3, 6])]
+    #[case::single_batch(100, vec![0])]
+    fn matches_sequential_highlighting(#[case] batch_lines: usize, #[case] starts: Vec<usize>) {
+        let highlighter = fake_highlighter().batch_lines(batch_lines);
+        let syntax = highlighter
+            .syntax_set
+            .find_syntax_by_extension("rs")
+            .unwrap();
+        let mut sequential = syntect::easy::HighlightLines::new(syntax, &highlighter.theme);
+        let expected = syntect::util::LinesWithEndings::from(SOURCE)
+            .map(|line| {
+                crate::highlight_line_to_line(
+                    &mut sequential,
+                    &highlighter.syntax_set,
+                    &highlighter.theme,
+                    line,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut document = Vec::new();
+        let mut received = Vec::new();
+        for batch in highlighter.spawn(SOURCE.to_string()) {
+            let batch = batch.unwrap();
+            received.push(batch.start);
+            batch.apply_to(&mut document);
+        }
+        assert_eq!(starts, received);
+        assert_eq!(expected, document);
+    }
+
+    #[test]
+    fn apply_to_replaces_covered_lines() {
+        let mut document = vec![Line::raw("a"), Line::raw("b"), Line::raw("c")];
+        HighlightBatch {
+            start: 1,
+            lines: vec![Line::raw("B")],
+        }
+        .apply_to(&mut document);
+        assert_eq!(
+            vec![Line::raw("a"), Line::raw("B"), Line::raw("c")],
+            document
+        );
+        HighlightBatch {
+            start: 4,
+            lines: vec![Line::raw("E")],
+        }
+        .apply_to(&mut document);
+        assert_eq!(Line::default(), document[3]);
+        assert_eq!(Line::raw("E"), document[4]);
+    }
+
+    #[test]
+    fn cancelled_worker_finishes() {
+        let mut worker = fake_highlighter()
+            .batch_lines(1)
+            .spawn("let x = 1;\n".repeat(10_000));
+        worker.cancel();
+        let received = worker.by_ref().count();
+        assert!(received < 10_000);
+        assert!(worker.is_finished());
+    }
+
+    #[test]
+    fn shared_token_cancels_worker_mid_batch() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut worker = fake_highlighter()
+            .batch_lines(10_000)
+            .spawn_with_token("let x = 1;\n".repeat(10_000), token);
+        assert!(worker.next().is_none());
+        assert!(worker.is_finished());
+        assert!(worker.token().is_cancelled());
+    }
+
+    #[test]
+    fn dropping_worker_leaves_shared_token() {
+        let token = CancellationToken::new();
+        let highlighter = fake_highlighter().batch_lines(1);
+        let dropped = highlighter.spawn_with_token("let x = 1;\n".repeat(10_000), token.clone());
+        let sibling = highlighter.spawn_with_token(SOURCE.to_string(), token.clone());
+        drop(dropped);
+        assert!(!token.is_cancelled());
+        assert_eq!(7, sibling.map(Result::unwrap).count());
+    }
+
+    #[test]
+    fn cancelling_worker_leaves_shared_token() {
+        let token = CancellationToken::new();
+        let highlighter = fake_highlighter().batch_lines(1);
+        let cancelled = highlighter.spawn_with_token("let x = 1;\n".repeat(10_000), token.clone());
+        let sibling = highlighter.spawn_with_token(SOURCE.to_string(), token.clone());
+        cancelled.cancel();
+        assert!(!token.is_cancelled());
+        assert!(cancelled.count() < 10_000);
+        assert_eq!(7, sibling.map(Result::unwrap).count());
+    }
+}
@@ -0,0 +1,201 @@
+use unicode_width::UnicodeWidthChar;
+
+/// Soft-wraps a [ratatui::text::Line](https://docs.rs/ratatui/latest/ratatui/text/struct.Line.html)
+/// into rows no wider than `width` display columns, keeping the style of every span.
+///
+/// Lines are broken at word boundaries (transitions between whitespace and non-whitespace). The
+/// whitespace a break falls on is dropped, as is any whitespace left trailing at the end of a row,
+/// so rows never end or begin with stray padding. A word too wide to fit on a row of its own is
+/// broken between characters. Spans that straddle a break are split into one span per row, each
+/// keeping the original style, and every row inherits the style and alignment of `line`.
+///
+/// Every row after the first is indented by `hanging_indent` columns of unstyled spaces, which
+/// keeps continuations visually attached to the code they belong to. A `hanging_indent` that
+/// leaves no room on the row is ignored. A `width` of zero is treated as one column.
+///
+/// # Examples
+/// ```
+/// use ratatui::style::{Style, Stylize};
+/// use ratatui::text::{Line, Span};
+///
+/// let line = Line::from(vec![
+///     Span::styled("let", Style::new().blue()),
+///     Span::raw(" answer = "),
+///     Span::styled("42", Style::new().green()),
+/// ]);
+/// let rows = syntect_tui::wrap_line(&line, 10, 2);
+/// assert_eq!(
+///     vec![
+///         Line::from(vec![Span::styled("let", Style::new().blue()), Span::raw(" answer")]),
+///         Line::from(vec![
+///             Span::raw("  "),
+///             Span::raw("= "),
+///             Span::styled("42", Style::new().green()),
+///         ]),
+///     ],
+///     rows
+/// );
+/// ```
+pub fn wrap_line<'a>(
+    line: &ratatui::text::Line<'a>,
+    width: usize,
+    hanging_indent: usize,
+) -> Vec<ratatui::text::Line<'a>> {
+    let width = width.max(1);
+    let indent = if hanging_indent < width {
+        hanging_indent
+    } else {
+        0
+    };
+    let characters: Vec<(usize, char)> = line
+        .spans
+        .iter()
+        .enumerate()
+        .flat_map(|(index, span)| span.content.chars().map(move |c| (index, c)))
+        .collect();
+
+    let mut rows: Vec<Vec<(usize, char)>> = vec![Vec::new()];
+    let mut row_start = 0;
+    let mut column = 0;
+    let break_row = |rows: &mut Vec<Vec<(usize, char)>>| {
+        if let Some(row) = rows.last_mut() {
+            while row.last().is_some_and(|(_, c)| c.is_whitespace()) {
+                row.pop();
+            }
+        }
+        rows.push(Vec::new());
+    };
+    for word in characters.chunk_by(|a, b| a.1.is_whitespace() == b.1.is_whitespace()) {
+        let word_width: usize = word.iter().map(|(_, c)| c.width().unwrap_or(0)).sum();
+        if column + word_width <= width {
+            rows.last_mut().unwrap().extend_from_slice(word);
+            column += word_width;
+            continue;
+        }
+        if word[0].1.is_whitespace() {
+            break_row(&mut rows);
+            row_start = indent;
+            column = indent;
+            continue;
+        }
+        if column > row_start {
+            break_row(&mut rows);
+            row_start = indent;
+            column = indent;
+        }
+        for &(index, character) in word {
+            let character_width = character.width().unwrap_or(0);
+            if column + character_width > width && column > row_start {
+                break_row(&mut rows);
+                row_start = indent;
+                column = indent;
+            }
+            rows.last_mut().unwrap().push((index, character));
+            column += character_width;
+        }
+    }
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(number, row)| {
+            let mut spans = Vec::new();
+            if number > 0 && indent > 0 {
+                spans.push(ratatui::text::Span::raw(" ".repeat(indent)));
+            }
+            spans.extend(row.chunk_by(|a, b| a.0 == b.0).map(|piece| {
+                let content: String = piece.iter().map(|(_, c)| c).collect();
+                ratatui::text::Span::styled(content, line.spans[piece[0].0].style)
+            }));
+            let mut wrapped = ratatui::text::Line::from(spans).style(line.style);
+            wrapped.alignment = line.alignment;
+            wrapped
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::*;
+
+    use super::*;
+    use ratatui::style::{Style, Stylize};
+    use ratatui::text::{Line, Span};
+
+    fn fake_line() -> Line<'static> {
+        Line::from(vec![
+            Span::styled("fn", Style::new().red()),
+            Span::raw(" "),
+            Span::styled("main() {}", Style::new().blue()),
+        ])
+    }
+
+    fn contents(rows: &[Line]) -> Vec<String> {
+        rows.iter().map(|row| row.to_string()).collect()
+    }
+
+    #[rstest]
+    #[case::fits(20, 0, vec!["fn main() {}"])]
+    #[case::at_word_boundary(10, 0, vec!["fn main()", "{}"])]
+    #[case::hanging_indent(10, 4, vec!["fn main()", "    {}"])]
+    #[case::long_word(4, 1, vec!["fn", " mai", " n()", " {}"])]
+    #[case::indent_too_wide(4, 4, vec!["fn", "main", "()", "{}"])]
+    #[case::zero_width(0, 0, vec!["f", "n", "m", "a", "i", "n", "(", ")", "{", "}"])]
+    fn check_wrap_line(
+        #[case] width: usize,
+        #[case] hanging_indent: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        assert_eq!(
+            expected,
+            contents(&wrap_line(&fake_line(), width, hanging_indent))
+        );
+    }
+
+    #[test]
+    fn wrap_line_splits_spans_across_rows() {
+        let rows = wrap_line(&fake_line(), 7, 1);
+        assert_eq!(
+            vec![
+                Line::from(vec![Span::styled("fn", Style::new().red())]),
+                Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled("main()", Style::new().blue()),
+                ]),
+                Line::from(vec![
+                    Span::raw(" "),
+                    Span::styled("{}", Style::new().blue())
+                ]),
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn wrap_line_measures_wide_characters() {
+        let line = Line::from("日本語");
+        assert_eq!(vec!["日本", "語"], contents(&wrap_line(&line, 5, 0)));
+    }
+
+    #[test]
+    fn wrap_line_keeps_leading_whitespace() {
+        let line = Line::from("    return value");
+        assert_eq!(
+            vec!["    return", "value"],
+            contents(&wrap_line(&line, 12, 0))
+        );
+    }
+
+    #[test]
+    fn wrap_line_keeps_line_style() {
+        let line = fake_line().style(Style::new().on_black()).centered();
+        for row in wrap_line(&line, 4, 0) {
+            assert_eq!(line.style, row.style);
+            assert_eq!(line.alignment, row.alignment);
+        }
+    }
+
+    #[test]
+    fn wrap_line_keeps_empty_lines() {
+        assert_eq!(vec![Line::default()], wrap_line(&Line::default(), 10, 2));
+    }
+}